@@ -1,7 +1,6 @@
 use hsds_client::{HsdsClient, BasicAuth};
 use std::time::{SystemTime, UNIX_EPOCH};
 // Initialize logging to see the HTTP request logs
-use log::{info, debug};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -31,7 +30,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Test 2: User domain access
     println!("\n2. 👤 Testing user domain access...");
     match client.domains().get_domain("/home/admin").await {
-        Ok(domain) => {
+        Ok(_) => {
             println!("   ✅ /home/admin domain accessible");
         }
         Err(e) => {
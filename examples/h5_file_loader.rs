@@ -1,12 +1,18 @@
-use hdf5::types::{FloatSize, IntSize};
+use hdf5::types::{FloatSize, IntSize, TypeDescriptor};
 use hsds_client::{
     HsdsClient, BasicAuth, 
     DatasetCreateRequest, DatasetValueRequest,
-    GroupCreateRequest
+    GroupCreateRequest, NameSanitizer, TransferStats, ElementCodec,
+    H5Type, ImportItem, ImportOptions, NameCollision,
 };
-use hdf5::{File as H5File, Group as H5Group, Dataset as H5Dataset};
+use hdf5::{File as H5File, Group as H5Group, Dataset as H5Dataset, Datatype as H5Datatype, LinkType};
 use serde_json::json;
+use std::collections::HashMap;
 use std::error::Error;
+use hdf5_sys::h5l::{H5L_info1_t, H5Lget_info1, H5Lget_val, H5Lunpack_elink_val};
+use hdf5_sys::h5p::H5P_DEFAULT;
+use hdf5_sys::h5t::H5Topen2;
+use std::ffi::{c_char, c_uint, CStr, CString};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::path::Path;
 use log::{info, warn, debug};
@@ -21,6 +27,8 @@ use std::io::{self, Write};
 /// - Handles different data types (integers, floats, strings)
 /// - Preserves hierarchical structure
 /// - Copies attributes (metadata)
+/// - Recreates soft links, external links and committed types, reporting
+///   anything HSDS cannot store
 /// - Provides progress feedback
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -42,11 +50,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // Open the HDF5 file
     let h5_file = H5File::open(h5_file_path)?;
     
-    // Initialize HSDS client
+    // Initialize HSDS client, timing every request of the load
+    let transfer_stats = TransferStats::new();
     let client = HsdsClient::new(
         "http://localhost:5101",
         BasicAuth::new("admin", "admin")
-    )?;
+    )?.with_transfer_stats(transfer_stats.clone());
     
     // Create a unique target file name with timestamp
     let timestamp = SystemTime::now()
@@ -66,6 +75,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("\n🔄 Loading HDF5 structure...");
     
     let load_stats = LoadStats::new();
+    let mut import_items = Vec::new();
     
     // Load the root group and all its contents
     load_group_recursive(
@@ -74,8 +84,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
         &target_file,
         &root_group_id,
         "/",
-        &load_stats
+        &load_stats,
+        &mut import_items,
     ).await?;
+
+    // Links and committed types only make sense once the hierarchy exists.
+    // Their names and targets are sanitized like the groups and datasets
+    // above; sanitized names can collide, so keep both under a suffixed name
+    let import_options = ImportOptions::new()
+        .with_name_sanitizer(NameSanitizer::new())
+        .with_name_collision(NameCollision::Rename);
+    let import = client.domains().import_items(&target_file, &import_items, &import_options).await?;
+    for entry in &import.untranslated {
+        warn!("Not imported: {} ({})", entry.path, entry.reason);
+    }
+    for collision in &import.collisions {
+        warn!("Name taken: {} imported as {:?}", collision.path, collision.linked_as);
+    }
     
     // Print summary
     println!("\n✅ File loading completed successfully!");
@@ -83,6 +108,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("   - Groups created: {}", load_stats.groups_created());
     println!("   - Datasets created: {}", load_stats.datasets_created());
     println!("   - Attributes copied: {}", load_stats.attributes_created());
+    println!("   - Links recreated: {}", import.links.len());
+    println!("   - Committed types: {}", import.datatypes.len());
+    println!("   - Not imported: {}", import.untranslated.len());
+    println!("   - Transfer: {}", transfer_stats.report());
+    if let Some(transfer) = &import.transfer {
+        println!("   - Link/type import: {}", transfer);
+    }
     println!("   - Target file: {}", target_file);
     
     // Optionally verify some data
@@ -204,6 +236,11 @@ impl ProgressBar {
 }
 
 /// Recursively load a group and all its contents
+///
+/// Soft links, external links and committed types are collected into
+/// `items`, under their HDF5 paths, for `DomainApi::import_items` to
+/// recreate after the load with the same name sanitizing.
+#[allow(clippy::too_many_arguments)]
 async fn load_group_recursive(
     h5_file: &H5File,
     client: &HsdsClient,
@@ -211,6 +248,7 @@ async fn load_group_recursive(
     parent_group_id: &str,
     current_path: &str,
     stats: &LoadStats,
+    items: &mut Vec<ImportItem>,
 ) -> Result<(), Box<dyn Error>> {
     info!("Processing group: {}", current_path);
     
@@ -226,6 +264,10 @@ async fn load_group_recursive(
     // Get all member names
     let member_names = h5_group.member_names()?;
     debug!("Found {} members in group '{}'", member_names.len(), current_path);
+    let link_types: HashMap<String, LinkType> = h5_group.iter_visit_default(HashMap::new(), |_, name, info, types| {
+        types.insert(name.to_string(), info.link_type);
+        true
+    })?;
     for member_name in member_names {
         let member_path = if current_path == "/" {
             format!("/{}", member_name)
@@ -234,6 +276,14 @@ async fn load_group_recursive(
         };
         
         debug!("Processing member: {}", member_path);
+
+        // Soft and external links are recreated as links, not followed
+        if let Some(link_type @ (LinkType::Soft | LinkType::External)) = link_types.get(&member_name) {
+            println!("   🔗 Found link: {}", member_name);
+            let external = matches!(link_type, LinkType::External);
+            items.push(link_item(&h5_group, current_path, &member_name, external));
+            continue;
+        }
         
         // Try to open as a group first
         if let Ok(sub_group) = h5_file.group(&member_path) {
@@ -244,7 +294,7 @@ async fn load_group_recursive(
             let group_request = GroupCreateRequest {
                 link: Some(hsds_client::LinkRequest {
                     id: parent_group_id.to_string(),
-                    name: member_name.clone(),
+                    name: hsds_name(&member_name),
                 }),
             };
             let hsds_group = client.groups().create_group(domain, Some(group_request)).await?;
@@ -261,7 +311,8 @@ async fn load_group_recursive(
                 domain,
                 &hsds_group.id,
                 &member_path,
-                stats
+                stats,
+                items,
             )).await?;
             
         } else if let Ok(dataset) = h5_file.dataset(&member_path) {
@@ -273,18 +324,114 @@ async fn load_group_recursive(
                 client,
                 domain,
                 parent_group_id,
-                &member_name,
+                &hsds_name(&member_name),
                 stats
             ).await?;
             
+        } else if let Some(datatype) = named_datatype(&h5_group, &member_name) {
+            // It's a committed type
+            println!("   🏷️  Found committed type: {}", member_name);
+            items.push(match hsds_type_name(&datatype.to_descriptor()?) {
+                Ok(type_name) => ImportItem::NamedType {
+                    group: current_path.to_string(),
+                    name: member_name.clone(),
+                    datatype: H5Type::named(type_name),
+                },
+                Err(e) => ImportItem::Unsupported {
+                    group: current_path.to_string(),
+                    name: member_name.clone(),
+                    kind: format!("committed type ({})", e),
+                },
+            });
+
         } else {
-            warn!("Unknown member type: {}", member_path);
+            items.push(ImportItem::Unsupported {
+                group: current_path.to_string(),
+                name: member_name.clone(),
+                kind: "object of unknown type".to_string(),
+            });
         }
     }
     
     Ok(())
 }
 
+/// Import item for a soft or external link, `Unsupported` if its value cannot be read
+fn link_item(group: &H5Group, group_path: &str, name: &str, external: bool) -> ImportItem {
+    let group_path = group_path.to_string();
+    let link_name = name.to_string();
+    match link_value(group, name, external) {
+        Ok((None, target)) => ImportItem::SoftLink { group: group_path, name: link_name, target },
+        Ok((Some(file), target)) => ImportItem::ExternalLink { group: group_path, name: link_name, file, target },
+        Err(e) => ImportItem::Unsupported { group: group_path, name: link_name, kind: format!("link ({})", e) },
+    }
+}
+
+/// Value of a soft link (`(None, path)`) or external link (`(Some(file), path)`)
+fn link_value(group: &H5Group, name: &str, external: bool) -> Result<(Option<String>, String), Box<dyn Error>> {
+    let c_name = CString::new(name)?;
+    let _lock = hdf5_sys::LOCK.lock();
+    // `H5Lget_info1` is the variant every supported HDF5 version exports
+    let mut info = H5L_info1_t::default();
+    // SAFETY: the name is NUL-terminated and `info` is a valid out-pointer
+    #[allow(deprecated)]
+    let status = unsafe { H5Lget_info1(group.id(), c_name.as_ptr(), &mut info, H5P_DEFAULT) };
+    if status < 0 {
+        return Err(format!("cannot get info of link '{}'", name).into());
+    }
+    // SAFETY: for soft and external links the union holds the value size
+    let val_size = unsafe { *info.u.val_size() };
+
+    let mut buf = vec![0u8; val_size];
+    // SAFETY: the buffer is writable for its full length and the name is NUL-terminated
+    let status = unsafe { H5Lget_val(group.id(), c_name.as_ptr(), buf.as_mut_ptr().cast(), buf.len(), H5P_DEFAULT) };
+    if status < 0 {
+        return Err(format!("cannot read the value of link '{}'", name).into());
+    }
+    if !external {
+        return Ok((None, CStr::from_bytes_until_nul(&buf)?.to_string_lossy().into_owned()));
+    }
+
+    let mut flags: c_uint = 0;
+    let mut file: *const c_char = std::ptr::null();
+    let mut path: *const c_char = std::ptr::null();
+    // SAFETY: the buffer holds the link value read above; the returned
+    // pointers point into it and are only used while it is alive
+    let status = unsafe { H5Lunpack_elink_val(buf.as_ptr().cast(), buf.len(), &mut flags, &mut file, &mut path) };
+    if status < 0 || file.is_null() || path.is_null() {
+        return Err(format!("cannot unpack external link '{}'", name).into());
+    }
+    // SAFETY: both pointers are non-null and NUL-terminated within `buf`
+    let (file, path) = unsafe { (CStr::from_ptr(file), CStr::from_ptr(path)) };
+    Ok((Some(file.to_string_lossy().into_owned()), path.to_string_lossy().into_owned()))
+}
+
+/// Open a committed type by its name in a group, `None` if the member is not one
+fn named_datatype(group: &H5Group, name: &str) -> Option<H5Datatype> {
+    let c_name = CString::new(name).ok()?;
+    let id = {
+        let _lock = hdf5_sys::LOCK.lock();
+        // SAFETY: the name is NUL-terminated; a failed open returns a negative id
+        unsafe { H5Topen2(group.id(), c_name.as_ptr(), H5P_DEFAULT) }
+    };
+    if id < 0 {
+        return None;
+    }
+    hdf5::from_id(id).ok()
+}
+
+/// Name HSDS can store for an HDF5 member or attribute name
+///
+/// Raw instrument channel names may contain characters HSDS rejects; they
+/// are replaced instead of aborting the upload.
+fn hsds_name(name: &str) -> String {
+    let sanitized = NameSanitizer::new().sanitize(name);
+    if sanitized != name {
+        warn!("Storing '{}' as '{}'", name.escape_debug(), sanitized);
+    }
+    sanitized
+}
+
 /// Copy a dataset from HDF5 to HSDS
 async fn copy_dataset(
     h5_dataset: &H5Dataset,
@@ -341,6 +488,12 @@ async fn copy_dataset_data(
 ) -> Result<(), Box<dyn Error>> {
     let shape = h5_dataset.shape();
     let total_elements: usize = shape.iter().product::<usize>();
+
+    // Null dataspaces and zero-length dimensions have no values to copy
+    if h5_dataset.size() == 0 {
+        debug!("Dataset shape: {:?} holds no values, skipping data copy", shape);
+        return Ok(());
+    }
     
     // Estimate data size using actual datatype size
     let dtype = h5_dataset.dtype()?;
@@ -831,33 +984,21 @@ fn extract_2d_chunk<T: Clone>(data: &Vec<T>, _total_rows: usize, cols: usize, st
 fn convert_hdf5_dtype_to_hsds(h5_dataset: &H5Dataset) -> Result<String, Box<dyn Error>> {
     // Use the actual HDF5 data type descriptor instead of trying to read
     let dtype = h5_dataset.dtype()?;
-    let type_desc = dtype.to_descriptor()?;
-    
+    hsds_type_name(&dtype.to_descriptor()?)
+}
+
+/// HSDS type name of an HDF5 type descriptor
+fn hsds_type_name(type_desc: &TypeDescriptor) -> Result<String, Box<dyn Error>> {
     match type_desc {
-        hdf5::types::TypeDescriptor::Float(FloatSize::U8) => Ok("H5T_IEEE_F64LE".to_string()),
-        hdf5::types::TypeDescriptor::Float(FloatSize::U4) => Ok("H5T_IEEE_F32LE".to_string()),
-        hdf5::types::TypeDescriptor::Integer(IntSize::U8) => {
-            // For 64-bit integers, default to signed
-            Ok("H5T_STD_I64LE".to_string())
-        },
-        hdf5::types::TypeDescriptor::Integer(IntSize::U4) => {
-            // For 32-bit integers, default to signed
-            Ok("H5T_STD_I32LE".to_string())
-        },
-        hdf5::types::TypeDescriptor::Integer(IntSize::U2) => {
-            // For 16-bit integers, default to signed
-            Ok("H5T_STD_I16LE".to_string())
-        },
-        hdf5::types::TypeDescriptor::Integer(IntSize::U1) => {
-            // For 8-bit integers, default to signed
-            Ok("H5T_STD_I8LE".to_string())
-        },
-        hdf5::types::TypeDescriptor::Unsigned(IntSize::U1) => {
-            // For unsigned 8-bit integers
-            Ok("H5T_STD_U8LE".to_string())
-        },
-        hdf5::types::TypeDescriptor::VarLenUnicode => Ok("H5T_STRING".to_string()),
-        hdf5::types::TypeDescriptor::VarLenAscii => Ok("H5T_STRING".to_string()),
+        TypeDescriptor::Float(FloatSize::U8) => Ok(codec_type::<f64>()),
+        TypeDescriptor::Float(FloatSize::U4) => Ok(codec_type::<f32>()),
+        TypeDescriptor::Integer(IntSize::U8) => Ok(codec_type::<i64>()),
+        TypeDescriptor::Integer(IntSize::U4) => Ok(codec_type::<i32>()),
+        TypeDescriptor::Integer(IntSize::U2) => Ok(codec_type::<i16>()),
+        TypeDescriptor::Integer(IntSize::U1) => Ok(codec_type::<i8>()),
+        TypeDescriptor::Unsigned(IntSize::U1) => Ok(codec_type::<u8>()),
+        TypeDescriptor::VarLenUnicode => Ok("H5T_STRING".to_string()),
+        TypeDescriptor::VarLenAscii => Ok("H5T_STRING".to_string()),
         _ => {
             warn!("Unsupported HDF5 data type: {:?}", type_desc);
             Err("Unsupported data type".into())
@@ -865,6 +1006,11 @@ fn convert_hdf5_dtype_to_hsds(h5_dataset: &H5Dataset) -> Result<String, Box<dyn
     }
 }
 
+/// Predefined HSDS type name of a primitive element type
+fn codec_type<T: ElementCodec>() -> String {
+    T::hsds_type().as_str().unwrap_or_default().to_string()
+}
+
 /// Convert flat array to 2D structure
 fn convert_flat_to_2d<T: Clone>(data: Vec<T>, rows: usize, cols: usize) -> Vec<Vec<T>> {
     let mut result = Vec::with_capacity(rows);
@@ -1052,7 +1198,7 @@ async fn copy_attribute_value(
     debug!("Successfully read attribute '{}': {:?}", attr_name, value);
     
     // Set the attribute in HSDS
-    match client.attributes().set_attribute(domain, object_id, attr_name, value).await {
+    match client.attributes().set_attribute(domain, object_id, &hsds_name(attr_name), value).await {
         Ok(_) => {
             debug!("Successfully set attribute '{}' in HSDS", attr_name);
             stats.increment_attributes();
@@ -0,0 +1,126 @@
+//! Derive macros for `hsds_client`
+//!
+//! Use them through the re-exports of `hsds_client`, whose paths the
+//! generated code refers to.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitStr, Type};
+
+/// Implement `ElementCodec` for a struct as an HSDS compound
+///
+/// Fields are stored in declaration order under their Rust names; every
+/// field type must implement `ElementCodec`. The compound has a binary
+/// encoding (fields packed without padding) when all its fields do. JSON
+/// elements are read from HSDS's array form or from an object keyed by
+/// field name.
+#[proc_macro_derive(ElementCodec)]
+pub fn derive_element_codec(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    element_codec(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Implement `schema::Schema` for a record type
+///
+/// The schema is registered under the type's name, or the one given with
+/// `#[schema(name = "...")]`. Its compound layout comes from the type's
+/// `ElementCodec`, usually derived alongside.
+#[proc_macro_derive(Schema, attributes(schema))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    schema(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Named fields of a struct, in declaration order
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<(&Ident, &Type)>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&input.ident, "compound structs need named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "only structs can be stored as compounds")),
+    };
+    if fields.is_empty() {
+        return Err(Error::new_spanned(&input.ident, "a compound needs at least one field"));
+    }
+    Ok(fields.iter().map(|f| (f.ident.as_ref().expect("named field"), &f.ty)).collect())
+}
+
+fn element_codec(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = named_fields(input)?;
+    let idents: Vec<_> = fields.iter().map(|(ident, _)| *ident).collect();
+    let types: Vec<_> = fields.iter().map(|(_, ty)| *ty).collect();
+    let names: Vec<_> = idents.iter().map(|ident| ident.to_string()).collect();
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let codec = quote!(::hsds_client::codec);
+
+    Ok(quote! {
+        impl #impl_generics #codec::ElementCodec for #name #ty_generics #where_clause {
+            const BINARY_SIZE: Option<usize> =
+                #codec::packed_size(&[#(<#types as #codec::ElementCodec>::BINARY_SIZE),*]);
+
+            fn hsds_type() -> #codec::JsonValue {
+                #codec::compound_type(&[#((#names, <#types as #codec::ElementCodec>::hsds_type())),*])
+            }
+
+            fn to_json(&self) -> #codec::JsonValue {
+                #codec::JsonValue::Array(vec![#(#codec::ElementCodec::to_json(&self.#idents)),*])
+            }
+
+            fn from_json(value: &#codec::JsonValue) -> Result<Self, String> {
+                let mut fields = #codec::compound_fields(value, &[#(#names),*])?.into_iter();
+                Ok(Self {
+                    #(#idents: <#types as #codec::ElementCodec>::from_json(fields.next().unwrap_or(value))
+                        .map_err(|e| format!("field '{}': {}", #names, e))?,)*
+                })
+            }
+
+            fn put_binary<B: #codec::BufMut>(&self, out: &mut B) -> Result<(), String> {
+                #(#codec::ElementCodec::put_binary(&self.#idents, out)
+                    .map_err(|e| format!("field '{}': {}", #names, e))?;)*
+                Ok(())
+            }
+
+            #[allow(unused_assignments)]
+            fn from_binary(bytes: &[u8]) -> Result<Self, String> {
+                let mut offset = 0;
+                Ok(Self {
+                    #(#idents: {
+                        let size = <#types as #codec::ElementCodec>::BINARY_SIZE
+                            .ok_or_else(|| format!("field '{}' has no binary encoding", #names))?;
+                        let field = bytes
+                            .get(offset..offset + size)
+                            .ok_or_else(|| format!("field '{}' runs past the {}-byte element", #names, bytes.len()))?;
+                        offset += size;
+                        <#types as #codec::ElementCodec>::from_binary(field)
+                            .map_err(|e| format!("field '{}': {}", #names, e))?
+                    },)*
+                })
+            }
+        }
+    })
+}
+
+fn schema(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut schema_name = input.ident.to_string();
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("schema")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                schema_name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        })?;
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::hsds_client::schema::Schema for #name #ty_generics #where_clause {
+            const NAME: &'static str = #schema_name;
+        }
+    })
+}
@@ -0,0 +1,335 @@
+use crate::{
+    apis::dataset::DatasetApi,
+    error::{HsdsError, HsdsResult},
+    models::{DatasetValueRequest, ShapeExtendRequest},
+};
+use log::{debug, warn};
+use reqwest::Method;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Attribute used as an advisory append lock on a dataset
+///
+/// The value is a JSON string `{"owner": "<uuid>", "acquired": <epoch seconds>}`.
+/// Creating an attribute that already exists fails with 409, which makes the
+/// attribute usable as a test-and-set lock shared with other clients.
+pub const APPEND_LOCK_ATTRIBUTE: &str = "__append_lock__";
+
+/// Options for [`DatasetApi::append_coordinated`]
+#[derive(Debug, Clone)]
+pub struct AppendOptions {
+    max_retries: u32,
+    backoff: Duration,
+    lock_ttl: Option<Duration>,
+}
+
+impl Default for AppendOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            backoff: Duration::from_millis(50),
+            lock_ttl: None,
+        }
+    }
+}
+
+impl AppendOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of retries after a conflict
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Initial backoff between retries (doubled after each conflict)
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Serialize appends with the lock attribute convention
+    ///
+    /// Locks older than `ttl` are considered abandoned and broken.
+    pub fn with_lock(mut self, ttl: Duration) -> Self {
+        self.lock_ttl = Some(ttl);
+        self
+    }
+}
+
+/// Region written by an append
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendResult {
+    /// First row written (inclusive)
+    pub start: u64,
+    /// Last row written (exclusive)
+    pub stop: u64,
+    /// Number of attempts needed, including the successful one
+    pub attempts: u32,
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Append rows along the first dimension with concurrent writers
+    ///
+    /// Space is allocated with an atomic server-side extend, so each writer
+    /// gets its own region, reported back by the server as the extended
+    /// selection. A conflicting extend is retried; once an extend succeeds
+    /// the rows are written into that region only, retrying the write on a
+    /// conflict but never extending again, so no gap rows are left. Servers
+    /// that do not report the extended selection are rejected with
+    /// [`HsdsError::InvalidResponse`], as the region cannot be derived
+    /// safely from a re-read shape. With [`AppendOptions::with_lock`]
+    /// appends are additionally serialized through [`APPEND_LOCK_ATTRIBUTE`].
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset (first dimension unlimited)
+    /// * `rows` - Number of rows being appended
+    /// * `value` - Row data as a nested JSON array (outer length `rows`)
+    /// * `options` - Retry and locking options
+    pub async fn append_coordinated(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        rows: u64,
+        value: serde_json::Value,
+        options: AppendOptions,
+    ) -> HsdsResult<AppendResult> {
+        let owner = match options.lock_ttl {
+            Some(ttl) => Some(self.acquire_append_lock(domain, dataset_id, ttl, &options).await?),
+            None => None,
+        };
+        let result = self.append_allocated(domain, dataset_id, rows, &value, &options).await;
+        if let Some(owner) = owner {
+            self.release_append_lock(domain, dataset_id, &owner).await;
+        }
+        result
+    }
+
+    /// Extend once, then write into the allocated region
+    async fn append_allocated(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        rows: u64,
+        value: &serde_json::Value,
+        options: &AppendOptions,
+    ) -> HsdsResult<AppendResult> {
+        let (start, stop, mut attempts) = self.allocate_rows(domain, dataset_id, rows, options).await?;
+        let mut backoff = options.backoff;
+
+        // The region is ours from here on: retry the write, never the extend
+        loop {
+            match self.write_rows(domain, dataset_id, start, stop, value).await {
+                Ok(()) => return Ok(AppendResult { start, stop, attempts }),
+                Err(HsdsError::Conflict(msg)) if attempts <= options.max_retries => {
+                    attempts += 1;
+                    debug!("Writing rows [{}:{}] of {} conflicted: {}", start, stop, dataset_id, msg);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Extend by `rows`, retrying conflicts, and return the allocated
+    /// region with the number of attempts
+    pub(crate) async fn allocate_rows(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        rows: u64,
+        options: &AppendOptions,
+    ) -> HsdsResult<(u64, u64, u32)> {
+        let mut backoff = options.backoff;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.extend_rows(domain, dataset_id, rows).await {
+                Ok((start, stop)) => return Ok((start, stop, attempts)),
+                Err(HsdsError::Conflict(msg)) if attempts <= options.max_retries => {
+                    debug!("Extending {} conflicted (attempt {}): {}", dataset_id, attempts, msg);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Extend the dataset by `rows` and return the region the server allocated
+    async fn extend_rows(&self, domain: &str, dataset_id: &str, rows: u64) -> HsdsResult<(u64, u64)> {
+        let path = format!("/datasets/{}/shape", dataset_id);
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = crate::client::HsdsClient::with_domain(req, domain)?;
+        req = req.json(&ShapeExtendRequest { extend: rows, extend_dim: 0 });
+        let response: serde_json::Value = self.client.execute(req).await?;
+
+        let selection = response.get("selection").and_then(|s| s.as_str()).ok_or_else(|| {
+            HsdsError::InvalidResponse(format!(
+                "Extending dataset {} did not report the allocated selection", dataset_id
+            ))
+        })?;
+        let (start, stop) = parse_first_range(selection)?;
+        if stop.checked_sub(start) != Some(rows) {
+            return Err(HsdsError::InvalidResponse(format!(
+                "Extended region [{}:{}] does not match {} appended rows", start, stop, rows
+            )));
+        }
+        Ok((start, stop))
+    }
+
+    /// Write rows `start..stop` spanning the trailing dimensions
+    pub(crate) async fn write_rows(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        start: u64,
+        stop: u64,
+        value: &serde_json::Value,
+    ) -> HsdsResult<()> {
+        let dims = self.get_dataset(domain, dataset_id).await?
+            .shape
+            .and_then(|s| s.dims)
+            .filter(|dims| !dims.is_empty())
+            .ok_or_else(|| HsdsError::invalid_param(format!(
+                "Dataset {} has no dimension to append along (scalar or null dataspace)", dataset_id
+            )))?;
+
+        let write = DatasetValueRequest {
+            start: Some(std::iter::once(start).chain(dims[1..].iter().map(|_| 0)).collect()),
+            stop: Some(std::iter::once(stop).chain(dims[1..].iter().copied()).collect()),
+            step: None,
+            points: None,
+            value: Some(value.clone()),
+            value_base64: None,
+        };
+        self.write_dataset_values(domain, dataset_id, write).await.map(|_| ())
+    }
+
+    /// Take the lock attribute, breaking it if older than the TTL
+    ///
+    /// A stale lock is deleted with `If-Match` on the `ETag` it was read
+    /// with, so a fresh lock another writer took in the meantime survives.
+    /// Stale locks on servers that send no `ETag` are waited out, not broken.
+    async fn acquire_append_lock(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        ttl: Duration,
+        options: &AppendOptions,
+    ) -> HsdsResult<String> {
+        let owner = uuid::Uuid::new_v4().to_string();
+        let attributes = self.client.attributes();
+        let mut backoff = options.backoff;
+
+        for _ in 0..=options.max_retries {
+            let lock = serde_json::json!({
+                "owner": owner,
+                "acquired": now_secs(),
+            });
+            let attr = serde_json::json!({
+                "type": {"class": "H5T_STRING", "charSet": "H5T_CSET_UTF8", "strPad": "H5T_STR_NULLTERM", "length": "H5T_VARIABLE"},
+                "value": lock.to_string(),
+            });
+
+            match attributes.set_attribute_raw(domain, "datasets", dataset_id, APPEND_LOCK_ATTRIBUTE, attr).await {
+                Ok(_) => return Ok(owner),
+                Err(HsdsError::Conflict(_)) => {
+                    if let Some(etag) = self.stale_lock_etag(domain, dataset_id, ttl).await {
+                        warn!("Breaking stale append lock on dataset {}", dataset_id);
+                        if attributes
+                            .delete_attribute_if_match(domain, "datasets", dataset_id, APPEND_LOCK_ATTRIBUTE, &etag)
+                            .await?
+                        {
+                            continue;
+                        }
+                        // Another writer broke or renewed it first; wait like for a live lock
+                    }
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(HsdsError::Conflict(format!("Timed out waiting for append lock on dataset {}", dataset_id)))
+    }
+
+    /// `ETag` of the current lock if its holder acquired it longer than `ttl` ago
+    async fn stale_lock_etag(&self, domain: &str, dataset_id: &str, ttl: Duration) -> Option<String> {
+        let (attr, etag) = self.client.attributes()
+            .get_attribute_tagged(domain, "datasets", dataset_id, APPEND_LOCK_ATTRIBUTE).await
+            .ok()
+            .flatten()?;
+        let Some(etag) = etag else {
+            warn!("No ETag for the append lock on dataset {}; not breaking it", dataset_id);
+            return None;
+        };
+        let acquired = attr.get("value")
+            .and_then(|v| v.as_str())
+            .and_then(|v| serde_json::from_str::<serde_json::Value>(v).ok())
+            .and_then(|v| v.get("acquired").and_then(|a| a.as_f64()));
+
+        match acquired {
+            Some(acquired) if now_secs() - acquired <= ttl.as_secs_f64() => None,
+            // Old and unreadable lock values are treated as abandoned
+            _ => Some(etag),
+        }
+    }
+
+    /// Delete the lock attribute if we still hold it
+    ///
+    /// The delete is conditional on the `ETag` the lock was read with, when
+    /// the server sends one, so a lock another writer took after breaking
+    /// ours as stale is left alone.
+    async fn release_append_lock(&self, domain: &str, dataset_id: &str, owner: &str) {
+        let attributes = self.client.attributes();
+        let Ok(Some((attr, etag))) = attributes
+            .get_attribute_tagged(domain, "datasets", dataset_id, APPEND_LOCK_ATTRIBUTE)
+            .await
+        else {
+            return;
+        };
+        let held_by_us = attr.get("value").and_then(|v| v.as_str()).is_some_and(|v| v.contains(owner));
+        if !held_by_us {
+            return;
+        }
+
+        let released = match &etag {
+            Some(etag) => attributes
+                .delete_attribute_if_match(domain, "datasets", dataset_id, APPEND_LOCK_ATTRIBUTE, etag)
+                .await
+                .map(|_| ()),
+            None => attributes.delete_attribute(domain, "datasets", dataset_id, APPEND_LOCK_ATTRIBUTE).await,
+        };
+        if let Err(e) = released {
+            warn!("Failed to release append lock on dataset {}: {}", dataset_id, e);
+        }
+    }
+}
+
+/// Parse the first `start:stop` range of a selection like "[10:20,0:4]"
+fn parse_first_range(selection: &str) -> HsdsResult<(u64, u64)> {
+    let first = selection
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .next()
+        .unwrap_or_default();
+    let (start, stop) = first.split_once(':').ok_or_else(|| {
+        HsdsError::InvalidResponse(format!("Unexpected selection '{}'", selection))
+    })?;
+    let parse = |v: &str| v.trim().parse::<u64>().map_err(|_| {
+        HsdsError::InvalidResponse(format!("Unexpected selection '{}'", selection))
+    });
+    Ok((parse(start)?, parse(stop)?))
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
@@ -0,0 +1,169 @@
+use crate::{
+    apis::{coords, dataset::DatasetApi},
+    client::HsdsClient,
+    codec::{self, ElementCodec},
+    decoder::BinaryDecoder,
+    error::{HsdsError, HsdsResult},
+    models::Shape,
+    types,
+    values::ShapedValue,
+};
+use log::debug;
+use ndarray::{ArrayD, ArrayView, Dimension, IxDyn};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+impl<'a> DatasetApi<'a> {
+    /// Read a dataset (or a selection of it) into an array of `T`
+    ///
+    /// The array's shape comes from the dataset's shape metadata: the
+    /// dataset's dimensions, or those of the selection, in which a
+    /// dimension selected by a single index keeps length 1 (`[3,:]` of a
+    /// 10×4 dataset is 1×4). A scalar dataset gives a 0-dimensional array and
+    /// a dataset without values an empty one.
+    ///
+    /// When `T`'s type matches the dataset's, the values are read as
+    /// binary; otherwise (e.g. `f64` from an `H5T_STD_I16LE` or big-endian
+    /// dataset) they are read as JSON and converted element by element,
+    /// failing with [`HsdsError::Conversion`] on a value `T` cannot hold.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string (e.g., "[0:10,3]"); whole dataset if `None`
+    pub async fn read_dataset_ndarray<T: ElementCodec>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+    ) -> HsdsResult<ArrayD<T>> {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        let shape: Option<Shape> = dataset.get("shape").and_then(|s| serde_json::from_value(s.clone()).ok());
+        let shape = match shape {
+            Some(shape) if shape.is_null() => vec![0],
+            Some(Shape { dims: Some(dims), .. }) => coords::selection_shape(select, &dims)?,
+            _ => Vec::new(),
+        };
+        let dims: Vec<usize> = shape.iter().map(|&dim| dim as usize).collect();
+        if shape.contains(&0) {
+            return Ok(ArrayD::from_shape_vec(IxDyn(&dims), Vec::new()).expect("empty shape holds no elements"));
+        }
+
+        let binary = T::BINARY_SIZE.is_some()
+            && dataset.get("type").is_some_and(|dtype| types::compatible_json(&T::hsds_type(), dtype));
+        let elements = if binary {
+            let bytes = self.read_dataset_values_with(domain, dataset_id, select, None, None, BinaryDecoder).await?;
+            codec::decode_binary(&bytes)?
+        } else {
+            debug!("Reading dataset {} as JSON for {} elements", dataset_id, std::any::type_name::<T>());
+            let response = self.read_dataset_values_json(domain, dataset_id, select, None, None).await?;
+            ShapedValue::new(response.get("value").cloned().unwrap_or(Value::Null), shape.clone()).to_vec()?
+        };
+        ArrayD::from_shape_vec(IxDyn(&dims), elements)
+            .map_err(|e| HsdsError::InvalidResponse(format!("Values do not fit shape {:?}: {}", shape, e)))
+    }
+
+    /// Write an array into a dataset, as a block starting at `start`
+    ///
+    /// The array covers the block `[start, start + shape)` of the dataset,
+    /// with one dimension per dataset dimension; without `start` it is
+    /// written at the origin (so an array of the dataset's shape replaces
+    /// all values). Blocks reaching past the dataset's dimensions fail with
+    /// [`HsdsError::InvalidParameter`] before anything is sent. Views in any
+    /// memory order (e.g. transposed or sliced) are written in row-major
+    /// order.
+    ///
+    /// When `T`'s type matches the dataset's, the values are sent as
+    /// binary; otherwise as JSON nested to the block's shape, which the
+    /// server converts to the dataset's type.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `array` - Values to write
+    /// * `start` - Offset of the block in each dimension; the origin if `None`
+    pub async fn write_dataset_ndarray<T, D>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        array: ArrayView<'_, T, D>,
+        start: Option<&[u64]>,
+    ) -> HsdsResult<()>
+    where
+        T: ElementCodec + Clone,
+        D: Dimension,
+    {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        let shape: Option<Shape> = dataset.get("shape").and_then(|s| serde_json::from_value(s.clone()).ok());
+        if shape.as_ref().is_some_and(Shape::is_null) {
+            return Err(HsdsError::invalid_param(format!(
+                "Dataset {} has a null dataspace and holds no values",
+                dataset_id
+            )));
+        }
+        let dims = shape.and_then(|s| s.dims).unwrap_or_default();
+        let select = block_selection(&dims, array.shape(), start)?;
+        if array.is_empty() {
+            debug!("Nothing to write to dataset {}", dataset_id);
+            return Ok(());
+        }
+
+        let elements: Vec<T> = array.iter().cloned().collect();
+        let binary = T::BINARY_SIZE.is_some()
+            && dataset.get("type").is_some_and(|dtype| types::compatible_json(&T::hsds_type(), dtype));
+        if binary {
+            let payload = codec::encode_binary(&elements)?;
+            return self
+                .write_region_raw(domain, dataset_id, select.as_deref(), payload, "application/octet-stream")
+                .await;
+        }
+
+        debug!("Writing {} elements to dataset {} as JSON", elements.len(), dataset_id);
+        let block: Vec<u64> = array.shape().iter().map(|&len| len as u64).collect();
+        let body = json!({"value": codec::encode_json(&elements, &block)?});
+        let path = format!("/datasets/{}/value", dataset_id);
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        if let Some(select) = &select {
+            req = HsdsClient::with_selection(req, select);
+        }
+        self.client.execute_empty(req.json(&body)).await
+    }
+}
+
+/// Selection of the block of `block` shape at `start` in a dataset of `dims`
+///
+/// `None` for a scalar dataset, which takes a 0-dimensional array.
+fn block_selection(dims: &[u64], block: &[usize], start: Option<&[u64]>) -> HsdsResult<Option<String>> {
+    if block.len() != dims.len() {
+        return Err(HsdsError::invalid_param(format!(
+            "A {}-dimensional array cannot be written to a {}-dimensional dataset",
+            block.len(),
+            dims.len()
+        )));
+    }
+    let origin = vec![0; dims.len()];
+    let start = start.unwrap_or(&origin);
+    if start.len() != dims.len() {
+        return Err(HsdsError::invalid_param(format!(
+            "Start offset {:?} does not match the dataset's {} dimensions",
+            start,
+            dims.len()
+        )));
+    }
+    if dims.is_empty() {
+        return Ok(None);
+    }
+    let mut parts = Vec::with_capacity(dims.len());
+    for ((&offset, &len), &dim) in start.iter().zip(block).zip(dims) {
+        let end = offset + len as u64;
+        if end > dim {
+            return Err(HsdsError::invalid_param(format!(
+                "Block {:?} at {:?} exceeds dataset dimensions {:?}",
+                block, start, dims
+            )));
+        }
+        parts.push(format!("{}:{}", offset, end));
+    }
+    Ok(Some(format!("[{}]", parts.join(","))))
+}
@@ -1,12 +1,24 @@
 use crate::{
+    apis::domain::wildcard_match,
     client::HsdsClient,
-    error::HsdsResult,
+    codec::{self, ElementCodec},
+    error::{HsdsError, HsdsResult},
+    models::WriteResult,
+    names::validate_name,
+    pagination::{self, PageCursor, PaginationOptions},
 };
+use base64::{Engine, engine::general_purpose};
+use bytes::Bytes;
+use futures_util::Stream;
 use reqwest::Method;
 
+/// Attribute payload size (in bytes of element data) above which
+/// [`AttributeApi::set_attribute_array`] switches to base64 transport
+pub const BINARY_ATTRIBUTE_THRESHOLD: usize = 64 * 1024;
+
 /// Attribute API operations
 pub struct AttributeApi<'a> {
-    client: &'a HsdsClient,
+    pub(crate) client: &'a HsdsClient,
 }
 
 impl<'a> AttributeApi<'a> {
@@ -28,11 +40,104 @@ impl<'a> AttributeApi<'a> {
     ) -> HsdsResult<serde_json::Value> {
         let path = format!("/{}/{}/attributes", collection, obj_uuid);
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
 
+    /// List the Attributes of an object whose names match a glob pattern
+    ///
+    /// The pattern (`*`, `?` and classes such as `cal_[xyz]`) is sent as a
+    /// `pattern` parameter for servers that filter attributes themselves;
+    /// the listing is filtered here either way.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type ("groups", "datasets", "datatypes")
+    /// * `obj_uuid` - UUID of the object
+    /// * `pattern` - Glob pattern attribute names must match
+    pub async fn list_attributes_matching(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        pattern: &str,
+    ) -> HsdsResult<Vec<serde_json::Value>> {
+        let path = format!("/{}/{}/attributes", collection, obj_uuid);
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        req = req.query(&[("pattern", pattern)]);
+
+        let mut listing: serde_json::Value = self.client.execute(req).await?;
+        match listing.get_mut("attributes").map(serde_json::Value::take) {
+            Some(serde_json::Value::Array(attributes)) => Ok(attributes
+                .into_iter()
+                .filter(|a| a.get("name").and_then(|n| n.as_str()).is_some_and(|name| wildcard_match(pattern, name)))
+                .collect()),
+            _ => Err(HsdsError::InvalidResponse(format!("Attribute listing of {} has no attributes", path))),
+        }
+    }
+
+    /// Stream all Attributes of an object with adaptive page sizes
+    ///
+    /// Each item is an attribute as listed by the server (`name`, `type`,
+    /// `shape`, ...). Page sizes adapt as described in [`PaginationOptions`].
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type ("groups", "datasets", "datatypes")
+    /// * `obj_uuid` - UUID of the object
+    /// * `options` - Page size and limit options
+    pub fn attributes_stream(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        options: PaginationOptions,
+    ) -> impl Stream<Item = HsdsResult<serde_json::Value>> + 'a {
+        let client = self.client;
+        let path = format!("/{}/{}/attributes", collection, obj_uuid);
+        let domain = domain.to_string();
+
+        pagination::paginate(
+            options,
+            move |limit, marker| {
+                let (path, domain) = (path.clone(), domain.clone());
+                async move {
+                    let mut req = client.request(Method::GET, &path).await?;
+                    req = HsdsClient::with_domain(req, &domain)?;
+                    req = HsdsClient::with_pagination(req, Some(limit), marker.as_deref());
+                    let mut page: serde_json::Value = client.execute(req).await?;
+                    match page.get_mut("attributes").map(serde_json::Value::take) {
+                        Some(serde_json::Value::Array(attributes)) => Ok(attributes),
+                        _ => Err(HsdsError::InvalidResponse(format!("Attribute listing of {} has no attributes", path))),
+                    }
+                }
+            },
+            attribute_marker,
+        )
+    }
+
+    /// Stream all Attributes of an object, each with the cursor that resumes after it
+    ///
+    /// See [`LinkApi::links_stream_resumable`](crate::LinkApi::links_stream_resumable)
+    /// for how the cursors continue an interrupted listing.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type ("groups", "datasets", "datatypes")
+    /// * `obj_uuid` - UUID of the object
+    /// * `options` - Page size, limit and start options
+    pub fn attributes_stream_resumable(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        options: PaginationOptions,
+    ) -> impl Stream<Item = HsdsResult<(serde_json::Value, PageCursor)>> + 'a {
+        pagination::with_cursors(self.attributes_stream(domain, collection, obj_uuid, options), attribute_marker)
+    }
+
     /// Create or update an Attribute
     /// 
     /// # Arguments
@@ -48,14 +153,44 @@ impl<'a> AttributeApi<'a> {
         obj_uuid: &str,
         attr_name: &str,
         attr_data: serde_json::Value,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<WriteResult> {
+        validate_name(attr_name)?;
         let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, 
                           urlencoding::encode(attr_name));
         let mut req = self.client.request(Method::PUT, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         req = req.json(&attr_data);
 
-        self.client.execute(req).await
+        self.client.execute_write(req).await
+    }
+
+    /// Create or overwrite an Attribute (`replace=1`)
+    ///
+    /// Unlike [`set_attribute_raw`](Self::set_attribute_raw), an existing
+    /// attribute of the same name is replaced instead of failing with 409.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    /// * `attr_data` - Attribute data and type definition
+    pub async fn replace_attribute_raw(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        attr_data: serde_json::Value,
+    ) -> HsdsResult<WriteResult> {
+        validate_name(attr_name)?;
+        let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, 
+                          urlencoding::encode(attr_name));
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        req = req.query(&[("replace", "1")]).json(&attr_data);
+
+        self.client.execute_write(req).await
     }
 
     /// Get an Attribute
@@ -75,7 +210,7 @@ impl<'a> AttributeApi<'a> {
         let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, 
                           urlencoding::encode(attr_name));
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
@@ -93,16 +228,146 @@ impl<'a> AttributeApi<'a> {
         collection: &str,
         obj_uuid: &str,
         attr_name: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<()> {
         let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, 
                           urlencoding::encode(attr_name));
         let mut req = self.client.request(Method::DELETE, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
-        self.client.execute(req).await
+        self.client.execute_empty(req).await
     }
 
-    /// Convenience methods for specific object types
+    /// Check whether an Attribute exists (HEAD request, 404 maps to `false`)
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    pub async fn attribute_exists(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+    ) -> HsdsResult<bool> {
+        let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, 
+                          urlencoding::encode(attr_name));
+        let mut req = self.client.request(Method::HEAD, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+
+        self.client.execute_exists(req).await
+    }
+
+    /// Create an Attribute from raw little-endian element bytes
+    ///
+    /// The value is sent base64-encoded (`"encoding": "base64"`), which is
+    /// far more compact than a JSON array for large numeric attributes.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    /// * `hsds_type` - Predefined element type (e.g. "H5T_IEEE_F64LE")
+    /// * `shape` - Attribute dimensions
+    /// * `data` - Element bytes in row-major order
+    #[allow(clippy::too_many_arguments)]
+    pub async fn set_attribute_binary(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        hsds_type: impl Into<serde_json::Value>,
+        shape: &[u64],
+        data: &[u8],
+    ) -> HsdsResult<WriteResult> {
+        let attr_data = serde_json::json!({
+            "type": hsds_type.into(),
+            "shape": shape,
+            "value": general_purpose::STANDARD.encode(data),
+            "encoding": "base64",
+        });
+        self.set_attribute_raw(domain, collection, obj_uuid, attr_name, attr_data).await
+    }
+
+    /// Get the value of an Attribute as raw element bytes
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    pub async fn get_attribute_binary(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+    ) -> HsdsResult<Bytes> {
+        let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, 
+                          urlencoding::encode(attr_name));
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        req = req.query(&[("encoding", "base64")]);
+
+        let attr: serde_json::Value = self.client.execute(req).await?;
+        let encoded = attr.get("value").and_then(|v| v.as_str()).ok_or_else(|| {
+            HsdsError::InvalidResponse(format!("Attribute '{}' value is not base64 encoded", attr_name))
+        })?;
+        general_purpose::STANDARD
+            .decode(encoded)
+            .map(Bytes::from)
+            .map_err(|e| HsdsError::InvalidResponse(format!("Invalid base64 attribute value: {}", e)))
+    }
+
+    /// Create an array Attribute of `T` elements, choosing JSON or binary transport
+    ///
+    /// Values larger than [`BINARY_ATTRIBUTE_THRESHOLD`] bytes are sent
+    /// base64-encoded; smaller ones, and types without a binary encoding,
+    /// as a plain JSON array.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    /// * `values` - Elements in row-major order
+    /// * `shape` - Attribute dimensions (defaults to `[values.len()]`)
+    pub async fn set_attribute_array<T>(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        values: &[T],
+        shape: Option<Vec<u64>>,
+    ) -> HsdsResult<WriteResult>
+    where
+        T: ElementCodec,
+    {
+        let shape = shape.unwrap_or_else(|| vec![values.len() as u64]);
+        if shape.iter().product::<u64>() != values.len() as u64 {
+            return Err(HsdsError::invalid_param(format!(
+                "Attribute shape {:?} does not match {} values", shape, values.len()
+            )));
+        }
+
+        if T::BINARY_SIZE.is_some_and(|size| values.len() * size > BINARY_ATTRIBUTE_THRESHOLD) {
+            let data = codec::encode_binary(values)?;
+            return self.set_attribute_binary(domain, collection, obj_uuid, attr_name, T::hsds_type(), &shape, &data).await;
+        }
+
+        let attr_data = serde_json::json!({
+            "type": T::hsds_type(),
+            "shape": shape,
+            "value": codec::encode_json(values, &shape)?,
+        });
+        self.set_attribute_raw(domain, collection, obj_uuid, attr_name, attr_data).await
+    }
+
+    // Convenience methods for specific object types
 
     /// List Group attributes
     pub async fn list_group_attributes(
@@ -224,7 +489,7 @@ impl<'a> AttributeApi<'a> {
         obj_uuid: &str,
         attr_name: &str,
         value: T,
-    ) -> HsdsResult<serde_json::Value>
+    ) -> HsdsResult<WriteResult>
     where
         T: serde::Serialize,
     {
@@ -243,7 +508,7 @@ impl<'a> AttributeApi<'a> {
         // Add shape if it's an array
         if let Some(shape) = inferred_shape {
             attr_data["shape"] = serde_json::Value::Array(
-                shape.into_iter().map(|dim| serde_json::Value::from(dim)).collect()
+                shape.into_iter().map(serde_json::Value::from).collect()
             );
         }
         
@@ -261,19 +526,39 @@ impl<'a> AttributeApi<'a> {
         object_id: &str,
         attr_name: &str,
         value: T,
-    ) -> HsdsResult<serde_json::Value>
+    ) -> HsdsResult<WriteResult>
     where
         T: serde::Serialize,
     {
-        let collection = match object_id.get(0..2) {
-            Some("g-") => "groups",
-            Some("d-") => "datasets",
-            Some("t-") => "datatypes",
-            _ => return Err(crate::error::HsdsError::InvalidParameter(
-                format!("Unknown object ID format: '{}'. Expected ID to start with 'g-', 'd-', or 't-'", object_id)
-            )),
-        };
+        let collection = super::collection_for_id(object_id)?;
 
         self.set_attribute_auto(domain, collection, object_id, attr_name, value).await
     }
 }
+
+/// Marker that continues an attribute listing after `attribute`
+fn attribute_marker(attribute: &serde_json::Value) -> String {
+    attribute.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string()
+}
+
+/// Attribute creation body with the type and shape of a read `attribute` and `value`
+pub(crate) fn attribute_put_body(attribute: &serde_json::Value, value: Option<serde_json::Value>) -> serde_json::Value {
+    let mut body = serde_json::Map::new();
+    body.insert("type".to_string(), attribute.get("type").cloned().unwrap_or(serde_json::Value::Null));
+    let shape = attribute.get("shape");
+    match shape.and_then(|s| s.get("class")).and_then(|c| c.as_str()) {
+        Some("H5S_NULL") => {
+            body.insert("shape".to_string(), serde_json::json!("H5S_NULL"));
+        }
+        Some("H5S_SIMPLE") => {
+            if let Some(dims) = shape.and_then(|s| s.get("dims")) {
+                body.insert("shape".to_string(), dims.clone());
+            }
+        }
+        _ => {}
+    }
+    if let Some(value) = value {
+        body.insert("value".to_string(), value);
+    }
+    serde_json::Value::Object(body)
+}
@@ -0,0 +1,99 @@
+use crate::{
+    apis::dataset::DatasetApi,
+    client::HsdsClient,
+    decoder::BinaryDecoder,
+    error::{HsdsError, HsdsResult},
+    models::Shape,
+    values,
+};
+use log::debug;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+impl<'a> DatasetApi<'a> {
+    /// Create a boolean dataset linked under a group
+    ///
+    /// The dataset uses [`values::bool_type`], h5py's enum convention, so
+    /// Python tooling reads it back as a boolean array. Returns the id of
+    /// the new dataset.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the group to link the dataset in
+    /// * `name` - Link name of the dataset
+    /// * `dims` - Dataset dimensions
+    pub async fn create_bool_dataset(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        dims: Vec<u64>,
+    ) -> HsdsResult<String> {
+        let body = json!({
+            "type": values::bool_type(),
+            "shape": dims,
+            "link": {"id": parent_group_id, "name": name},
+        });
+        let mut req = self.client.request(Method::POST, "/datasets").await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        let created: Value = self.client.execute(req.json(&body)).await?;
+        created.get("id").and_then(Value::as_str).map(String::from).ok_or_else(|| {
+            HsdsError::InvalidResponse("Dataset create response has no id".to_string())
+        })
+    }
+
+    /// Read a boolean dataset as a flat array (row-major)
+    ///
+    /// Works on datasets of [`values::bool_type`] and on plain 8-bit integer
+    /// datasets, where any non-zero value reads as `true`.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    pub async fn read_bool(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+    ) -> HsdsResult<Vec<bool>> {
+        if self.bool_dataset_shape(domain, dataset_id).await?.is_some_and(|s| s.is_empty()) {
+            return Ok(Vec::new());
+        }
+        let bytes = self.read_dataset_values_with(domain, dataset_id, select, None, None, BinaryDecoder).await?;
+        Ok(bytes.iter().map(|&b| b != 0).collect())
+    }
+
+    /// Write booleans to a dataset (row-major) as 0/1 bytes
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `data` - Values filling the selection
+    pub async fn write_bool(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        data: &[bool],
+    ) -> HsdsResult<()> {
+        self.bool_dataset_shape(domain, dataset_id).await?;
+        debug!("Writing {} booleans to dataset {}", data.len(), dataset_id);
+        let bytes: Vec<u8> = data.iter().map(|&b| b as u8).collect();
+        self.write_region_raw(domain, dataset_id, select, bytes, "application/octet-stream").await
+    }
+
+    /// Shape of a dataset, failing unless its type holds booleans as single bytes
+    async fn bool_dataset_shape(&self, domain: &str, dataset_id: &str) -> HsdsResult<Option<Shape>> {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        let dtype = dataset.get("type").unwrap_or(&Value::Null);
+        if values::is_bool_type(dtype) {
+            Ok(dataset.get("shape").and_then(|s| serde_json::from_value(s.clone()).ok()))
+        } else {
+            Err(HsdsError::invalid_param(format!(
+                "Dataset {} has type {}, which does not hold booleans", dataset_id, dtype
+            )))
+        }
+    }
+}
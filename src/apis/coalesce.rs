@@ -0,0 +1,158 @@
+use crate::{
+    apis::dataset::DatasetApi,
+    error::{HsdsError, HsdsResult},
+};
+use log::debug;
+use serde_json::Value;
+
+/// Options for [`DatasetApi::read_points_coalesced`]
+#[derive(Debug, Clone)]
+pub struct CoalesceOptions {
+    max_gap: u64,
+    max_overread: f64,
+}
+
+impl Default for CoalesceOptions {
+    fn default() -> Self {
+        Self {
+            max_gap: 64,
+            max_overread: 4.0,
+        }
+    }
+}
+
+impl CoalesceOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest gap along the first dimension between points of one cluster
+    pub fn with_max_gap(mut self, gap: u64) -> Self {
+        self.max_gap = gap;
+        self
+    }
+
+    /// Largest ratio of bounding box elements to points read as a hyperslab
+    ///
+    /// Clusters whose bounding box holds more elements than this many times
+    /// their point count are read by point selection instead.
+    pub fn with_max_overread(mut self, ratio: f64) -> Self {
+        self.max_overread = ratio;
+        self
+    }
+}
+
+/// Points close together along the first dimension, by input index
+struct Cluster {
+    indices: Vec<usize>,
+    lower: Vec<u64>,
+    upper: Vec<u64>,
+}
+
+impl Cluster {
+    fn new(index: usize, point: &[u64]) -> Self {
+        Self { indices: vec![index], lower: point.to_vec(), upper: point.to_vec() }
+    }
+
+    fn add(&mut self, index: usize, point: &[u64]) {
+        self.indices.push(index);
+        for (axis, &x) in point.iter().enumerate() {
+            self.lower[axis] = self.lower[axis].min(x);
+            self.upper[axis] = self.upper[axis].max(x);
+        }
+    }
+
+    fn volume(&self) -> f64 {
+        self.lower.iter().zip(&self.upper).map(|(lo, hi)| (hi - lo + 1) as f64).product()
+    }
+
+    fn selection(&self) -> String {
+        let ranges: Vec<String> = self.lower.iter().zip(&self.upper).map(|(lo, hi)| format!("{}:{}", lo, hi + 1)).collect();
+        format!("[{}]", ranges.join(","))
+    }
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Read scattered points, merging nearby ones into hyperslab reads
+    ///
+    /// Points are grouped into clusters along the first dimension. A cluster
+    /// whose bounding box is small enough relative to its point count is
+    /// read as one hyperslab and its points extracted client-side; all
+    /// remaining points are read with a single point selection. Values are
+    /// returned in the order of `points`.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `points` - Coordinates to read (all of the dataset's rank)
+    /// * `options` - Clustering thresholds
+    pub async fn read_points_coalesced(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        points: &[Vec<u64>],
+        options: &CoalesceOptions,
+    ) -> HsdsResult<Vec<Value>> {
+        let rank = points.first().map(Vec::len).unwrap_or_default();
+        if rank == 0 || points.iter().any(|p| p.len() != rank) {
+            return Err(HsdsError::invalid_param("Points must be non-empty and all of the same rank"));
+        }
+
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by_key(|&i| &points[i]);
+        let mut clusters: Vec<Cluster> = Vec::new();
+        for i in order {
+            match clusters.last_mut() {
+                Some(cluster) if points[i][0] - cluster.upper[0] <= options.max_gap => cluster.add(i, &points[i]),
+                _ => clusters.push(Cluster::new(i, &points[i])),
+            }
+        }
+
+        let mut values: Vec<Value> = vec![Value::Null; points.len()];
+        let mut scattered: Vec<usize> = Vec::new();
+        let mut requests = 0;
+        for cluster in clusters {
+            if cluster.indices.len() < 2 || cluster.volume() > options.max_overread * cluster.indices.len() as f64 {
+                scattered.extend(cluster.indices);
+                continue;
+            }
+            requests += 1;
+            let block = self.read_dataset_values_json(domain, dataset_id, Some(&cluster.selection()), None, None).await?;
+            let block = block.get("value").unwrap_or(&Value::Null);
+            for &i in &cluster.indices {
+                values[i] = extract(block, &points[i], &cluster.lower)?;
+            }
+        }
+
+        if !scattered.is_empty() {
+            requests += 1;
+            let selected: Vec<Vec<u64>> = scattered.iter().map(|&i| points[i].clone()).collect();
+            let response = self.read_dataset_points(domain, dataset_id, selected).await?;
+            let read = response.get("value").and_then(Value::as_array).ok_or_else(|| {
+                HsdsError::InvalidResponse("Point selection response has no value array".to_string())
+            })?;
+            if read.len() != scattered.len() {
+                return Err(HsdsError::InvalidResponse(format!(
+                    "Point selection returned {} values for {} points", read.len(), scattered.len()
+                )));
+            }
+            for (&i, value) in scattered.iter().zip(read) {
+                values[i] = value.clone();
+            }
+        }
+
+        debug!("Read {} points of {} in {} requests", points.len(), dataset_id, requests);
+        Ok(values)
+    }
+}
+
+/// Element of a hyperslab block (as nested JSON arrays) at a point
+fn extract(block: &Value, point: &[u64], lower: &[u64]) -> HsdsResult<Value> {
+    let mut current = block;
+    for (axis, (&x, &lo)) in point.iter().zip(lower).enumerate() {
+        current = current.get((x - lo) as usize).ok_or_else(|| {
+            HsdsError::InvalidResponse(format!("Hyperslab block too small along dimension {} for point {:?}", axis, point))
+        })?;
+    }
+    Ok(current.clone())
+}
@@ -0,0 +1,111 @@
+use crate::{
+    apis::dataset::DatasetApi,
+    client::HsdsClient,
+    codec::{self, ElementCodec},
+    decoder::BinaryDecoder,
+    error::{HsdsError, HsdsResult},
+    values,
+};
+use bytes::Bytes;
+use log::debug;
+use num_complex::Complex;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+/// Floating-point component of a complex element (`f32` or `f64`)
+///
+/// `Complex<T>` is stored through its [`ElementCodec`] as the h5py `{r, i}`
+/// compound of two `T` components.
+pub trait ComplexComponent: ElementCodec + Copy {}
+
+impl ComplexComponent for f32 {}
+
+impl ComplexComponent for f64 {}
+
+impl<'a> DatasetApi<'a> {
+    /// Create a complex dataset linked under a group
+    ///
+    /// The dataset uses [`values::complex_type`] with components of `T`, so
+    /// h5py reads it back as complex64 (`f32`) or complex128 (`f64`).
+    /// Returns the id of the new dataset.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the group to link the dataset in
+    /// * `name` - Link name of the dataset
+    /// * `dims` - Dataset dimensions
+    pub async fn create_complex_dataset<T: ComplexComponent>(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        dims: Vec<u64>,
+    ) -> HsdsResult<String> {
+        let body = json!({
+            "type": Complex::<T>::hsds_type(),
+            "shape": dims,
+            "link": {"id": parent_group_id, "name": name},
+        });
+        let mut req = self.client.request(Method::POST, "/datasets").await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        let created: Value = self.client.execute(req.json(&body)).await?;
+        created.get("id").and_then(Value::as_str).map(String::from).ok_or_else(|| {
+            HsdsError::InvalidResponse("Dataset create response has no id".to_string())
+        })
+    }
+
+    /// Read a complex dataset as a flat array (row-major)
+    ///
+    /// The dataset must follow the h5py `{r, i}` compound convention with
+    /// components of exactly `T`, e.g. `read_complex::<f32>` for complex64.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    pub async fn read_complex<T: ComplexComponent>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+    ) -> HsdsResult<Vec<Complex<T>>> {
+        self.check_complex_type::<T>(domain, dataset_id).await?;
+        let bytes = self.read_dataset_values_with(domain, dataset_id, select, None, None, BinaryDecoder).await?;
+        codec::decode_binary(&bytes)
+    }
+
+    /// Write complex values to a dataset (row-major) as packed `{r, i}` pairs
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `data` - Values filling the selection
+    pub async fn write_complex<T: ComplexComponent>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        data: &[Complex<T>],
+    ) -> HsdsResult<()> {
+        self.check_complex_type::<T>(domain, dataset_id).await?;
+        debug!("Writing {} complex values to dataset {}", data.len(), dataset_id);
+        let payload = Bytes::from(codec::encode_binary(data)?);
+        self.write_region_raw(domain, dataset_id, select, payload, "application/octet-stream").await
+    }
+
+    /// Fail unless the dataset is a complex compound with components of `T`
+    async fn check_complex_type<T: ComplexComponent>(&self, domain: &str, dataset_id: &str) -> HsdsResult<()> {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        let dtype = dataset.get("type").unwrap_or(&Value::Null);
+        match values::complex_base(dtype) {
+            Some(base) if T::hsds_type() == base => Ok(()),
+            Some(base) => Err(HsdsError::invalid_param(format!(
+                "Dataset {} has complex components of type {}, not {}", dataset_id, base, T::hsds_type()
+            ))),
+            None => Err(HsdsError::invalid_param(format!(
+                "Dataset {} has type {}, which is not an {{r, i}} complex compound", dataset_id, dtype
+            ))),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{
+    apis::attribute::{attribute_put_body, AttributeApi},
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+};
+use log::{debug, info, warn};
+use reqwest::Method;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Options for conditional updates, [`AttributeApi::update_attribute_if_with`]
+/// and `DatasetApi::update_region_with` (with the `ndarray` feature)
+#[derive(Debug, Clone)]
+pub struct UpdateOptions {
+    pub(crate) max_retries: u32,
+    pub(crate) backoff: Duration,
+    pub(crate) require_etag: bool,
+}
+
+impl Default for UpdateOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: Duration::from_millis(100),
+            require_etag: false,
+        }
+    }
+}
+
+impl UpdateOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of re-reads after a concurrent modification
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Initial delay before a retry (doubled on each retry)
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Fail with [`HsdsError::OperationFailed`] instead of writing
+    /// unconditionally when the server sends no `ETag`
+    pub fn with_require_etag(mut self, required: bool) -> Self {
+        self.require_etag = required;
+        self
+    }
+}
+
+/// Outcome of [`AttributeApi::update_attribute_if`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeSwap {
+    /// The predicate held and the new value was written
+    Swapped,
+    /// The predicate did not hold for the current value; nothing was written
+    Rejected { current: Value },
+}
+
+impl AttributeSwap {
+    pub fn is_swapped(&self) -> bool {
+        matches!(self, Self::Swapped)
+    }
+}
+
+impl<'a> AttributeApi<'a> {
+    /// Replace an attribute's value only if the current value satisfies `predicate`
+    ///
+    /// A compare-and-swap for attributes several writers update, such as a
+    /// run status. See [`update_attribute_if_with`](Self::update_attribute_if_with)
+    /// for the concurrency behavior.
+    ///
+    /// ```no_run
+    /// # use hsds_client::{AttributeSwap, HsdsClient, NoAuth};
+    /// # use serde_json::json;
+    /// # async fn run(client: HsdsClient) -> hsds_client::HsdsResult<()> {
+    /// let claimed = client.attributes()
+    ///     .update_attribute_if("/home/runs.h5", "groups", "g-1", "status", |v| v == "queued", json!("running"))
+    ///     .await?;
+    /// if let AttributeSwap::Rejected { current } = claimed {
+    ///     println!("Run already {}", current);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    /// * `predicate` - Check on the current value
+    /// * `new_value` - Value written when the check passes
+    pub async fn update_attribute_if<P>(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        predicate: P,
+        new_value: Value,
+    ) -> HsdsResult<AttributeSwap>
+    where
+        P: FnMut(&Value) -> bool,
+    {
+        self.update_attribute_if_with(domain, collection, obj_uuid, attr_name, &UpdateOptions::default(), predicate, new_value)
+            .await
+    }
+
+    /// Conditionally replace an attribute's value, with retry options
+    ///
+    /// The attribute is read and `predicate` is called on its value; if it
+    /// returns `false` the current value is returned as
+    /// [`AttributeSwap::Rejected`]. Otherwise the new value is written with
+    /// the attribute's type and shape. When the server returns an `ETag`
+    /// with the attribute, the write is sent with `If-Match`, and a write
+    /// rejected because the attribute changed in between (`412` or `409`)
+    /// re-reads it and checks `predicate` again, so no concurrent write is
+    /// lost.
+    ///
+    /// Servers that send no `ETag` for attributes get a read-check-write:
+    /// a write landing between the read and the write is overwritten. The
+    /// window is one round trip; use [`UpdateOptions::with_require_etag`] to
+    /// fail instead where that is not acceptable.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    /// * `options` - Retry options
+    /// * `predicate` - Check on the current value
+    /// * `new_value` - Value written when the check passes
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_attribute_if_with<P>(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        options: &UpdateOptions,
+        mut predicate: P,
+        new_value: Value,
+    ) -> HsdsResult<AttributeSwap>
+    where
+        P: FnMut(&Value) -> bool,
+    {
+        let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, urlencoding::encode(attr_name));
+        let mut backoff = options.backoff;
+        let mut attempts = 0;
+        loop {
+            let mut req = self.client.request(Method::GET, &path).await?;
+            req = HsdsClient::with_domain(req, domain)?;
+            let (attribute, etag): (Value, _) = self.client.execute_tagged(req).await?;
+            let current = attribute.get("value").cloned().unwrap_or(Value::Null);
+            if !predicate(&current) {
+                debug!("Attribute '{}' of {} is {}; not updated", attr_name, obj_uuid, current);
+                return Ok(AttributeSwap::Rejected { current });
+            }
+            match &etag {
+                None if options.require_etag => {
+                    return Err(HsdsError::OperationFailed(format!(
+                        "Server sent no ETag for attribute '{}' of {}; refusing an unconditional write",
+                        attr_name, obj_uuid
+                    )))
+                }
+                None => warn!("No ETag for attribute '{}' of {}; writing without If-Match", attr_name, obj_uuid),
+                Some(_) => {}
+            }
+
+            let mut req = self.client.request(Method::PUT, &path).await?;
+            req = HsdsClient::with_domain(req, domain)?;
+            if let Some(etag) = &etag {
+                req = req.header(reqwest::header::IF_MATCH, etag);
+            }
+            req = req.query(&[("replace", "1")]).json(&attribute_put_body(&attribute, Some(new_value.clone())));
+
+            match self.client.execute_write(req).await {
+                Ok(_) => return Ok(AttributeSwap::Swapped),
+                Err(HsdsError::Conflict(message)) if etag.is_some() && attempts < options.max_retries => {
+                    attempts += 1;
+                    info!("Attribute '{}' of {} changed during update ({}); retrying in {:?}", attr_name, obj_uuid, message, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Read an attribute with its `ETag`, `None` if it does not exist
+    pub(crate) async fn get_attribute_tagged(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+    ) -> HsdsResult<Option<(Value, Option<String>)>> {
+        let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, urlencoding::encode(attr_name));
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        match self.client.execute_tagged(req).await {
+            Ok(tagged) => Ok(Some(tagged)),
+            Err(HsdsError::ObjectNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write an attribute only if it is unchanged since it was read with `etag`
+    ///
+    /// An existing attribute is replaced, sent with `If-Match` if it came
+    /// with an `ETag`; a missing one is created, which fails if another
+    /// writer created it first. Returns `false` when the write lost to a
+    /// concurrent one (`412`/`409`).
+    pub(crate) async fn put_attribute_if_match(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        body: Value,
+        existing: Option<Option<&str>>,
+    ) -> HsdsResult<bool> {
+        let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, urlencoding::encode(attr_name));
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        if let Some(etag) = existing {
+            req = req.query(&[("replace", "1")]);
+            if let Some(etag) = etag {
+                req = req.header(reqwest::header::IF_MATCH, etag);
+            }
+        }
+        match self.client.execute_write(req.json(&body)).await {
+            Ok(_) => Ok(true),
+            Err(HsdsError::Conflict(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Delete an attribute only if it is unchanged since it was read with `etag`
+    ///
+    /// Returns `false` if it changed (`412`/`409`) or is already gone.
+    pub(crate) async fn delete_attribute_if_match(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        etag: &str,
+    ) -> HsdsResult<bool> {
+        let path = format!("/{}/{}/attributes/{}", collection, obj_uuid, urlencoding::encode(attr_name));
+        let mut req = self.client.request(Method::DELETE, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?.header(reqwest::header::IF_MATCH, etag);
+        match self.client.execute_empty(req).await {
+            Ok(()) => Ok(true),
+            Err(HsdsError::Conflict(_)) | Err(HsdsError::ObjectNotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
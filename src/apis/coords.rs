@@ -0,0 +1,195 @@
+use crate::{
+    apis::{
+        dataset::DatasetApi,
+        scaling::{ReadOptions, Scaling, UNITS_ATTRIBUTE},
+        search::{object_attributes, scalar},
+    },
+    error::{HsdsError, HsdsResult},
+    models::Shape,
+};
+use log::debug;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Attribute of a dataset listing the dimension scales attached to each dimension
+pub const DIMENSION_LIST_ATTRIBUTE: &str = "DIMENSION_LIST";
+
+/// Attribute naming a dimension scale
+const SCALE_NAME_ATTRIBUTE: &str = "NAME";
+
+/// Coordinates along one dimension of a read
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    /// Dimension scale dataset, `None` if no scale is attached
+    pub scale_id: Option<String>,
+    /// Scale's `NAME` attribute
+    pub name: Option<String>,
+    /// Scale's `units` attribute
+    pub units: Option<String>,
+    /// Coordinates of the selected positions (the indices without a scale)
+    pub values: Vec<Value>,
+}
+
+/// Values of a selection with the coordinates of each dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinatedValues {
+    /// Values as nested arrays
+    pub values: Value,
+    /// Dataset's `units` attribute
+    pub units: Option<String>,
+    /// One axis per dataset dimension
+    pub axes: Vec<Axis>,
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Read values together with the coordinates of each dimension
+    ///
+    /// Coordinates come from the dimension scales listed in the dataset's
+    /// [`DIMENSION_LIST_ATTRIBUTE`] (the first scale of a dimension is
+    /// used), read over the same range as the dimension's part of the
+    /// selection. Dimensions without a scale get their selected indices. A
+    /// dimension selected by a single index keeps its axis with one
+    /// coordinate, as the values keep it with length 1.
+    /// With scaling enabled in `options`, the dataset's and each scale's
+    /// own `scale_factor`/`add_offset` are applied.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string (e.g., "[0:100,5:10]"); whole dataset if `None`
+    /// * `options` - Conventions to apply
+    pub async fn read_with_coords(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        options: &ReadOptions,
+    ) -> HsdsResult<CoordinatedValues> {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        let dims: Vec<u64> = dataset
+            .get("shape")
+            .and_then(|s| serde_json::from_value::<Shape>(s.clone()).ok())
+            .and_then(|s| s.dims)
+            .unwrap_or_default();
+        let parts = split_selection(select, dims.len())?;
+
+        let attributes = object_attributes(self.client, domain, dataset_id).await?;
+        let scale_ids = attached_scales(&attributes, dims.len());
+        let mut values = self.read_values(domain, dataset_id, select, &ReadOptions::new()).await?;
+        if options.applies_scaling() {
+            Scaling::from_attributes(&attributes)?.apply_json(&mut values);
+        }
+
+        let mut axes = Vec::with_capacity(dims.len());
+        for (axis, (&len, part)) in dims.iter().zip(&parts).enumerate() {
+            let Some(scale_id) = scale_ids.get(axis).cloned().flatten() else {
+                axes.push(Axis { scale_id: None, name: None, units: None, values: indices(part, len)? });
+                continue;
+            };
+            let scale_attributes = object_attributes(self.client, domain, &scale_id).await?;
+            let range = (!part.is_empty()).then(|| format!("[{}]", part));
+            let mut coordinates = self.read_values(domain, &scale_id, range.as_deref(), &ReadOptions::new()).await?;
+            if options.applies_scaling() {
+                Scaling::from_attributes(&scale_attributes)?.apply_json(&mut coordinates);
+            }
+            axes.push(Axis {
+                scale_id: Some(scale_id),
+                name: string_attribute(&scale_attributes, SCALE_NAME_ATTRIBUTE),
+                units: string_attribute(&scale_attributes, UNITS_ATTRIBUTE),
+                values: match coordinates {
+                    Value::Array(items) => items,
+                    single => vec![single],
+                },
+            });
+        }
+
+        debug!("Read dataset {} with {} coordinate axes", dataset_id, axes.len());
+        Ok(CoordinatedValues {
+            values,
+            units: string_attribute(&attributes, UNITS_ATTRIBUTE),
+            axes,
+        })
+    }
+}
+
+/// Per-dimension parts of a selection ("" for a whole dimension)
+pub(crate) fn split_selection(select: Option<&str>, rank: usize) -> HsdsResult<Vec<String>> {
+    let Some(selection) = select else {
+        return Ok(vec![String::new(); rank]);
+    };
+    let inner = selection.trim().trim_start_matches('[').trim_end_matches(']');
+    let parts: Vec<String> = inner.split(',').map(|p| p.trim().to_string()).collect();
+    if parts.len() != rank {
+        return Err(HsdsError::invalid_param(format!(
+            "Selection '{}' has {} dimensions; the dataset has {}", selection, parts.len(), rank
+        )));
+    }
+    Ok(parts)
+}
+
+/// Indices selected by one dimension's part of a selection
+pub(crate) fn indices(part: &str, len: u64) -> HsdsResult<Vec<Value>> {
+    let (start, stop, step) = part_range(part, len)?;
+    Ok((start..stop).step_by(step as usize).map(Value::from).collect())
+}
+
+/// Shape of the values a selection reads from a dataset of `dims`
+///
+/// A dimension selected by a single index (`"3"`) keeps length 1, as HSDS
+/// reads it like `"3:4"`, so the shape always has the dataset's rank.
+pub(crate) fn selection_shape(select: Option<&str>, dims: &[u64]) -> HsdsResult<Vec<u64>> {
+    let parts = split_selection(select, dims.len())?;
+    parts
+        .iter()
+        .zip(dims)
+        .map(|(part, &len)| {
+            let (start, stop, step) = part_range(part, len)?;
+            Ok(stop.saturating_sub(start).div_ceil(step))
+        })
+        .collect()
+}
+
+/// `(start, stop, step)` of one dimension's part of a selection, clamped to `len`
+fn part_range(part: &str, len: u64) -> HsdsResult<(u64, u64, u64)> {
+    let invalid = || HsdsError::invalid_param(format!("Unsupported selection '{}' for coordinates", part));
+    let bound = |s: &str, default: u64| -> HsdsResult<u64> {
+        if s.trim().is_empty() { Ok(default) } else { s.trim().parse().map_err(|_| invalid()) }
+    };
+    let fields: Vec<&str> = part.split(':').collect();
+    let (start, stop, step) = match fields.as_slice() {
+        [""] => (0, len, 1),
+        [index] => {
+            let index = bound(index, 0)?;
+            (index, index + 1, 1)
+        }
+        [start, stop] => (bound(start, 0)?, bound(stop, len)?, 1),
+        [start, stop, step] => (bound(start, 0)?, bound(stop, len)?, bound(step, 1)?.max(1)),
+        _ => return Err(invalid()),
+    };
+    Ok((start.min(len), stop.min(len), step))
+}
+
+/// Dataset id of the first scale attached to each dimension
+fn attached_scales(attributes: &HashMap<String, Value>, rank: usize) -> Vec<Option<String>> {
+    let Some(Value::Array(per_dim)) = attributes.get(DIMENSION_LIST_ATTRIBUTE) else {
+        return vec![None; rank];
+    };
+    per_dim
+        .iter()
+        .map(|refs| {
+            let first = match refs {
+                Value::Array(refs) => refs.first()?,
+                single => single,
+            };
+            // References are "datasets/<id>" (or a bare id)
+            let id = first.as_str()?.rsplit('/').next()?;
+            id.starts_with("d-").then(|| id.to_string())
+        })
+        .chain(std::iter::repeat(None))
+        .take(rank)
+        .collect()
+}
+
+fn string_attribute(attributes: &HashMap<String, Value>, name: &str) -> Option<String> {
+    attributes.get(name).map(scalar).and_then(Value::as_str).map(String::from)
+}
@@ -0,0 +1,864 @@
+use crate::{
+    apis::{attribute::{attribute_put_body, AttributeApi}, collection_for_id, domain::{wildcard_match, DomainApi}, group::GroupApi},
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::{Acl, DatasetValueRequest, Link, LinkClass, LinkCreateRequest, LinkOrder},
+    transfer::{TransferReport, TransferStats},
+};
+use futures_util::TryStreamExt;
+use log::{debug, info, warn};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// An object created by a copy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CopiedObject {
+    pub source_id: String,
+    pub target_id: String,
+}
+
+/// A copied link whose name was already taken in the target group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkCollision {
+    /// Target group holding the existing link
+    pub group_id: String,
+    /// Name of the source link
+    pub name: String,
+    /// Name the copy was linked under, `None` if it was skipped
+    pub linked_as: Option<String>,
+}
+
+/// Record of a copy in progress
+///
+/// Every object is recorded as soon as it is created, and marked complete
+/// once its values, attributes and links are copied. The journal is
+/// serializable so it can be persisted between attempts: passing the same
+/// journal to another copy call resumes where the previous one stopped,
+/// and [`GroupApi::rollback_copy`] deletes everything it recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CopyJournal {
+    /// Domain the objects are created in
+    pub target_domain: Option<String>,
+    /// Created objects in creation order
+    pub created: Vec<CopiedObject>,
+    /// Source ids whose contents are fully copied
+    pub completed: BTreeSet<String>,
+    /// Link to the copied tree in a pre-existing group, as (group id, name)
+    pub top_link: Option<(String, String)>,
+    /// Whether the copy ran to completion
+    pub finished: bool,
+    /// Name collisions resolved by the [`NameCollision`] policy
+    #[serde(default)]
+    pub collisions: Vec<LinkCollision>,
+    /// Requests, bytes and latencies of the last copy call (including any rollback)
+    #[serde(skip)]
+    pub transfer: Option<TransferReport>,
+}
+
+impl CopyJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id of the copy of a source object, if it was created
+    pub fn target_of(&self, source_id: &str) -> Option<&str> {
+        self.created
+            .iter()
+            .find(|o| o.source_id == source_id)
+            .map(|o| o.target_id.as_str())
+    }
+
+    fn record(&mut self, source_id: &str, target_id: &str) {
+        self.created.push(CopiedObject {
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+        });
+    }
+
+    fn record_collision(&mut self, collision: LinkCollision) {
+        // A resumed copy meets the collisions of earlier attempts again, and
+        // a name taken during the copy updates where the link ended up
+        match self.collisions.iter_mut().find(|c| c.group_id == collision.group_id && c.name == collision.name) {
+            Some(existing) => *existing = collision,
+            None => self.collisions.push(collision),
+        }
+    }
+
+    /// Whether the copy created the object with this target id
+    fn created_target(&self, target_id: &str) -> bool {
+        self.created.iter().any(|o| o.target_id == target_id)
+    }
+}
+
+/// What to do with an attribute that already exists on the target object
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttributeOverwrite {
+    /// Replace it with the source attribute
+    #[default]
+    Replace,
+    /// Keep the target's attribute
+    Skip,
+    /// Stop with a `Conflict` error
+    Fail,
+}
+
+/// What to do with a copied link whose name is taken in the target group
+///
+/// A link the copy itself made (e.g. by an earlier attempt of a resumed
+/// copy) is not a collision.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameCollision {
+    /// Stop with a `Conflict` error
+    #[default]
+    Error,
+    /// Keep the existing link and do not copy the object
+    ///
+    /// A name taken by someone else only after the object was copied fails
+    /// with `Conflict`, so the copy is not left unlinked.
+    Skip,
+    /// Replace the existing link (the object it pointed to is only unlinked)
+    Overwrite,
+    /// Link the copy under the first free name `<name>_1`, `<name>_2`, ...
+    Rename,
+}
+
+/// Options for [`GroupApi::copy_group`] and [`AttributeApi::copy_attributes`]
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    rollback_on_error: bool,
+    include_attributes: Vec<String>,
+    exclude_attributes: Vec<String>,
+    attribute_overwrite: AttributeOverwrite,
+    name_collision: NameCollision,
+    copy_acls: bool,
+    acl_users: HashMap<String, String>,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            rollback_on_error: true,
+            include_attributes: Vec::new(),
+            exclude_attributes: Vec::new(),
+            attribute_overwrite: AttributeOverwrite::Replace,
+            name_collision: NameCollision::Error,
+            copy_acls: false,
+            acl_users: HashMap::new(),
+        }
+    }
+}
+
+impl CopyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delete all created objects if the copy fails (the default)
+    ///
+    /// When disabled, a failed copy leaves its objects in place and the
+    /// journal can be passed to a later call to resume.
+    pub fn with_rollback_on_error(mut self, rollback: bool) -> Self {
+        self.rollback_on_error = rollback;
+        self
+    }
+
+    /// Copy only attributes matching one of these patterns (`*` and `?` wildcards)
+    ///
+    /// Without include patterns every attribute is copied.
+    pub fn with_include_attributes<S: Into<String>>(mut self, patterns: impl IntoIterator<Item = S>) -> Self {
+        self.include_attributes = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Never copy attributes matching one of these patterns (`*` and `?` wildcards)
+    ///
+    /// Exclusion wins over inclusion.
+    pub fn with_exclude_attributes<S: Into<String>>(mut self, patterns: impl IntoIterator<Item = S>) -> Self {
+        self.exclude_attributes = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Policy for attributes already present on the target (default: replace)
+    pub fn with_attribute_overwrite(mut self, policy: AttributeOverwrite) -> Self {
+        self.attribute_overwrite = policy;
+        self
+    }
+
+    /// Policy for links whose name is taken in the target group (default: error)
+    ///
+    /// Collisions are recorded in the [`CopyJournal`]. For the link to the
+    /// copied tree itself, `Skip` fails like `Error`.
+    pub fn with_name_collision(mut self, policy: NameCollision) -> Self {
+        self.name_collision = policy;
+        self
+    }
+
+    /// Also copy the source domain's ACLs onto the target domain (default: off)
+    ///
+    /// ACLs are applied once the objects are copied; this needs `readACL`
+    /// on the source and `updateACL` on the target domain.
+    pub fn with_acls(mut self, copy: bool) -> Self {
+        self.copy_acls = copy;
+        self
+    }
+
+    /// Rename ACL users on the way, as (source user, target user) pairs
+    ///
+    /// Users mapped to an empty name are left out; unmapped users keep
+    /// their name.
+    pub fn with_acl_user_map<S: Into<String>>(mut self, map: impl IntoIterator<Item = (S, S)>) -> Self {
+        self.acl_users = map.into_iter().map(|(from, to)| (from.into(), to.into())).collect();
+        self
+    }
+
+    /// Name an ACL user of the source domain gets on the target, `None` if left out
+    pub fn acl_user(&self, user: &str) -> Option<String> {
+        match self.acl_users.get(user) {
+            Some(mapped) if mapped.is_empty() => None,
+            Some(mapped) => Some(mapped.clone()),
+            None => Some(user.to_string()),
+        }
+    }
+
+    /// Whether the include/exclude patterns select an attribute name
+    pub fn copies_attribute(&self, name: &str) -> bool {
+        (self.include_attributes.is_empty() || self.include_attributes.iter().any(|p| wildcard_match(p, name)))
+            && !self.exclude_attributes.iter().any(|p| wildcard_match(p, name))
+    }
+}
+
+impl<'a> AttributeApi<'a> {
+    /// Copy the attributes of an object onto another, possibly in another domain
+    ///
+    /// Attributes are selected by the include/exclude patterns of `options`
+    /// and written with their type, shape and value; an attribute already on
+    /// the target is handled by the options' [`AttributeOverwrite`] policy.
+    /// Returns the names of the attributes written, in listing order.
+    ///
+    /// # Arguments
+    /// * `source_domain` - Domain of the source object
+    /// * `source_id` - UUID of the group, dataset or datatype to copy from
+    /// * `target_domain` - Domain of the target object
+    /// * `target_id` - UUID of the group, dataset or datatype to copy onto
+    /// * `options` - Attribute filters and overwrite policy
+    pub async fn copy_attributes(
+        &self,
+        source_domain: &str,
+        source_id: &str,
+        target_domain: &str,
+        target_id: &str,
+        options: &CopyOptions,
+    ) -> HsdsResult<Vec<String>> {
+        copy_attributes(self.client, source_domain, source_id, target_domain, target_id, options, options.attribute_overwrite)
+            .await
+    }
+}
+
+impl<'a> DomainApi<'a> {
+    /// Copy the ACLs of one domain onto another
+    ///
+    /// Every user's ACL is read from the source and set on the target under
+    /// the name given by the options' user map, so permission setups survive
+    /// a migration to another server or user base. Permissions the target
+    /// grants beyond the copied ones are left as they are. Returns the
+    /// target users written, sorted.
+    ///
+    /// # Arguments
+    /// * `source_domain` - Domain to read the ACLs from
+    /// * `target_domain` - Domain to set the ACLs on
+    /// * `options` - User map
+    pub async fn copy_acls(&self, source_domain: &str, target_domain: &str, options: &CopyOptions) -> HsdsResult<Vec<String>> {
+        let acls = self.get_acls(source_domain).await?;
+        let mut mapped: Vec<(String, Acl)> = acls.users
+            .into_iter()
+            .filter_map(|(user, acl)| options.acl_user(&user).map(|target| (target, acl)))
+            .collect();
+        mapped.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (user, acl) in &mapped {
+            self.set_acl(target_domain, user, acl).await?;
+        }
+        debug!("Copied {} ACLs from {} to {}", mapped.len(), source_domain, target_domain);
+        Ok(mapped.into_iter().map(|(user, _)| user).collect())
+    }
+}
+
+impl<'a> GroupApi<'a> {
+    /// Copy a group tree, possibly into another domain
+    ///
+    /// Sub-groups, datasets (with values), committed datatypes, attributes
+    /// and soft/external links are copied; objects reachable by several
+    /// links are copied once and linked again. Dataset values are copied as
+    /// JSON in one request per dataset. Attributes are filtered by the
+    /// options' include/exclude patterns and always replaced, so that
+    /// resuming rewrites the attributes of an interrupted object. Links
+    /// whose name is already taken in the target are handled by the
+    /// options' [`NameCollision`] policy. With [`CopyOptions::with_acls`]
+    /// the source domain's ACLs are copied last.
+    ///
+    /// Progress is recorded in `journal`. If the copy fails and rollback is
+    /// enabled, everything created so far is deleted; otherwise calling
+    /// again with the same journal resumes the copy.
+    /// Either way the journal's [`transfer`](CopyJournal::transfer) report
+    /// holds the requests, bytes and latencies of the call.
+    /// 
+    /// # Arguments
+    /// * `source_domain` - Domain of the group to copy
+    /// * `source_group_id` - UUID of the group to copy
+    /// * `target_domain` - Domain to copy into
+    /// * `target_parent_id` - UUID of the group the copy is linked into
+    /// * `name` - Link name of the copy
+    /// * `journal` - Copy journal (new, or from a failed attempt)
+    /// * `options` - Copy options
+    #[allow(clippy::too_many_arguments)]
+    pub async fn copy_group(
+        &self,
+        source_domain: &str,
+        source_group_id: &str,
+        target_domain: &str,
+        target_parent_id: &str,
+        name: &str,
+        journal: &mut CopyJournal,
+        options: &CopyOptions,
+    ) -> HsdsResult<String> {
+        info!("Copying group {} from {} to {}", source_group_id, source_domain, target_domain);
+        check_journal(journal, target_domain)?;
+        let stats = TransferStats::new();
+        let client = self.client.clone().with_transfer_stats(stats.clone());
+        let groups = client.groups();
+
+        let result = async {
+            let target_id = match journal.target_of(source_group_id) {
+                Some(id) => id.to_string(),
+                None => {
+                    let created = groups.create_group(target_domain, None).await?;
+                    journal.record(source_group_id, &created.id);
+                    created.id
+                }
+            };
+            if journal.top_link.is_none() {
+                let linked_as =
+                    link_target(&client, target_domain, target_parent_id, name, &target_id, options.name_collision, journal)
+                        .await?;
+                journal.top_link = Some((target_parent_id.to_string(), linked_as));
+            }
+            groups.copy_contents(source_domain, source_group_id, target_domain, &target_id, journal, options).await?;
+            if options.copy_acls {
+                client.domains().copy_acls(source_domain, target_domain, options).await?;
+            }
+            Ok(target_id)
+        }
+        .await;
+
+        let result = groups.finish_copy(target_domain, journal, options, result).await;
+        journal.transfer = Some(stats.report());
+        result
+    }
+
+    /// Copy the contents of a group into an existing group, possibly in another domain
+    ///
+    /// Like [`copy_group`](Self::copy_group), but the links and attributes
+    /// of the source group are merged into `target_group_id` instead of a
+    /// new group, e.g. to import a file's root into an existing domain.
+    /// Links whose name is already taken are handled by the options'
+    /// [`NameCollision`] policy and listed in the journal. Rollback deletes
+    /// the created objects; links replaced by `Overwrite` are not restored.
+    ///
+    /// # Arguments
+    /// * `source_domain` - Domain of the group to copy
+    /// * `source_group_id` - UUID of the group whose contents are copied
+    /// * `target_domain` - Domain to copy into
+    /// * `target_group_id` - UUID of the existing group receiving the contents
+    /// * `journal` - Copy journal (new, or from a failed attempt)
+    /// * `options` - Copy options
+    pub async fn copy_group_contents(
+        &self,
+        source_domain: &str,
+        source_group_id: &str,
+        target_domain: &str,
+        target_group_id: &str,
+        journal: &mut CopyJournal,
+        options: &CopyOptions,
+    ) -> HsdsResult<()> {
+        info!("Copying contents of group {} from {} into {} in {}", source_group_id, source_domain, target_group_id, target_domain);
+        check_journal(journal, target_domain)?;
+        let stats = TransferStats::new();
+        let client = self.client.clone().with_transfer_stats(stats.clone());
+        let groups = client.groups();
+
+        let result = async {
+            groups.copy_contents(source_domain, source_group_id, target_domain, target_group_id, journal, options).await?;
+            if options.copy_acls {
+                client.domains().copy_acls(source_domain, target_domain, options).await?;
+            }
+            Ok(())
+        }
+        .await;
+        let result = groups.finish_copy(target_domain, journal, options, result).await;
+        journal.transfer = Some(stats.report());
+        result
+    }
+
+    /// Delete the objects and top-level link recorded in a copy journal
+    ///
+    /// Objects are deleted newest first. Failures are logged and skipped so
+    /// that as much as possible is removed; the journal is reset afterwards.
+    /// 
+    /// # Arguments
+    /// * `journal` - Journal of the copy to undo
+    pub async fn rollback_copy(&self, journal: &mut CopyJournal) -> HsdsResult<()> {
+        let Some(domain) = journal.target_domain.clone() else {
+            return Ok(());
+        };
+        warn!("Rolling back copy into {} ({} objects)", domain, journal.created.len());
+
+        if let Some((parent, name)) = &journal.top_link {
+            if let Err(e) = self.client.links().delete_link(&domain, parent, name).await {
+                warn!("Failed to delete link '{}' in {}: {}", name, parent, e);
+            }
+        }
+        for object in journal.created.iter().rev() {
+            let result = match collection_for_id(&object.target_id) {
+                Ok("groups") => self.delete_group(&domain, &object.target_id).await,
+                Ok("datasets") => self.client.datasets().delete_dataset(&domain, &object.target_id).await,
+                Ok(_) => self.client.datatypes().delete_datatype(&domain, &object.target_id).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Failed to delete {} during rollback: {}", object.target_id, e);
+            }
+        }
+        *journal = CopyJournal::default();
+        Ok(())
+    }
+
+    /// Copy the links and attributes of a group into an existing group
+    pub(crate) async fn copy_contents(
+        &self,
+        source_domain: &str,
+        source_group_id: &str,
+        target_domain: &str,
+        target_group_id: &str,
+        journal: &mut CopyJournal,
+        options: &CopyOptions,
+    ) -> HsdsResult<()> {
+        let mut pending = VecDeque::from([(source_group_id.to_string(), target_group_id.to_string())]);
+        let mut visited: HashSet<String> = HashSet::from([source_group_id.to_string()]);
+
+        while let Some((source_gid, target_gid)) = pending.pop_front() {
+            let links: Vec<_> = self.client.links()
+                .links_stream(source_domain, &source_gid, LinkOrder::CreateOrder)
+                .try_collect()
+                .await?;
+
+            // Only groups the copy did not create can hold links of their own
+            let mut taken: HashMap<String, LinkCreateRequest> = if journal.created_target(&target_gid) {
+                HashMap::new()
+            } else {
+                self.client.links()
+                    .links_stream(target_domain, &target_gid, LinkOrder::Name)
+                    .map_ok(|l| (l.title.clone(), link_request(&l)))
+                    .try_collect()
+                    .await?
+            };
+
+            for link in links {
+                let hard_source = match (&link.class, &link.id) {
+                    (Some(LinkClass::Soft) | Some(LinkClass::External), _) | (_, None) => None,
+                    (_, Some(source_id)) => Some(source_id),
+                };
+                let mut request = match hard_source {
+                    Some(source_id) => LinkCreateRequest {
+                        id: journal.target_of(source_id).map(String::from),
+                        h5path: None,
+                        h5domain: None,
+                    },
+                    None => link_request(&link),
+                };
+
+                let name = match taken.get(&link.title) {
+                    Some(existing) if !same_target(existing, &request) => {
+                        let linked_as = match options.name_collision {
+                            NameCollision::Error => {
+                                return Err(HsdsError::Conflict(format!(
+                                    "Link '{}' already exists in group {}", link.title, target_gid
+                                )))
+                            }
+                            NameCollision::Skip => None,
+                            NameCollision::Overwrite => {
+                                self.client.links().delete_link(target_domain, &target_gid, &link.title).await?;
+                                Some(link.title.clone())
+                            }
+                            NameCollision::Rename => Some(free_name(&taken, &link.title, &request)),
+                        };
+                        debug!("Link '{}' in {} is taken; linking the copy as {:?}", link.title, target_gid, linked_as);
+                        journal.record_collision(LinkCollision {
+                            group_id: target_gid.clone(),
+                            name: link.title.clone(),
+                            linked_as: linked_as.clone(),
+                        });
+                        match linked_as {
+                            Some(name) => name,
+                            None => continue,
+                        }
+                    }
+                    _ => link.title.clone(),
+                };
+
+                if let Some(source_id) = hard_source {
+                    let target_id = self.copy_object(source_domain, source_id, target_domain, journal, options).await?;
+                    if source_id.starts_with("g-") && visited.insert(source_id.clone()) {
+                        pending.push_back((source_id.clone(), target_id.clone()));
+                    }
+                    request.id = Some(target_id);
+                }
+                let linked_as = self
+                    .create_copied_link(target_domain, &target_gid, &link.title, name, &request, &mut taken, journal, options)
+                    .await?;
+                if let Some(name) = linked_as {
+                    taken.insert(name, request);
+                }
+            }
+
+            if !journal.completed.contains(&source_gid) {
+                copy_attributes(self.client, source_domain, &source_gid, target_domain, &target_gid, options, AttributeOverwrite::Replace)
+                    .await?;
+                journal.completed.insert(source_gid);
+            }
+        }
+        Ok(())
+    }
+
+    /// Link a copy under `name`, applying the collision policy if the name
+    /// was taken after the target's links were listed
+    ///
+    /// A link to the same target (e.g. made by an earlier attempt of a
+    /// resumed copy) counts as created. Under `Skip` a hard link fails with
+    /// `Conflict` instead, so the copied object is not left unlinked.
+    /// Returns the name linked under, `None` if skipped.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_copied_link(
+        &self,
+        target_domain: &str,
+        target_gid: &str,
+        source_name: &str,
+        mut name: String,
+        request: &LinkCreateRequest,
+        taken: &mut HashMap<String, LinkCreateRequest>,
+        journal: &mut CopyJournal,
+        options: &CopyOptions,
+    ) -> HsdsResult<Option<String>> {
+        let links = self.client.links();
+        let mut collided = false;
+        for attempt in 1.. {
+            let message = match links.create_link(target_domain, target_gid, &name, request.clone()).await {
+                Ok(_) => break,
+                Err(HsdsError::Conflict(message)) if attempt < MAX_COLLISION_ATTEMPTS => message,
+                Err(e) => return Err(e),
+            };
+            let existing = links.get_link(target_domain, target_gid, &name).await?;
+            let existing = link_request(&serde_json::from_value(existing.get("link").cloned().unwrap_or_default())?);
+            if same_target(&existing, request) {
+                break;
+            }
+            debug!("Link '{}' in {} was taken during the copy", name, target_gid);
+            taken.insert(name.clone(), existing);
+            collided = true;
+            match options.name_collision {
+                NameCollision::Skip if request.id.is_none() => {
+                    journal.record_collision(LinkCollision {
+                        group_id: target_gid.to_string(),
+                        name: source_name.to_string(),
+                        linked_as: None,
+                    });
+                    return Ok(None);
+                }
+                NameCollision::Error | NameCollision::Skip => {
+                    return Err(HsdsError::Conflict(format!(
+                        "Link '{}' already exists in group {}: {}", name, target_gid, message
+                    )))
+                }
+                NameCollision::Overwrite => links.delete_link(target_domain, target_gid, &name).await?,
+                NameCollision::Rename => name = free_name(taken, source_name, request),
+            }
+        }
+        if collided {
+            journal.record_collision(LinkCollision {
+                group_id: target_gid.to_string(),
+                name: source_name.to_string(),
+                linked_as: Some(name.clone()),
+            });
+        }
+        Ok(Some(name))
+    }
+
+    /// Record the outcome of a copy, rolling back on failure if requested
+    pub(crate) async fn finish_copy<T>(
+        &self,
+        target_domain: &str,
+        journal: &mut CopyJournal,
+        options: &CopyOptions,
+        result: HsdsResult<T>,
+    ) -> HsdsResult<T> {
+        match result {
+            Ok(value) => {
+                journal.finished = true;
+                debug!("Copy into {} finished ({} objects)", target_domain, journal.created.len());
+                Ok(value)
+            }
+            Err(e) => {
+                if options.rollback_on_error {
+                    self.rollback_copy(journal).await?;
+                } else {
+                    warn!("Copy into {} failed after {} objects; journal kept for resume", target_domain, journal.created.len());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Create (or look up) the copy of a dataset or datatype, or an empty group
+    async fn copy_object(
+        &self,
+        source_domain: &str,
+        source_id: &str,
+        target_domain: &str,
+        journal: &mut CopyJournal,
+        options: &CopyOptions,
+    ) -> HsdsResult<String> {
+        let collection = collection_for_id(source_id)?;
+        let target_id = match journal.target_of(source_id) {
+            Some(id) => id.to_string(),
+            None => {
+                let body = match collection {
+                    "groups" => json!({}),
+                    "datasets" => dataset_create_body(&get_json(self.client, source_domain, &format!("/datasets/{}", source_id)).await?),
+                    _ => {
+                        let source = get_json(self.client, source_domain, &format!("/datatypes/{}", source_id)).await?;
+                        json!({"type": source.get("type").cloned().unwrap_or(Value::Null)})
+                    }
+                };
+                let mut req = self.client.request(Method::POST, &format!("/{}", collection)).await?;
+                req = HsdsClient::with_domain(req, target_domain)?;
+                let created: Value = self.client.execute(req.json(&body)).await?;
+                let id = created.get("id").and_then(|id| id.as_str()).ok_or_else(|| {
+                    HsdsError::InvalidResponse(format!("Create response for copy of {} has no id", source_id))
+                })?;
+                journal.record(source_id, id);
+                id.to_string()
+            }
+        };
+
+        // Groups are completed when their links are walked
+        if collection != "groups" && !journal.completed.contains(source_id) {
+            if collection == "datasets" {
+                copy_values(self.client, source_domain, source_id, target_domain, &target_id).await?;
+            }
+            copy_attributes(self.client, source_domain, source_id, target_domain, &target_id, options, AttributeOverwrite::Replace)
+                .await?;
+            journal.completed.insert(source_id.to_string());
+        }
+        Ok(target_id)
+    }
+}
+
+/// Bind a journal to its target domain, rejecting reuse for another one
+pub(crate) fn check_journal(journal: &mut CopyJournal, target_domain: &str) -> HsdsResult<()> {
+    match &journal.target_domain {
+        Some(domain) if domain != target_domain => Err(HsdsError::invalid_param(format!(
+            "Copy journal belongs to domain '{}', not '{}'", domain, target_domain
+        ))),
+        Some(_) => Ok(()),
+        None => {
+            journal.target_domain = Some(target_domain.to_string());
+            Ok(())
+        }
+    }
+}
+
+/// Hard-link an object, accepting an existing link to the same object
+///
+/// A name taken by another link is handled by `policy`; returns the name
+/// the object was linked under.
+async fn link_target(
+    client: &HsdsClient,
+    domain: &str,
+    group_id: &str,
+    name: &str,
+    target_id: &str,
+    policy: NameCollision,
+    journal: &mut CopyJournal,
+) -> HsdsResult<String> {
+    let mut candidate = name.to_string();
+    let mut collided = false;
+    for attempt in 1.. {
+        let message = match client.links().create_hard_link(domain, group_id, &candidate, target_id).await {
+            Ok(_) => break,
+            Err(HsdsError::Conflict(message)) => message,
+            Err(e) => return Err(e),
+        };
+        let existing = client.links().get_link(domain, group_id, &candidate).await?;
+        if existing.get("link").and_then(|l| l.get("id")).and_then(|id| id.as_str()) == Some(target_id) {
+            break;
+        }
+        match policy {
+            NameCollision::Overwrite if attempt == 1 => client.links().delete_link(domain, group_id, name).await?,
+            NameCollision::Rename => candidate = format!("{}_{}", name, attempt),
+            _ => {
+                return Err(HsdsError::Conflict(format!(
+                    "Link '{}' already exists in group {}: {}", candidate, group_id, message
+                )))
+            }
+        }
+        collided = true;
+    }
+
+    if collided {
+        journal.record_collision(LinkCollision {
+            group_id: group_id.to_string(),
+            name: name.to_string(),
+            linked_as: Some(candidate.clone()),
+        });
+    }
+    Ok(candidate)
+}
+
+/// Names tried for one link before a persistent `Conflict` is returned
+pub(crate) const MAX_COLLISION_ATTEMPTS: usize = 1000;
+
+/// Creation body reproducing where a link points
+fn link_request(link: &Link) -> LinkCreateRequest {
+    match (&link.class, &link.id) {
+        (Some(LinkClass::Soft) | Some(LinkClass::External), _) | (_, None) => LinkCreateRequest {
+            id: None,
+            h5path: link.h5path.clone(),
+            h5domain: link.h5domain.clone(),
+        },
+        (_, Some(id)) => LinkCreateRequest { id: Some(id.clone()), h5path: None, h5domain: None },
+    }
+}
+
+/// Whether two links point to the same place
+fn same_target(a: &LinkCreateRequest, b: &LinkCreateRequest) -> bool {
+    a.id == b.id && a.h5path == b.h5path && a.h5domain == b.h5domain
+}
+
+/// First `<name>_<n>` that is free, or already links the same target
+fn free_name(taken: &HashMap<String, LinkCreateRequest>, name: &str, request: &LinkCreateRequest) -> String {
+    (1..)
+        .map(|n| format!("{}_{}", name, n))
+        .find(|candidate| taken.get(candidate).is_none_or(|existing| same_target(existing, request)))
+        .expect("only finitely many names are taken")
+}
+
+async fn get_json(client: &HsdsClient, domain: &str, path: &str) -> HsdsResult<Value> {
+    let mut req = client.request(Method::GET, path).await?;
+    req = HsdsClient::with_domain(req, domain)?;
+    client.execute(req).await
+}
+
+/// Dataset creation body reproducing a dataset's type, shape and properties
+pub(crate) fn dataset_create_body(source: &Value) -> Value {
+    let mut body = serde_json::Map::new();
+    body.insert("type".to_string(), source.get("type").cloned().unwrap_or(Value::Null));
+
+    let shape = source.get("shape");
+    match shape.and_then(|s| s.get("class")).and_then(|c| c.as_str()) {
+        Some("H5S_NULL") => {
+            body.insert("shape".to_string(), json!("H5S_NULL"));
+        }
+        Some("H5S_SCALAR") | None => {}
+        Some(_) => {
+            if let Some(dims) = shape.and_then(|s| s.get("dims")) {
+                body.insert("shape".to_string(), dims.clone());
+            }
+            if let Some(maxdims) = shape.and_then(|s| s.get("maxdims")) {
+                body.insert("maxdims".to_string(), maxdims.clone());
+            }
+        }
+    }
+    if let Some(properties) = source.get("creationProperties") {
+        body.insert("creationProperties".to_string(), properties.clone());
+    }
+    Value::Object(body)
+}
+
+/// Copy all values of a dataset (nothing for null or empty dataspaces)
+async fn copy_values(client: &HsdsClient, source_domain: &str, source_id: &str, target_domain: &str, target_id: &str) -> HsdsResult<()> {
+    let source = get_json(client, source_domain, &format!("/datasets/{}", source_id)).await?;
+    let shape = source.get("shape");
+    if shape.and_then(|s| s.get("class")).and_then(|c| c.as_str()) == Some("H5S_NULL") {
+        return Ok(());
+    }
+    let dims: Vec<u64> = shape
+        .and_then(|s| s.get("dims"))
+        .and_then(|d| serde_json::from_value(d.clone()).ok())
+        .unwrap_or_default();
+    if dims.contains(&0) {
+        return Ok(());
+    }
+
+    let values = client.datasets().read_dataset_values_json(source_domain, source_id, None, None, None).await?;
+    let request = DatasetValueRequest {
+        start: None,
+        stop: None,
+        step: None,
+        points: None,
+        value: values.get("value").cloned(),
+        value_base64: None,
+    };
+    client.datasets().write_dataset_values(target_domain, target_id, request).await?;
+    Ok(())
+}
+
+/// Copy the attributes of an object selected by `options`, returning the names written
+async fn copy_attributes(
+    client: &HsdsClient,
+    source_domain: &str,
+    source_id: &str,
+    target_domain: &str,
+    target_id: &str,
+    options: &CopyOptions,
+    overwrite: AttributeOverwrite,
+) -> HsdsResult<Vec<String>> {
+    let collection = collection_for_id(source_id)?;
+    let target_collection = collection_for_id(target_id)?;
+    let listing = client.attributes().list_attributes(source_domain, collection, source_id).await?;
+    let names: Vec<String> = listing
+        .get("attributes")
+        .and_then(|a| a.as_array())
+        .map(|attrs| attrs.iter().filter_map(|a| a.get("name").and_then(|n| n.as_str()).map(String::from)).collect())
+        .unwrap_or_default();
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut copied = Vec::new();
+    for name in names {
+        if !options.copies_attribute(&name) || !seen.insert(name.clone()) {
+            continue;
+        }
+        let attribute = client.attributes().get_attribute(source_domain, collection, source_id, &name).await?;
+        let body = attribute_put_body(&attribute, attribute.get("value").cloned());
+        let attributes = client.attributes();
+        let result = match overwrite {
+            AttributeOverwrite::Replace => {
+                attributes.replace_attribute_raw(target_domain, target_collection, target_id, &name, body).await
+            }
+            _ => attributes.set_attribute_raw(target_domain, target_collection, target_id, &name, body).await,
+        };
+        match result {
+            Ok(_) => copied.push(name),
+            Err(HsdsError::Conflict(_)) if overwrite == AttributeOverwrite::Skip => {
+                debug!("Kept existing attribute '{}' of {}", name, target_id);
+            }
+            Err(HsdsError::Conflict(message)) => {
+                return Err(HsdsError::Conflict(format!(
+                    "Attribute '{}' already exists on {}: {}", name, target_id, message
+                )));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(copied)
+}
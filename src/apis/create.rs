@@ -0,0 +1,157 @@
+use crate::{
+    apis::dataset::DatasetApi,
+    chunking::{self, AccessPattern},
+    codec::{self, ElementCodec},
+    error::{HsdsError, HsdsResult},
+    models::{Dataset, DatasetCreateRequest, LinkRequest, ShapeSpec},
+    raw::RawWriteBuffer,
+};
+use log::debug;
+
+/// Options for [`DatasetApi::create_dataset_with_data`]
+#[derive(Debug, Clone)]
+pub struct CreateOptions {
+    chunk_threshold_bytes: usize,
+    access: AccessPattern,
+    deflate: Option<u32>,
+    target_request_bytes: usize,
+}
+
+impl Default for CreateOptions {
+    fn default() -> Self {
+        Self {
+            chunk_threshold_bytes: chunking::DEFAULT_TARGET_CHUNK_BYTES,
+            access: AccessPattern::RowStreaming,
+            deflate: None,
+            target_request_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl CreateOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a chunked layout when the data is larger than this many bytes
+    pub fn with_chunk_threshold_bytes(mut self, bytes: usize) -> Self {
+        self.chunk_threshold_bytes = bytes;
+        self
+    }
+
+    /// Access pattern the chunk shape is chosen for
+    pub fn with_access(mut self, access: AccessPattern) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Compress chunks with deflate at this level (implies a chunked layout)
+    pub fn with_deflate(mut self, level: u32) -> Self {
+        self.deflate = Some(level);
+        self
+    }
+
+    /// Approximate payload size of each write (at least one row is sent)
+    pub fn with_target_request_bytes(mut self, bytes: usize) -> Self {
+        self.target_request_bytes = bytes.max(1);
+        self
+    }
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Create a dataset holding `data` and link it into a group, in one call
+    ///
+    /// The HSDS type is taken from `T`, which needs a binary encoding
+    /// ([`ElementCodec::BINARY_SIZE`]). Data larger than the options' chunk
+    /// threshold (or compressed) gets a chunked layout from
+    /// [`chunking::recommend`]. The values are then written as binary region
+    /// writes of whole rows along the first dimension. If a write fails the
+    /// dataset is left in place, linked and partially written.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the group to link the dataset into
+    /// * `name` - Link name of the dataset
+    /// * `data` - Values in row-major order
+    /// * `dims` - Dataset dimensions (empty for a scalar)
+    /// * `options` - Layout and request sizing
+    pub async fn create_dataset_with_data<T: ElementCodec>(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        data: &[T],
+        dims: &[u64],
+        options: &CreateOptions,
+    ) -> HsdsResult<Dataset> {
+        let count = dims.iter().product::<u64>();
+        if count != data.len() as u64 {
+            return Err(HsdsError::invalid_param(format!(
+                "{} values do not fill dimensions {:?} ({} elements)", data.len(), dims, count
+            )));
+        }
+
+        let size = codec::binary_size::<T>()?;
+
+        let mut request = DatasetCreateRequest {
+            data_type: serde_json::from_value(T::hsds_type())?,
+            shape: Some(ShapeSpec::Dimensions(dims.to_vec())),
+            maxdims: None,
+            creation_properties: None,
+            link: Some(LinkRequest { id: parent_group_id.to_string(), name: name.to_string() }),
+        };
+        if data.len() * size > options.chunk_threshold_bytes || options.deflate.is_some() {
+            request = request.auto_chunk(options.access);
+        }
+        if let Some(level) = options.deflate {
+            request = request.with_deflate(level);
+        }
+        let dataset = self.create_dataset(domain, request).await?;
+        debug!("Created dataset {} as '{}'; writing {} values", dataset.id, name, data.len());
+        if data.is_empty() {
+            return Ok(dataset);
+        }
+
+        let mut buffer = RawWriteBuffer::new();
+        let Some((_, row_dims)) = dims.split_first() else {
+            self.write_region_raw(domain, &dataset.id, None, buffer.encode(data)?, "application/octet-stream").await?;
+            return Ok(dataset);
+        };
+        let row_len = row_dims.iter().product::<u64>() as usize;
+        let batch_rows = (options.target_request_bytes / (row_len * size).max(1)).max(1);
+        let trailing = ",:".repeat(row_dims.len());
+        for (i, batch) in data.chunks(batch_rows * row_len).enumerate() {
+            let first = (i * batch_rows) as u64;
+            let selection = format!("[{}:{}{}]", first, first + (batch.len() / row_len) as u64, trailing);
+            self.write_region_raw(domain, &dataset.id, Some(&selection), buffer.encode(batch)?, "application/octet-stream")
+                .await?;
+        }
+        Ok(dataset)
+    }
+
+    /// Create a dataset holding an array and link it into a group, in one call
+    ///
+    /// The dataset takes the array's shape; see
+    /// [`create_dataset_with_data`](Self::create_dataset_with_data).
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the group to link the dataset into
+    /// * `name` - Link name of the dataset
+    /// * `array` - Values to store
+    /// * `options` - Layout and request sizing
+    #[cfg(feature = "ndarray")]
+    pub async fn create_dataset_from_array<T: ElementCodec + Clone>(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        array: &ndarray::ArrayD<T>,
+        options: &CreateOptions,
+    ) -> HsdsResult<Dataset> {
+        let dims: Vec<u64> = array.shape().iter().map(|&d| d as u64).collect();
+        let standard = array.as_standard_layout();
+        let data = standard.as_slice().expect("standard layout arrays are contiguous");
+        self.create_dataset_with_data(domain, parent_group_id, name, data, &dims, options).await
+    }
+}
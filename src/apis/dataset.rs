@@ -1,16 +1,25 @@
 use crate::{
+    auth::BasicAuth,
+    chunking::{self, AccessPattern},
     client::HsdsClient,
-    error::HsdsResult,
-    models::{Dataset, Datasets, DatasetCreateRequest, DatasetValueRequest, ShapeUpdateRequest, 
-             StringDataType, DataTypeSpec, ShapeSpec, StringCharSet, StringPadding, StringLength, LinkRequest},
+    decoder::{JsonDecoder, ResponseDecoder},
+    error::{HsdsError, HsdsResult},
+    handle::DatasetHandle,
+    json_stream::ValueArrayParser,
+    names::validate_name,
+    models::{Dataset, Datasets, DatasetCreateRequest, DatasetValueRequest, PointsRequest, ShapeUpdateRequest, ResizeKind, ResizeOutcome, Shape, StorageInfo,
+             H5Type, ShapeSpec, LinkRequest, WriteResult},
 };
-use reqwest::Method;
+use futures_util::TryStreamExt;
+use reqwest::{Method, RequestBuilder};
 use bytes::Bytes;
+use serde::de::DeserializeOwned;
 use log::debug;
+use url::Url;
 
 /// Dataset API operations  
 pub struct DatasetApi<'a> {
-    client: &'a HsdsClient,
+    pub(crate) client: &'a HsdsClient,
 }
 
 impl<'a> DatasetApi<'a> {
@@ -28,20 +37,100 @@ impl<'a> DatasetApi<'a> {
         domain: &str,
         request: DatasetCreateRequest,
     ) -> HsdsResult<Dataset> {
+        if let Some(link) = &request.link {
+            validate_name(&link.name)?;
+        }
 
         let mut req = self.client.request(Method::POST, "/datasets").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         req = req.json(&request);
 
         debug!("Creating dataset in domain: {}", domain);
-        debug!("DatasetCreateRequest: {:?}", request);
+        debug!("DatasetCreateRequest: {}", self.client.log_options().payload(&request));
 
         let result = self.client.execute(req).await;
 
         if let Err(ref err) = result {
             debug!("Error details: {:?}", err);
         }
-        return result
+        result
+    }
+
+    /// Get an existing Dataset by link name, or create it if missing
+    ///
+    /// Resolves `name` in the parent group. If it links to a dataset whose
+    /// type and shape are compatible with `request`, that dataset is returned;
+    /// an incompatible or non-dataset object is a `Conflict` error. Otherwise
+    /// the dataset is created and linked as `name`, so re-running an ingestion
+    /// script does not create duplicates. If another writer creates the link
+    /// concurrently, its dataset is checked and returned the same way.
+    ///
+    /// Without a shape `request` matches only a scalar dataset. A fixed-size
+    /// dataset must have the requested dims; a resizable one the requested
+    /// maxdims and rank, since its dims may have changed since creation.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the parent group
+    /// * `name` - Link name of the dataset in the parent group
+    /// * `request` - Dataset creation parameters (the link is set from `parent_group_id`/`name`)
+    pub async fn get_or_create_dataset(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        mut request: DatasetCreateRequest,
+    ) -> HsdsResult<Dataset> {
+        if let Some(existing) = self.resolve_child_dataset(domain, parent_group_id, name, &request).await? {
+            return Ok(existing);
+        }
+
+        request.link = Some(LinkRequest {
+            id: parent_group_id.to_string(),
+            name: name.to_string(),
+        });
+        match self.create_dataset(domain, request.clone()).await {
+            Ok(dataset) => Ok(dataset),
+            // Lost a creation race: the link now exists
+            Err(HsdsError::Conflict(_)) => self
+                .resolve_child_dataset(domain, parent_group_id, name, &request)
+                .await?
+                .ok_or_else(|| HsdsError::Conflict(format!(
+                    "Link '{}' in group {} conflicts but cannot be resolved", name, parent_group_id
+                ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Resolve a child link to a dataset compatible with `request`, `None` if the link does not exist
+    async fn resolve_child_dataset(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        request: &DatasetCreateRequest,
+    ) -> HsdsResult<Option<Dataset>> {
+        let link = match self.client.links().get_link(domain, parent_group_id, name).await {
+            Ok(link) => link,
+            Err(HsdsError::ObjectNotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let target = link.get("link")
+            .and_then(|l| l.get("id"))
+            .and_then(|id| id.as_str())
+            .filter(|id| id.starts_with("d-"))
+            .ok_or_else(|| HsdsError::Conflict(format!(
+                "Link '{}' in group {} does not refer to a dataset", name, parent_group_id
+            )))?;
+
+        let existing = self.get_dataset(domain, target).await?;
+        if let Some(reason) = incompatibility(&existing, request) {
+            return Err(HsdsError::Conflict(format!(
+                "Existing dataset '{}' ({}) is incompatible: {}", name, existing.id, reason
+            )));
+        }
+        debug!("Reusing existing dataset {} for link '{}'", existing.id, name);
+        Ok(Some(existing))
     }
 
     /// List all Datasets in Domain
@@ -50,7 +139,7 @@ impl<'a> DatasetApi<'a> {
     /// * `domain` - Domain path
     pub async fn list_datasets(&self, domain: &str) -> HsdsResult<Datasets> {
         let mut req = self.client.request(Method::GET, "/datasets").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
@@ -67,12 +156,52 @@ impl<'a> DatasetApi<'a> {
     ) -> HsdsResult<Dataset> {
         let path = format!("/datasets/{}", dataset_id);
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
 
+    /// Open a Dataset as a handle with cached metadata
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    pub async fn open(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+    ) -> HsdsResult<DatasetHandle<'a>> {
+        let info = self.get_dataset(domain, dataset_id).await?;
+        Ok(DatasetHandle::new(self.client, domain, info))
+    }
+
+    /// Get storage statistics of a Dataset
+    ///
+    /// Uses the verbose dataset GET, which reports the allocated size and
+    /// the number of written chunks. Servers that do not compute these leave
+    /// the corresponding fields `None`.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    pub async fn storage_info(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+    ) -> HsdsResult<StorageInfo> {
+        let path = format!("/datasets/{}", dataset_id);
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        req = req.query(&[("verbose", "1")]);
+
+        let dataset: Dataset = self.client.execute(req).await?;
+        Ok(StorageInfo::from(&dataset))
+    }
+
     /// Delete a Dataset
+    ///
+    /// Links to the dataset are left in place, pointing at nothing; use
+    /// [`delete_dataset_with`](Self::delete_dataset_with) to remove them too.
     /// 
     /// # Arguments
     /// * `domain` - Domain path
@@ -81,12 +210,12 @@ impl<'a> DatasetApi<'a> {
         &self,
         domain: &str,
         dataset_id: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<()> {
         let path = format!("/datasets/{}", dataset_id);
         let mut req = self.client.request(Method::DELETE, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
-        self.client.execute(req).await
+        self.client.execute_empty(req).await
     }
 
     /// Get Dataset shape information
@@ -101,7 +230,7 @@ impl<'a> DatasetApi<'a> {
     ) -> HsdsResult<serde_json::Value> {
         let path = format!("/datasets/{}/shape", dataset_id);
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
@@ -117,13 +246,64 @@ impl<'a> DatasetApi<'a> {
         domain: &str,
         dataset_id: &str,
         request: ShapeUpdateRequest,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<WriteResult> {
         let path = format!("/datasets/{}/shape", dataset_id);
         let mut req = self.client.request(Method::PUT, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         req = req.json(&request);
 
-        self.client.execute(req).await
+        self.client.execute_write(req).await
+    }
+
+    /// Resize a Dataset with validation
+    ///
+    /// Checks the new shape against the dataset's rank and `maxdims` (`0` is
+    /// unlimited) before sending it. Shrinking any dimension discards data,
+    /// so it is rejected unless `allow_shrink` is set.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `new_shape` - New dimensions
+    /// * `allow_shrink` - Permit dimensions to shrink
+    pub async fn resize_dataset(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        new_shape: Vec<u64>,
+        allow_shrink: bool,
+    ) -> HsdsResult<ResizeOutcome> {
+        let dataset = self.get_dataset(domain, dataset_id).await?;
+        let shape = dataset.shape.ok_or_else(|| {
+            HsdsError::InvalidResponse(format!("Dataset {} has no shape information", dataset_id))
+        })?;
+        if shape.is_null() {
+            return Err(HsdsError::invalid_param(format!(
+                "Dataset {} has a null dataspace and cannot be resized", dataset_id
+            )));
+        }
+        let current = shape.dims.clone().unwrap_or_default();
+
+        let kind = check_resize(&current, shape.maxdims.as_deref(), &new_shape)?;
+        if kind.shrinks() && !allow_shrink {
+            return Err(HsdsError::invalid_param(format!(
+                "Resizing dataset {} from {:?} to {:?} shrinks it and discards data; pass allow_shrink to confirm",
+                dataset_id, current, new_shape
+            )));
+        }
+        if kind == ResizeKind::Unchanged {
+            return Ok(ResizeOutcome { previous: current.clone(), shape: current, kind });
+        }
+
+        debug!("Resizing dataset {} from {:?} to {:?} ({:?})", dataset_id, current, new_shape, kind);
+        self.update_dataset_shape(domain, dataset_id, ShapeUpdateRequest { shape: new_shape.clone() }).await?;
+
+        let effective = self.get_dataset(domain, dataset_id).await?
+            .shape
+            .and_then(|s| s.dims)
+            .unwrap_or(new_shape);
+
+        Ok(ResizeOutcome { previous: current, shape: effective, kind })
     }
 
     /// Get Dataset type information
@@ -138,12 +318,15 @@ impl<'a> DatasetApi<'a> {
     ) -> HsdsResult<serde_json::Value> {
         let path = format!("/datasets/{}/type", dataset_id);
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
 
     /// Write values to Dataset
+    ///
+    /// Writing an empty array to a dataset without values (null dataspace
+    /// or a zero-length dimension) succeeds with an empty result.
     /// 
     /// # Arguments
     /// * `domain` - Domain path
@@ -154,16 +337,131 @@ impl<'a> DatasetApi<'a> {
         domain: &str,
         dataset_id: &str,
         request: DatasetValueRequest,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<WriteResult> {
         let path = format!("/datasets/{}/value", dataset_id);
         let mut req = self.client.request(Method::PUT, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         req = req.json(&request);
 
-        self.client.execute(req).await
+        let empty = request.value.as_ref().is_some_and(|v| v.as_array().is_some_and(Vec::is_empty))
+            || request.value_base64.as_deref() == Some("");
+        match self.client.execute_write(req).await {
+            Err(HsdsError::InvalidParameter(message)) if empty => {
+                if self.holds_no_values(domain, dataset_id).await? {
+                    debug!("Skipped empty write to dataset {} without values", dataset_id);
+                    Ok(WriteResult::default())
+                } else {
+                    Err(HsdsError::InvalidParameter(message))
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Write a binary payload to a region of a Dataset
+    ///
+    /// The payload is sent as-is with the given content type (normally
+    /// `application/octet-stream` with little-endian elements), without any
+    /// JSON encoding. `Bytes` and `Vec<u8>` payloads are passed through
+    /// without copying; see [`RawWriteBuffer`](crate::raw::RawWriteBuffer)
+    /// for reusing encode buffers across calls.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string (e.g., "[0:1000]"); whole dataset if `None`
+    /// * `data` - Binary payload
+    /// * `content_type` - Content type of the payload
+    pub async fn write_region_raw(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        data: impl Into<Bytes>,
+        content_type: &str,
+    ) -> HsdsResult<()> {
+        let data = data.into();
+        debug!("Writing {} raw bytes to dataset {}", data.len(), dataset_id);
+
+        let path = format!("/datasets/{}/value", dataset_id);
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+
+        if let Some(selection) = select {
+            req = HsdsClient::with_selection(req, selection);
+        }
+
+        let empty = data.is_empty();
+        req = req
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(data);
+
+        match self.client.execute_empty(req).await {
+            Err(HsdsError::InvalidParameter(message)) if empty => {
+                if self.holds_no_values(domain, dataset_id).await? {
+                    debug!("Skipped empty write to dataset {} without values", dataset_id);
+                    Ok(())
+                } else {
+                    Err(HsdsError::InvalidParameter(message))
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Write binary values with client-side deflate compression
+    ///
+    /// The payload is sent with `Content-Encoding: deflate` when compressing
+    /// shrinks it, and as a plain binary write otherwise. Returns the number
+    /// of bytes sent.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `data` - Uncompressed little-endian payload
+    /// * `compression` - Compression settings
+    #[cfg(feature = "compression")]
+    pub async fn write_region_compressed(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        data: impl Into<Bytes>,
+        compression: &crate::compression::UploadCompression,
+    ) -> HsdsResult<usize> {
+        let data = data.into();
+        let Some(compressed) = compression.deflate(&data) else {
+            let sent = data.len();
+            self.write_region_raw(domain, dataset_id, select, data, "application/octet-stream").await?;
+            return Ok(sent);
+        };
+        debug!("Writing {} bytes deflated to {} to dataset {}", data.len(), compressed.len(), dataset_id);
+
+        let path = format!("/datasets/{}/value", dataset_id);
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+
+        if let Some(selection) = select {
+            req = HsdsClient::with_selection(req, selection);
+        }
+
+        let sent = compressed.len();
+        req = req
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .header(reqwest::header::CONTENT_ENCODING, "deflate")
+            .body(compressed);
+
+        self.client.execute_empty(req).await?;
+        Ok(sent)
     }
 
     /// Read values from Dataset
+    ///
+    /// Selections too long for a URL are sent in a POST body instead of the
+    /// `select` query parameter (not combinable with `query`). A dataset
+    /// without values (null dataspace or a zero-length dimension) reads as
+    /// an empty body.
     /// 
     /// # Arguments
     /// * `domain` - Domain path
@@ -179,22 +477,23 @@ impl<'a> DatasetApi<'a> {
         query: Option<&str>,
         limit: Option<u32>,
     ) -> HsdsResult<Bytes> {
-        let path = format!("/datasets/{}/value", dataset_id);
-        let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
-
-        if let Some(selection) = select {
-            req = HsdsClient::with_selection(req, selection);
-        }
-
-        if let Some(q) = query {
-            req = HsdsClient::with_query(req, q, limit);
+        let req = value_read_request(self.client, domain, dataset_id, select, query, limit).await?;
+        match self.client.execute_bytes(req).await {
+            Err(HsdsError::InvalidParameter(message)) => {
+                if self.holds_no_values(domain, dataset_id).await? {
+                    Ok(Bytes::new())
+                } else {
+                    Err(HsdsError::InvalidParameter(message))
+                }
+            }
+            result => result,
         }
-
-        self.client.execute_bytes(req).await
     }
 
     /// Read values from Dataset as JSON
+    ///
+    /// A dataset without values (null dataspace or a zero-length dimension)
+    /// reads as `{"value": []}`.
     /// 
     /// # Arguments
     /// * `domain` - Domain path
@@ -210,22 +509,119 @@ impl<'a> DatasetApi<'a> {
         query: Option<&str>,
         limit: Option<u32>,
     ) -> HsdsResult<serde_json::Value> {
-        let path = format!("/datasets/{}/value", dataset_id);
-        let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
-
-        if let Some(selection) = select {
-            req = HsdsClient::with_selection(req, selection);
+        match self.read_dataset_values_with(domain, dataset_id, select, query, limit, JsonDecoder::new()).await {
+            Err(HsdsError::InvalidParameter(message)) => {
+                if self.holds_no_values(domain, dataset_id).await? {
+                    Ok(serde_json::json!({"value": []}))
+                } else {
+                    Err(HsdsError::InvalidParameter(message))
+                }
+            }
+            result => result,
         }
+    }
 
-        if let Some(q) = query {
-            req = HsdsClient::with_query(req, q, limit);
+    /// Read values from Dataset as typed elements, parsing the JSON response as it arrives
+    ///
+    /// Unlike [`read_dataset_values_json`](Self::read_dataset_values_json),
+    /// the body is never buffered whole: each element of the `value` array
+    /// is deserialized into `T` as soon as its bytes have been received,
+    /// roughly halving peak memory for large selections. Returns a flat
+    /// array (row-major); compound elements deserialize from their JSON
+    /// arrays, e.g. into tuples.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    pub async fn read_dataset_values_streamed<T>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+    ) -> HsdsResult<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        let shape: Option<Shape> = dataset.get("shape").and_then(|s| serde_json::from_value(s.clone()).ok());
+        if shape.as_ref().is_some_and(Shape::is_empty) {
+            return Ok(Vec::new());
         }
+        let dims = shape.and_then(|s| s.dims).unwrap_or_default();
+        let rank = crate::apis::coords::selection_shape(select, &dims)?.len();
+
+        let req = value_read_request(self.client, domain, dataset_id, select, None, None).await?;
+        let response = self.client.execute_streaming(req.header(reqwest::header::ACCEPT, "application/json")).await?;
+        let (values, dims) = self.client.cancellable(async {
+            let mut parser = ValueArrayParser::new(rank).with_special_floats(self.client.options().special_floats());
+            let mut body = response.bytes_stream();
+            let mut received = 0u64;
+            while let Some(chunk) = body.try_next().await? {
+                received += chunk.len() as u64;
+                self.client.check_response_size(received)?;
+                parser.feed(&chunk)?;
+            }
+            parser.finish()
+        }).await?;
+
+        if values.len() as u64 != dims.iter().product::<u64>() {
+            return Err(HsdsError::InvalidResponse(format!(
+                "Dataset {} returned {} values for a selection of shape {:?}", dataset_id, values.len(), dims
+            )));
+        }
+        debug!("Streamed {} values from dataset {}", values.len(), dataset_id);
+        Ok(values)
+    }
 
-        // Set Accept header for JSON response
-        req = req.header("Accept", "application/json");
+    /// Read values from Dataset using a custom response decoder
+    ///
+    /// The decoder selects the `Accept` type (JSON, binary, MessagePack, ...)
+    /// and converts the response body.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string
+    /// * `query` - Optional query condition
+    /// * `limit` - Optional limit for query results
+    /// * `decoder` - Response decoder
+    pub async fn read_dataset_values_with<D>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        query: Option<&str>,
+        limit: Option<u32>,
+        decoder: D,
+    ) -> HsdsResult<D::Output>
+    where
+        D: ResponseDecoder,
+    {
+        let req = value_read_request(self.client, domain, dataset_id, select, query, limit).await?;
+        self.client.execute_with(req, decoder).await
+    }
 
-        self.client.execute(req).await
+    /// URL reading a Dataset's values, for pasting into a browser or a plain HTTP tool
+    ///
+    /// See [`HsdsClient::shareable_url`]; the URL returns the selection as
+    /// JSON. Credentials embedded in it are readable by anyone holding it.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `credentials` - Basic-auth credentials to embed, `None` for public domains
+    pub fn value_url(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        credentials: Option<&BasicAuth>,
+    ) -> HsdsResult<Url> {
+        let path = format!("/datasets/{}/value", dataset_id);
+        let params: Vec<(&str, &str)> = select.map(|s| ("select", s)).into_iter().collect();
+        self.client.shareable_url(&path, domain, &params, credentials)
     }
 
     /// Read specific data points from Dataset
@@ -240,33 +636,199 @@ impl<'a> DatasetApi<'a> {
         dataset_id: &str,
         points: Vec<Vec<u64>>,
     ) -> HsdsResult<serde_json::Value> {
+        self.read_values_post(domain, dataset_id, &PointsRequest::points(points)).await
+    }
+
+    /// Read values with the selection in a POST body
+    ///
+    /// Accepts point lists, hyperslab selections and compound field subsets
+    /// that would be too long for query parameters.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `request` - Points or selection, and optional fields
+    pub async fn read_values_post(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        request: &PointsRequest,
+    ) -> HsdsResult<serde_json::Value> {
+        if request.points.is_some() == request.select.is_some() {
+            return Err(HsdsError::invalid_param("A POST value read needs either points or a selection"));
+        }
         let path = format!("/datasets/{}/value", dataset_id);
         let mut req = self.client.request(Method::POST, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
-        req = req.json(&points);
+        req = HsdsClient::with_domain(req, domain)?;
+        req = req.json(request);
+
+        self.client.execute(req).await
+    }
+
+    /// Whether a dataset holds no values (null dataspace or a zero-length dimension)
+    ///
+    /// Used to turn the server's rejection of reads and writes on such
+    /// datasets into empty results.
+    pub(crate) async fn holds_no_values(&self, domain: &str, dataset_id: &str) -> HsdsResult<bool> {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        let shape: Option<Shape> = dataset.get("shape").and_then(|s| serde_json::from_value(s.clone()).ok());
+        Ok(shape.is_some_and(|shape| shape.is_empty()))
+    }
 
+    /// Dataset description as JSON, for types the [`Dataset`] model cannot hold (e.g. enums)
+    pub(crate) async fn get_dataset_json(&self, domain: &str, dataset_id: &str) -> HsdsResult<serde_json::Value> {
+        let path = format!("/datasets/{}", dataset_id);
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
         self.client.execute(req).await
     }
 }
 
+/// Longest URL-encoded selection sent as a query parameter
+///
+/// Longer selections (many dimensions or segments) would push the request
+/// line past common URL length limits, so reads send them in a POST body.
+pub(crate) const MAX_SELECT_QUERY_LEN: usize = 2048;
+
+/// Build a value read, moving an oversized selection into a POST body
+pub(crate) async fn value_read_request(
+    client: &HsdsClient,
+    domain: &str,
+    dataset_id: &str,
+    select: Option<&str>,
+    query: Option<&str>,
+    limit: Option<u32>,
+) -> HsdsResult<RequestBuilder> {
+    let path = format!("/datasets/{}/value", dataset_id);
+    if let (Some(selection), None) = (select, query) {
+        if urlencoding::encode(selection).len() > MAX_SELECT_QUERY_LEN {
+            debug!("Selection of {} bytes sent in POST body", selection.len());
+            let req = client.request(Method::POST, &path).await?;
+            return Ok(HsdsClient::with_domain(req, domain)?.json(&PointsRequest::selection(selection)));
+        }
+    }
+
+    let mut req = client.request(Method::GET, &path).await?;
+    req = HsdsClient::with_domain(req, domain)?;
+
+    if let Some(selection) = select {
+        req = HsdsClient::with_selection(req, selection);
+    }
+
+    if let Some(q) = query {
+        req = HsdsClient::with_query(req, q, limit);
+    }
+    Ok(req)
+}
+
+/// Validate a resize against rank and maxdims and classify its direction
+pub(crate) fn check_resize(current: &[u64], maxdims: Option<&[u64]>, new_shape: &[u64]) -> HsdsResult<ResizeKind> {
+    if current.len() != new_shape.len() {
+        return Err(HsdsError::invalid_param(format!(
+            "New shape {:?} has rank {}, dataset has rank {}", new_shape, new_shape.len(), current.len()
+        )));
+    }
+
+    let mut grows = false;
+    let mut shrinks = false;
+    for (axis, (&old, &new)) in current.iter().zip(new_shape).enumerate() {
+        grows |= new > old;
+        shrinks |= new < old;
+
+        if new == old {
+            continue;
+        }
+
+        // Without maxdims the dataset is not resizable; a maxdim of 0 is unlimited
+        let max = match maxdims {
+            Some(maxdims) => maxdims.get(axis).copied().unwrap_or(old),
+            None => {
+                return Err(HsdsError::invalid_param(format!(
+                    "Dataset is not resizable (no maxdims); cannot change dimension {} from {} to {}", axis, old, new
+                )));
+            }
+        };
+        if max != 0 && new > max {
+            return Err(HsdsError::invalid_param(format!(
+                "Dimension {} cannot be resized to {}: maximum extent is {}", axis, new, max
+            )));
+        }
+    }
+
+    Ok(match (grows, shrinks) {
+        (false, false) => ResizeKind::Unchanged,
+        (true, false) => ResizeKind::Grow,
+        (false, true) => ResizeKind::Shrink,
+        (true, true) => ResizeKind::Mixed,
+    })
+}
+
+/// Describe why an existing dataset does not match a creation request
+pub(crate) fn incompatibility(existing: &Dataset, request: &DatasetCreateRequest) -> Option<String> {
+    if let Some(existing_type) = &existing.data_type {
+        if !existing_type.is_compatible_with(&request.data_type) {
+            return Some(format!("type {:?} does not match requested {:?}", existing_type, request.data_type));
+        }
+    }
+
+    let existing_shape = existing.shape.as_ref()?;
+    let dims = match &request.shape {
+        // Without a shape the server creates a scalar dataset
+        None if !existing_shape.is_scalar() => {
+            return Some(format!("shape class {} is not H5S_SCALAR", existing_shape.class));
+        }
+        Some(ShapeSpec::Null(_)) if !existing_shape.is_null() => {
+            return Some(format!("shape class {} is not H5S_NULL", existing_shape.class));
+        }
+        Some(ShapeSpec::Dimensions(dims)) => dims,
+        _ => return None,
+    };
+    if existing_shape.class != "H5S_SIMPLE" {
+        return Some(format!("shape class {} is not H5S_SIMPLE", existing_shape.class));
+    }
+    let existing_dims = existing_shape.dims.clone().unwrap_or_default();
+    if existing_dims.len() != dims.len() {
+        return Some(format!("rank {} does not match requested rank {}", existing_dims.len(), dims.len()));
+    }
+
+    // A resizable dataset may have been extended or shrunk since it was
+    // created, so only its maximum extents must match; a fixed-size one
+    // still has the requested dims
+    let existing_max = resizable_maxdims(&existing_dims, existing_shape.maxdims.as_ref());
+    match (existing_max, resizable_maxdims(dims, request.maxdims.as_ref())) {
+        (None, None) if &existing_dims != dims => {
+            Some(format!("dims {:?} do not match requested {:?}", existing_dims, dims))
+        }
+        (None, None) => None,
+        (Some(existing_max), Some(max)) if existing_max != max => {
+            Some(format!("maxdims {:?} do not match requested {:?}", existing_max, max))
+        }
+        (Some(_), Some(_)) => None,
+        (Some(existing_max), None) => {
+            Some(format!("dataset is resizable (maxdims {:?}) but a fixed-size one was requested", existing_max))
+        }
+        (None, Some(max)) => Some(format!("dataset is fixed-size but maxdims {:?} were requested", max)),
+    }
+}
+
+/// Maximum extents of a shape, `None` if no dimension can be resized
+fn resizable_maxdims<'a>(dims: &[u64], maxdims: Option<&'a Vec<u64>>) -> Option<&'a Vec<u64>> {
+    maxdims.filter(|maxdims| maxdims.iter().zip(dims).any(|(&max, &dim)| max == 0 || max != dim))
+}
+
 impl DatasetCreateRequest {
     /// Create a dataset from an HSDS data type string
-    /// This method determines the appropriate DataTypeSpec based on the HSDS type
+    /// This method determines the appropriate H5Type based on the HSDS type
     pub fn from_hsds_type(
         hsds_type: &str,
         dimensions: Vec<u64>,
     ) -> Self {
         let data_type = match hsds_type {
             // String types - use structured string type
-            "H5T_STRING" => DataTypeSpec::String(StringDataType::variable_ascii()),
-            
-            // Numeric types - use predefined types
-            "H5T_STD_U8LE" | "H5T_STD_I8LE" | "H5T_STD_U16LE" | "H5T_STD_I16LE" |
-            "H5T_STD_U32LE" | "H5T_STD_I32LE" | "H5T_STD_I64LE" |
-            "H5T_IEEE_F32LE" | "H5T_IEEE_F64LE" => DataTypeSpec::Predefined(hsds_type.to_string()),
-            
-            // Default to predefined for any other type
-            _ => DataTypeSpec::Predefined(hsds_type.to_string()),
+            "H5T_STRING" => H5Type::variable_ascii(),
+
+            // Numeric and any other types - use predefined types
+            _ => H5Type::named(hsds_type),
         };
 
         Self {
@@ -292,60 +854,59 @@ impl DatasetCreateRequest {
         });
         request
     }
-}
-
-impl StringDataType {
-    /// Create a new variable-length UTF-8 string type
-    pub fn variable_utf8() -> Self {
-        Self {
-            class: "H5T_STRING".to_string(),
-            char_set: StringCharSet::Utf8,
-            str_pad: StringPadding::NullPad,
-            length: StringLength::Variable("H5T_VARIABLE".to_string()),
-        }
-    }
-
-    /// Create a new fixed-length UTF-8 string type
-    pub fn fixed_utf8(length: u32) -> Self {
-        Self {
-            class: "H5T_STRING".to_string(),
-            char_set: StringCharSet::Utf8,
-            str_pad: StringPadding::NullPad,
-            length: StringLength::Fixed(length),
-        }
-    }
 
-    /// Create a new variable-length ASCII string type
-    pub fn variable_ascii() -> Self {
-        Self {
-            class: "H5T_STRING".to_string(),
-            char_set: StringCharSet::Ascii,
-            str_pad: StringPadding::NullPad,
-            length: StringLength::Variable("H5T_VARIABLE".to_string()),
+    /// Request a chunked layout with dimensions from [`chunking::recommend`]
+    ///
+    /// The element size is derived from the data type (8 bytes if unknown)
+    /// and the target is [`chunking::DEFAULT_TARGET_CHUNK_BYTES`]. Existing
+    /// creation properties other than the layout are kept. Without a
+    /// dimensioned shape the request is returned unchanged.
+    ///
+    /// # Arguments
+    /// * `access` - Dominant access pattern of the dataset
+    pub fn auto_chunk(mut self, access: AccessPattern) -> Self {
+        let Some(ShapeSpec::Dimensions(shape)) = &self.shape else {
+            return self;
+        };
+        let dtype_size = chunking::type_size(&self.data_type).unwrap_or(8);
+        let dims = chunking::recommend(shape, dtype_size, chunking::DEFAULT_TARGET_CHUNK_BYTES, access);
+        if dims.is_empty() {
+            return self;
         }
-    }
+        debug!("Auto chunk dims {:?} for shape {:?}", dims, shape);
 
-    /// Create a new fixed-length ASCII string type
-    pub fn fixed_ascii(length: u32) -> Self {
-        Self {
-            class: "H5T_STRING".to_string(),
-            char_set: StringCharSet::Ascii,
-            str_pad: StringPadding::NullPad,
-            length: StringLength::Fixed(length),
-        }
+        let layout = serde_json::json!({"class": "H5D_CHUNKED", "dims": dims});
+        let mut properties = match self.creation_properties.take() {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        properties.insert("layout".to_string(), layout);
+        self.creation_properties = Some(serde_json::Value::Object(properties));
+        self
     }
 
-    /// Create a string type with custom parameters
-    pub fn custom(
-        char_set: StringCharSet,
-        str_pad: StringPadding,
-        length: StringLength,
-    ) -> Self {
-        Self {
-            class: "H5T_STRING".to_string(),
-            char_set,
-            str_pad,
-            length,
+    /// Add a deflate (gzip) filter to the dataset's filter pipeline
+    ///
+    /// # Arguments
+    /// * `level` - Compression level (1-9)
+    pub fn with_deflate(mut self, level: u32) -> Self {
+        let filter = serde_json::json!({
+            "class": "H5Z_FILTER_DEFLATE",
+            "id": 1,
+            "name": "deflate",
+            "level": level.clamp(1, 9),
+        });
+        let mut properties = match self.creation_properties.take() {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        match properties.get_mut("filters").and_then(|f| f.as_array_mut()) {
+            Some(filters) => filters.push(filter),
+            None => {
+                properties.insert("filters".to_string(), serde_json::Value::Array(vec![filter]));
+            }
         }
+        self.creation_properties = Some(serde_json::Value::Object(properties));
+        self
     }
 }
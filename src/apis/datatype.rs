@@ -1,6 +1,7 @@
 use crate::{
     client::HsdsClient,
     error::HsdsResult,
+    models::Datatype,
 };
 use reqwest::Method;
 
@@ -23,9 +24,9 @@ impl<'a> DatatypeApi<'a> {
         &self,
         domain: &str,
         datatype_def: serde_json::Value,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<Datatype> {
         let mut req = self.client.request(Method::POST, "/datatypes").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         req = req.json(&datatype_def);
 
         self.client.execute(req).await
@@ -43,7 +44,7 @@ impl<'a> DatatypeApi<'a> {
     ) -> HsdsResult<serde_json::Value> {
         let path = format!("/datatypes/{}", datatype_id);
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
@@ -57,11 +58,11 @@ impl<'a> DatatypeApi<'a> {
         &self,
         domain: &str,
         datatype_id: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<()> {
         let path = format!("/datatypes/{}", datatype_id);
         let mut req = self.client.request(Method::DELETE, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
-        self.client.execute(req).await
+        self.client.execute_empty(req).await
     }
 }
@@ -0,0 +1,101 @@
+use crate::{
+    apis::dataset::DatasetApi,
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+};
+use log::{info, warn};
+use reqwest::Method;
+
+/// Options for [`DatasetApi::delete_dataset_with`]
+#[derive(Debug, Clone)]
+pub struct DeleteOptions {
+    unlink: bool,
+}
+
+impl Default for DeleteOptions {
+    fn default() -> Self {
+        Self { unlink: true }
+    }
+}
+
+impl DeleteOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove the hard links to the deleted object (on by default)
+    ///
+    /// When off, the links are left in place and reported as broken.
+    pub fn with_unlink(mut self, unlink: bool) -> Self {
+        self.unlink = unlink;
+        self
+    }
+}
+
+/// Outcome of [`DatasetApi::delete_dataset_with`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeleteReport {
+    /// Paths of the hard links that were removed
+    pub unlinked: Vec<String>,
+    /// Paths of links left pointing at the deleted object: soft links
+    /// resolving to it, and hard links when unlinking is off or failed
+    pub broken: Vec<String>,
+}
+
+impl DeleteReport {
+    /// Whether no link points at the deleted object any more
+    pub fn is_clean(&self) -> bool {
+        self.broken.is_empty()
+    }
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Delete a Dataset along with the links pointing at it
+    ///
+    /// Walks the domain's link graph from the root (see
+    /// [`DomainApi::audit_links`](crate::DomainApi::audit_links)) to find
+    /// the links to the dataset, deletes the dataset, then removes its hard
+    /// links. Soft links whose path resolved to the dataset are left alone,
+    /// since the path may be reused, and reported as broken. The walk lists
+    /// every reachable group, so it costs as much as a link audit;
+    /// [`delete_dataset`](Self::delete_dataset) deletes the object only.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `options` - Whether to remove the links
+    pub async fn delete_dataset_with(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        options: &DeleteOptions,
+    ) -> HsdsResult<DeleteReport> {
+        let graph = self.client.domains().link_graph(domain).await?;
+        let hard_links = graph.hard_links_to(dataset_id);
+        let mut report = DeleteReport { broken: graph.soft_links_to(dataset_id), ..DeleteReport::default() };
+
+        let path = format!("/datasets/{}", dataset_id);
+        let mut req = self.client.request(Method::DELETE, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        self.client.execute_empty(req).await?;
+
+        for (group_id, name, link_path) in hard_links {
+            if !options.unlink {
+                report.broken.push(link_path);
+                continue;
+            }
+            match self.client.links().delete_link(domain, &group_id, &name).await {
+                Ok(()) | Err(HsdsError::ObjectNotFound(_)) => report.unlinked.push(link_path),
+                Err(e) => {
+                    warn!("Failed to unlink {} from {}: {}", link_path, dataset_id, e);
+                    report.broken.push(link_path);
+                }
+            }
+        }
+        report.broken.sort();
+        info!(
+            "Deleted dataset {} in {}: {} links removed, {} broken", dataset_id, domain, report.unlinked.len(), report.broken.len()
+        );
+        Ok(report)
+    }
+}
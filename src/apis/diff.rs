@@ -0,0 +1,139 @@
+use crate::{
+    apis::{attribute::AttributeApi, collection_for_id},
+    client::HsdsClient,
+    codec::{self, ElementCodec},
+    error::HsdsResult,
+};
+use reqwest::Method;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// An attribute as compared by [`AttributeApi::diff_attributes`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeEntry {
+    pub name: String,
+    /// HSDS type JSON
+    pub datatype: Value,
+    /// HSDS shape JSON (`class` and `dims`)
+    pub shape: Value,
+    /// Value as listed by the server
+    pub value: Value,
+}
+
+impl AttributeEntry {
+    /// The value as `T` elements, flat (row-major)
+    pub fn elements<T: ElementCodec>(&self) -> HsdsResult<Vec<T>> {
+        if self.shape.get("class").and_then(Value::as_str) == Some("H5S_NULL") {
+            return Ok(Vec::new());
+        }
+        let rank = self.shape.get("dims").and_then(Value::as_array).map_or(0, Vec::len);
+        codec::decode_json(&self.value, rank)
+    }
+
+    fn from_listing(mut attribute: Value) -> Option<Self> {
+        let name = attribute.get("name")?.as_str()?.to_string();
+        let mut take = |key: &str| attribute.get_mut(key).map(Value::take).unwrap_or(Value::Null);
+        Some(Self { datatype: take("type"), shape: take("shape"), value: take("value"), name })
+    }
+}
+
+/// An attribute present on both objects with different type, shape or value
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeChange {
+    pub name: String,
+    /// The attribute on the first object
+    pub before: AttributeEntry,
+    /// The attribute on the second object
+    pub after: AttributeEntry,
+}
+
+impl AttributeChange {
+    pub fn type_changed(&self) -> bool {
+        self.before.datatype != self.after.datatype
+    }
+
+    pub fn shape_changed(&self) -> bool {
+        self.before.shape != self.after.shape
+    }
+
+    pub fn value_changed(&self) -> bool {
+        self.before.value != self.after.value
+    }
+}
+
+/// Differences between the attributes of two objects, each sorted by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttributeDiff {
+    /// Attributes only the second object has
+    pub added: Vec<AttributeEntry>,
+    /// Attributes only the first object has
+    pub removed: Vec<AttributeEntry>,
+    /// Attributes both have, with a different type, shape or value
+    pub changed: Vec<AttributeChange>,
+}
+
+impl AttributeDiff {
+    /// Whether both objects have the same attributes
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<'a> AttributeApi<'a> {
+    /// Compare the attributes of two objects, possibly in different domains
+    ///
+    /// Attributes are matched by name and compare equal when their type,
+    /// shape and value JSON are equal, so e.g. the calibration metadata of
+    /// two shots can be checked in one call. The objects may be groups,
+    /// datasets or committed datatypes, in any combination.
+    ///
+    /// # Arguments
+    /// * `a_domain` - Domain of the first object
+    /// * `a_obj` - UUID of the first object
+    /// * `b_domain` - Domain of the second object
+    /// * `b_obj` - UUID of the second object
+    pub async fn diff_attributes(
+        &self,
+        a_domain: &str,
+        a_obj: &str,
+        b_domain: &str,
+        b_obj: &str,
+    ) -> HsdsResult<AttributeDiff> {
+        let (before, mut after) = futures_util::try_join!(
+            listed_attributes(self.client, a_domain, a_obj),
+            listed_attributes(self.client, b_domain, b_obj),
+        )?;
+
+        let mut diff = AttributeDiff::default();
+        for (name, before) in before {
+            match after.remove(&name) {
+                None => diff.removed.push(before),
+                Some(after) if after != before => {
+                    diff.changed.push(AttributeChange { name, before, after });
+                }
+                Some(_) => {}
+            }
+        }
+        diff.added = after.into_values().collect();
+        Ok(diff)
+    }
+}
+
+/// Attributes of an object with their values, by name
+async fn listed_attributes(client: &HsdsClient, domain: &str, object_id: &str) -> HsdsResult<BTreeMap<String, AttributeEntry>> {
+    let path = format!("/{}/{}/attributes", collection_for_id(object_id)?, object_id);
+    let mut req = client.request(Method::GET, &path).await?;
+    req = HsdsClient::with_domain(req, domain)?;
+    req = req.query(&[("IncludeData", "1")]);
+
+    let mut listing: Value = client.execute(req).await?;
+    let attributes = match listing.get_mut("attributes").map(Value::take) {
+        Some(Value::Array(attributes)) => attributes,
+        _ => Vec::new(),
+    };
+    Ok(attributes
+        .into_iter()
+        .filter_map(AttributeEntry::from_listing)
+        .map(|entry| (entry.name.clone(), entry))
+        .collect())
+}
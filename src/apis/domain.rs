@@ -1,14 +1,28 @@
 use crate::{
     client::HsdsClient,
-    error::HsdsResult,
-    models::{Domain, DomainCreateRequest},
+    error::{HsdsError, HsdsResult},
+    domain_path::DomainPath,
+    models::{Acl, Acls, Domain, DomainCreateRequest, DomainEntry, DomainKind, DomainListing, GroupCreateRequest, LinkRequest},
+    apis::dataset::incompatibility,
+    template::{attribute_type_mismatch, AttributeTemplate, DomainTemplate, ValidationReport, ViolationKind},
 };
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+#[cfg(feature = "chrono")]
+use crate::timestamps::{TimeRange, Timestamped};
+use futures_util::{stream, Stream, StreamExt};
 use reqwest::Method;
 use log::{debug, info};
 
+/// First delay between polls of [`DomainApi::wait_until_ready`]
+const READINESS_INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Longest delay between polls of [`DomainApi::wait_until_ready`]
+const READINESS_MAX_BACKOFF: Duration = Duration::from_secs(1);
+
 /// Domain API operations
 pub struct DomainApi<'a> {
-    client: &'a HsdsClient,
+    pub(crate) client: &'a HsdsClient,
 }
 
 impl<'a> DomainApi<'a> {
@@ -17,6 +31,10 @@ impl<'a> DomainApi<'a> {
     }
 
     /// Create a new Domain or Folder
+    ///
+    /// The path must agree with the requested kind: a folder request for a
+    /// path with a file extension, or a file request for a path ending in
+    /// '/', is rejected before anything is sent.
     /// 
     /// # Arguments
     /// * `domain` - Domain path (e.g., "/home/user/myfile.h5")
@@ -27,18 +45,81 @@ impl<'a> DomainApi<'a> {
         request: Option<DomainCreateRequest>,
     ) -> HsdsResult<Domain> {
         info!("Creating domain: {}", domain);
+        let kind = match &request {
+            Some(r) if r.is_folder() => DomainKind::Folder,
+            _ => DomainKind::File,
+        };
+        DomainPath::parse(domain)?.check_kind(kind)?;
         let mut req = self.client.request(Method::PUT, "/").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         debug!("HTTP PUT / with domain={}", domain);
 
         if let Some(body) = request {
-            debug!("Request body: {:?}", body);
+            debug!("Request body: {}", self.client.log_options().payload(&body));
             req = req.json(&body);
         }
 
         self.client.execute(req).await
     }
 
+    /// Wait until a newly created domain and its root group respond
+    ///
+    /// Right after [`create_domain`](Self::create_domain) a domain may still
+    /// be propagating, and requests for it or its root group fail with 404.
+    /// This polls both with exponential backoff (from 50 ms, capped at 1 s)
+    /// while they are not found or the server reports a transient error,
+    /// and fails with `OperationFailed` once `timeout` has passed. Other
+    /// errors are returned at once. Folders are ready when they respond.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `timeout` - Longest time to wait
+    pub async fn wait_until_ready(&self, domain: &str, timeout: Duration) -> HsdsResult<Domain> {
+        let deadline = Instant::now() + timeout;
+        let mut backoff = READINESS_INITIAL_BACKOFF;
+        let mut attempts = 0u32;
+        loop {
+            attempts += 1;
+            let result = match self.get_domain(domain).await {
+                Ok(info) => match &info.root {
+                    Some(root) => self.client.groups().get_group(domain, root, None).await.map(|_| info),
+                    None => Ok(info),
+                },
+                Err(e) => Err(e),
+            };
+            let error = match result {
+                Ok(info) => {
+                    debug!("Domain {} ready after {} attempts", domain, attempts);
+                    return Ok(info);
+                }
+                Err(e @ (HsdsError::DomainNotFound(_) | HsdsError::ObjectNotFound(_))) => e,
+                Err(HsdsError::Api { status, message }) if matches!(status, 429 | 502 | 503 | 504) => {
+                    HsdsError::Api { status, message }
+                }
+                Err(e) => return Err(e),
+            };
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(HsdsError::OperationFailed(format!(
+                    "Domain {} not ready after {:?} ({} attempts): {}", domain, timeout, attempts, error
+                )));
+            }
+            debug!("Domain {} not ready yet ({}); retrying in {:?}", domain, error, backoff);
+            tokio::time::sleep(backoff.min(deadline - now)).await;
+            backoff = (backoff * 2).min(READINESS_MAX_BACKOFF);
+        }
+    }
+
+    /// Wait for a created domain if the client is configured to (see
+    /// [`ClientOptions::with_readiness_wait`](crate::ClientOptions::with_readiness_wait))
+    pub(crate) async fn await_created(&self, domain: &str) -> HsdsResult<()> {
+        if let Some(timeout) = self.client.options().readiness_timeout() {
+            self.wait_until_ready(domain, timeout).await?;
+        }
+        Ok(())
+    }
+
     /// Get information about a domain
     /// 
     /// # Arguments
@@ -46,23 +127,315 @@ impl<'a> DomainApi<'a> {
     pub async fn get_domain(&self, domain: &str) -> HsdsResult<Domain> {
         info!("Getting domain: {}", domain);
         let mut req = self.client.request(Method::GET, "/").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         debug!("HTTP GET / with domain={}", domain);
 
         self.client.execute(req).await
     }
 
+    /// Check whether a domain or folder exists (HEAD request, 404 maps to `false`)
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    pub async fn domain_exists(&self, domain: &str) -> HsdsResult<bool> {
+        let mut req = self.client.request(Method::HEAD, "/").await?;
+        req = HsdsClient::with_domain(req, domain)?;
+
+        self.client.execute_exists(req).await
+    }
+
+    /// Get a domain and check that it is of the expected kind
+    ///
+    /// Fails with `InvalidParameter` if e.g. a folder is found where a file
+    /// domain was expected.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `expected` - Expected kind
+    pub async fn get_domain_of_kind(&self, domain: &str, expected: DomainKind) -> HsdsResult<Domain> {
+        let found = self.get_domain(domain).await?;
+        if found.kind() != expected {
+            return Err(HsdsError::invalid_param(format!(
+                "'{}' is a {}, expected a {}", domain, found.kind(), expected
+            )));
+        }
+        Ok(found)
+    }
+
+    /// Get the ACL of one user on a domain
+    ///
+    /// Requires `readACL` permission on the domain. The pseudo-user
+    /// "default" holds the permissions of every user without an ACL of
+    /// their own, including anonymous requests.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `user` - User name
+    pub async fn get_acl(&self, domain: &str, user: &str) -> HsdsResult<Acl> {
+        let path = format!("/acls/{}", urlencoding::encode(user));
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+
+        let response: serde_json::Value = self.client.execute(req).await?;
+        let acl = response.get("acl").cloned().ok_or_else(|| {
+            HsdsError::InvalidResponse(format!("ACL response for '{}' has no acl", user))
+        })?;
+        Ok(serde_json::from_value(acl)?)
+    }
+
+    /// Get the ACLs of every user with one on a domain
+    ///
+    /// Requires `readACL` permission on the domain. Both the object form
+    /// (`{"user": {...}}`) and the list form (`[{"userName": "user", ...}]`)
+    /// of the response are accepted.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    pub async fn get_acls(&self, domain: &str) -> HsdsResult<Acls> {
+        let mut req = self.client.request(Method::GET, "/acls").await?;
+        req = HsdsClient::with_domain(req, domain)?;
+
+        let response: serde_json::Value = self.client.execute(req).await?;
+        match response.get("acls") {
+            Some(serde_json::Value::Array(entries)) => {
+                let mut users = HashMap::new();
+                for entry in entries {
+                    let user = entry.get("userName").and_then(|u| u.as_str()).ok_or_else(|| {
+                        HsdsError::InvalidResponse(format!("ACL entry without userName on {}", domain))
+                    })?;
+                    users.insert(user.to_string(), serde_json::from_value(entry.clone())?);
+                }
+                Ok(Acls { users })
+            }
+            Some(acls) => Ok(serde_json::from_value(acls.clone())?),
+            None => Err(HsdsError::InvalidResponse(format!("ACL response for {} has no acls", domain))),
+        }
+    }
+
+    /// Set the ACL of one user on a domain
+    ///
+    /// Requires `updateACL` permission on the domain. Only the permissions
+    /// set in `acl` are changed; an ACL is created if the user has none.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `user` - User name
+    /// * `acl` - Permissions to set
+    pub async fn set_acl(&self, domain: &str, user: &str, acl: &Acl) -> HsdsResult<()> {
+        let path = format!("/acls/{}", urlencoding::encode(user));
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        debug!("Setting ACL of '{}' on {}", user, domain);
+
+        self.client.execute_empty(req.json(acl)).await
+    }
+
+    /// Check whether anyone can read a domain without credentials
+    ///
+    /// Reads the "default" ACL. If the caller may not read ACLs, the domain
+    /// is probed with an anonymous request instead, which also covers
+    /// servers that grant public access through configuration rather than
+    /// ACLs.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    pub async fn is_publicly_readable(&self, domain: &str) -> HsdsResult<bool> {
+        match self.get_acl(domain, "default").await {
+            Ok(acl) => return Ok(acl.read == Some(true)),
+            Err(HsdsError::ObjectNotFound(_)) if self.domain_exists(domain).await? => return Ok(false),
+            Err(HsdsError::PermissionDenied(_) | HsdsError::Auth(_)) => {}
+            Err(e) => return Err(e),
+        }
+        debug!("ACLs of {} not readable, probing anonymously", domain);
+        let anonymous = self.client.without_credentials();
+        match anonymous.domains().get_domain(domain).await {
+            Ok(_) => Ok(true),
+            Err(HsdsError::PermissionDenied(_) | HsdsError::Auth(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Create a domain and populate it from a template
+    ///
+    /// Groups, datasets and attributes are created parent-first. If a step
+    /// fails the partially populated domain is left in place for inspection.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `template` - Layout to instantiate
+    pub async fn create_domain_from_template(
+        &self,
+        domain: &str,
+        template: &DomainTemplate,
+    ) -> HsdsResult<Domain> {
+        let created = self.create_domain(domain, None).await?;
+        self.await_created(domain).await?;
+        let root_id = created.root.clone()
+            .ok_or_else(|| HsdsError::InvalidResponse(format!("Domain {} has no root group", domain)))?;
+
+        let mut pending = vec![(root_id, &template.root)];
+        while let Some((group_id, group)) = pending.pop() {
+            self.apply_attributes(domain, "groups", &group_id, &group.attributes).await?;
+
+            for (name, dataset) in &group.datasets {
+                let request = dataset.create_request(&group_id, name)?;
+                let created = self.client.datasets().create_dataset(domain, request).await?;
+                self.apply_attributes(domain, "datasets", &created.id, &dataset.attributes).await?;
+            }
+
+            for (name, child) in &group.groups {
+                let request = GroupCreateRequest {
+                    link: Some(LinkRequest {
+                        id: group_id.clone(),
+                        name: name.clone(),
+                    }),
+                };
+                let created = self.client.groups().create_group(domain, Some(request)).await?;
+                pending.push((created.id, child));
+            }
+        }
+
+        Ok(created)
+    }
+
+    /// Check an existing domain against a template
+    ///
+    /// Reports missing groups, datasets and attributes, datasets whose type
+    /// or shape differs, and attributes with a different type. Datasets with
+    /// `maxdims` in the template only need to match in rank. Objects not in
+    /// the template are ignored.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `template` - Expected layout
+    pub async fn validate_domain(
+        &self,
+        domain: &str,
+        template: &DomainTemplate,
+    ) -> HsdsResult<ValidationReport> {
+        let root_id = self.get_domain(domain).await?
+            .root
+            .ok_or_else(|| HsdsError::InvalidResponse(format!("Domain {} has no root group", domain)))?;
+
+        let mut report = ValidationReport::default();
+        let mut pending = vec![(String::new(), root_id, &template.root)];
+        while let Some((path, group_id, group)) = pending.pop() {
+            let group_path = if path.is_empty() { "/".to_string() } else { path.clone() };
+            self.check_attributes(domain, "groups", &group_id, &group_path, &group.attributes, &mut report).await?;
+
+            for (name, dataset) in &group.datasets {
+                let dataset_path = format!("{}/{}", path, name);
+                let Some(id) = self.check_link(domain, &group_id, name, "d-", &dataset_path, &mut report).await? else {
+                    continue;
+                };
+                let existing = self.client.datasets().get_dataset(domain, &id).await?;
+                if let Some(reason) = incompatibility(&existing, &dataset.create_request(&group_id, name)?) {
+                    report.push(&dataset_path, ViolationKind::DatasetMismatch, reason);
+                }
+                self.check_attributes(domain, "datasets", &id, &dataset_path, &dataset.attributes, &mut report).await?;
+            }
+
+            for (name, child) in &group.groups {
+                let child_path = format!("{}/{}", path, name);
+                if let Some(id) = self.check_link(domain, &group_id, name, "g-", &child_path, &mut report).await? {
+                    pending.push((child_path, id, child));
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Resolve a hard link to an object of the expected kind, recording violations
+    async fn check_link(
+        &self,
+        domain: &str,
+        group_id: &str,
+        name: &str,
+        id_prefix: &str,
+        path: &str,
+        report: &mut ValidationReport,
+    ) -> HsdsResult<Option<String>> {
+        let link = match self.client.links().get_link(domain, group_id, name).await {
+            Ok(link) => link,
+            Err(HsdsError::ObjectNotFound(_)) => {
+                report.push(path, ViolationKind::MissingObject, "object does not exist");
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let id = link.get("link").and_then(|l| l.get("id")).and_then(|id| id.as_str());
+        match id {
+            Some(id) if id.starts_with(id_prefix) => Ok(Some(id.to_string())),
+            _ => {
+                let expected = if id_prefix == "g-" { "group" } else { "dataset" };
+                report.push(path, ViolationKind::WrongObjectKind, format!("link does not refer to a {}", expected));
+                Ok(None)
+            }
+        }
+    }
+
+    /// Check that template attributes exist with the expected types
+    async fn check_attributes(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        path: &str,
+        attributes: &BTreeMap<String, AttributeTemplate>,
+        report: &mut ValidationReport,
+    ) -> HsdsResult<()> {
+        let api = self.client.attributes();
+        for (name, attr) in attributes {
+            let attr_path = format!("{}@{}", path, name);
+            let existing = match api.get_attribute(domain, collection, obj_uuid, name).await {
+                Ok(existing) => existing,
+                Err(HsdsError::ObjectNotFound(_)) => {
+                    report.push(attr_path, ViolationKind::MissingAttribute, "attribute does not exist");
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            if let (Some(expected), Some(actual)) = (&attr.data_type, existing.get("type")) {
+                if let Some(reason) = attribute_type_mismatch(expected, actual) {
+                    report.push(attr_path, ViolationKind::AttributeTypeMismatch, reason);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Set template attributes on an object
+    async fn apply_attributes(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attributes: &BTreeMap<String, AttributeTemplate>,
+    ) -> HsdsResult<()> {
+        let api = self.client.attributes();
+        for (name, attr) in attributes {
+            match attr.attribute_body() {
+                Some(body) => api.set_attribute_raw(domain, collection, obj_uuid, name, body).await?,
+                None => api.set_attribute_auto(domain, collection, obj_uuid, name, &attr.value).await?,
+            };
+        }
+        Ok(())
+    }
+
     /// Delete a domain
     /// 
     /// # Arguments
     /// * `domain` - Domain path
-    pub async fn delete_domain(&self, domain: &str) -> HsdsResult<serde_json::Value> {
+    pub async fn delete_domain(&self, domain: &str) -> HsdsResult<()> {
         info!("Deleting domain: {}", domain);
         let mut req = self.client.request(Method::DELETE, "/").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         debug!("HTTP DELETE / with domain={}", domain);
 
-        self.client.execute(req).await
+        self.client.execute_empty(req).await
     }
 
     /// List domains (when no domain parameter provided)
@@ -74,14 +447,287 @@ impl<'a> DomainApi<'a> {
         self.client.execute(req).await
     }
 
+    /// List one page of the domains in a folder
+    /// 
+    /// # Arguments
+    /// * `folder` - Folder path (a trailing slash is added if missing)
+    /// * `limit` - Optional maximum number of entries
+    /// * `marker` - Optional name of the last entry of the previous page
+    pub async fn list_folder(
+        &self,
+        folder: &str,
+        limit: Option<u32>,
+        marker: Option<&str>,
+    ) -> HsdsResult<DomainListing> {
+        info!("Listing folder: {}", folder);
+        let folder = DomainPath::folder(folder)?;
+        let mut req = self.client.request(Method::GET, "/domains").await?;
+        req = HsdsClient::with_domain(req, &folder)?;
+        req = HsdsClient::with_pagination(req, limit, marker);
+
+        self.client.execute(req).await
+    }
+
+    /// Find file domains under a folder
+    ///
+    /// Folders are listed page by page and, if `recursive`, descended into
+    /// depth-first. File domains whose name (last path segment) matches
+    /// `pattern` are yielded as they are found; `*` matches any run of
+    /// characters and `?` a single one. A failed listing is yielded as an
+    /// error and the walk continues with the remaining folders.
+    /// 
+    /// # Arguments
+    /// * `root_folder` - Folder to start from
+    /// * `pattern` - Optional name pattern (e.g. "*.h5")
+    /// * `recursive` - Whether to descend into subfolders
+    pub fn find_domains(
+        &self,
+        root_folder: &str,
+        pattern: Option<&str>,
+        recursive: bool,
+    ) -> impl Stream<Item = HsdsResult<DomainEntry>> + 'a {
+        const PAGE_SIZE: u32 = 1000;
+
+        struct Walk {
+            folders: Vec<String>,
+            current: Option<(String, String)>,
+            ready: VecDeque<DomainEntry>,
+        }
+
+        let client = self.client;
+        let pattern = pattern.map(String::from);
+        let walk = Walk {
+            folders: vec![root_folder.to_string()],
+            current: None,
+            ready: VecDeque::new(),
+        };
+
+        stream::unfold(walk, move |mut walk| {
+            let pattern = pattern.clone();
+            async move {
+                loop {
+                    if let Some(entry) = walk.ready.pop_front() {
+                        return Some((Ok(entry), walk));
+                    }
+
+                    let (folder, marker) = match walk.current.take() {
+                        Some((folder, marker)) => (folder, Some(marker)),
+                        None => (walk.folders.pop()?, None),
+                    };
+                    let page = match client.domains().list_folder(&folder, Some(PAGE_SIZE), marker.as_deref()).await {
+                        Ok(page) => page,
+                        Err(e) => return Some((Err(e), walk)),
+                    };
+
+                    if page.domains.len() == PAGE_SIZE as usize {
+                        if let Some(last) = page.domains.last() {
+                            walk.current = Some((folder, last.name.clone()));
+                        }
+                    }
+                    for entry in page.domains {
+                        match entry.kind() {
+                            DomainKind::Folder if recursive => walk.folders.push(entry.name),
+                            DomainKind::Folder => {}
+                            DomainKind::File => {
+                                let name = entry.name.rsplit('/').next().unwrap_or_default();
+                                if pattern.as_deref().is_none_or(|p| wildcard_match(p, name)) {
+                                    walk.ready.push_back(entry);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Find file domains under a folder last modified within a time range
+    ///
+    /// Like [`find_domains`](Self::find_domains), keeping only entries whose
+    /// `lastModified` lies in `range`. Entries without a modification time
+    /// are skipped.
+    /// 
+    /// # Arguments
+    /// * `root_folder` - Folder to start from
+    /// * `pattern` - Optional name pattern (e.g. "*.h5")
+    /// * `recursive` - Whether to descend into subfolders
+    /// * `range` - Modification time range
+    #[cfg(feature = "chrono")]
+    pub fn find_domains_modified(
+        &self,
+        root_folder: &str,
+        pattern: Option<&str>,
+        recursive: bool,
+        range: TimeRange,
+    ) -> impl Stream<Item = HsdsResult<DomainEntry>> + 'a {
+        self.find_domains(root_folder, pattern, recursive).filter(move |entry| {
+            let keep = match entry {
+                Ok(entry) => entry.last_modified_at().is_some_and(|t| range.contains(t)),
+                Err(_) => true,
+            };
+            std::future::ready(keep)
+        })
+    }
+
     /// Create a folder (convenience method)
+    ///
+    /// The path may be given with or without a trailing slash; it is sent
+    /// without one, as the server expects.
     /// 
     /// # Arguments
     /// * `domain` - Domain path
     pub async fn create_folder(&self, domain: &str) -> HsdsResult<Domain> {
         info!("Creating folder: {}", domain);
-        let request = DomainCreateRequest { folder: Some(1) };
+        let path = DomainPath::parse(domain)?;
+        path.check_kind(DomainKind::Folder)?;
+        let domain = path.as_str().trim_end_matches('/');
+
+        let request = DomainCreateRequest::folder();
         debug!("Using folder creation parameters: {:?}", request);
         self.create_domain(domain, Some(request)).await
     }
+
+    /// Create many domains concurrently
+    ///
+    /// Results are returned in the same order as `domains`, one per path, so
+    /// a failure for one path does not abort the others.
+    /// 
+    /// # Arguments
+    /// * `domains` - Domain paths to create
+    /// * `request` - Domain creation parameters applied to every domain
+    /// * `concurrency` - Maximum number of requests in flight (at least 1)
+    pub async fn create_domains<S: AsRef<str>>(
+        &self,
+        domains: &[S],
+        request: Option<DomainCreateRequest>,
+        concurrency: usize,
+    ) -> Vec<(String, HsdsResult<Domain>)> {
+        info!("Creating {} domains (concurrency {})", domains.len(), concurrency);
+        stream::iter(domains.iter().map(|d| d.as_ref().to_string()))
+            .map(|domain| {
+                let request = request.clone();
+                async move {
+                    let result = self.create_domain(&domain, request).await;
+                    (domain, result)
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Delete many domains concurrently
+    ///
+    /// Results are returned in the same order as `domains`, one per path.
+    /// 
+    /// # Arguments
+    /// * `domains` - Domain paths to delete
+    /// * `concurrency` - Maximum number of requests in flight (at least 1)
+    pub async fn delete_domains<S: AsRef<str>>(
+        &self,
+        domains: &[S],
+        concurrency: usize,
+    ) -> Vec<(String, HsdsResult<()>)> {
+        info!("Deleting {} domains (concurrency {})", domains.len(), concurrency);
+        stream::iter(domains.iter().map(|d| d.as_ref().to_string()))
+            .map(|domain| async move {
+                let result = self.delete_domain(&domain).await;
+                (domain, result)
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+}
+
+/// Match `name` against a glob pattern
+///
+/// `*` matches any run of characters, `?` a single one and `[...]` one
+/// character of a class such as `[0-9_]` (`[!...]` negates it), as in the
+/// `fnmatch` patterns HSDS filters links with. An unclosed `[` is literal.
+pub(crate) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = glob_tokens(pattern);
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(GlobToken::AnyRun) => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(token) if token.matches(name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last '*' absorb one more character
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|t| matches!(t, GlobToken::AnyRun))
+}
+
+enum GlobToken {
+    AnyRun,
+    AnyChar,
+    Char(char),
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+impl GlobToken {
+    /// Whether the token matches a single character
+    fn matches(&self, c: char) -> bool {
+        match self {
+            GlobToken::AnyRun | GlobToken::AnyChar => true,
+            GlobToken::Char(expected) => *expected == c,
+            GlobToken::Class { negated, ranges } => ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated,
+        }
+    }
+}
+
+fn glob_tokens(pattern: &str) -> Vec<GlobToken> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => tokens.push(GlobToken::AnyRun),
+            '?' => tokens.push(GlobToken::AnyChar),
+            '[' => {
+                let negated = matches!(chars.get(i + 1), Some('!' | '^'));
+                let start = i + 1 + usize::from(negated);
+                // A ']' right after the opening bracket is a member, not the end
+                let close = chars.iter().skip(start + 1).position(|&c| c == ']').map(|k| start + 1 + k);
+                match close {
+                    Some(end) => {
+                        let mut ranges = Vec::new();
+                        let mut k = start;
+                        while k < end {
+                            if k + 2 < end && chars[k + 1] == '-' {
+                                ranges.push((chars[k], chars[k + 2]));
+                                k += 3;
+                            } else {
+                                ranges.push((chars[k], chars[k]));
+                                k += 1;
+                            }
+                        }
+                        tokens.push(GlobToken::Class { negated, ranges });
+                        i = end;
+                    }
+                    None => tokens.push(GlobToken::Char('[')),
+                }
+            }
+            c => tokens.push(GlobToken::Char(c)),
+        }
+        i += 1;
+    }
+    tokens
 }
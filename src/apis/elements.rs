@@ -0,0 +1,190 @@
+use crate::{
+    apis::{attribute::AttributeApi, coords, dataset::DatasetApi},
+    client::HsdsClient,
+    codec::{self, ElementCodec},
+    decoder::BinaryDecoder,
+    error::{HsdsError, HsdsResult},
+    models::{Shape, WriteResult},
+    values::ShapedValue,
+};
+use log::debug;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+impl<'a> DatasetApi<'a> {
+    /// Create a dataset of `T` elements linked under a group
+    ///
+    /// The dataset gets `T`'s [`ElementCodec::hsds_type`]. Returns the id
+    /// of the new dataset.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the group to link the dataset in
+    /// * `name` - Link name of the dataset
+    /// * `dims` - Dataset dimensions
+    pub async fn create_dataset_of<T: ElementCodec>(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        dims: Vec<u64>,
+    ) -> HsdsResult<String> {
+        crate::names::validate_name(name)?;
+        let body = json!({
+            "type": T::hsds_type(),
+            "shape": dims,
+            "link": {"id": parent_group_id, "name": name},
+        });
+        let mut req = self.client.request(Method::POST, "/datasets").await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        let created: Value = self.client.execute(req.json(&body)).await?;
+        created.get("id").and_then(Value::as_str).map(String::from).ok_or_else(|| {
+            HsdsError::InvalidResponse("Dataset create response has no id".to_string())
+        })
+    }
+
+    /// Read a dataset as `T` elements, flat (row-major)
+    ///
+    /// Types with a binary encoding are read as binary, others as JSON.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    pub async fn read_elements<T: ElementCodec>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+    ) -> HsdsResult<Vec<T>> {
+        let shape = self.element_shape(domain, dataset_id).await?;
+        if shape.as_ref().is_some_and(Shape::is_empty) {
+            return Ok(Vec::new());
+        }
+        if T::BINARY_SIZE.is_some() {
+            let bytes = self.read_dataset_values_with(domain, dataset_id, select, None, None, BinaryDecoder).await?;
+            return codec::decode_binary(&bytes);
+        }
+        let dims = shape.and_then(|s| s.dims).unwrap_or_default();
+        let response = self.read_dataset_values_json(domain, dataset_id, select, None, None).await?;
+        ShapedValue::from_response(response, coords::selection_shape(select, &dims)?).to_vec()
+    }
+
+    /// Read a dataset's values with the shape they are returned in
+    ///
+    /// The values are converted when the result is, with
+    /// [`ShapedValue::to_vec`] or `to_ndarray`, using the shape the server
+    /// reports or else the exact shape of the selection: dimensions
+    /// selected by a single index keep length 1, so `[3,:]` of a 10×4
+    /// dataset is 1×4, as HSDS returns it. A dataset without values reads as an empty result.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    pub async fn read_shaped(&self, domain: &str, dataset_id: &str, select: Option<&str>) -> HsdsResult<ShapedValue> {
+        let shape = self.element_shape(domain, dataset_id).await?;
+        let dims = match shape {
+            Some(Shape { dims: Some(dims), .. }) => dims,
+            Some(shape) if shape.is_empty() => return Ok(ShapedValue::new(json!([]), vec![0])),
+            _ => Vec::new(),
+        };
+        let selection_shape = coords::selection_shape(select, &dims)?;
+        if selection_shape.contains(&0) {
+            return Ok(ShapedValue::new(json!([]), selection_shape));
+        }
+        let response = self.read_dataset_values_json(domain, dataset_id, select, None, None).await?;
+        Ok(ShapedValue::from_response(response, selection_shape))
+    }
+
+    /// Write `T` elements (row-major) to a dataset
+    ///
+    /// Types with a binary encoding are written as binary, others as a
+    /// JSON array nested to the shape of the selection.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `data` - Values filling the selection
+    pub async fn write_elements<T: ElementCodec>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        data: &[T],
+    ) -> HsdsResult<()> {
+        debug!("Writing {} {} elements to dataset {}", data.len(), std::any::type_name::<T>(), dataset_id);
+        if T::BINARY_SIZE.is_some() {
+            let payload = codec::encode_binary(data)?;
+            return self.write_region_raw(domain, dataset_id, select, payload, "application/octet-stream").await;
+        }
+
+        let dims = self.element_shape(domain, dataset_id).await?.and_then(|s| s.dims).unwrap_or_default();
+        let body = json!({"value": codec::encode_json(data, &coords::selection_shape(select, &dims)?)?});
+        let path = format!("/datasets/{}/value", dataset_id);
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        if let Some(select) = select {
+            req = HsdsClient::with_selection(req, select);
+        }
+        self.client.execute_empty(req.json(&body)).await
+    }
+
+    async fn element_shape(&self, domain: &str, dataset_id: &str) -> HsdsResult<Option<Shape>> {
+        let dataset = self.get_dataset_json(domain, dataset_id).await?;
+        Ok(dataset.get("shape").and_then(|s| serde_json::from_value(s.clone()).ok()))
+    }
+}
+
+impl<'a> AttributeApi<'a> {
+    /// Create or replace an attribute holding `T` elements
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    /// * `values` - Elements in row-major order
+    /// * `shape` - Attribute dimensions (defaults to `[values.len()]`)
+    pub async fn set_attribute_elements<T: ElementCodec>(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        values: &[T],
+        shape: Option<Vec<u64>>,
+    ) -> HsdsResult<WriteResult> {
+        let shape = shape.unwrap_or_else(|| vec![values.len() as u64]);
+        let attr_data = json!({
+            "type": T::hsds_type(),
+            "shape": shape,
+            "value": codec::encode_json(values, &shape)?,
+        });
+        self.replace_attribute_raw(domain, collection, obj_uuid, attr_name, attr_data).await
+    }
+
+    /// Read an attribute as `T` elements, flat (row-major)
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    pub async fn get_attribute_elements<T: ElementCodec>(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+    ) -> HsdsResult<Vec<T>> {
+        let attribute = self.get_attribute(domain, collection, obj_uuid, attr_name).await?;
+        let shape: Option<Shape> = attribute.get("shape").and_then(|s| serde_json::from_value(s.clone()).ok());
+        if shape.as_ref().is_some_and(Shape::is_empty) {
+            return Ok(Vec::new());
+        }
+        let rank = shape.and_then(|s| s.dims).map_or(0, |dims| dims.len());
+        codec::decode_json(attribute.get("value").unwrap_or(&Value::Null), rank)
+    }
+}
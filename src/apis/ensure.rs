@@ -0,0 +1,132 @@
+use crate::{
+    apis::domain::DomainApi,
+    error::{HsdsError, HsdsResult},
+    models::{Acl, Domain, DomainKind},
+};
+use log::{debug, info};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Expectations checked by [`DomainApi::ensure_domain`]
+#[derive(Debug, Clone)]
+pub struct EnsureDomainOptions {
+    owner: Option<String>,
+    acls: BTreeMap<String, Acl>,
+    readiness_timeout: Duration,
+}
+
+impl Default for EnsureDomainOptions {
+    fn default() -> Self {
+        Self {
+            owner: None,
+            acls: BTreeMap::new(),
+            readiness_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl EnsureDomainOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the domain to be owned by `user`
+    pub fn with_owner(mut self, user: impl Into<String>) -> Self {
+        self.owner = Some(user.into());
+        self
+    }
+
+    /// Require `user`'s ACL to grant or deny what `acl` sets
+    ///
+    /// Permissions left unset in `acl` are not checked; a user without an
+    /// ACL of their own is checked against the domain's `default` ACL.
+    pub fn with_acl(mut self, user: impl Into<String>, acl: Acl) -> Self {
+        self.acls.insert(user.into(), acl);
+        self
+    }
+
+    /// Longest time to wait for a domain another worker just created (default 10 s)
+    pub fn with_readiness_timeout(mut self, timeout: Duration) -> Self {
+        self.readiness_timeout = timeout;
+        self
+    }
+}
+
+/// Outcome of [`DomainApi::ensure_domain`]
+#[derive(Debug, Clone)]
+pub struct EnsuredDomain {
+    /// The domain as read back from the server
+    pub domain: Domain,
+    /// Whether this call created it (false if another caller did)
+    pub created: bool,
+}
+
+impl<'a> DomainApi<'a> {
+    /// Create a file domain unless it exists, for workers racing to create it
+    ///
+    /// Exactly one caller creates the domain; the others get a conflict
+    /// from the server, which is handled by waiting until the domain
+    /// responds (it may still be propagating). Every caller then checks the
+    /// domain against `options` and gets it as read back from the server,
+    /// so all racers see the same result. A path that exists as a folder,
+    /// or a domain with another owner or ACLs than expected, fails with
+    /// [`HsdsError::Conflict`].
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `options` - Expected owner and ACLs, and how long to wait
+    pub async fn ensure_domain(&self, domain: &str, options: &EnsureDomainOptions) -> HsdsResult<EnsuredDomain> {
+        let created = match self.create_domain(domain, None).await {
+            Ok(_) => {
+                info!("Created domain {}", domain);
+                true
+            }
+            Err(HsdsError::Conflict(message)) => {
+                debug!("Domain {} already exists ({})", domain, message);
+                false
+            }
+            Err(e) => return Err(e),
+        };
+        let info = self.wait_until_ready(domain, options.readiness_timeout).await?;
+
+        if info.kind() != DomainKind::File {
+            return Err(HsdsError::Conflict(format!("{} exists as a folder", domain)));
+        }
+        if let Some(owner) = &options.owner {
+            if info.owner.as_deref() != Some(owner.as_str()) {
+                return Err(HsdsError::Conflict(format!(
+                    "Domain {} is owned by {}, expected {}", domain, info.owner.as_deref().unwrap_or("nobody"), owner
+                )));
+            }
+        }
+        if !options.acls.is_empty() {
+            let acls = self.get_acls(domain).await?;
+            for (user, expected) in &options.acls {
+                let actual = acls.users.get(user).or_else(|| acls.users.get("default")).cloned().unwrap_or_default();
+                if let Some(permission) = acl_mismatch(expected, &actual) {
+                    return Err(HsdsError::Conflict(format!(
+                        "Domain {}: {} permission of {} is not as expected", domain, permission, user
+                    )));
+                }
+            }
+        }
+
+        Ok(EnsuredDomain { domain: info, created })
+    }
+}
+
+/// First permission `expected` sets that `actual` does not grant the same way
+fn acl_mismatch(expected: &Acl, actual: &Acl) -> Option<&'static str> {
+    let permissions = [
+        ("create", expected.create, actual.create),
+        ("read", expected.read, actual.read),
+        ("update", expected.update, actual.update),
+        ("delete", expected.delete, actual.delete),
+        ("readACL", expected.read_acl, actual.read_acl),
+        ("updateACL", expected.update_acl, actual.update_acl),
+    ];
+    permissions
+        .into_iter()
+        .find(|(_, want, have)| want.is_some_and(|want| want != have.unwrap_or(false)))
+        .map(|(name, _, _)| name)
+}
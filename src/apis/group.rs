@@ -1,14 +1,16 @@
 use crate::{
     client::HsdsClient,
-    error::HsdsResult,
-    models::{Group, GroupCreateRequest},
+    error::{HsdsError, HsdsResult},
+    handle::GroupHandle,
+    models::{Group, GroupChildren, GroupCreateRequest, LinkClass, LinkRequest},
+    names::validate_name,
 };
 use reqwest::Method;
 use log::{debug, info};
 
 /// Group API operations
 pub struct GroupApi<'a> {
-    client: &'a HsdsClient,
+    pub(crate) client: &'a HsdsClient,
 }
 
 impl<'a> GroupApi<'a> {
@@ -27,12 +29,15 @@ impl<'a> GroupApi<'a> {
         request: Option<GroupCreateRequest>,
     ) -> HsdsResult<Group> {
         info!("Creating group in domain: {}", domain);
+        if let Some(link) = request.as_ref().and_then(|r| r.link.as_ref()) {
+            validate_name(&link.name)?;
+        }
         let mut req = self.client.request(Method::POST, "/groups").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         debug!("HTTP POST /groups with domain={}", domain);
 
         if let Some(body) = request {
-            debug!("Request body: {:?}", body);
+            debug!("Request body: {}", self.client.log_options().payload(&body));
             req = req.json(&body);
         }
 
@@ -46,7 +51,7 @@ impl<'a> GroupApi<'a> {
     pub async fn list_groups(&self, domain: &str) -> HsdsResult<serde_json::Value> {
         info!("Listing groups in domain: {}", domain);
         let mut req = self.client.request(Method::GET, "/groups").await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         debug!("HTTP GET /groups with domain={}", domain);
 
         self.client.execute(req).await
@@ -67,7 +72,7 @@ impl<'a> GroupApi<'a> {
         info!("Getting group {} in domain: {}", group_id, domain);
         let path = format!("/groups/{}", group_id);
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         debug!("HTTP GET {} with domain={}", path, domain);
 
         if let Some(alias) = get_alias {
@@ -78,6 +83,16 @@ impl<'a> GroupApi<'a> {
         self.client.execute(req).await
     }
 
+    /// Open a Group as a handle with cached metadata
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    pub async fn open(&self, domain: &str, group_id: &str) -> HsdsResult<GroupHandle<'a>> {
+        let info = self.get_group(domain, group_id, None).await?;
+        Ok(GroupHandle::new(self.client, domain, info))
+    }
+
     /// Delete a Group
     /// 
     /// # Arguments
@@ -87,13 +102,159 @@ impl<'a> GroupApi<'a> {
         &self,
         domain: &str,
         group_id: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<()> {
         info!("Deleting group {} in domain: {}", group_id, domain);
         let path = format!("/groups/{}", group_id);
         let mut req = self.client.request(Method::DELETE, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         debug!("HTTP DELETE {} with domain={}", path, domain);
 
-        self.client.execute(req).await
+        self.client.execute_empty(req).await
+    }
+
+    /// List the direct children of a Group, split by object type
+    ///
+    /// Pages through the group's links, so a tree view can expand one node
+    /// without listing every group or dataset in the domain. Hard links are
+    /// sorted by the collection of their target; soft and external links
+    /// are returned unresolved.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    pub async fn children_of(&self, domain: &str, group_id: &str) -> HsdsResult<GroupChildren> {
+        const PAGE_SIZE: u32 = 1000;
+        info!("Listing children of group {} in domain: {}", group_id, domain);
+
+        let mut children = GroupChildren::default();
+        let mut marker: Option<String> = None;
+        loop {
+            let page = self.client.links()
+                .list_links(domain, group_id, Some(PAGE_SIZE), marker.as_deref())
+                .await?;
+            let count = page.links.len();
+            marker = page.links.last().map(|l| l.title.clone());
+
+            for link in page.links {
+                match (&link.class, link.collection.as_deref()) {
+                    (Some(LinkClass::Hard) | None, Some("groups")) => children.groups.push(link),
+                    (Some(LinkClass::Hard) | None, Some("datasets")) => children.datasets.push(link),
+                    (Some(LinkClass::Hard) | None, Some("datatypes")) => children.datatypes.push(link),
+                    _ => children.unresolved.push(link),
+                }
+            }
+
+            if count < PAGE_SIZE as usize || marker.is_none() {
+                break;
+            }
+        }
+        debug!("Group {} has {} children", group_id, children.len());
+        Ok(children)
+    }
+
+    /// Get the id of a child group by link name, creating it if missing
+    ///
+    /// If another writer creates the link concurrently, the existing group is
+    /// returned. A link to a non-group object is a `Conflict` error.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the parent group
+    /// * `name` - Link name of the child group
+    pub async fn get_or_create_group(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+    ) -> HsdsResult<String> {
+        if let Some(id) = self.resolve_child_group(domain, parent_group_id, name).await? {
+            return Ok(id);
+        }
+
+        debug!("Creating missing group '{}' under {}", name, parent_group_id);
+        let request = GroupCreateRequest {
+            link: Some(LinkRequest {
+                id: parent_group_id.to_string(),
+                name: name.to_string(),
+            }),
+        };
+        match self.create_group(domain, Some(request)).await {
+            Ok(group) => Ok(group.id),
+            // Lost a creation race: the link now exists
+            Err(HsdsError::Conflict(_)) => self
+                .resolve_child_group(domain, parent_group_id, name)
+                .await?
+                .ok_or_else(|| HsdsError::Conflict(format!(
+                    "Link '{}' in group {} conflicts but cannot be resolved", name, parent_group_id
+                ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Ensure every group along an absolute path exists and return the last one's id
+    ///
+    /// Missing intermediate groups are created and linked, like `mkdir -p`.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `path` - Absolute group path (e.g. "/a/b/c"); "/" returns the root group
+    pub async fn ensure_group_path(&self, domain: &str, path: &str) -> HsdsResult<String> {
+        info!("Ensuring group path {} in domain: {}", path, domain);
+        if !path.starts_with('/') {
+            return Err(HsdsError::invalid_param(format!(
+                "Group path '{}' must be absolute", path
+            )));
+        }
+
+        let domain_info = self.client.domains().get_domain(domain).await?;
+        let mut current = domain_info.root.ok_or_else(|| {
+            HsdsError::invalid_param(format!("Domain '{}' has no root group (is it a folder?)", domain))
+        })?;
+
+        for name in path.split('/').filter(|s| !s.is_empty()) {
+            current = self.get_or_create_group(domain, &current, name).await?;
+        }
+
+        Ok(current)
+    }
+
+    /// Id of the group at an absolute path, `None` if any group along it is missing
+    ///
+    /// Unlike [`ensure_group_path`](Self::ensure_group_path) nothing is created.
+    pub(crate) async fn find_group_path(&self, domain: &str, path: &str) -> HsdsResult<Option<String>> {
+        let domain_info = self.client.domains().get_domain(domain).await?;
+        let Some(mut current) = domain_info.root else {
+            return Ok(None);
+        };
+        for name in path.split('/').filter(|s| !s.is_empty()) {
+            match self.resolve_child_group(domain, &current, name).await? {
+                Some(id) => current = id,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(current))
+    }
+
+    /// Resolve a child link to a group id, `None` if the link does not exist
+    async fn resolve_child_group(
+        &self,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+    ) -> HsdsResult<Option<String>> {
+        match self.client.links().get_link(domain, parent_group_id, name).await {
+            Ok(link) => {
+                let id = link.get("link")
+                    .and_then(|l| l.get("id"))
+                    .and_then(|id| id.as_str())
+                    .filter(|id| id.starts_with("g-"))
+                    .ok_or_else(|| HsdsError::Conflict(format!(
+                        "Link '{}' in group {} does not refer to a group", name, parent_group_id
+                    )))?;
+                Ok(Some(id.to_string()))
+            }
+            Err(HsdsError::ObjectNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 }
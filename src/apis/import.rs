@@ -0,0 +1,344 @@
+use crate::{
+    apis::{copy::{NameCollision, MAX_COLLISION_ATTEMPTS}, domain::DomainApi},
+    domain_path::DomainPath,
+    error::{ErrorCode, HsdsError, HsdsResult},
+    models::H5Type,
+    names::NameSanitizer,
+    transfer::{TransferReport, TransferStats},
+};
+use log::{debug, info, warn};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// A link or committed type of an HDF5 file, to be recreated in a domain
+///
+/// Groups and datasets are copied by the loader itself; these are the
+/// objects that only make sense once the hierarchy exists. Each item names
+/// the HDF5 path of the group holding it and its name in that group.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportItem {
+    /// Soft link to a path in the same file
+    SoftLink { group: String, name: String, target: String },
+    /// External link to `target` in the HDF5 file `file`
+    ExternalLink { group: String, name: String, file: String, target: String },
+    /// Committed (named) datatype
+    NamedType { group: String, name: String, datatype: H5Type },
+    /// Object HSDS has no counterpart for (e.g. a user-defined link),
+    /// described by `kind`
+    Unsupported { group: String, name: String, kind: String },
+}
+
+impl ImportItem {
+    /// HDF5 path of the item, e.g. "/calibration/latest"
+    pub fn path(&self) -> String {
+        let (group, name) = self.location();
+        item_path(group, name)
+    }
+
+    /// Group path and name of the item
+    fn location(&self) -> (&str, &str) {
+        match self {
+            Self::SoftLink { group, name, .. }
+            | Self::ExternalLink { group, name, .. }
+            | Self::NamedType { group, name, .. }
+            | Self::Unsupported { group, name, .. } => (group, name),
+        }
+    }
+}
+
+fn item_path(group: &str, name: &str) -> String {
+    format!("{}/{}", group.trim_end_matches('/'), name)
+}
+
+/// Options for [`DomainApi::import_items`]
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    external_domains: BTreeMap<String, String>,
+    name_collision: NameCollision,
+    name_sanitizer: Option<NameSanitizer>,
+}
+
+impl ImportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Link to `domain` where an external link names the HDF5 file `file`
+    ///
+    /// File names without a mapping are stored as relative domain targets
+    /// if they are bare or `./`-style names (the file being loaded next to
+    /// this one), and reported as untranslated otherwise.
+    pub fn with_external_domain(mut self, file: impl Into<String>, domain: impl Into<String>) -> Self {
+        self.external_domains.insert(file.into(), domain.into());
+        self
+    }
+
+    /// Policy for items whose name is taken in their group (default: error)
+    ///
+    /// Collisions are listed in [`ImportReport::collisions`]; with `Error`
+    /// the item is reported as untranslated instead. `Overwrite` only
+    /// unlinks what held the name: a committed type (or other object) it
+    /// linked stays in the domain, unreachable unless linked elsewhere.
+    /// `Rename` gives up with `Conflict` after 1000 taken names.
+    pub fn with_name_collision(mut self, policy: NameCollision) -> Self {
+        self.name_collision = policy;
+        self
+    }
+
+    /// Map names HSDS cannot store to valid ones instead of reporting them
+    ///
+    /// Applied to every component of an item's group path and name, and of
+    /// link targets, so links keep pointing at objects loaded under the
+    /// same sanitized names. [`ImportReport`] lists items by their original
+    /// HDF5 paths.
+    pub fn with_name_sanitizer(mut self, sanitizer: NameSanitizer) -> Self {
+        self.name_sanitizer = Some(sanitizer);
+        self
+    }
+
+    /// Name as it is created, sanitized if the options say so
+    fn name(&self, name: &str) -> String {
+        match &self.name_sanitizer {
+            Some(sanitizer) => sanitizer.sanitize(name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Path with each component sanitized if the options say so
+    fn path(&self, path: &str) -> String {
+        if self.name_sanitizer.is_none() {
+            return path.to_string();
+        }
+        path.split('/')
+            .map(|part| match part {
+                "" | "." | ".." => part.to_string(),
+                _ => self.name(part),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Domain an external link to `file` should point at, if any
+    fn external_domain(&self, file: &str) -> Option<String> {
+        if let Some(domain) = self.external_domains.get(file) {
+            return Some(domain.clone());
+        }
+        (DomainPath::is_relative_target(file) && !file.contains('\\')).then(|| file.to_string())
+    }
+}
+
+/// An item [`DomainApi::import_items`] could not recreate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Untranslated {
+    /// HDF5 path of the item
+    pub path: String,
+    /// Why it was left out
+    pub reason: String,
+}
+
+/// An imported item whose name was already taken in its group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCollision {
+    /// HDF5 path of the item
+    pub path: String,
+    /// Path the item was created at, `None` if it was skipped
+    pub linked_as: Option<String>,
+}
+
+/// Outcome of [`DomainApi::import_items`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// HDF5 paths of the soft and external links created
+    pub links: Vec<String>,
+    /// Ids of the committed datatypes created, by HDF5 path
+    pub datatypes: BTreeMap<String, String>,
+    /// Items left out, committed types first
+    pub untranslated: Vec<Untranslated>,
+    /// Name collisions resolved by the [`NameCollision`] policy
+    pub collisions: Vec<ImportCollision>,
+    /// Requests, bytes and latencies of the import
+    pub transfer: Option<TransferReport>,
+}
+
+impl ImportReport {
+    /// Whether every item was recreated
+    pub fn is_complete(&self) -> bool {
+        self.untranslated.is_empty()
+    }
+}
+
+impl<'a> DomainApi<'a> {
+    /// Recreate the links and committed types of an HDF5 file in a domain
+    ///
+    /// Committed types are created first, so their ids in
+    /// [`ImportReport::datatypes`] can be used for datasets created
+    /// afterwards. Soft links keep their target path, which HSDS resolves
+    /// on access like HDF5 does, and external links point at the domain
+    /// `options` maps their file to. Missing groups along an item's path
+    /// are created.
+    ///
+    /// An item whose name is taken in its group is handled by the options'
+    /// [`NameCollision`] policy and listed in [`ImportReport::collisions`].
+    /// Items HSDS cannot store, and items the server rejects as invalid or
+    /// conflicting (e.g. a name taken under the `Error` policy), are
+    /// reported in [`ImportReport::untranslated`] instead of failing the
+    /// import; other errors abort it. The report's
+    /// [`transfer`](ImportReport::transfer) holds the requests, bytes and
+    /// latencies of the call.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `items` - Links and committed types found in the HDF5 file
+    /// * `options` - Domains for the files of external links and the collision policy
+    pub async fn import_items(
+        &self,
+        domain: &str,
+        items: &[ImportItem],
+        options: &ImportOptions,
+    ) -> HsdsResult<ImportReport> {
+        info!("Importing {} links and committed types into domain: {}", items.len(), domain);
+        let stats = TransferStats::new();
+        let client = self.client.clone().with_transfer_stats(stats.clone());
+        let domains = client.domains();
+        let mut report = ImportReport::default();
+        let mut groups = BTreeMap::new();
+        let types_first = items
+            .iter()
+            .filter(|item| matches!(item, ImportItem::NamedType { .. }))
+            .chain(items.iter().filter(|item| !matches!(item, ImportItem::NamedType { .. })));
+
+        for item in types_first {
+            let path = item.path();
+            match domains.import_item(domain, item, options, &mut groups, &mut report.collisions).await {
+                Ok(Imported::Datatype(id)) => {
+                    report.datatypes.insert(path, id);
+                }
+                Ok(Imported::Link) => report.links.push(path),
+                Ok(Imported::Skipped) => {}
+                Err(e) if matches!(e.error_code(), ErrorCode::InvalidParameter | ErrorCode::Conflict) => {
+                    warn!("Not importing {}: {}", path, e);
+                    report.untranslated.push(Untranslated { path, reason: e.to_string() });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        report.transfer = Some(stats.report());
+        Ok(report)
+    }
+
+    /// Create one item, resolving a taken name by the options' policy
+    async fn import_item(
+        &self,
+        domain: &str,
+        item: &ImportItem,
+        options: &ImportOptions,
+        groups: &mut BTreeMap<String, String>,
+        collisions: &mut Vec<ImportCollision>,
+    ) -> HsdsResult<Imported> {
+        if let Some(reason) = untranslatable(item, options) {
+            return Err(HsdsError::invalid_param(reason));
+        }
+        let (group, name) = item.location();
+        let (group, name) = (options.path(group), options.name(name));
+        let (group, name) = (group.as_str(), name.as_str());
+        let group_id = self.import_group(domain, group, groups).await?;
+        let conflict = match self.create_item(domain, item, &group_id, name, options).await {
+            Err(HsdsError::Conflict(message)) => message,
+            result => return result,
+        };
+
+        let (linked_as, imported) = match options.name_collision {
+            NameCollision::Error => return Err(HsdsError::Conflict(conflict)),
+            NameCollision::Skip => (None, Imported::Skipped),
+            NameCollision::Overwrite => {
+                self.client.links().delete_link(domain, &group_id, name).await?;
+                (Some(name.to_string()), self.create_item(domain, item, &group_id, name, options).await?)
+            }
+            NameCollision::Rename => {
+                let mut n = 1;
+                loop {
+                    let candidate = format!("{}_{}", name, n);
+                    match self.create_item(domain, item, &group_id, &candidate, options).await {
+                        Ok(imported) => break (Some(candidate), imported),
+                        // A server answering 409 for another reason must not rename forever
+                        Err(HsdsError::Conflict(_)) if n < MAX_COLLISION_ATTEMPTS => n += 1,
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        };
+        debug!("{} is taken; imported as {:?}", item.path(), linked_as);
+        collisions.push(ImportCollision {
+            path: item.path(),
+            linked_as: linked_as.map(|name| item_path(group, &name)),
+        });
+        Ok(imported)
+    }
+
+    /// Create one item under `name` in a group
+    async fn create_item(
+        &self,
+        domain: &str,
+        item: &ImportItem,
+        group_id: &str,
+        name: &str,
+        options: &ImportOptions,
+    ) -> HsdsResult<Imported> {
+        match item {
+            ImportItem::SoftLink { target, .. } => {
+                self.client.links().create_soft_link(domain, group_id, name, &options.path(target)).await?;
+                Ok(Imported::Link)
+            }
+            ImportItem::ExternalLink { file, target, .. } => {
+                let target_domain = options.external_domain(file).unwrap_or_default();
+                let target = options.path(target);
+                self.client.links().create_external_link(domain, group_id, name, &target, &target_domain).await?;
+                Ok(Imported::Link)
+            }
+            ImportItem::NamedType { datatype, .. } => {
+                let definition = json!({"type": datatype, "link": {"id": group_id, "name": name}});
+                let committed = self.client.datatypes().commit_datatype(domain, definition).await?;
+                debug!("Committed {} as {}", item.path(), committed.id);
+                Ok(Imported::Datatype(committed.id))
+            }
+            ImportItem::Unsupported { .. } => Ok(Imported::Skipped),
+        }
+    }
+
+    /// Id of the group at an HDF5 path, creating it if missing
+    async fn import_group(
+        &self,
+        domain: &str,
+        path: &str,
+        groups: &mut BTreeMap<String, String>,
+    ) -> HsdsResult<String> {
+        if let Some(id) = groups.get(path) {
+            return Ok(id.clone());
+        }
+        let id = self.client.groups().ensure_group_path(domain, path).await?;
+        groups.insert(path.to_string(), id.clone());
+        Ok(id)
+    }
+}
+
+/// What importing one item created
+enum Imported {
+    Link,
+    Datatype(String),
+    Skipped,
+}
+
+/// Why an item cannot be recreated, if it cannot
+fn untranslatable(item: &ImportItem, options: &ImportOptions) -> Option<String> {
+    match item {
+        ImportItem::SoftLink { target, .. } if target.is_empty() => Some("Soft link has an empty target".to_string()),
+        ImportItem::ExternalLink { file, .. } if options.external_domain(file).is_none() => {
+            Some(format!("No domain for the external file '{}'", file))
+        }
+        ImportItem::NamedType { datatype, .. } => datatype
+            .committed_id()
+            .map(|id| format!("Type refers to the committed type {} of the source file", id)),
+        ImportItem::Unsupported { kind, .. } => Some(format!("HSDS cannot store {}", kind)),
+        _ => None,
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{
+    apis::attribute::AttributeApi,
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::DatasetValueRequest,
+};
+use base64::{Engine, engine::general_purpose};
+use log::{debug, info, warn};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+/// Group (linked from the root) holding datasets that back large attributes
+pub const LARGE_ATTRIBUTE_GROUP: &str = "/__large_attributes__";
+
+/// Prefix of the value of an attribute whose data lives in a dataset
+///
+/// A spilled attribute keeps its name but becomes a variable-length string
+/// `"hsds-large-attribute:<dataset id>"`. The dataset has the attribute's
+/// original type and shape and is hard-linked under its own id in
+/// [`LARGE_ATTRIBUTE_GROUP`], so domain copies and snapshots carry it along.
+pub const LARGE_ATTRIBUTE_PREFIX: &str = "hsds-large-attribute:";
+
+/// Options for [`AttributeApi::set_large_attribute`]
+#[derive(Debug, Clone)]
+pub struct LargeAttributeOptions {
+    max_inline_bytes: usize,
+    spill: bool,
+}
+
+impl Default for LargeAttributeOptions {
+    fn default() -> Self {
+        Self {
+            max_inline_bytes: 1024 * 1024,
+            spill: true,
+        }
+    }
+}
+
+impl LargeAttributeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest request body (in bytes of JSON) written as a plain attribute
+    ///
+    /// Also the size of the row slices a spilled value is written in.
+    pub fn with_max_inline_bytes(mut self, bytes: usize) -> Self {
+        self.max_inline_bytes = bytes;
+        self
+    }
+
+    /// Whether oversized values may be stored in a dataset (the default)
+    ///
+    /// Without spilling, oversized values are still sent as attributes and
+    /// the server's error is returned if it rejects them.
+    pub fn with_spill(mut self, spill: bool) -> Self {
+        self.spill = spill;
+        self
+    }
+}
+
+/// Where a large attribute's data was stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeStorage {
+    /// A regular attribute
+    Inline,
+    /// A dataset (by id) referenced from the attribute
+    Dataset(String),
+}
+
+impl<'a> AttributeApi<'a> {
+    /// Create or overwrite an attribute that may be too large for the server
+    ///
+    /// Values whose request body exceeds the inline limit, or that the
+    /// server rejects with `413 Payload Too Large`, are written to a dataset
+    /// following the [`LARGE_ATTRIBUTE_PREFIX`] convention, in slices of
+    /// rows that each stay under the inline limit. A dataset
+    /// backing a previous value of the attribute is deleted, but only if it
+    /// is linked under its id in [`LARGE_ATTRIBUTE_GROUP`]; a plain string
+    /// value that merely looks like a reference never deletes a dataset.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    /// * `attr_data` - Attribute body (`type`, `shape`, `value`, optional `encoding`)
+    /// * `options` - Inline limit and spill options
+    pub async fn set_large_attribute(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        attr_data: Value,
+        options: &LargeAttributeOptions,
+    ) -> HsdsResult<AttributeStorage> {
+        let previous = self.spilled_dataset(domain, collection, obj_uuid, attr_name).await?;
+        let size = serde_json::to_vec(&attr_data)?.len();
+
+        let storage = if options.spill && size > options.max_inline_bytes {
+            debug!("Attribute '{}' is {} bytes; storing it in a dataset", attr_name, size);
+            self.spill(domain, collection, obj_uuid, attr_name, &attr_data, options.max_inline_bytes).await?
+        } else {
+            match self.replace_attribute_raw(domain, collection, obj_uuid, attr_name, attr_data.clone()).await {
+                Ok(_) => AttributeStorage::Inline,
+                Err(HsdsError::Api { status: 413, message }) if options.spill => {
+                    info!("Server rejected attribute '{}' as too large ({}); storing it in a dataset", attr_name, message);
+                    self.spill(domain, collection, obj_uuid, attr_name, &attr_data, options.max_inline_bytes).await?
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        if let Some((group_id, old)) = previous {
+            if storage != AttributeStorage::Dataset(old.clone()) {
+                self.delete_spilled_dataset(domain, &group_id, &old).await;
+            }
+        }
+        Ok(storage)
+    }
+
+    /// Get an attribute, resolving values stored in a dataset
+    ///
+    /// For a spilled attribute the returned JSON has the dataset's `type`,
+    /// `shape` and `value` in place of the reference string; other
+    /// attributes are returned unchanged. As on write, a reference is only
+    /// followed if its dataset is linked in [`LARGE_ATTRIBUTE_GROUP`].
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    pub async fn get_large_attribute(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+    ) -> HsdsResult<Value> {
+        let mut attribute = self.get_attribute(domain, collection, obj_uuid, attr_name).await?;
+        let Some((_, dataset_id)) = self.linked_spill(domain, attr_name, &attribute).await? else {
+            return Ok(attribute);
+        };
+
+        let mut req = self.client.request(Method::GET, &format!("/datasets/{}", dataset_id)).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        let dataset: Value = self.client.execute(req).await?;
+        let values = self.client.datasets().read_dataset_values_json(domain, &dataset_id, None, None, None).await?;
+        attribute["type"] = dataset.get("type").cloned().unwrap_or(Value::Null);
+        attribute["shape"] = dataset.get("shape").cloned().unwrap_or(Value::Null);
+        attribute["value"] = values.get("value").cloned().unwrap_or(Value::Null);
+        Ok(attribute)
+    }
+
+    /// Delete an attribute and the dataset holding its value, if any
+    ///
+    /// As with [`set_large_attribute`](Self::set_large_attribute), only a
+    /// dataset linked in [`LARGE_ATTRIBUTE_GROUP`] is deleted.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `collection` - Object collection type
+    /// * `obj_uuid` - UUID of the object
+    /// * `attr_name` - Name of the attribute
+    pub async fn delete_large_attribute(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+    ) -> HsdsResult<()> {
+        let spilled = self.spilled_dataset(domain, collection, obj_uuid, attr_name).await?;
+        self.delete_attribute(domain, collection, obj_uuid, attr_name).await?;
+        if let Some((group_id, dataset_id)) = spilled {
+            self.delete_spilled_dataset(domain, &group_id, &dataset_id).await;
+        }
+        Ok(())
+    }
+
+    /// Write an attribute's data to a new dataset and point the attribute at it
+    async fn spill(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+        attr_data: &Value,
+        max_bytes: usize,
+    ) -> HsdsResult<AttributeStorage> {
+        let group_id = self.client.groups().ensure_group_path(domain, LARGE_ATTRIBUTE_GROUP).await?;
+
+        let mut body = json!({"type": attr_data.get("type").cloned().unwrap_or(Value::Null)});
+        if let Some(dims) = attr_data.get("shape").filter(|s| s.is_array()) {
+            body["shape"] = dims.clone();
+        }
+        let mut req = self.client.request(Method::POST, "/datasets").await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        let created: Value = self.client.execute(req.json(&body)).await?;
+        let dataset_id = created.get("id").and_then(|id| id.as_str()).map(String::from).ok_or_else(|| {
+            HsdsError::InvalidResponse("Dataset create response has no id".to_string())
+        })?;
+
+        let result = async {
+            self.client.links().create_hard_link(domain, &group_id, &dataset_id, &dataset_id).await?;
+
+            self.write_spilled_value(domain, &dataset_id, attr_data, max_bytes).await?;
+
+            let reference = json!({
+                "type": {
+                    "class": "H5T_STRING",
+                    "charSet": "H5T_CSET_UTF8",
+                    "strPad": "H5T_STR_NULLTERM",
+                    "length": "H5T_VARIABLE"
+                },
+                "value": format!("{}{}", LARGE_ATTRIBUTE_PREFIX, dataset_id),
+            });
+            self.replace_attribute_raw(domain, collection, obj_uuid, attr_name, reference).await
+        }
+        .await;
+
+        match result {
+            Ok(_) => Ok(AttributeStorage::Dataset(dataset_id)),
+            Err(e) => {
+                self.delete_spilled_dataset(domain, &group_id, &dataset_id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Write a spilled value in row slices of at most `max_bytes` each
+    ///
+    /// Slices are selections along the first dimension, sized by their
+    /// encoded length; a slice the server still rejects with `413` is split
+    /// in half and retried. Scalars and values whose rows cannot be told
+    /// apart are written in one request.
+    async fn write_spilled_value(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        attr_data: &Value,
+        max_bytes: usize,
+    ) -> HsdsResult<()> {
+        let dims: Vec<u64> = attr_data
+            .get("shape")
+            .and_then(|s| s.as_array())
+            .map(|dims| dims.iter().filter_map(|d| d.as_u64()).collect())
+            .unwrap_or_default();
+        let value = attr_data.get("value").cloned();
+        let base64 = attr_data.get("encoding").and_then(|e| e.as_str()) == Some("base64");
+        let Some(rows) = SpillRows::new(value.as_ref(), base64, dims.first().copied()) else {
+            let request = DatasetValueRequest {
+                start: None,
+                stop: None,
+                step: None,
+                points: None,
+                value_base64: if base64 { value.as_ref().and_then(|v| v.as_str()).map(String::from) } else { None },
+                value: if base64 { None } else { value },
+            };
+            self.client.datasets().write_dataset_values(domain, dataset_id, request).await?;
+            return Ok(());
+        };
+
+        // Pending slices as (first row, end row), next one last
+        let mut pending = rows.slices(max_bytes);
+        pending.reverse();
+        while let Some((first, end)) = pending.pop() {
+            let mut start = vec![0; dims.len()];
+            start[0] = first;
+            let mut stop = dims.clone();
+            stop[0] = end;
+            let (value, value_base64) = rows.value(first, end);
+            let request = DatasetValueRequest {
+                start: Some(start),
+                stop: Some(stop),
+                step: None,
+                points: None,
+                value,
+                value_base64,
+            };
+            match self.client.datasets().write_dataset_values(domain, dataset_id, request).await {
+                Ok(_) => {}
+                Err(HsdsError::Api { status: 413, .. }) if end - first > 1 => {
+                    let middle = first + (end - first) / 2;
+                    debug!("Rows {}..{} of dataset {} are too large; splitting them", first, end, dataset_id);
+                    pending.push((middle, end));
+                    pending.push((first, middle));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Group and dataset ids backing an existing attribute, if it is spilled
+    ///
+    /// A reference is only trusted if the dataset it names is hard-linked
+    /// under its id in [`LARGE_ATTRIBUTE_GROUP`]; the group is looked up
+    /// without being created.
+    async fn spilled_dataset(
+        &self,
+        domain: &str,
+        collection: &str,
+        obj_uuid: &str,
+        attr_name: &str,
+    ) -> HsdsResult<Option<(String, String)>> {
+        match self.get_attribute(domain, collection, obj_uuid, attr_name).await {
+            Ok(attribute) => self.linked_spill(domain, attr_name, &attribute).await,
+            Err(HsdsError::ObjectNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Group and dataset ids an attribute value refers to, if the dataset
+    /// is hard-linked under its id in [`LARGE_ATTRIBUTE_GROUP`]
+    async fn linked_spill(
+        &self,
+        domain: &str,
+        attr_name: &str,
+        attribute: &Value,
+    ) -> HsdsResult<Option<(String, String)>> {
+        let Some(dataset_id) = spilled_id(attribute) else {
+            return Ok(None);
+        };
+        let Some(group_id) = self.client.groups().find_group_path(domain, LARGE_ATTRIBUTE_GROUP).await? else {
+            warn!("Attribute '{}' names {} but {} does not exist; not treating it as spilled", attr_name, dataset_id, LARGE_ATTRIBUTE_GROUP);
+            return Ok(None);
+        };
+        let linked = match self.client.links().get_link(domain, &group_id, dataset_id).await {
+            Ok(link) => link.get("link").and_then(|l| l.get("id")).and_then(|id| id.as_str()) == Some(dataset_id),
+            Err(HsdsError::ObjectNotFound(_)) => false,
+            Err(e) => return Err(e),
+        };
+        if !linked {
+            warn!("Attribute '{}' names {} but it is not linked in {}; not treating it as spilled", attr_name, dataset_id, LARGE_ATTRIBUTE_GROUP);
+            return Ok(None);
+        }
+        Ok(Some((group_id, dataset_id.to_string())))
+    }
+
+    /// Unlink and delete a backing dataset, logging failures
+    async fn delete_spilled_dataset(&self, domain: &str, group_id: &str, dataset_id: &str) {
+        match self.client.links().delete_link(domain, group_id, dataset_id).await {
+            Ok(()) | Err(HsdsError::ObjectNotFound(_)) => {}
+            Err(e) => warn!("Failed to unlink attribute dataset {}: {}", dataset_id, e),
+        }
+        if let Err(e) = self.client.datasets().delete_dataset(domain, dataset_id).await {
+            warn!("Failed to delete attribute dataset {}: {}", dataset_id, e);
+        }
+    }
+}
+
+/// Rows of a spilled value along its first dimension
+enum SpillRows {
+    /// JSON value, one element per row
+    Json(Vec<Value>),
+    /// Base64-decoded value with the byte size of a row
+    Binary { bytes: Vec<u8>, row_bytes: usize },
+}
+
+impl SpillRows {
+    /// Split a value into `rows` rows, `None` if it does not have that many
+    fn new(value: Option<&Value>, base64: bool, rows: Option<u64>) -> Option<Self> {
+        let rows = usize::try_from(rows?).ok().filter(|&n| n > 0)?;
+        if base64 {
+            let bytes = general_purpose::STANDARD.decode(value?.as_str()?).ok()?;
+            (!bytes.is_empty() && bytes.len() % rows == 0).then(|| Self::Binary { row_bytes: bytes.len() / rows, bytes })
+        } else {
+            let values = value?.as_array()?;
+            (values.len() == rows).then(|| Self::Json(values.clone()))
+        }
+    }
+
+    /// Encoded size of a row in a request body
+    fn row_size(&self, row: usize) -> usize {
+        match self {
+            Self::Json(values) => serde_json::to_vec(&values[row]).map_or(0, |v| v.len()) + 1,
+            Self::Binary { row_bytes, .. } => row_bytes.div_ceil(3) * 4,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Json(values) => values.len(),
+            Self::Binary { bytes, row_bytes } => bytes.len() / row_bytes,
+        }
+    }
+
+    /// Consecutive row ranges whose encoded size stays under `max_bytes`
+    ///
+    /// A row larger than the limit on its own gets a slice to itself.
+    fn slices(&self, max_bytes: usize) -> Vec<(u64, u64)> {
+        let mut slices = Vec::new();
+        let (mut first, mut size) = (0, 0);
+        for row in 0..self.len() {
+            let row_size = self.row_size(row);
+            if row > first && size + row_size > max_bytes {
+                slices.push((first as u64, row as u64));
+                (first, size) = (row, 0);
+            }
+            size += row_size;
+        }
+        slices.push((first as u64, self.len() as u64));
+        slices
+    }
+
+    /// `value` or `value_base64` of the rows `first..end`
+    fn value(&self, first: u64, end: u64) -> (Option<Value>, Option<String>) {
+        let (first, end) = (first as usize, end as usize);
+        match self {
+            Self::Json(values) => (Some(Value::Array(values[first..end].to_vec())), None),
+            Self::Binary { bytes, row_bytes } => {
+                (None, Some(general_purpose::STANDARD.encode(&bytes[first * row_bytes..end * row_bytes])))
+            }
+        }
+    }
+}
+
+/// Dataset id referenced by a spilled attribute's value
+fn spilled_id(attribute: &Value) -> Option<&str> {
+    attribute
+        .get("value")
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.strip_prefix(LARGE_ATTRIBUTE_PREFIX))
+}
@@ -1,8 +1,12 @@
 use crate::{
+    apis::domain::wildcard_match,
     client::HsdsClient,
     error::HsdsResult,
-    models::{Links, LinkCreateRequest},
+    models::{Link, Links, LinkCreateRequest, LinkOrder, WriteResult},
+    names::validate_name,
+    pagination::{self, PageCursor, PaginationOptions},
 };
+use futures_util::Stream;
 use reqwest::Method;
 
 /// Link API operations
@@ -28,15 +32,143 @@ impl<'a> LinkApi<'a> {
         group_id: &str,
         limit: Option<u32>,
         marker: Option<&str>,
+    ) -> HsdsResult<Links> {
+        self.list_links_ordered(domain, group_id, LinkOrder::Name, limit, marker).await
+    }
+
+    /// List Links in a Group in a given order
+    ///
+    /// With [`LinkOrder::CreateOrder`] links come back in the order they
+    /// were created, e.g. acquisition channels in the order they were added;
+    /// each link's `created` timestamp is included.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    /// * `order` - Listing order
+    /// * `limit` - Maximum number of links to return
+    /// * `marker` - Link name to start listing after
+    pub async fn list_links_ordered(
+        &self,
+        domain: &str,
+        group_id: &str,
+        order: LinkOrder,
+        limit: Option<u32>,
+        marker: Option<&str>,
     ) -> HsdsResult<Links> {
         let path = format!("/groups/{}/links", group_id);
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         req = HsdsClient::with_pagination(req, limit, marker);
 
+        if order == LinkOrder::CreateOrder {
+            req = req.query(&[("CreateOrder", "1")]);
+        }
+
         self.client.execute(req).await
     }
 
+    /// List the Links in a Group whose names match a glob pattern
+    ///
+    /// The pattern (`*`, `?` and classes such as `chan[0-9]*`) is sent as
+    /// HSDS's `pattern` parameter so the server filters large groups; the
+    /// links are filtered again here for servers that ignore it.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    /// * `pattern` - Glob pattern link names must match
+    pub async fn list_links_matching(
+        &self,
+        domain: &str,
+        group_id: &str,
+        pattern: &str,
+    ) -> HsdsResult<Vec<Link>> {
+        let path = format!("/groups/{}/links", group_id);
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        req = req.query(&[("pattern", pattern)]);
+
+        let page: Links = self.client.execute(req).await?;
+        Ok(page.links.into_iter().filter(|link| wildcard_match(pattern, &link.title)).collect())
+    }
+
+    /// Stream all Links in a Group, fetching pages as needed
+    ///
+    /// Uses the default [`PaginationOptions`]; see
+    /// [`links_stream_with`](Self::links_stream_with). A failed page request
+    /// is yielded as an error and ends the stream.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    /// * `order` - Listing order
+    pub fn links_stream(
+        &self,
+        domain: &str,
+        group_id: &str,
+        order: LinkOrder,
+    ) -> impl Stream<Item = HsdsResult<Link>> + 'a {
+        self.links_stream_with(domain, group_id, order, PaginationOptions::default())
+    }
+
+    /// Stream all Links in a Group with adaptive page sizes
+    ///
+    /// The page size adapts to response latency, as configured by `options`.
+    /// Throttled requests (429/503) are retried by the client's retry
+    /// policy, and the time spent retrying shrinks the next page.
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    /// * `order` - Listing order
+    /// * `options` - Page size and limit options
+    pub fn links_stream_with(
+        &self,
+        domain: &str,
+        group_id: &str,
+        order: LinkOrder,
+        options: PaginationOptions,
+    ) -> impl Stream<Item = HsdsResult<Link>> + 'a {
+        let client = self.client;
+        let (domain, group_id) = (domain.to_string(), group_id.to_string());
+
+        pagination::paginate(
+            options,
+            move |limit, marker| {
+                let (domain, group_id) = (domain.clone(), group_id.clone());
+                async move {
+                    let page = client.links()
+                        .list_links_ordered(&domain, &group_id, order, Some(limit), marker.as_deref())
+                        .await?;
+                    Ok(page.links)
+                }
+            },
+            |link: &Link| link.title.clone(),
+        )
+    }
+
+    /// Stream all Links in a Group, each with the cursor that resumes after it
+    ///
+    /// Store the cursor of the last link processed; passing it back with
+    /// [`PaginationOptions::with_cursor`] continues an interrupted traversal
+    /// after that link instead of from the beginning of the group.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    /// * `order` - Listing order
+    /// * `options` - Page size, limit and start options
+    pub fn links_stream_resumable(
+        &self,
+        domain: &str,
+        group_id: &str,
+        order: LinkOrder,
+        options: PaginationOptions,
+    ) -> impl Stream<Item = HsdsResult<(Link, PageCursor)>> + 'a {
+        pagination::with_cursors(self.links_stream_with(domain, group_id, order, options), |link| link.title.clone())
+    }
+
     /// Create a Link in a Group
     /// 
     /// # Arguments
@@ -50,14 +182,15 @@ impl<'a> LinkApi<'a> {
         group_id: &str,
         link_name: &str,
         request: LinkCreateRequest,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<WriteResult> {
+        validate_name(link_name)?;
         let path = format!("/groups/{}/links/{}", group_id, 
                           urlencoding::encode(link_name));
         let mut req = self.client.request(Method::PUT, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
         req = req.json(&request);
 
-        self.client.execute(req).await
+        self.client.execute_write(req).await
     }
 
     /// Get information about a Link
@@ -75,11 +208,31 @@ impl<'a> LinkApi<'a> {
         let path = format!("/groups/{}/links/{}", group_id, 
                           urlencoding::encode(link_name));
         let mut req = self.client.request(Method::GET, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
         self.client.execute(req).await
     }
 
+    /// Check whether a Link exists (HEAD request, 404 maps to `false`)
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `group_id` - UUID of the group
+    /// * `link_name` - Name of the link
+    pub async fn link_exists(
+        &self,
+        domain: &str,
+        group_id: &str,
+        link_name: &str,
+    ) -> HsdsResult<bool> {
+        let path = format!("/groups/{}/links/{}", group_id, 
+                          urlencoding::encode(link_name));
+        let mut req = self.client.request(Method::HEAD, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+
+        self.client.execute_exists(req).await
+    }
+
     /// Delete a Link
     /// 
     /// # Arguments
@@ -91,13 +244,13 @@ impl<'a> LinkApi<'a> {
         domain: &str,
         group_id: &str,
         link_name: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<()> {
         let path = format!("/groups/{}/links/{}", group_id, 
                           urlencoding::encode(link_name));
         let mut req = self.client.request(Method::DELETE, &path).await?;
-        req = HsdsClient::with_domain(req, domain);
+        req = HsdsClient::with_domain(req, domain)?;
 
-        self.client.execute(req).await
+        self.client.execute_empty(req).await
     }
 
     /// Create a hard link (convenience method)
@@ -113,7 +266,7 @@ impl<'a> LinkApi<'a> {
         group_id: &str,
         link_name: &str,
         target_id: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<WriteResult> {
         let request = LinkCreateRequest {
             id: Some(target_id.to_string()),
             h5path: None,
@@ -136,7 +289,7 @@ impl<'a> LinkApi<'a> {
         group_id: &str,
         link_name: &str,
         target_path: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<WriteResult> {
         let request = LinkCreateRequest {
             id: None,
             h5path: Some(target_path.to_string()),
@@ -147,13 +300,19 @@ impl<'a> LinkApi<'a> {
     }
 
     /// Create an external link (convenience method)
+    ///
+    /// `target_domain` may be relative to the folder of `domain` (e.g.
+    /// `calib.h5`, `./calib/gain.h5` or `../shared/base.h5`, see
+    /// [`DomainPath::is_relative_target`](crate::DomainPath::is_relative_target)).
+    /// It is stored as written, so a bundle of domains linked this way can
+    /// be moved to another folder without rewriting its links.
     /// 
     /// # Arguments
     /// * `domain` - Domain path
     /// * `group_id` - UUID of the source group
     /// * `link_name` - Name of the link
     /// * `target_path` - Path to the target object
-    /// * `target_domain` - External domain URL
+    /// * `target_domain` - External domain URL, absolute or relative
     pub async fn create_external_link(
         &self,
         domain: &str,
@@ -161,7 +320,7 @@ impl<'a> LinkApi<'a> {
         link_name: &str,
         target_path: &str,
         target_domain: &str,
-    ) -> HsdsResult<serde_json::Value> {
+    ) -> HsdsResult<WriteResult> {
         let request = LinkCreateRequest {
             id: None,
             h5path: Some(target_path.to_string()),
@@ -0,0 +1,451 @@
+use crate::{
+    apis::domain::DomainApi,
+    client::HsdsClient,
+    domain_path::DomainPath,
+    error::{HsdsError, HsdsResult},
+    models::{Link, LinkClass, LinkOrder},
+};
+use futures_util::TryStreamExt;
+use log::{debug, info};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// Maximum number of soft links followed while resolving one path
+const MAX_SOFT_LINK_DEPTH: usize = 16;
+
+/// An object reachable through more than one hard link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedObject {
+    pub id: String,
+    /// Every link path pointing at the object
+    pub paths: Vec<String>,
+}
+
+/// A soft or external link whose target does not exist
+#[derive(Debug, Clone)]
+pub struct BrokenLink {
+    /// Path of the link itself
+    pub path: String,
+    pub class: LinkClass,
+    /// Target path, prefixed with the domain for external links
+    pub target: String,
+    pub reason: String,
+}
+
+/// Result of [`DomainApi::audit_links`]
+#[derive(Debug, Clone, Default)]
+pub struct LinkAuditReport {
+    /// Number of objects reachable from the root group (root included)
+    pub reachable: usize,
+    /// Objects with more than one parent link
+    pub multiple_parents: Vec<SharedObject>,
+    /// Groups and datasets that exist but cannot be reached from the root
+    pub unreachable: Vec<String>,
+    pub broken_links: Vec<BrokenLink>,
+}
+
+impl LinkAuditReport {
+    /// Whether the domain has no orphans and no broken links
+    ///
+    /// Shared objects are reported but not considered a problem.
+    pub fn is_clean(&self) -> bool {
+        self.unreachable.is_empty() && self.broken_links.is_empty()
+    }
+}
+
+/// Clients used to resolve external links into other domains
+///
+/// Each client serves the domains under a path prefix, e.g. a client with
+/// other credentials for "/shared/"; the longest matching prefix wins.
+/// Domains no prefix matches are resolved with the auditing client.
+///
+/// Relative target domains (see [`DomainPath::is_relative_target`]) are
+/// resolved against the folder of the domain holding the link, or against
+/// a fixed folder set with [`with_relative_base`](Self::with_relative_base).
+#[derive(Clone, Default)]
+pub struct ExternalLinkOptions {
+    clients: Vec<(String, HsdsClient)>,
+    relative_base: Option<DomainPath>,
+}
+
+impl ExternalLinkOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve domains under `prefix` with `client`
+    pub fn with_client(mut self, prefix: impl Into<String>, client: HsdsClient) -> Self {
+        self.clients.push((prefix.into(), client));
+        self
+    }
+
+    /// Resolve relative target domains against this folder
+    ///
+    /// Like HDF5's external link prefix, this lets a relocated bundle be
+    /// checked where it now lives without rewriting its links.
+    pub fn with_relative_base(mut self, folder: DomainPath) -> Self {
+        self.relative_base = Some(folder);
+        self
+    }
+
+    /// Target domain of an external link held by `linking_domain`
+    ///
+    /// # Arguments
+    /// * `linking_domain` - Domain holding the link
+    /// * `target` - The link's target domain, absolute or relative
+    pub fn resolve_domain(&self, linking_domain: &str, target: &str) -> HsdsResult<DomainPath> {
+        let target = target.trim();
+        let target = target.strip_prefix("hdf5:/").filter(|t| t.starts_with('/')).unwrap_or(target);
+        match &self.relative_base {
+            Some(folder) if DomainPath::is_relative_target(target) => DomainPath::folder(folder.as_str())?.resolve(target),
+            _ => DomainPath::parse(linking_domain)?.resolve(target),
+        }
+    }
+
+    fn client_for<'c>(&'c self, domain: &str, default: &'c HsdsClient) -> &'c HsdsClient {
+        self.clients
+            .iter()
+            .filter(|(prefix, _)| domain.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, client)| client)
+            .unwrap_or(default)
+    }
+}
+
+/// Outcome of resolving one external link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalTarget {
+    /// The target exists; holds its object id
+    Resolved(String),
+    DomainNotFound,
+    /// The domain exists but the path does not resolve
+    PathNotFound(String),
+    /// The target could not be checked (e.g. permission denied)
+    Inaccessible(String),
+}
+
+/// An external link and the state of its target
+#[derive(Debug, Clone)]
+pub struct ExternalLinkCheck {
+    /// Path of the link itself
+    pub path: String,
+    /// Target domain, resolved against the linking domain if relative
+    pub target_domain: String,
+    pub target_path: String,
+    pub target: ExternalTarget,
+}
+
+impl ExternalLinkCheck {
+    /// Whether the target is known to be missing
+    pub fn is_dead(&self) -> bool {
+        matches!(self.target, ExternalTarget::DomainNotFound | ExternalTarget::PathNotFound(_))
+    }
+}
+
+/// Hard-link graph of a domain, built from the root group
+pub(crate) struct LinkGraph {
+    pub(crate) root: String,
+    /// Links of every reachable group
+    children: HashMap<String, Vec<Link>>,
+    /// Link paths by target object id
+    pub(crate) parents: BTreeMap<String, Vec<String>>,
+    /// Soft and external links as (containing group, link path, link)
+    indirect: Vec<(String, String, Link)>,
+}
+
+impl LinkGraph {
+    /// Hard links to an object as (group id, link name, link path)
+    pub(crate) fn hard_links_to(&self, object_id: &str) -> Vec<(String, String, String)> {
+        let mut links: Vec<_> = self.children
+            .iter()
+            .flat_map(|(group_id, links)| links.iter().map(move |link| (group_id, link)))
+            .filter(|(_, link)| link.id.as_deref() == Some(object_id) && !matches!(link.class, Some(LinkClass::Soft | LinkClass::External)))
+            .map(|(group_id, link)| {
+                // Groups were walked under the first path that reached them
+                let group_path = self.parents.get(group_id).and_then(|paths| paths.first()).map_or("", String::as_str);
+                (group_id.clone(), link.title.clone(), format!("{}/{}", group_path, link.title))
+            })
+            .collect();
+        links.sort_by(|a, b| a.2.cmp(&b.2));
+        links
+    }
+
+    /// Paths of soft links that resolve to an object
+    pub(crate) fn soft_links_to(&self, object_id: &str) -> Vec<String> {
+        self.indirect
+            .iter()
+            .filter(|(_, _, link)| matches!(link.class, Some(LinkClass::Soft)))
+            .filter(|(group_id, _, link)| self.resolve(group_id, link.h5path.as_deref().unwrap_or_default(), 0).as_deref() == Ok(object_id))
+            .map(|(_, path, _)| path.clone())
+            .collect()
+    }
+
+    /// Resolve a soft link path to an object id within the domain
+    fn resolve(&self, from_group: &str, h5path: &str, depth: usize) -> Result<String, String> {
+        if depth > MAX_SOFT_LINK_DEPTH {
+            return Err("too many levels of soft links".to_string());
+        }
+        let mut current = if h5path.starts_with('/') { self.root.clone() } else { from_group.to_string() };
+        let segments: Vec<&str> = h5path.split('/').filter(|s| !s.is_empty() && *s != ".").collect();
+
+        for (i, name) in segments.iter().enumerate() {
+            let link = self.children
+                .get(&current)
+                .and_then(|links| links.iter().find(|l| l.title == *name))
+                .ok_or_else(|| format!("'{}' not found", segments[..=i].join("/")))?;
+            current = match (&link.class, &link.id) {
+                (Some(LinkClass::Soft), _) => {
+                    self.resolve(&current, link.h5path.as_deref().unwrap_or_default(), depth + 1)?
+                }
+                (Some(LinkClass::External), _) => {
+                    return Err(format!("'{}' is an external link", segments[..=i].join("/")))
+                }
+                (_, Some(id)) => id.clone(),
+                (_, None) => return Err(format!("'{}' has no target", segments[..=i].join("/"))),
+            };
+        }
+        Ok(current)
+    }
+}
+
+impl<'a> DomainApi<'a> {
+    /// Build the link graph of a domain and report hygiene problems
+    ///
+    /// Walks every group reachable from the root and reports objects with
+    /// several parent links, groups and datasets no link path reaches, soft
+    /// links whose path does not resolve, and external links whose target
+    /// domain does not exist. Paths inside other domains are checked by
+    /// [`validate_external_links`](Self::validate_external_links).
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    pub async fn audit_links(&self, domain: &str) -> HsdsResult<LinkAuditReport> {
+        info!("Auditing links of domain: {}", domain);
+        let graph = self.link_graph(domain).await?;
+        let mut report = LinkAuditReport {
+            reachable: graph.parents.len() + 1,
+            ..LinkAuditReport::default()
+        };
+
+        for (id, paths) in &graph.parents {
+            if paths.len() > 1 {
+                report.multiple_parents.push(SharedObject { id: id.clone(), paths: paths.clone() });
+            }
+        }
+
+        let mut listed: Vec<String> = Vec::new();
+        let groups = self.client.groups().list_groups(domain).await?;
+        if let Some(ids) = groups.get("groups").and_then(|g| g.as_array()) {
+            listed.extend(ids.iter().filter_map(|id| id.as_str()).map(String::from));
+        }
+        listed.extend(self.client.datasets().list_datasets(domain).await?.datasets);
+        report.unreachable = listed
+            .into_iter()
+            .filter(|id| *id != graph.root && !graph.parents.contains_key(id))
+            .collect();
+        report.unreachable.sort();
+        report.unreachable.dedup();
+
+        let mut domain_exists: HashMap<String, bool> = HashMap::new();
+        for (group_id, path, link) in &graph.indirect {
+            let target = link.h5path.clone().unwrap_or_default();
+            match link.class {
+                Some(LinkClass::External) => {
+                    let raw_domain = link.h5domain.clone().unwrap_or_default();
+                    let resolved = ExternalLinkOptions::default().resolve_domain(domain, &raw_domain);
+                    let target_domain = resolved.as_ref().map_or(raw_domain, plain_domain);
+                    let exists = match domain_exists.get(&target_domain) {
+                        Some(exists) => *exists,
+                        None => {
+                            let exists = match &resolved {
+                                Ok(resolved) => self.domain_exists(resolved).await.unwrap_or(false),
+                                Err(_) => false,
+                            };
+                            domain_exists.insert(target_domain.clone(), exists);
+                            exists
+                        }
+                    };
+                    if !exists {
+                        report.broken_links.push(BrokenLink {
+                            path: path.clone(),
+                            class: LinkClass::External,
+                            target: format!("{}:{}", target_domain, target),
+                            reason: format!("domain '{}' does not exist", target_domain),
+                        });
+                    }
+                }
+                _ => {
+                    if let Err(reason) = graph.resolve(group_id, &target, 0) {
+                        report.broken_links.push(BrokenLink {
+                            path: path.clone(),
+                            class: LinkClass::Soft,
+                            target,
+                            reason,
+                        });
+                    }
+                }
+            }
+        }
+
+        debug!(
+            "Link audit of {}: {} reachable, {} shared, {} unreachable, {} broken",
+            domain, report.reachable, report.multiple_parents.len(), report.unreachable.len(), report.broken_links.len()
+        );
+        Ok(report)
+    }
+
+    /// Resolve every external link of a domain in its target domain
+    ///
+    /// Each link's target domain is opened and its path walked link by
+    /// link, so links left dangling after files were moved or restructured
+    /// show up as [`ExternalTarget::DomainNotFound`] or
+    /// [`ExternalTarget::PathNotFound`].
+    /// 
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `options` - Clients (credentials) for other domains
+    pub async fn validate_external_links(
+        &self,
+        domain: &str,
+        options: &ExternalLinkOptions,
+    ) -> HsdsResult<Vec<ExternalLinkCheck>> {
+        info!("Validating external links of domain: {}", domain);
+        let graph = self.link_graph(domain).await?;
+        let mut checks = Vec::new();
+        let mut roots: HashMap<String, Result<String, ExternalTarget>> = HashMap::new();
+
+        for (_, path, link) in graph.indirect.iter().filter(|(_, _, l)| matches!(l.class, Some(LinkClass::External))) {
+            let raw_domain = link.h5domain.clone().unwrap_or_default();
+            let target_path = link.h5path.clone().unwrap_or_default();
+            let target_domain = match options.resolve_domain(domain, &raw_domain) {
+                Ok(resolved) => plain_domain(&resolved),
+                Err(e) => {
+                    checks.push(ExternalLinkCheck {
+                        path: path.clone(),
+                        target_domain: raw_domain,
+                        target_path,
+                        target: ExternalTarget::Inaccessible(e.to_string()),
+                    });
+                    continue;
+                }
+            };
+            let client = options.client_for(&target_domain, self.client);
+
+            let root = match roots.get(&target_domain) {
+                Some(root) => root.clone(),
+                None => {
+                    let root = match client.domains().get_domain(&target_domain).await {
+                        Ok(info) => info.root.ok_or(ExternalTarget::DomainNotFound),
+                        Err(HsdsError::ObjectNotFound(_) | HsdsError::DomainNotFound(_)) => Err(ExternalTarget::DomainNotFound),
+                        Err(e) => Err(ExternalTarget::Inaccessible(e.to_string())),
+                    };
+                    roots.insert(target_domain.clone(), root.clone());
+                    root
+                }
+            };
+            let target = match root {
+                Ok(root) => resolve_remote(client, &target_domain, &root, &target_path).await,
+                Err(target) => target,
+            };
+            debug!("External link {} -> {}:{} is {:?}", path, target_domain, target_path, target);
+
+            checks.push(ExternalLinkCheck {
+                path: path.clone(),
+                target_domain,
+                target_path,
+                target,
+            });
+        }
+        Ok(checks)
+    }
+
+    /// Walk all groups reachable from the root and record their links
+    pub(crate) async fn link_graph(&self, domain: &str) -> HsdsResult<LinkGraph> {
+        let root = self.get_domain(domain).await?.root.ok_or_else(|| {
+            HsdsError::invalid_param(format!("Domain '{}' has no root group (is it a folder?)", domain))
+        })?;
+
+        let mut graph = LinkGraph {
+            root: root.clone(),
+            children: HashMap::new(),
+            parents: BTreeMap::new(),
+            indirect: Vec::new(),
+        };
+        let mut visited: HashSet<String> = HashSet::from([root.clone()]);
+        let mut pending = VecDeque::from([(root, String::new())]);
+
+        while let Some((group_id, group_path)) = pending.pop_front() {
+            let links: Vec<Link> = self.client.links()
+                .links_stream(domain, &group_id, LinkOrder::Name)
+                .try_collect()
+                .await?;
+
+            for link in &links {
+                let path = format!("{}/{}", group_path, link.title);
+                match (&link.class, &link.id) {
+                    (Some(LinkClass::Soft) | Some(LinkClass::External), _) | (_, None) => {
+                        graph.indirect.push((group_id.clone(), path, link.clone()));
+                    }
+                    (_, Some(id)) => {
+                        graph.parents.entry(id.clone()).or_default().push(path.clone());
+                        if id.starts_with("g-") && visited.insert(id.clone()) {
+                            pending.push_back((id.clone(), path));
+                        }
+                    }
+                }
+            }
+            graph.children.insert(group_id, links);
+        }
+        Ok(graph)
+    }
+}
+
+/// Domain path without the `hdf5:/` scheme prefix
+fn plain_domain(domain: &DomainPath) -> String {
+    domain.as_str().strip_prefix("hdf5:/").unwrap_or(domain.as_str()).to_string()
+}
+
+/// Resolve an absolute path in another domain by walking its links
+async fn resolve_remote(client: &HsdsClient, domain: &str, root: &str, h5path: &str) -> ExternalTarget {
+    let mut current = root.to_string();
+    let mut remaining: VecDeque<String> = h5path.split('/').filter(|s| !s.is_empty()).map(String::from).collect();
+    let mut walked: Vec<String> = Vec::new();
+    let mut soft_links = 0;
+
+    while let Some(name) = remaining.pop_front() {
+        walked.push(name.clone());
+        let link = match client.links().get_link(domain, &current, &name).await {
+            Ok(value) => serde_json::from_value::<Link>(value.get("link").cloned().unwrap_or_default()),
+            Err(HsdsError::ObjectNotFound(_)) => {
+                return ExternalTarget::PathNotFound(format!("'/{}' not found", walked.join("/")))
+            }
+            Err(e) => return ExternalTarget::Inaccessible(e.to_string()),
+        };
+        let link = match link {
+            Ok(link) => link,
+            Err(e) => return ExternalTarget::Inaccessible(format!("Unexpected link response: {}", e)),
+        };
+
+        match (&link.class, link.id) {
+            (Some(LinkClass::Soft), _) => {
+                soft_links += 1;
+                if soft_links > MAX_SOFT_LINK_DEPTH {
+                    return ExternalTarget::PathNotFound("too many levels of soft links".to_string());
+                }
+                let target = link.h5path.unwrap_or_default();
+                if target.starts_with('/') {
+                    current = root.to_string();
+                    walked.clear();
+                }
+                for segment in target.split('/').filter(|s| !s.is_empty()).rev() {
+                    remaining.push_front(segment.to_string());
+                }
+            }
+            (Some(LinkClass::External), _) => {
+                return ExternalTarget::Inaccessible(format!("'/{}' is another external link", walked.join("/")))
+            }
+            (_, Some(id)) => current = id,
+            (_, None) => return ExternalTarget::PathNotFound(format!("'/{}' has no target", walked.join("/"))),
+        }
+    }
+    ExternalTarget::Resolved(current)
+}
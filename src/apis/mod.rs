@@ -4,6 +4,31 @@ pub mod link;
 pub mod dataset;
 pub mod datatype;
 pub mod attribute;
+pub mod append;
+pub mod link_audit;
+pub mod copy;
+pub mod large_attribute;
+pub mod search;
+pub mod coalesce;
+pub mod boolean;
+pub mod scaling;
+pub mod coords;
+pub mod upload;
+pub mod create;
+pub mod elements;
+pub mod diff;
+pub mod ensure;
+pub mod conditional;
+pub mod delete;
+pub mod import;
+#[cfg(feature = "chrono")]
+pub mod time;
+#[cfg(feature = "num-complex")]
+pub mod complex;
+#[cfg(feature = "ndarray")]
+pub mod update;
+#[cfg(feature = "ndarray")]
+pub mod arrays;
 
 // Re-export all APIs
 pub use domain::DomainApi;
@@ -11,4 +36,37 @@ pub use group::GroupApi;
 pub use link::LinkApi;
 pub use dataset::DatasetApi;
 pub use datatype::DatatypeApi;
-pub use attribute::AttributeApi;
+pub use attribute::{AttributeApi, BINARY_ATTRIBUTE_THRESHOLD};
+pub use append::{AppendOptions, AppendResult, APPEND_LOCK_ATTRIBUTE};
+pub use copy::{AttributeOverwrite, CopiedObject, CopyJournal, CopyOptions, LinkCollision, NameCollision};
+pub use large_attribute::{AttributeStorage, LargeAttributeOptions, LARGE_ATTRIBUTE_GROUP, LARGE_ATTRIBUTE_PREFIX};
+pub use search::{AttributePredicate, Comparison};
+pub use coalesce::CoalesceOptions;
+pub use coords::{Axis, CoordinatedValues, DIMENSION_LIST_ATTRIBUTE};
+pub use upload::{UploadHandle, UploadOptions, UploadProgress, UploadStatus};
+pub use create::CreateOptions;
+pub use diff::{AttributeChange, AttributeDiff, AttributeEntry};
+pub use ensure::{EnsureDomainOptions, EnsuredDomain};
+pub use conditional::{AttributeSwap, UpdateOptions};
+pub use delete::{DeleteOptions, DeleteReport};
+pub use import::{ImportCollision, ImportItem, ImportOptions, ImportReport, Untranslated};
+pub use scaling::{ReadOptions, ScaledValue, Scaling, ADD_OFFSET_ATTRIBUTE, SCALE_FACTOR_ATTRIBUTE, UNITS_ATTRIBUTE};
+#[cfg(feature = "num-complex")]
+pub use complex::ComplexComponent;
+#[cfg(feature = "chrono")]
+pub use time::{TimeEncoding, TIME_UNITS_ATTRIBUTE};
+pub use link_audit::{BrokenLink, ExternalLinkCheck, ExternalLinkOptions, ExternalTarget, LinkAuditReport, SharedObject};
+
+use crate::error::{HsdsError, HsdsResult};
+
+/// Collection name ("groups", "datasets", "datatypes") for an object id
+pub(crate) fn collection_for_id(object_id: &str) -> HsdsResult<&'static str> {
+    match object_id.get(0..2) {
+        Some("g-") => Ok("groups"),
+        Some("d-") => Ok("datasets"),
+        Some("t-") => Ok("datatypes"),
+        _ => Err(HsdsError::InvalidParameter(
+            format!("Unknown object ID format: '{}'. Expected ID to start with 'g-', 'd-', or 't-'", object_id)
+        )),
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{
+    apis::{attribute::AttributeApi, collection_for_id, dataset::DatasetApi, search::{object_attributes, scalar}},
+    error::{HsdsError, HsdsResult},
+    values,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Attribute holding an object's physical units (CF convention)
+pub const UNITS_ATTRIBUTE: &str = "units";
+
+/// Attribute multiplying stored values on read (CF convention)
+pub const SCALE_FACTOR_ATTRIBUTE: &str = "scale_factor";
+
+/// Attribute added to stored values after scaling (CF convention)
+pub const ADD_OFFSET_ATTRIBUTE: &str = "add_offset";
+
+/// Linear packing of a dataset: `physical = stored * scale_factor + add_offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scaling {
+    pub scale_factor: f64,
+    pub add_offset: f64,
+}
+
+impl Default for Scaling {
+    fn default() -> Self {
+        Self { scale_factor: 1.0, add_offset: 0.0 }
+    }
+}
+
+impl Scaling {
+    pub fn new(scale_factor: f64, add_offset: f64) -> Self {
+        Self { scale_factor, add_offset }
+    }
+
+    /// Whether applying this scaling leaves values unchanged
+    pub fn is_identity(&self) -> bool {
+        self.scale_factor == 1.0 && self.add_offset == 0.0
+    }
+
+    /// Physical value of a stored value
+    pub fn apply(&self, stored: f64) -> f64 {
+        stored * self.scale_factor + self.add_offset
+    }
+
+    /// Apply to every number of a (possibly nested) JSON value
+    ///
+    /// Non-numeric elements are left as they are.
+    pub fn apply_json(&self, value: &mut Value) {
+        match value {
+            Value::Number(n) => {
+                if let Some(scaled) = n.as_f64().map(|x| self.apply(x)).and_then(serde_json::Number::from_f64) {
+                    *n = scaled;
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.apply_json(item)),
+            _ => {}
+        }
+    }
+
+    /// Scaling recorded in an object's attributes (identity where absent)
+    pub(crate) fn from_attributes(attributes: &HashMap<String, Value>) -> HsdsResult<Self> {
+        let factor = |name: &str, default: f64| match attributes.get(name).map(scalar) {
+            None => Ok(default),
+            Some(value) => value.as_f64().ok_or_else(|| {
+                HsdsError::InvalidResponse(format!("Attribute '{}' is not a number: {}", name, value))
+            }),
+        };
+        Ok(Self {
+            scale_factor: factor(SCALE_FACTOR_ATTRIBUTE, 1.0)?,
+            add_offset: factor(ADD_OFFSET_ATTRIBUTE, 0.0)?,
+        })
+    }
+}
+
+/// Floating-point type scaled values are returned as
+pub trait ScaledValue: Copy {
+    fn from_f64(value: f64) -> Self;
+}
+
+impl ScaledValue for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl ScaledValue for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+/// Options for reads that can apply CF conventions
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    apply_scaling: bool,
+}
+
+impl ReadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply the dataset's `scale_factor` and `add_offset` to values read (off by default)
+    pub fn with_scaling(mut self, apply: bool) -> Self {
+        self.apply_scaling = apply;
+        self
+    }
+
+    pub(crate) fn applies_scaling(&self) -> bool {
+        self.apply_scaling
+    }
+}
+
+impl<'a> AttributeApi<'a> {
+    /// Set the `units` attribute of an object
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `object_id` - UUID of the group, dataset or datatype
+    /// * `units` - Units string, e.g. "A" or "m/s"
+    pub async fn set_units(&self, domain: &str, object_id: &str, units: &str) -> HsdsResult<()> {
+        let collection = collection_for_id(object_id)?;
+        let body = json!({
+            "type": {
+                "class": "H5T_STRING",
+                "charSet": "H5T_CSET_UTF8",
+                "strPad": "H5T_STR_NULLTERM",
+                "length": "H5T_VARIABLE"
+            },
+            "value": units,
+        });
+        self.replace_attribute_raw(domain, collection, object_id, UNITS_ATTRIBUTE, body).await?;
+        Ok(())
+    }
+
+    /// Get the `units` attribute of an object, `None` if it has none
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `object_id` - UUID of the group, dataset or datatype
+    pub async fn get_units(&self, domain: &str, object_id: &str) -> HsdsResult<Option<String>> {
+        let collection = collection_for_id(object_id)?;
+        match self.get_attribute(domain, collection, object_id, UNITS_ATTRIBUTE).await {
+            Ok(attribute) => Ok(attribute.get("value").map(scalar).and_then(Value::as_str).map(String::from)),
+            Err(HsdsError::ObjectNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Record a dataset's packing as `scale_factor` and `add_offset` attributes
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `scaling` - Scale factor and offset
+    pub async fn set_scaling(&self, domain: &str, dataset_id: &str, scaling: &Scaling) -> HsdsResult<()> {
+        let collection = collection_for_id(dataset_id)?;
+        for (name, value) in [(SCALE_FACTOR_ATTRIBUTE, scaling.scale_factor), (ADD_OFFSET_ATTRIBUTE, scaling.add_offset)] {
+            let body = json!({"type": "H5T_IEEE_F64LE", "value": value});
+            self.replace_attribute_raw(domain, collection, dataset_id, name, body).await?;
+        }
+        Ok(())
+    }
+
+    /// Get a dataset's packing, the identity if it has no scaling attributes
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    pub async fn get_scaling(&self, domain: &str, dataset_id: &str) -> HsdsResult<Scaling> {
+        Scaling::from_attributes(&object_attributes(self.client, domain, dataset_id).await?)
+    }
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Read values with the dataset's scale factor and offset applied
+    ///
+    /// Returns a flat array (row-major) of physical values; a dataset
+    /// without scaling attributes is returned as stored.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    pub async fn get_scaled<T: ScaledValue>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+    ) -> HsdsResult<Vec<T>> {
+        let scaling = self.client.attributes().get_scaling(domain, dataset_id).await?;
+        let special_floats = self.client.options().special_floats();
+        let response = self.read_dataset_values_json(domain, dataset_id, select, None, None).await?;
+        let value = response.get("value").unwrap_or(&Value::Null);
+        let shape = values::shape_of(value);
+        values::flatten(value)
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                special_floats.parse(value).map(|x| T::from_f64(scaling.apply(x))).ok_or_else(|| {
+                    HsdsError::conversion(values::unravel_index(i as u64, &shape), value, "not a number")
+                })
+            })
+            .collect()
+    }
+
+    /// Read values as JSON with read options applied
+    ///
+    /// Returns the nested `value` array (or scalar) of the selection.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `options` - Conventions to apply, e.g. `ReadOptions::new().with_scaling(true)`
+    pub async fn read_values(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        options: &ReadOptions,
+    ) -> HsdsResult<Value> {
+        let mut response = self.read_dataset_values_json(domain, dataset_id, select, None, None).await?;
+        let mut value = response.get_mut("value").map(Value::take).unwrap_or(Value::Null);
+        if options.applies_scaling() {
+            let scaling = self.client.attributes().get_scaling(domain, dataset_id).await?;
+            if !scaling.is_identity() {
+                scaling.apply_json(&mut value);
+            }
+        }
+        Ok(value)
+    }
+}
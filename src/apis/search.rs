@@ -0,0 +1,226 @@
+use crate::{
+    apis::{collection_for_id, domain::DomainApi},
+    client::HsdsClient,
+    error::HsdsResult,
+};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use log::{debug, info};
+use reqwest::Method;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Number of objects whose attributes are fetched concurrently
+const SEARCH_CONCURRENCY: usize = 8;
+
+/// Comparison operator of an attribute predicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Condition on the attributes of an object
+///
+/// Values are compared by type: numbers numerically (integers and floats
+/// alike), strings lexicographically, booleans and arrays for equality
+/// only. A one-element array attribute compares like its element. Values
+/// of different types never match, not even with [`Comparison::Ne`].
+///
+/// ```
+/// use hsds_client::AttributePredicate;
+///
+/// let predicate = AttributePredicate::eq("sensor", "bolometer")
+///     .and(AttributePredicate::gt("gain", 2.5));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributePredicate {
+    /// The object has an attribute of this name
+    Exists(String),
+    /// The named attribute compares to a value
+    Compare {
+        name: String,
+        op: Comparison,
+        value: Value,
+    },
+    /// The named attribute is a string containing a substring
+    Contains { name: String, substring: String },
+    All(Vec<AttributePredicate>),
+    Any(Vec<AttributePredicate>),
+    Not(Box<AttributePredicate>),
+}
+
+impl AttributePredicate {
+    pub fn exists(name: impl Into<String>) -> Self {
+        Self::Exists(name.into())
+    }
+
+    pub fn compare(name: impl Into<String>, op: Comparison, value: impl Into<Value>) -> Self {
+        Self::Compare { name: name.into(), op, value: value.into() }
+    }
+
+    pub fn eq(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self::compare(name, Comparison::Eq, value)
+    }
+
+    pub fn ne(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self::compare(name, Comparison::Ne, value)
+    }
+
+    pub fn lt(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self::compare(name, Comparison::Lt, value)
+    }
+
+    pub fn le(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self::compare(name, Comparison::Le, value)
+    }
+
+    pub fn gt(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self::compare(name, Comparison::Gt, value)
+    }
+
+    pub fn ge(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self::compare(name, Comparison::Ge, value)
+    }
+
+    pub fn contains(name: impl Into<String>, substring: impl Into<String>) -> Self {
+        Self::Contains { name: name.into(), substring: substring.into() }
+    }
+
+    /// Both this and `other` hold
+    pub fn and(self, other: Self) -> Self {
+        match self {
+            Self::All(mut all) => {
+                all.push(other);
+                Self::All(all)
+            }
+            first => Self::All(vec![first, other]),
+        }
+    }
+
+    /// This or `other` holds
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Self::Any(mut any) => {
+                any.push(other);
+                Self::Any(any)
+            }
+            first => Self::Any(vec![first, other]),
+        }
+    }
+
+    /// Negation of this predicate
+    pub fn negate(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+
+    /// Evaluate against an object's attribute values by name
+    pub fn matches(&self, attributes: &HashMap<String, Value>) -> bool {
+        match self {
+            Self::Exists(name) => attributes.contains_key(name),
+            Self::Compare { name, op, value } => attributes
+                .get(name)
+                .is_some_and(|actual| compare(scalar(actual), *op, value)),
+            Self::Contains { name, substring } => attributes
+                .get(name)
+                .and_then(|actual| scalar(actual).as_str())
+                .is_some_and(|s| s.contains(substring.as_str())),
+            Self::All(all) => all.iter().all(|p| p.matches(attributes)),
+            Self::Any(any) => any.iter().any(|p| p.matches(attributes)),
+            Self::Not(inner) => !inner.matches(attributes),
+        }
+    }
+}
+
+/// The element of a one-element array, otherwise the value itself
+pub(crate) fn scalar(value: &Value) -> &Value {
+    match value {
+        Value::Array(items) if items.len() == 1 => &items[0],
+        other => other,
+    }
+}
+
+fn compare(actual: &Value, op: Comparison, expected: &Value) -> bool {
+    let ordering = match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => None,
+        },
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ if std::mem::discriminant(actual) == std::mem::discriminant(expected)
+            && matches!(op, Comparison::Eq | Comparison::Ne) =>
+        {
+            return (actual == expected) == (op == Comparison::Eq);
+        }
+        _ => None,
+    };
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        Comparison::Eq => ordering == Ordering::Equal,
+        Comparison::Ne => ordering != Ordering::Equal,
+        Comparison::Lt => ordering == Ordering::Less,
+        Comparison::Le => ordering != Ordering::Greater,
+        Comparison::Gt => ordering == Ordering::Greater,
+        Comparison::Ge => ordering != Ordering::Less,
+    }
+}
+
+impl<'a> DomainApi<'a> {
+    /// Find the objects of a domain whose attributes match a predicate
+    ///
+    /// Walks every group, dataset and committed datatype reachable from the
+    /// root and evaluates `predicate` on its attributes. Returns the link
+    /// paths of the matching objects, sorted ("/" for the root group); an
+    /// object reachable by several links is reported under each of them.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `predicate` - Attribute condition, e.g. `AttributePredicate::eq("sensor", "bolometer")`
+    pub async fn search_attributes(
+        &self,
+        domain: &str,
+        predicate: &AttributePredicate,
+    ) -> HsdsResult<Vec<String>> {
+        info!("Searching attributes of domain: {}", domain);
+        let graph = self.link_graph(domain).await?;
+        let mut objects: Vec<(String, Vec<String>)> = vec![(graph.root.clone(), vec!["/".to_string()])];
+        objects.extend(graph.parents);
+
+        let client = self.client;
+        let mut paths: Vec<String> = stream::iter(objects)
+            .map(|(id, paths)| async move {
+                let attributes = object_attributes(client, domain, &id).await?;
+                HsdsResult::Ok(if predicate.matches(&attributes) { paths } else { Vec::new() })
+            })
+            .buffer_unordered(SEARCH_CONCURRENCY)
+            .try_concat()
+            .await?;
+        paths.sort();
+        debug!("Attribute search in {} matched {} paths", domain, paths.len());
+        Ok(paths)
+    }
+}
+
+/// All attribute values of an object by name
+pub(crate) async fn object_attributes(client: &HsdsClient, domain: &str, object_id: &str) -> HsdsResult<HashMap<String, Value>> {
+    let path = format!("/{}/{}/attributes", collection_for_id(object_id)?, object_id);
+    let mut req = client.request(Method::GET, &path).await?;
+    req = HsdsClient::with_domain(req, domain)?;
+    req = req.query(&[("IncludeData", "1")]);
+
+    let listing: Value = client.execute(req).await?;
+    let attributes = listing.get("attributes").and_then(|a| a.as_array()).cloned().unwrap_or_default();
+    Ok(attributes
+        .into_iter()
+        .filter_map(|mut attribute| {
+            let name = attribute.get("name")?.as_str()?.to_string();
+            Some((name, attribute.get_mut("value").map(Value::take).unwrap_or(Value::Null)))
+        })
+        .collect())
+}
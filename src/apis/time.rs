@@ -0,0 +1,254 @@
+use crate::{
+    apis::{attribute::AttributeApi, collection_for_id, dataset::DatasetApi},
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    timestamps::to_datetime,
+    values,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::debug;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+/// Attribute of a time dataset naming its encoding
+///
+/// For a time attribute the units are stored in the sibling attribute
+/// `"<name>_units"`.
+pub const TIME_UNITS_ATTRIBUTE: &str = super::scaling::UNITS_ATTRIBUTE;
+
+/// How instants are stored in a dataset or attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeEncoding {
+    /// Fractional seconds since the Unix epoch as `f64`
+    EpochSeconds,
+    /// Nanoseconds since the Unix epoch as `i64`
+    EpochNanoseconds,
+    /// RFC 3339 / ISO 8601 strings in UTC, e.g. `"2024-05-01T12:00:00.5Z"`
+    Iso8601,
+}
+
+impl TimeEncoding {
+    /// Value of the units attribute recording this encoding
+    pub fn units(&self) -> &'static str {
+        match self {
+            Self::EpochSeconds => "seconds since 1970-01-01T00:00:00Z",
+            Self::EpochNanoseconds => "nanoseconds since 1970-01-01T00:00:00Z",
+            Self::Iso8601 => "ISO 8601",
+        }
+    }
+
+    /// Encoding named by a units attribute
+    ///
+    /// Besides [`units`](Self::units) this accepts the CF form with a space
+    /// (`"seconds since 1970-01-01 00:00:00"`) and the short `"s"` and `"ns"`.
+    pub fn from_units(units: &str) -> Option<Self> {
+        let units = units.trim().to_ascii_lowercase();
+        let epoch = |unit: &str| {
+            units == unit || units.strip_prefix(unit).is_some_and(|rest| rest.starts_with(" since 1970-01-01"))
+        };
+        if epoch("seconds") || units == "s" {
+            Some(Self::EpochSeconds)
+        } else if epoch("nanoseconds") || units == "ns" {
+            Some(Self::EpochNanoseconds)
+        } else if units == "iso 8601" || units == "iso8601" {
+            Some(Self::Iso8601)
+        } else {
+            None
+        }
+    }
+
+    /// HSDS type of stored values
+    pub fn hsds_type(&self) -> Value {
+        match self {
+            Self::EpochSeconds => json!({"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"}),
+            Self::EpochNanoseconds => json!({"class": "H5T_INTEGER", "base": "H5T_STD_I64LE"}),
+            Self::Iso8601 => json!({
+                "class": "H5T_STRING",
+                "charSet": "H5T_CSET_UTF8",
+                "strPad": "H5T_STR_NULLTERM",
+                "length": "H5T_VARIABLE"
+            }),
+        }
+    }
+
+    /// Stored value of an instant
+    pub fn encode(&self, time: &DateTime<Utc>) -> HsdsResult<Value> {
+        match self {
+            Self::EpochSeconds => {
+                Ok(json!(time.timestamp() as f64 + f64::from(time.timestamp_subsec_nanos()) * 1e-9))
+            }
+            Self::EpochNanoseconds => time.timestamp_nanos_opt().map(Value::from).ok_or_else(|| {
+                HsdsError::invalid_param(format!("{} is out of range for epoch nanoseconds", time))
+            }),
+            Self::Iso8601 => Ok(json!(time.to_rfc3339_opts(SecondsFormat::AutoSi, true))),
+        }
+    }
+
+    /// Instant of a stored value
+    pub fn decode(&self, value: &Value) -> HsdsResult<DateTime<Utc>> {
+        let decoded = match self {
+            Self::EpochSeconds => value.as_f64().and_then(to_datetime),
+            Self::EpochNanoseconds => value.as_i64().map(DateTime::from_timestamp_nanos),
+            Self::Iso8601 => value
+                .as_str()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|t| t.with_timezone(&Utc)),
+        };
+        decoded.ok_or_else(|| HsdsError::InvalidResponse(format!("{} is not a time value in {}", value, self.units())))
+    }
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Write instants to a dataset and record the encoding in its units attribute
+    ///
+    /// The dataset's type must suit the encoding (see
+    /// [`TimeEncoding::hsds_type`]). Values fill the selection in row-major
+    /// order.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `times` - Instants to write
+    /// * `encoding` - Stored representation
+    pub async fn write_timestamps(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        times: &[DateTime<Utc>],
+        encoding: TimeEncoding,
+    ) -> HsdsResult<()> {
+        let encoded = times.iter().map(|t| encoding.encode(t)).collect::<HsdsResult<Vec<_>>>()?;
+        debug!("Writing {} timestamps to dataset {} as {}", times.len(), dataset_id, encoding.units());
+
+        let path = format!("/datasets/{}/value", dataset_id);
+        let mut req = self.client.request(Method::PUT, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        if let Some(selection) = select {
+            req = HsdsClient::with_selection(req, selection);
+        }
+        self.client.execute_empty(req.json(&json!({"value": encoded}))).await?;
+
+        self.client.attributes()
+            .replace_attribute_raw(domain, "datasets", dataset_id, TIME_UNITS_ATTRIBUTE, units_attribute(encoding))
+            .await?;
+        Ok(())
+    }
+
+    /// Read instants from a dataset, decoding them as its units attribute says
+    ///
+    /// Without a units attribute, string datasets are read as ISO 8601,
+    /// integer datasets as epoch nanoseconds and float datasets as epoch
+    /// seconds.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    pub async fn read_timestamps(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+    ) -> HsdsResult<Vec<DateTime<Utc>>> {
+        let encoding = match self.client.attributes().get_attribute(domain, "datasets", dataset_id, TIME_UNITS_ATTRIBUTE).await {
+            Ok(attribute) => encoding_of_units(&attribute)?,
+            Err(HsdsError::ObjectNotFound(_)) => {
+                let dataset = self.get_dataset_json(domain, dataset_id).await?;
+                encoding_of_type(dataset.get("type").unwrap_or(&Value::Null)).ok_or_else(|| {
+                    HsdsError::invalid_param(format!("Dataset {} has no units and a non-time type", dataset_id))
+                })?
+            }
+            Err(e) => return Err(e),
+        };
+
+        let response = self.read_dataset_values_json(domain, dataset_id, select, None, None).await?;
+        let read = response.get("value").unwrap_or(&Value::Null);
+        values::flatten(read).iter().map(|value| encoding.decode(value)).collect()
+    }
+}
+
+impl<'a> AttributeApi<'a> {
+    /// Set an attribute to an instant, recording the encoding in `"<name>_units"`
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `object_id` - UUID of the group, dataset or datatype
+    /// * `attr_name` - Name of the attribute
+    /// * `time` - Instant to store
+    /// * `encoding` - Stored representation
+    pub async fn set_timestamp_attribute(
+        &self,
+        domain: &str,
+        object_id: &str,
+        attr_name: &str,
+        time: &DateTime<Utc>,
+        encoding: TimeEncoding,
+    ) -> HsdsResult<()> {
+        let collection = collection_for_id(object_id)?;
+        let body = json!({"type": encoding.hsds_type(), "value": encoding.encode(time)?});
+        self.replace_attribute_raw(domain, collection, object_id, attr_name, body).await?;
+        self.replace_attribute_raw(domain, collection, object_id, &units_name(attr_name), units_attribute(encoding))
+            .await?;
+        Ok(())
+    }
+
+    /// Get an instant stored by [`set_timestamp_attribute`](Self::set_timestamp_attribute)
+    ///
+    /// Without a units attribute the encoding is inferred from the value as
+    /// for [`DatasetApi::read_timestamps`].
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `object_id` - UUID of the group, dataset or datatype
+    /// * `attr_name` - Name of the attribute
+    pub async fn get_timestamp_attribute(
+        &self,
+        domain: &str,
+        object_id: &str,
+        attr_name: &str,
+    ) -> HsdsResult<DateTime<Utc>> {
+        let collection = collection_for_id(object_id)?;
+        let attribute = self.get_attribute(domain, collection, object_id, attr_name).await?;
+        let encoding = match self.get_attribute(domain, collection, object_id, &units_name(attr_name)).await {
+            Ok(units) => encoding_of_units(&units)?,
+            Err(HsdsError::ObjectNotFound(_)) => encoding_of_type(attribute.get("type").unwrap_or(&Value::Null))
+                .ok_or_else(|| {
+                    HsdsError::invalid_param(format!("Attribute '{}' has no units and a non-time type", attr_name))
+                })?,
+            Err(e) => return Err(e),
+        };
+        let value = attribute.get("value").unwrap_or(&Value::Null);
+        let value = match value {
+            Value::Array(items) if items.len() == 1 => &items[0],
+            other => other,
+        };
+        encoding.decode(value)
+    }
+}
+
+fn units_name(attr_name: &str) -> String {
+    format!("{}_{}", attr_name, TIME_UNITS_ATTRIBUTE)
+}
+
+fn units_attribute(encoding: TimeEncoding) -> Value {
+    json!({"type": TimeEncoding::Iso8601.hsds_type(), "value": encoding.units()})
+}
+
+/// Encoding named by a units attribute (as returned by `get_attribute`)
+fn encoding_of_units(attribute: &Value) -> HsdsResult<TimeEncoding> {
+    let units = attribute.get("value").and_then(Value::as_str).unwrap_or_default();
+    TimeEncoding::from_units(units)
+        .ok_or_else(|| HsdsError::invalid_param(format!("Units '{}' are not a known time encoding", units)))
+}
+
+/// Default encoding for a value type without units
+fn encoding_of_type(dtype: &Value) -> Option<TimeEncoding> {
+    match dtype.get("class").and_then(Value::as_str) {
+        Some("H5T_STRING") => Some(TimeEncoding::Iso8601),
+        Some("H5T_INTEGER") => Some(TimeEncoding::EpochNanoseconds),
+        Some("H5T_FLOAT") => Some(TimeEncoding::EpochSeconds),
+        _ => None,
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    apis::{conditional::UpdateOptions, dataset::{value_read_request, DatasetApi}},
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    values,
+};
+use log::{debug, info, warn};
+use ndarray::{ArrayD, ArrayViewMutD, IxDyn};
+use reqwest::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+impl<'a> DatasetApi<'a> {
+    /// Update a region of a dataset in place with a closure
+    ///
+    /// Reads the selected region, hands it to `update` as a mutable view and
+    /// writes the result back. See [`update_region_with`](Self::update_region_with)
+    /// for the concurrency behavior.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string (e.g., "[0:10,3]"); whole dataset if `None`
+    /// * `update` - Closure modifying the region's values
+    pub async fn update_region<T, F>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        update: F,
+    ) -> HsdsResult<()>
+    where
+        T: DeserializeOwned + Serialize,
+        F: FnMut(&mut ArrayViewMutD<T>),
+    {
+        self.update_region_with(domain, dataset_id, select, &UpdateOptions::default(), update).await
+    }
+
+    /// Update a region of a dataset in place with a closure, with retry options
+    ///
+    /// The view has the region's full rank: a selection of one index along a
+    /// dimension keeps that dimension with length 1. When the server returns
+    /// an `ETag` with the values, the write is sent with `If-Match`, and a
+    /// write rejected because the region changed in between (`412` or `409`)
+    /// re-reads the region and calls `update` again on the fresh values, so
+    /// the closure may run more than once. Servers without ETags on value
+    /// reads get an unconditional write, unless
+    /// [`UpdateOptions::with_require_etag`] is set. A dataset without values (null
+    /// dataspace or a zero-length dimension) is left alone without calling
+    /// `update`.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `options` - Retry options
+    /// * `update` - Closure modifying the region's values
+    pub async fn update_region_with<T, F>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        select: Option<&str>,
+        options: &UpdateOptions,
+        mut update: F,
+    ) -> HsdsResult<()>
+    where
+        T: DeserializeOwned + Serialize,
+        F: FnMut(&mut ArrayViewMutD<T>),
+    {
+        let shape = self.get_dataset(domain, dataset_id).await?.shape;
+        if shape.as_ref().is_some_and(|s| s.is_empty()) {
+            debug!("Dataset {} holds no values; nothing to update", dataset_id);
+            return Ok(());
+        }
+        let rank = shape.and_then(|s| s.dims).map(|d| d.len()).unwrap_or_default();
+
+        let mut backoff = options.backoff;
+        let mut attempts = 0;
+        loop {
+            let req = value_read_request(self.client, domain, dataset_id, select, None, None).await?;
+            let (response, etag): (Value, _) = self.client.execute_tagged(req).await?;
+            let value = response.get("value").ok_or_else(|| {
+                HsdsError::InvalidResponse("Value read response has no value".to_string())
+            })?;
+            let mut region: ArrayD<T> = to_array(value, rank)?;
+            match &etag {
+                None if options.require_etag => {
+                    return Err(HsdsError::OperationFailed(format!(
+                        "Server sent no ETag for values of dataset {}; refusing an unconditional write", dataset_id
+                    )))
+                }
+                None => warn!("No ETag for values of dataset {}; writing without If-Match", dataset_id),
+                Some(_) => {}
+            }
+            update(&mut region.view_mut());
+
+            let path = format!("/datasets/{}/value", dataset_id);
+            let mut req = self.client.request(Method::PUT, &path).await?;
+            req = HsdsClient::with_domain(req, domain)?;
+            if let Some(selection) = select {
+                req = HsdsClient::with_selection(req, selection);
+            }
+            if let Some(etag) = &etag {
+                req = req.header(reqwest::header::IF_MATCH, etag);
+            }
+            req = req.json(&json!({"value": to_nested(&region)?}));
+
+            match self.client.execute_empty(req).await {
+                Ok(()) => {
+                    debug!("Updated {} elements of dataset {}", region.len(), dataset_id);
+                    return Ok(());
+                }
+                Err(HsdsError::Conflict(message)) if etag.is_some() && attempts < options.max_retries => {
+                    attempts += 1;
+                    info!("Region of dataset {} changed during update ({}); retrying in {:?}", dataset_id, message, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Array of a value read's nested JSON arrays, `rank` levels deep
+fn to_array<T: DeserializeOwned>(value: &Value, rank: usize) -> HsdsResult<ArrayD<T>> {
+    let mut dims = Vec::with_capacity(rank);
+    let mut current = value;
+    for _ in 0..rank {
+        let items = current.as_array().ok_or_else(|| {
+            HsdsError::InvalidResponse(format!("Expected {}-dimensional nested arrays of values", rank))
+        })?;
+        dims.push(items.len());
+        match items.first() {
+            Some(first) => current = first,
+            None => break,
+        }
+    }
+    dims.resize(rank, 0);
+
+    let elements = values::convert_nested(value, rank)?;
+    ArrayD::from_shape_vec(IxDyn(&dims), elements)
+        .map_err(|e| HsdsError::InvalidResponse(format!("Ragged value arrays: {}", e)))
+}
+
+/// Nested JSON arrays of an array's elements, in row-major order
+fn to_nested<T: Serialize>(array: &ArrayD<T>) -> HsdsResult<Value> {
+    fn nest(elements: &mut impl Iterator<Item = Value>, dims: &[usize]) -> Value {
+        match dims.split_first() {
+            None => elements.next().unwrap_or(Value::Null),
+            Some((&len, rest)) => Value::Array((0..len).map(|_| nest(elements, rest)).collect()),
+        }
+    }
+    let elements: Vec<Value> = array.iter().map(serde_json::to_value).collect::<Result<_, _>>()?;
+    Ok(nest(&mut elements.into_iter(), array.shape()))
+}
@@ -0,0 +1,351 @@
+use crate::{
+    apis::dataset::DatasetApi,
+    client::HsdsClient,
+    codec::{self, ElementCodec},
+    error::{HsdsError, HsdsResult},
+    raw::RawWriteBuffer,
+    tuning::{is_congestion, AdaptiveTuning, AimdController, TuningState},
+};
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use log::{debug, info, warn};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+use tokio::task::JoinHandle;
+
+/// Options for [`DatasetApi::spawn_upload`]
+#[derive(Debug, Clone)]
+pub struct UploadOptions {
+    target_request_bytes: usize,
+    rows_per_request: Option<u64>,
+    adaptive: Option<AdaptiveTuning>,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            target_request_bytes: 4 * 1024 * 1024,
+            rows_per_request: None,
+            adaptive: None,
+        }
+    }
+}
+
+impl UploadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Approximate payload size of each write (at least one row is sent)
+    pub fn with_target_request_bytes(mut self, bytes: usize) -> Self {
+        self.target_request_bytes = bytes.max(1);
+        self
+    }
+
+    /// Write exactly this many rows per request, overriding the target size
+    pub fn with_rows_per_request(mut self, rows: u64) -> Self {
+        self.rows_per_request = Some(rows.max(1));
+        self
+    }
+
+    /// Adapt request size and concurrency to the server instead
+    ///
+    /// Writes start small and one at a time and are scaled up or down with
+    /// the observed latency and throttling (see [`AdaptiveTuning`]);
+    /// throttled or timed-out writes are retried. The fixed sizing options
+    /// are ignored.
+    pub fn with_adaptive(mut self, tuning: AdaptiveTuning) -> Self {
+        self.adaptive = Some(tuning);
+        self
+    }
+
+    fn rows_per_request(&self, row_bytes: usize) -> u64 {
+        self.rows_per_request
+            .unwrap_or_else(|| (self.target_request_bytes / row_bytes.max(1)).max(1) as u64)
+    }
+}
+
+/// Progress of a background upload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// Rows acknowledged by the server
+    pub rows_written: u64,
+    /// Rows to upload in total
+    pub total_rows: u64,
+    /// Payload bytes acknowledged by the server
+    pub bytes_sent: u64,
+}
+
+/// State of a background upload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadStatus {
+    Running,
+    Completed,
+    /// Stopped by an error (the message); rows before it were written
+    Failed(String),
+    /// Stopped by [`UploadHandle::abort`]
+    Aborted,
+}
+
+#[derive(Debug)]
+struct UploadState {
+    progress: UploadProgress,
+    status: UploadStatus,
+    tuning: Option<TuningState>,
+}
+
+fn lock(state: &Mutex<UploadState>) -> MutexGuard<'_, UploadState> {
+    // The state is only assigned whole, so a poisoned lock still holds a consistent value
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Handle to an upload running in a background task
+///
+/// Returned by [`DatasetApi::spawn_upload`]. Dropping the handle detaches
+/// the task, which keeps running to completion; call [`abort`](Self::abort)
+/// to stop it.
+pub struct UploadHandle {
+    task: JoinHandle<HsdsResult<UploadProgress>>,
+    state: Arc<Mutex<UploadState>>,
+}
+
+impl UploadHandle {
+    /// Rows and bytes written so far
+    pub fn progress(&self) -> UploadProgress {
+        lock(&self.state).progress
+    }
+
+    /// Current state of the upload
+    pub fn status(&self) -> UploadStatus {
+        lock(&self.state).status.clone()
+    }
+
+    /// Current request size and concurrency of an adaptive upload
+    pub fn tuning(&self) -> Option<TuningState> {
+        lock(&self.state).tuning
+    }
+
+    /// Whether the upload has stopped, successfully or not
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stop the upload after the write in flight (which may or may not land)
+    pub fn abort(&self) {
+        let mut state = lock(&self.state);
+        if state.status == UploadStatus::Running {
+            state.status = UploadStatus::Aborted;
+        }
+        self.task.abort();
+    }
+
+    /// Wait for the upload to finish
+    ///
+    /// Returns the final progress, [`HsdsError::Cancelled`] if the upload
+    /// was aborted, or the error that stopped it.
+    pub async fn wait(self) -> HsdsResult<UploadProgress> {
+        match self.task.await {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => Err(HsdsError::Cancelled),
+            Err(e) => Err(HsdsError::OperationFailed(format!("Upload task panicked: {}", e))),
+        }
+    }
+}
+
+impl<'a> DatasetApi<'a> {
+    /// Upload rows to a dataset in a background task
+    ///
+    /// `data` holds whole rows (along the first dimension) in row-major
+    /// order and is written starting at row `start_row`, one region write
+    /// per batch of rows as configured by `options`. The task runs on the
+    /// current tokio runtime with a clone of this client, so the caller can
+    /// keep producing data and check on the upload through the returned
+    /// handle. Must be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `start_row` - First row to write
+    /// * `data` - Values of the rows to write
+    /// * `options` - Request sizing
+    pub fn spawn_upload<T>(
+        &self,
+        domain: &str,
+        dataset_id: &str,
+        start_row: u64,
+        data: Vec<T>,
+        options: UploadOptions,
+    ) -> UploadHandle
+    where
+        T: ElementCodec + Send + Sync + 'static,
+    {
+        let state = Arc::new(Mutex::new(UploadState {
+            progress: UploadProgress::default(),
+            status: UploadStatus::Running,
+            tuning: None,
+        }));
+        let client = self.client.clone();
+        let (domain, dataset_id) = (domain.to_string(), dataset_id.to_string());
+        let task_state = state.clone();
+        let task = tokio::spawn(async move {
+            let result = upload_rows(&client, &domain, &dataset_id, start_row, &data, &options, &task_state).await;
+            let mut state = lock(&task_state);
+            state.status = match &result {
+                Ok(_) => UploadStatus::Completed,
+                Err(e) => {
+                    warn!("Upload to dataset {} failed: {}", dataset_id, e);
+                    UploadStatus::Failed(e.to_string())
+                }
+            };
+            result
+        });
+        UploadHandle { task, state }
+    }
+}
+
+async fn upload_rows<T: ElementCodec>(
+    client: &HsdsClient,
+    domain: &str,
+    dataset_id: &str,
+    start_row: u64,
+    data: &[T],
+    options: &UploadOptions,
+    state: &Mutex<UploadState>,
+) -> HsdsResult<UploadProgress> {
+    let element_size = codec::binary_size::<T>()?;
+    let dims = client.datasets().get_dataset(domain, dataset_id).await?
+        .shape
+        .and_then(|s| s.dims)
+        .unwrap_or_default();
+    let Some((_, row_dims)) = dims.split_first() else {
+        return Err(HsdsError::invalid_param(format!("Dataset {} has no rows to upload to", dataset_id)));
+    };
+    let row_len = row_dims.iter().product::<u64>().max(1) as usize;
+    if !data.len().is_multiple_of(row_len) {
+        return Err(HsdsError::invalid_param(format!(
+            "{} values are not a whole number of {}-element rows", data.len(), row_len
+        )));
+    }
+    let total_rows = (data.len() / row_len) as u64;
+    let trailing = ",:".repeat(row_dims.len());
+    lock(state).progress.total_rows = total_rows;
+    if let Some(tuning) = &options.adaptive {
+        let rows = Rows { data, row_len, start_row, trailing: &trailing };
+        return upload_adaptive(client, domain, dataset_id, rows, tuning, state).await;
+    }
+    let batch_rows = options.rows_per_request(row_len * element_size);
+    debug!("Uploading {} rows to dataset {} in batches of {}", total_rows, dataset_id, batch_rows);
+
+    let datasets = client.datasets();
+    let mut buffer = RawWriteBuffer::new();
+    let mut progress = UploadProgress { total_rows, ..Default::default() };
+    for batch in data.chunks(batch_rows as usize * row_len) {
+        let rows = (batch.len() / row_len) as u64;
+        let first = start_row + progress.rows_written;
+        let selection = format!("[{}:{}{}]", first, first + rows, trailing);
+        let payload = buffer.encode(batch)?;
+        let bytes = payload.len() as u64;
+        datasets.write_region_raw(domain, dataset_id, Some(&selection), payload, "application/octet-stream").await?;
+
+        progress.rows_written += rows;
+        progress.bytes_sent += bytes;
+        lock(state).progress = progress;
+    }
+    Ok(progress)
+}
+
+/// Rows of an upload: `data` split into `row_len`-element rows written from `start_row`
+struct Rows<'d, T> {
+    data: &'d [T],
+    row_len: usize,
+    start_row: u64,
+    trailing: &'d str,
+}
+
+impl<T> Rows<'_, T> {
+    fn total(&self) -> u64 {
+        (self.data.len() / self.row_len) as u64
+    }
+
+    /// Selection and values of `count` rows from row `first` of the data
+    fn batch(&self, first: u64, count: u64) -> (String, &[T]) {
+        let target = self.start_row + first;
+        let selection = format!("[{}:{}{}]", target, target + count, self.trailing);
+        let values = &self.data[first as usize * self.row_len..(first + count) as usize * self.row_len];
+        (selection, values)
+    }
+}
+
+/// Upload with request size and concurrency chosen by an AIMD controller
+async fn upload_adaptive<T: ElementCodec>(
+    client: &HsdsClient,
+    domain: &str,
+    dataset_id: &str,
+    rows: Rows<'_, T>,
+    tuning: &AdaptiveTuning,
+    state: &Mutex<UploadState>,
+) -> HsdsResult<UploadProgress> {
+    let datasets = client.datasets();
+    let row_bytes = rows.row_len * codec::binary_size::<T>()?;
+    let mut controller = AimdController::new(tuning.clone());
+    let mut buffer = RawWriteBuffer::new();
+    let mut progress = UploadProgress { total_rows: rows.total(), ..Default::default() };
+    let mut next_row = 0;
+    // Throttled batches to send again, as (first row, rows, attempts so far)
+    let mut retries: VecDeque<(u64, u64, u32)> = VecDeque::new();
+    let mut in_flight = FuturesUnordered::new();
+    lock(state).tuning = Some(controller.state());
+    debug!("Uploading {} rows to dataset {} adaptively", rows.total(), dataset_id);
+
+    loop {
+        while in_flight.len() < controller.state().in_flight {
+            let (first, count, attempts) = match retries.pop_front() {
+                Some(retry) => retry,
+                None if next_row < rows.total() => {
+                    let count = (controller.state().request_bytes / row_bytes.max(1)).max(1) as u64;
+                    let count = count.min(rows.total() - next_row);
+                    next_row += count;
+                    (next_row - count, count, 0)
+                }
+                None => break,
+            };
+            let (selection, values) = rows.batch(first, count);
+            let payload = buffer.encode(values)?;
+            let generation = controller.generation();
+            let delay = (attempts > 0).then(|| tuning.backoff(attempts));
+            let datasets = &datasets;
+            in_flight.push(async move {
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+                let bytes = payload.len() as u64;
+                let started = Instant::now();
+                let result = datasets
+                    .write_region_raw(domain, dataset_id, Some(&selection), payload, "application/octet-stream")
+                    .await;
+                (first, count, attempts, bytes, generation, started.elapsed(), result)
+            });
+        }
+
+        let Some((first, count, attempts, bytes, generation, latency, result)) = in_flight.next().await else {
+            break;
+        };
+        match result {
+            Ok(()) => {
+                controller.on_success(generation, latency);
+                progress.rows_written += count;
+                progress.bytes_sent += bytes;
+            }
+            Err(e) if is_congestion(&e) && attempts < tuning.max_retries() => {
+                info!("Write of rows {}..{} to {} pushed back ({}); retrying", first, first + count, dataset_id, e);
+                controller.on_congestion(generation);
+                retries.push_back((first, count, attempts + 1));
+            }
+            Err(e) => return Err(e),
+        }
+        let mut shared = lock(state);
+        shared.progress = progress;
+        shared.tuning = Some(controller.state());
+    }
+    Ok(progress)
+}
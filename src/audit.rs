@@ -0,0 +1,67 @@
+use std::time::SystemTime;
+
+/// A successful mutating request, as reported to an [`AuditHook`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// HTTP method of the operation (e.g. "PUT", "DELETE")
+    pub operation: String,
+    /// Request path (e.g. "/datasets/d-1234/value")
+    pub path: String,
+    /// Domain the operation applied to
+    pub domain: Option<String>,
+    /// UUID of the object named in the path, if any
+    pub object_id: Option<String>,
+    /// Authenticated user name, if the authentication scheme has one
+    pub user: Option<String>,
+    /// User the operation was made on behalf of, when impersonating
+    pub acting_user: Option<String>,
+    /// When the server acknowledged the operation
+    pub timestamp: SystemTime,
+    /// HTTP status returned by the server
+    pub status: u16,
+}
+
+impl AuditEvent {
+    /// Event for a request about to be sent; status and timestamp are set on success
+    pub(crate) fn new(request: &reqwest::Request, user: Option<&str>, acting_user: Option<&str>) -> Self {
+        let url = request.url();
+        let domain = url
+            .query_pairs()
+            .find(|(key, _)| key == "domain")
+            .map(|(_, value)| value.into_owned());
+        let object_id = url
+            .path_segments()
+            .and_then(|mut segments| segments.find(|s| ["g-", "d-", "t-"].iter().any(|p| s.starts_with(p))))
+            .map(String::from);
+
+        Self {
+            operation: request.method().to_string(),
+            path: url.path().to_string(),
+            domain,
+            object_id,
+            user: user.map(String::from),
+            acting_user: acting_user.map(String::from),
+            timestamp: SystemTime::now(),
+            status: 0,
+        }
+    }
+}
+
+/// Callback invoked after every successful mutating request
+///
+/// Register with [`HsdsClient::with_audit_hook`](crate::HsdsClient::with_audit_hook)
+/// to persist an audit trail of changes made through the client. The hook
+/// runs on the request's task, so it should hand events off (e.g. to a
+/// channel) rather than block. Closures taking `&AuditEvent` implement it.
+pub trait AuditHook: Send + Sync {
+    fn on_mutation(&self, event: &AuditEvent);
+}
+
+impl<F> AuditHook for F
+where
+    F: Fn(&AuditEvent) + Send + Sync,
+{
+    fn on_mutation(&self, event: &AuditEvent) {
+        self(event)
+    }
+}
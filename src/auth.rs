@@ -1,15 +1,22 @@
 use crate::error::{HsdsError, HsdsResult};
+use crate::logging::REDACTED;
 use base64::{Engine, engine::general_purpose};
+use std::fmt;
 
 /// Authentication trait for HSDS API
 #[async_trait::async_trait]
 pub trait Authentication: Send + Sync {
     /// Apply authentication to the request headers
     async fn apply_auth(&self, headers: &mut reqwest::header::HeaderMap) -> HsdsResult<()>;
+
+    /// User name the requests are made as, if the scheme carries one
+    fn username(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Basic authentication using username/password
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct BasicAuth {
     username: String,
     password: String,
@@ -22,6 +29,19 @@ impl BasicAuth {
             password: password.into(),
         }
     }
+
+    pub(crate) fn credentials(&self) -> (&str, &str) {
+        (&self.username, &self.password)
+    }
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("username", &self.username)
+            .field("password", &REDACTED)
+            .finish()
+    }
 }
 
 #[async_trait::async_trait]
@@ -39,10 +59,14 @@ impl Authentication for BasicAuth {
         
         Ok(())
     }
+
+    fn username(&self) -> Option<&str> {
+        Some(&self.username)
+    }
 }
 
 /// Bearer token authentication
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct BearerAuth {
     token: String,
 }
@@ -55,6 +79,14 @@ impl BearerAuth {
     }
 }
 
+impl fmt::Debug for BearerAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BearerAuth")
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
 #[async_trait::async_trait]
 impl Authentication for BearerAuth {
     async fn apply_auth(&self, headers: &mut reqwest::header::HeaderMap) -> HsdsResult<()> {
@@ -79,3 +111,44 @@ impl Authentication for NoAuth {
         Ok(())
     }
 }
+
+/// Acting user sent alongside a service account's credentials
+///
+/// HSDS itself has no notion of impersonation; deployments that support it
+/// (typically an authenticating proxy in front of the service nodes) read
+/// the end user from a header or query parameter of their choosing. Set it
+/// with [`HsdsClient::with_impersonation`](crate::HsdsClient::with_impersonation).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Impersonation {
+    /// Send the user in a request header
+    Header {
+        name: reqwest::header::HeaderName,
+        user: String,
+    },
+    /// Send the user as a query parameter
+    Query { name: String, user: String },
+}
+
+impl Impersonation {
+    /// Act as `user` via the header `name` (e.g. "X-Acting-User")
+    pub fn header(name: &str, user: impl Into<String>) -> HsdsResult<Self> {
+        let user = user.into();
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| HsdsError::invalid_param(format!("Invalid impersonation header '{}': {}", name, e)))?;
+        reqwest::header::HeaderValue::from_str(&user)
+            .map_err(|e| HsdsError::invalid_param(format!("Invalid acting user '{}': {}", user, e)))?;
+        Ok(Self::Header { name, user })
+    }
+
+    /// Act as `user` via the query parameter `name` (e.g. "acting_user")
+    pub fn query(name: impl Into<String>, user: impl Into<String>) -> Self {
+        Self::Query { name: name.into(), user: user.into() }
+    }
+
+    /// The user acted on behalf of
+    pub fn user(&self) -> &str {
+        match self {
+            Self::Header { user, .. } | Self::Query { user, .. } => user,
+        }
+    }
+}
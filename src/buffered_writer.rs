@@ -0,0 +1,223 @@
+use crate::{
+    client::HsdsClient,
+    codec::{self, ElementCodec},
+    error::{HsdsError, HsdsResult},
+    raw::RawWriteBuffer,
+};
+use log::{debug, warn};
+use std::time::{Duration, Instant};
+
+/// Flush thresholds for a [`BufferedWriter`]
+#[derive(Debug, Clone)]
+pub struct BufferedWriterOptions {
+    max_rows: u64,
+    max_bytes: usize,
+    flush_interval: Duration,
+}
+
+impl Default for BufferedWriterOptions {
+    fn default() -> Self {
+        Self {
+            max_rows: 10_000,
+            max_bytes: 4 * 1024 * 1024,
+            flush_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl BufferedWriterOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flush once this many rows are buffered
+    pub fn with_max_rows(mut self, rows: u64) -> Self {
+        self.max_rows = rows.max(1);
+        self
+    }
+
+    /// Flush once the buffered payload reaches this many bytes
+    pub fn with_max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes.max(1);
+        self
+    }
+
+    /// Flush once the oldest buffered row is this old
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+}
+
+/// Write-behind writer consolidating small row writes into region writes
+///
+/// Rows written at consecutive positions are buffered client-side and sent
+/// as one binary region write when a row, byte or age threshold is reached,
+/// so a producer writing a few samples at a time issues a request per batch
+/// instead of per call. A write that does not continue the buffered run
+/// flushes the run first. A failed flush keeps its rows buffered for the
+/// next attempt.
+///
+/// As with [`RecordLogWriter`](crate::RecordLogWriter), age is only checked
+/// when rows are written; call [`flush_if_due`](Self::flush_if_due) from a
+/// timer for quiet periods, and [`flush`](Self::flush) before dropping the
+/// writer.
+pub struct BufferedWriter<'a, T: ElementCodec + Clone> {
+    client: &'a HsdsClient,
+    domain: String,
+    dataset_id: String,
+    /// Elements per row (product of the trailing dimensions)
+    row_len: usize,
+    /// Bytes per element in the binary payload
+    element_size: usize,
+    /// Selection suffix covering the trailing dimensions
+    trailing: String,
+    options: BufferedWriterOptions,
+    start: u64,
+    buffer: Vec<T>,
+    oldest: Option<Instant>,
+    written: u64,
+    requests: u64,
+    encoder: RawWriteBuffer,
+}
+
+impl<'a, T: ElementCodec + Clone> BufferedWriter<'a, T> {
+    /// Open a writer for an existing dataset of at least one dimension
+    ///
+    /// Rows are positions along the first dimension. `T` needs a binary
+    /// encoding ([`ElementCodec::BINARY_SIZE`]).
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `options` - Flush thresholds
+    pub async fn open(
+        client: &'a HsdsClient,
+        domain: &str,
+        dataset_id: &str,
+        options: BufferedWriterOptions,
+    ) -> HsdsResult<BufferedWriter<'a, T>> {
+        let element_size = codec::binary_size::<T>()?;
+        let dataset = client.datasets().get_dataset(domain, dataset_id).await?;
+        let dims = dataset.shape.and_then(|s| s.dims).unwrap_or_default();
+        let Some((_, row_dims)) = dims.split_first() else {
+            return Err(HsdsError::invalid_param(format!("Dataset {} has no rows to write", dataset_id)));
+        };
+
+        Ok(Self {
+            client,
+            domain: domain.to_string(),
+            dataset_id: dataset.id,
+            row_len: row_dims.iter().product::<u64>().max(1) as usize,
+            element_size,
+            trailing: ",:".repeat(row_dims.len()),
+            options,
+            start: 0,
+            buffer: Vec::new(),
+            oldest: None,
+            written: 0,
+            requests: 0,
+            encoder: RawWriteBuffer::new(),
+        })
+    }
+
+    /// UUID of the underlying dataset
+    pub fn dataset_id(&self) -> &str {
+        &self.dataset_id
+    }
+
+    /// Number of rows waiting to be flushed
+    pub fn buffered_rows(&self) -> u64 {
+        (self.buffer.len() / self.row_len) as u64
+    }
+
+    /// Number of rows written to the server
+    pub fn written_rows(&self) -> u64 {
+        self.written
+    }
+
+    /// Number of region writes sent so far
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Row following the last one written or buffered
+    pub fn next_row(&self) -> u64 {
+        self.start + self.buffered_rows()
+    }
+
+    /// Buffer rows starting at `start_row`, flushing when a threshold is reached
+    ///
+    /// # Arguments
+    /// * `start_row` - Position of the first row along the first dimension
+    /// * `values` - Whole rows in row-major order
+    pub async fn write(&mut self, start_row: u64, values: &[T]) -> HsdsResult<()> {
+        if !values.len().is_multiple_of(self.row_len) {
+            return Err(HsdsError::invalid_param(format!(
+                "{} values are not a whole number of {}-element rows", values.len(), self.row_len
+            )));
+        }
+        if !self.buffer.is_empty() && start_row != self.next_row() {
+            self.flush().await?;
+        }
+        if self.buffer.is_empty() {
+            self.start = start_row;
+        }
+        self.buffer.extend_from_slice(values);
+        self.oldest.get_or_insert_with(Instant::now);
+        self.flush_if_due().await
+    }
+
+    /// Buffer rows following the last ones written, see [`write`](Self::write)
+    pub async fn push(&mut self, values: &[T]) -> HsdsResult<()> {
+        self.write(self.next_row(), values).await
+    }
+
+    /// Flush if the row, byte or age threshold has been reached
+    pub async fn flush_if_due(&mut self) -> HsdsResult<()> {
+        let full = self.buffered_rows() >= self.options.max_rows
+            || self.buffer.len() * self.element_size >= self.options.max_bytes;
+        let aged = self.oldest.is_some_and(|t| t.elapsed() >= self.options.flush_interval);
+        if full || aged {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Write all buffered rows in one region write
+    ///
+    /// On failure the rows stay buffered and the error is returned.
+    pub async fn flush(&mut self) -> HsdsResult<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let rows = self.buffered_rows();
+        let selection = format!("[{}:{}{}]", self.start, self.start + rows, self.trailing);
+        let payload = self.encoder.encode(&self.buffer)?;
+        self.client.datasets()
+            .write_region_raw(&self.domain, &self.dataset_id, Some(&selection), payload, "application/octet-stream")
+            .await?;
+        debug!("Flushed {} rows to {} at {}", rows, self.dataset_id, selection);
+
+        self.buffer.clear();
+        self.oldest = None;
+        self.start += rows;
+        self.written += rows;
+        self.requests += 1;
+        Ok(())
+    }
+}
+
+impl<T: ElementCodec + Clone> Drop for BufferedWriter<'_, T> {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            warn!(
+                "BufferedWriter for {} dropped with {} unflushed rows",
+                self.dataset_id,
+                self.buffered_rows()
+            );
+        }
+    }
+}
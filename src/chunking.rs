@@ -0,0 +1,98 @@
+//! Chunk shape recommendations
+//!
+//! HSDS stores chunked datasets as one object per chunk, so the chunk shape
+//! decides both the request count and the amount of data touched per read.
+//! [`recommend`] proposes chunk dimensions close to a target chunk size for
+//! a given access pattern.
+
+use crate::models::{H5Type, StringLength, TypeDef};
+
+/// Default target chunk size (HSDS works best with chunks of 1-4 MiB)
+pub const DEFAULT_TARGET_CHUNK_BYTES: usize = 2 * 1024 * 1024;
+
+/// How a dataset is mostly written and read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessPattern {
+    /// Whole rows along the first dimension (appends, time series)
+    ///
+    /// Chunks span the full trailing dimensions and as many rows as fit.
+    RowStreaming,
+    /// Arbitrary n-dimensional blocks (images, volumes, hyperslabs)
+    ///
+    /// Chunks are kept roughly cubic so every axis is sliced evenly.
+    Hyperslab,
+}
+
+/// Propose chunk dimensions for a dataset
+///
+/// A dimension of extent 0 (e.g. an empty unlimited dimension that will
+/// grow) is treated as unbounded. The result never exceeds non-zero extents
+/// and every chunk dimension is at least 1.
+///
+/// # Arguments
+/// * `shape` - Current dataset dimensions
+/// * `dtype_size` - Size of one element in bytes
+/// * `target_chunk_bytes` - Desired chunk size in bytes
+/// * `access` - Dominant access pattern
+pub fn recommend(shape: &[u64], dtype_size: usize, target_chunk_bytes: usize, access: AccessPattern) -> Vec<u64> {
+    if shape.is_empty() {
+        return Vec::new();
+    }
+    let target_elems = (target_chunk_bytes / dtype_size.max(1)).max(1) as u64;
+
+    match access {
+        AccessPattern::RowStreaming => {
+            let mut budget = target_elems;
+            let mut chunk = vec![1u64; shape.len()];
+            for i in (1..shape.len()).rev() {
+                let extent = if shape[i] == 0 { budget } else { shape[i] };
+                chunk[i] = extent.min(budget).max(1);
+                budget = (budget / chunk[i]).max(1);
+            }
+            chunk[0] = if shape[0] == 0 { budget } else { shape[0].min(budget) }.max(1);
+            chunk
+        }
+        AccessPattern::Hyperslab => {
+            let side = (target_elems as f64).powf(1.0 / shape.len() as f64).ceil() as u64;
+            let mut chunk: Vec<u64> = shape.iter().map(|&d| if d == 0 { side } else { d }).collect();
+            while chunk.iter().product::<u64>() > target_elems {
+                let (largest, _) = chunk.iter().enumerate().max_by_key(|(_, c)| **c).unwrap_or((0, &1));
+                if chunk[largest] == 1 {
+                    break;
+                }
+                chunk[largest] = chunk[largest].div_ceil(2);
+            }
+            chunk
+        }
+    }
+}
+
+/// Element size in bytes of a type, if it has a fixed known size
+pub fn type_size(data_type: &H5Type) -> Option<usize> {
+    match data_type {
+        H5Type::Named(name) => predefined_size(name),
+        H5Type::Defined(definition) => match definition {
+            TypeDef::Integer { base, .. } | TypeDef::Float { base, .. } => predefined_size(base),
+            TypeDef::String { length: StringLength::Fixed(length), .. } => Some(*length as usize),
+            TypeDef::Enum { base, .. } => type_size(base),
+            TypeDef::Array { base, dims, .. } => Some(type_size(base)? * dims.iter().product::<u64>() as usize),
+            TypeDef::Compound { fields, .. } => fields.iter().map(|f| type_size(&f.field_type)).sum(),
+            TypeDef::Opaque { size, .. } => Some(*size as usize),
+            _ => None,
+        },
+        H5Type::Other(_) => None,
+    }
+}
+
+/// Size of a predefined HDF5 integer or float type ("H5T_STD_I32LE" -> 4)
+fn predefined_size(name: &str) -> Option<usize> {
+    let bits: usize = name
+        .strip_prefix("H5T_STD_")
+        .or_else(|| name.strip_prefix("H5T_IEEE_"))?
+        .trim_start_matches(['I', 'U', 'F'])
+        .trim_end_matches("LE")
+        .trim_end_matches("BE")
+        .parse()
+        .ok()?;
+    Some(bits / 8)
+}
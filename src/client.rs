@@ -1,64 +1,731 @@
 use crate::{
-    auth::Authentication,
+    audit::{AuditEvent, AuditHook},
+    auth::{Authentication, BasicAuth, Impersonation, NoAuth},
     error::{HsdsError, HsdsResult},
-    models::ErrorResponse,
+    models::{ErrorResponse, Href, WriteResult},
     apis::{DomainApi, GroupApi, LinkApi, DatasetApi, DatatypeApi, AttributeApi},
+    decoder::ResponseDecoder,
+    dry_run::DryRunLog,
+    domain_path::DomainPath,
+    logging::{self, LogOptions, SlowRequest},
+    strict::{self, Strictness},
+    transfer::TransferStats,
+    values::SpecialFloats,
 };
+use futures_util::TryStreamExt;
 use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use log::{debug, info, warn};
 use serde::Deserialize;
+use std::future::Future;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
+/// Outcome of [`HsdsClient::warm_up`]
+#[derive(Debug, Clone)]
+pub struct WarmUpReport {
+    /// Addresses the server host name resolved to
+    pub addresses: Vec<SocketAddr>,
+    /// Time spent resolving the host name
+    pub dns_time: Duration,
+    /// Time for the `/about` request, including connection setup
+    pub request_time: Duration,
+    /// Server information returned by `/about`
+    pub about: serde_json::Value,
+}
+
+/// Endpoint serving the domains under a prefix, see [`HsdsClient::with_route`]
+#[derive(Clone)]
+struct Route {
+    /// Domain folder without a trailing slash (e.g. "/public")
+    prefix: String,
+    base_url: Url,
+    auth: Arc<dyn Authentication>,
+}
+
+impl Route {
+    /// Whether a domain lies at or under the prefix
+    fn matches(&self, domain: &str) -> bool {
+        domain.strip_prefix(&self.prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+/// Per-client request settings, see [`HsdsClient::with_options`]
+///
+/// Retries apply only to requests that do not modify server state, after a
+/// connection failure, a timeout or a `429`/`502`/`503`/`504` response.
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    timeout: Option<Duration>,
+    max_retries: u32,
+    backoff: Duration,
+    max_response_size: Option<u64>,
+    cancellation: Option<CancellationToken>,
+    readiness_timeout: Option<Duration>,
+    special_floats: SpecialFloats,
+    strictness: Strictness,
+    slow_request_threshold: Option<Duration>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            max_retries: 0,
+            backoff: Duration::from_millis(200),
+            max_response_size: None,
+            cancellation: None,
+            readiness_timeout: None,
+            special_floats: SpecialFloats::default(),
+            strictness: Strictness::default(),
+            slow_request_threshold: None,
+        }
+    }
+}
+
+impl ClientOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time limit for each request, from sending it to reading the whole body
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum number of retries of a failed read (none by default)
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Initial delay before a retry (doubled on each retry)
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Largest response body accepted, in bytes (see [`HsdsClient::with_max_response_size`])
+    pub fn with_max_response_size(mut self, bytes: u64) -> Self {
+        self.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Abort requests once `token` is cancelled (see [`HsdsClient::with_cancellation`])
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Have composite helpers wait up to `timeout` for domains they create
+    ///
+    /// Helpers that create a domain and then populate it (templates,
+    /// snapshots, test domains) call [`DomainApi::wait_until_ready`](crate::DomainApi::wait_until_ready)
+    /// in between, for servers where a new domain takes a moment to propagate.
+    pub fn with_readiness_wait(mut self, timeout: Duration) -> Self {
+        self.readiness_timeout = Some(timeout);
+        self
+    }
+
+    /// Accept NaN and infinity encoded as strings or `null` in typed float reads
+    ///
+    /// Applies to [`read_dataset_values_streamed`](crate::DatasetApi::read_dataset_values_streamed)
+    /// and [`get_scaled`](crate::DatasetApi::get_scaled); JSON reads return
+    /// the values as sent.
+    pub fn with_special_floats(mut self, special_floats: SpecialFloats) -> Self {
+        self.special_floats = special_floats;
+        self
+    }
+
+    /// How JSON responses are matched against the models they are read into
+    ///
+    /// [`Strictness::Strict`] fails a response with
+    /// [`HsdsError::InvalidResponse`] listing every field the model does not
+    /// have, for CI against new server versions; fields collected into a
+    /// model's `extra` map still pass. The default is lenient.
+    pub fn with_strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    /// Log requests that take longer than `threshold` as warnings
+    ///
+    /// Each slow request is logged with its method and path, domain, object
+    /// id, `select` selection, bytes sent and received, outcome and timing,
+    /// measured until the response headers arrive. Set it on the client for
+    /// all requests, or scope it to one call with
+    /// [`HsdsClient::with_options`]. Off by default.
+    pub fn with_slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub fn max_response_size(&self) -> Option<u64> {
+        self.max_response_size
+    }
+
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    pub fn readiness_timeout(&self) -> Option<Duration> {
+        self.readiness_timeout
+    }
+
+    pub fn special_floats(&self) -> SpecialFloats {
+        self.special_floats
+    }
+
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
+    pub fn slow_request_threshold(&self) -> Option<Duration> {
+        self.slow_request_threshold
+    }
+}
+
 /// Main HSDS client
+///
+/// The client is `Send + Sync` and cheap to clone: clones share the
+/// connection pool, credentials and hooks, and hold no per-request state,
+/// so one client can serve any number of concurrent tasks. Scoped variants
+/// ([`with_options`](Self::with_options), [`with_cancellation`](Self::with_cancellation),
+/// ...) are clones too and do not affect the client they were made from.
 #[derive(Clone)]
 pub struct HsdsClient {
     client: Client,
     base_url: Url,
     auth: Arc<dyn Authentication>,
+    log_options: LogOptions,
+    read_only: bool,
+    dry_run: Option<DryRunLog>,
+    audit: Option<Arc<dyn AuditHook>>,
+    impersonation: Option<Impersonation>,
+    options: ClientOptions,
+    routes: Arc<Vec<Route>>,
+    transfer_stats: Vec<TransferStats>,
+    default_domain: Option<DomainPath>,
+    http2_prior_knowledge: bool,
 }
 
 impl HsdsClient {
     /// Create a new HSDS client with authentication
+    ///
+    /// Without the `rustls-tls` or `native-tls` feature the client speaks
+    /// plain HTTP only, and an `https` base URL is rejected here.
     pub fn new(
         base_url: impl AsRef<str>,
         auth: impl Authentication + 'static,
     ) -> HsdsResult<Self> {
-        let base_url = Url::parse(base_url.as_ref())?;
-        let client = Client::builder()
-            .user_agent(concat!(
-                env!("CARGO_PKG_NAME"),
-                "/",
-                env!("CARGO_PKG_VERSION")
-            ))
-            .build()?;
+        let base_url = Self::normalize_base_url(Url::parse(base_url.as_ref())?);
+        Self::check_tls(&base_url)?;
+        let client = Self::http_client(false)?;
 
         Ok(Self {
             client,
             base_url,
             auth: Arc::new(auth),
+            log_options: LogOptions::default(),
+            read_only: false,
+            dry_run: None,
+            audit: None,
+            impersonation: None,
+            options: ClientOptions::default(),
+            routes: Arc::default(),
+            transfer_stats: Vec::new(),
+            default_domain: None,
+            http2_prior_knowledge: false,
         })
     }
 
     /// Create a new HSDS client with custom reqwest client
+    ///
+    /// Like [`new`](Self::new), rejects an `https` base URL when no TLS
+    /// feature is enabled.
     pub fn with_client(
         client: Client,
         base_url: impl AsRef<str>,
         auth: impl Authentication + 'static,
     ) -> HsdsResult<Self> {
-        let base_url = Url::parse(base_url.as_ref())?;
+        let base_url = Self::normalize_base_url(Url::parse(base_url.as_ref())?);
+        Self::check_tls(&base_url)?;
 
         Ok(Self {
             client,
             base_url,
             auth: Arc::new(auth),
+            log_options: LogOptions::default(),
+            read_only: false,
+            dry_run: None,
+            audit: None,
+            impersonation: None,
+            options: ClientOptions::default(),
+            routes: Arc::default(),
+            transfer_stats: Vec::new(),
+            default_domain: None,
+            http2_prior_knowledge: false,
         })
     }
 
+    /// Create a read-only client without credentials
+    ///
+    /// For browsing public data: requests are sent unauthenticated, which
+    /// the server treats as the "default" user, and mutating calls are
+    /// rejected client-side (see [`with_read_only`](Self::with_read_only)).
+    pub fn anonymous(base_url: impl AsRef<str>) -> HsdsResult<Self> {
+        Ok(Self::new(base_url, NoAuth)?.with_read_only(true))
+    }
+
+    /// Read-only, unauthenticated clone sharing this client's connection pool
+    pub(crate) fn without_credentials(&self) -> Self {
+        let mut client = self.clone();
+        client.auth = Arc::new(NoAuth);
+        client.routes = Arc::new(
+            self.routes.iter().map(|route| Route { auth: Arc::new(NoAuth), ..route.clone() }).collect(),
+        );
+        client.impersonation = None;
+        client.read_only = true;
+        client
+    }
+
+    /// Clone this client with a separate connection pool
+    ///
+    /// Needed when the clone is driven by a different tokio runtime.
+    #[cfg_attr(not(feature = "test-util"), allow(dead_code))]
+    pub(crate) fn with_new_connection_pool(&self) -> HsdsResult<Self> {
+        let mut client = self.clone();
+        client.client = Self::http_client(self.http2_prior_knowledge)?;
+        Ok(client)
+    }
+
+    /// Speak HTTP/2 from the first byte, without HTTP/1.1 upgrade or ALPN
+    ///
+    /// For servers reached over cleartext HTTP/2 (h2c), e.g. HSDS behind an
+    /// in-cluster service mesh. All requests to a server are multiplexed
+    /// over one connection, so many small metadata requests in flight at
+    /// once no longer queue behind each other as on HTTP/1.1. The server
+    /// must accept HTTP/2 prior knowledge; others fail every request.
+    ///
+    /// Replaces the connection pool, so call it before cloning the client.
+    ///
+    /// ```no_run
+    /// # use hsds_client::{HsdsClient, NoAuth};
+    /// # fn run() -> hsds_client::HsdsResult<()> {
+    /// let client = HsdsClient::new("http://hsds.hsds.svc.cluster.local:5101", NoAuth)?
+    ///     .with_http2_prior_knowledge()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "http2")]
+    pub fn with_http2_prior_knowledge(mut self) -> HsdsResult<Self> {
+        self.client = Self::http_client(true)?;
+        self.http2_prior_knowledge = true;
+        Ok(self)
+    }
+
+    /// Whether requests are sent as HTTP/2 with prior knowledge
+    pub fn uses_http2_prior_knowledge(&self) -> bool {
+        self.http2_prior_knowledge
+    }
+
+    /// Build the reqwest client used by [`new`](Self::new)
+    fn http_client(http2_prior_knowledge: bool) -> HsdsResult<Client> {
+        let builder = Client::builder().user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ));
+        #[cfg(feature = "http2")]
+        let builder = if http2_prior_knowledge { builder.http2_prior_knowledge() } else { builder };
+        #[cfg(not(feature = "http2"))]
+        debug_assert!(!http2_prior_knowledge);
+        Ok(builder.build()?)
+    }
+
+    /// Send requests for domains under a folder to another server
+    ///
+    /// Requests whose `domain` parameter lies at or under `prefix` (e.g.
+    /// "/internal" covers "/internal/a.h5" but not "/internals.h5") go to
+    /// `base_url` with `auth` instead of this client's endpoint and
+    /// credentials, so one client can serve several deployments. The most
+    /// specific matching prefix wins; requests without a domain always use
+    /// the client's own endpoint. All other settings are shared.
+    ///
+    /// ```no_run
+    /// # use hsds_client::{BasicAuth, HsdsClient, NoAuth};
+    /// # fn main() -> hsds_client::HsdsResult<()> {
+    /// let client = HsdsClient::new("http://public-hsds:5101", NoAuth)?
+    ///     .with_route("/internal", "https://hsds.corp.example", BasicAuth::new("svc", "secret"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `prefix` - Domain folder, e.g. "/internal"
+    /// * `base_url` - Endpoint of the server holding those domains
+    /// * `auth` - Credentials for that server
+    pub fn with_route(
+        mut self,
+        prefix: &str,
+        base_url: impl AsRef<str>,
+        auth: impl Authentication + 'static,
+    ) -> HsdsResult<Self> {
+        let prefix = DomainPath::parse(prefix)?.path().trim_end_matches('/').to_string();
+        let base_url = Self::normalize_base_url(Url::parse(base_url.as_ref())?);
+        Self::check_tls(&base_url)?;
+
+        let mut routes = self.routes.as_ref().clone();
+        routes.retain(|route| route.prefix != prefix);
+        routes.push(Route { prefix, base_url, auth: Arc::new(auth) });
+        // Most specific first, so the first match wins
+        routes.sort_by_key(|route| std::cmp::Reverse(route.prefix.len()));
+        self.routes = Arc::new(routes);
+        Ok(self)
+    }
+
+    /// Base URL requests for a domain are sent to
+    ///
+    /// An empty domain stands for the [default domain](Self::with_default_domain).
+    pub fn base_url_for(&self, domain: &str) -> &Url {
+        let path = match (domain, &self.default_domain) {
+            ("", Some(default)) => Some(default.path().to_string()),
+            _ => DomainPath::parse(domain).map(|d| d.path().to_string()).ok(),
+        };
+        path.and_then(|p| self.route(&p)).map_or(&self.base_url, |route| &route.base_url)
+    }
+
+    /// Fully qualified GET URL of an endpoint, for sharing
+    ///
+    /// The URL carries the domain (and bucket) and `params` as query
+    /// parameters, and points at the route serving the domain, so it works
+    /// in a browser or any tool that speaks plain HTTP. With `credentials`
+    /// the user name and password are embedded in the URL; anyone holding
+    /// it can use them, so pass `None` for public domains.
+    ///
+    /// # Arguments
+    /// * `path` - Endpoint path, e.g. "/datasets/d-1234/value"
+    /// * `domain` - Domain path (empty for the default domain)
+    /// * `params` - Further query parameters
+    /// * `credentials` - Basic-auth credentials to embed, if any
+    pub fn shareable_url(
+        &self,
+        path: &str,
+        domain: &str,
+        params: &[(&str, &str)],
+        credentials: Option<&BasicAuth>,
+    ) -> HsdsResult<Url> {
+        let domain = match (domain, &self.default_domain) {
+            ("", Some(default)) => default.clone(),
+            ("", None) => return Err(HsdsError::invalid_param("No domain given and the client has no default domain")),
+            _ => DomainPath::parse(domain)?,
+        };
+        let mut url = self.base_url_for(&domain).join(path.trim_start_matches('/'))?;
+        let mut query = domain_pair(&domain);
+        for (name, value) in params {
+            query.push_str(&format!("&{}={}", urlencoding::encode(name), urlencoding::encode(value)));
+        }
+        url.set_query(Some(&query));
+
+        if let Some(auth) = credentials {
+            let (username, password) = auth.credentials();
+            url.set_username(username)
+                .and_then(|_| url.set_password(Some(password)))
+                .map_err(|_| HsdsError::invalid_param(format!("Credentials cannot be embedded in {}", url.origin().ascii_serialization())))?;
+        }
+        Ok(url)
+    }
+
+    fn route(&self, domain: &str) -> Option<&Route> {
+        self.routes.iter().find(|route| route.matches(domain))
+    }
+
+    /// Set the logging options (payload logging and size caps)
+    pub fn with_log_options(mut self, options: LogOptions) -> Self {
+        self.log_options = options;
+        self
+    }
+
+    /// Cap the size of response bodies, in bytes
+    ///
+    /// A response whose `Content-Length` exceeds the cap fails with
+    /// [`HsdsError::ResponseTooLarge`] before its body is read, and a body
+    /// without a declared length is abandoned as soon as it passes the cap.
+    /// Protects services from pulling an entire multi-GB dataset through
+    /// the JSON path by accident. Unlimited by default.
+    pub fn with_max_response_size(mut self, bytes: u64) -> Self {
+        self.options.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Largest response body accepted, `None` if unlimited
+    pub fn max_response_size(&self) -> Option<u64> {
+        self.options.max_response_size
+    }
+
+    /// Clone this client with different request settings
+    ///
+    /// The clone shares the connection pool, credentials and modes of this
+    /// client, so it is cheap to make one per scope, e.g. aggressive
+    /// timeouts for interactive reads next to patient settings for batch
+    /// writes:
+    ///
+    /// ```no_run
+    /// # use hsds_client::{HsdsClient, NoAuth};
+    /// # use std::time::Duration;
+    /// # let client = HsdsClient::new("http://localhost:5101", NoAuth).unwrap();
+    /// let ui = client.with_options(|o| o.with_timeout(Duration::from_secs(2)).with_max_retries(1));
+    /// let batch = client.with_options(|o| o.with_timeout(Duration::from_secs(600)).with_max_retries(5));
+    /// ```
+    pub fn with_options(&self, configure: impl FnOnce(ClientOptions) -> ClientOptions) -> Self {
+        let mut client = self.clone();
+        client.options = configure(self.options.clone());
+        client
+    }
+
+    /// Clone this client with requests bound to a cancellation token
+    ///
+    /// Once `token` is cancelled, requests in flight are aborted and new
+    /// ones fail with [`HsdsError::Cancelled`] before anything is sent.
+    /// Every helper built on the clone (listing streams, walkers, copies,
+    /// chunked reads and writes) therefore stops at its next request.
+    ///
+    /// Without a token, dropping a helper's future or stream cancels it as
+    /// well: the client spawns no background tasks, so nothing keeps
+    /// running and the in-flight request's connection is closed. Work
+    /// already acknowledged by the server (e.g. the first objects of a
+    /// copy) is not rolled back in either case; a copy journal can resume
+    /// it later.
+    ///
+    /// ```no_run
+    /// # use hsds_client::{HsdsClient, NoAuth};
+    /// # use tokio_util::sync::CancellationToken;
+    /// # async fn run(client: HsdsClient) -> hsds_client::HsdsResult<()> {
+    /// let token = CancellationToken::new();
+    /// let scoped = client.with_cancellation(token.clone());
+    /// // e.g. from a "Stop" button: token.cancel();
+    /// let values = scoped.datasets().read_dataset_values_json("/home/a.h5", "d-1", None, None, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cancellation(&self, token: CancellationToken) -> Self {
+        self.with_options(|o| o.with_cancellation(token))
+    }
+
+    /// Run `work` until it completes or this client's cancellation token is cancelled
+    pub(crate) async fn cancellable<T>(&self, work: impl Future<Output = HsdsResult<T>>) -> HsdsResult<T> {
+        match &self.options.cancellation {
+            None => work.await,
+            Some(token) => tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(HsdsError::Cancelled),
+                result = work => result,
+            },
+        }
+    }
+
+    /// Request settings of this client
+    pub fn options(&self) -> &ClientOptions {
+        &self.options
+    }
+
+    /// Fail if `size` bytes of response body exceed the configured cap
+    pub(crate) fn check_response_size(&self, size: u64) -> HsdsResult<()> {
+        match self.options.max_response_size {
+            Some(limit) if size > limit => Err(HsdsError::ResponseTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject mutating requests client-side
+    ///
+    /// A read-only client fails every create, update and delete call with
+    /// [`HsdsError::ReadOnly`] before anything is sent, regardless of the
+    /// server's ACLs. Handles and helpers created from it inherit the mode.
+    /// Clone a shared client to get a read-only view on the same connection
+    /// pool, e.g. for analysis code that opens archived files:
+    ///
+    /// ```no_run
+    /// # use hsds_client::{HsdsClient, NoAuth};
+    /// # let client = HsdsClient::new("http://localhost:5101", NoAuth).unwrap();
+    /// let archive = client.clone().with_read_only(true);
+    /// ```
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Whether mutating requests are rejected client-side
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Record mutating requests in `log` instead of sending them
+    ///
+    /// Reads still go to the server, so composite helpers (recursive delete,
+    /// imports, sync) can walk the real hierarchy and report every create,
+    /// update and delete they would make. Calls that only need a success
+    /// status (deletes, value writes) return `Ok`; calls whose result comes
+    /// from the server (e.g. the id of a created group) fail with
+    /// [`HsdsError::DryRun`].
+    pub fn with_dry_run(mut self, log: DryRunLog) -> Self {
+        self.dry_run = Some(log);
+        self
+    }
+
+    /// Whether mutating requests are recorded instead of sent
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// Report every successful mutating request to `hook`
+    ///
+    /// Requests rejected by the server or recorded in dry-run mode are not
+    /// reported.
+    pub fn with_audit_hook(mut self, hook: impl AuditHook + 'static) -> Self {
+        self.audit = Some(Arc::new(hook));
+        self
+    }
+
+    /// Record the timing and size of every request in `stats`
+    ///
+    /// Recorders add up: a client made from one that already records keeps
+    /// feeding the earlier recorders too.
+    pub fn with_transfer_stats(mut self, stats: TransferStats) -> Self {
+        self.transfer_stats.push(stats);
+        self
+    }
+
+    /// Use `domain` for API calls made with an empty domain path
+    ///
+    /// For applications that work within one file for their lifetime:
+    /// `client.groups().get_group("", &root_id, None)` then reads from the
+    /// default domain. The domain is filled in when a request is sent (so
+    /// routes apply to it); without a default, an empty domain fails with
+    /// [`HsdsError::InvalidParameter`].
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path, e.g. "/home/user/run42.h5"
+    pub fn with_default_domain(mut self, domain: &str) -> HsdsResult<Self> {
+        self.default_domain = Some(DomainPath::parse(domain)?);
+        Ok(self)
+    }
+
+    /// Domain used for API calls made with an empty domain path
+    pub fn default_domain(&self) -> Option<&DomainPath> {
+        self.default_domain.as_ref()
+    }
+
+    /// Act on behalf of another user
+    ///
+    /// Every request carries the acting user as configured by
+    /// `impersonation`, in addition to this client's own credentials, and
+    /// audit events record it. Clones share the connection pool, so a
+    /// gateway can derive one client per end user from its service client:
+    ///
+    /// ```no_run
+    /// # use hsds_client::{BasicAuth, HsdsClient, Impersonation};
+    /// # fn main() -> hsds_client::HsdsResult<()> {
+    /// let service = HsdsClient::new("http://localhost:5101", BasicAuth::new("ingest", "secret"))?;
+    /// let as_alice = service.clone().with_impersonation(Impersonation::header("X-Acting-User", "alice")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_impersonation(mut self, impersonation: Impersonation) -> Self {
+        self.impersonation = Some(impersonation);
+        self
+    }
+
+    /// User this client acts on behalf of, if impersonating
+    pub fn acting_user(&self) -> Option<&str> {
+        self.impersonation.as_ref().map(Impersonation::user)
+    }
+
     /// Get the base URL
+    ///
+    /// The path always ends with `/` so that it acts as a prefix for all
+    /// endpoints (e.g. `https://host/api/hsds/`).
     pub fn base_url(&self) -> &Url {
         &self.base_url
     }
 
+    /// Ensure the base URL path ends with a slash so it can be used as a prefix
+    fn normalize_base_url(mut url: Url) -> Url {
+        if !url.path().ends_with('/') {
+            let path = format!("{}/", url.path());
+            url.set_path(&path);
+        }
+        url
+    }
+
+    /// Reject an `https` URL when no TLS backend is compiled in
+    fn check_tls(url: &Url) -> HsdsResult<()> {
+        if url.scheme() == "https" && !cfg!(any(feature = "rustls-tls", feature = "native-tls")) {
+            return Err(HsdsError::invalid_param(format!(
+                "{} needs TLS, but hsds_client was built without the rustls-tls or native-tls feature", url
+            )));
+        }
+        Ok(())
+    }
+
+    /// Resolve an endpoint path (e.g. "/datasets") against the base URL
+    ///
+    /// Endpoint paths are treated as relative to the base path, so deployments
+    /// behind a reverse proxy prefix keep that prefix.
+    pub fn endpoint_url(&self, path: &str) -> HsdsResult<Url> {
+        Ok(self.base_url.join(path.trim_start_matches('/'))?)
+    }
+
+    /// Get the logging options
+    pub fn log_options(&self) -> &LogOptions {
+        &self.log_options
+    }
+
+    /// Pay connection setup costs up front
+    ///
+    /// Resolves the server's host name, then issues a `GET /about`, which
+    /// opens (and for https, TLS-handshakes) a pooled connection that later
+    /// requests reuse. Call it at startup so the first data write is not
+    /// delayed by DNS and TLS.
+    pub async fn warm_up(&self) -> HsdsResult<WarmUpReport> {
+        let host = self.base_url.host_str().ok_or_else(|| {
+            HsdsError::invalid_param(format!("Base URL '{}' has no host", self.base_url))
+        })?;
+        let port = self.base_url.port_or_known_default().unwrap_or(80);
+
+        let started = Instant::now();
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| HsdsError::OperationFailed(format!("DNS lookup for {} failed: {}", host, e)))?
+            .collect();
+        let dns_time = started.elapsed();
+        debug!("Resolved {} to {:?} in {:?}", host, addresses, dns_time);
+
+        let started = Instant::now();
+        let req = self.request(reqwest::Method::GET, "/about").await?;
+        let about = self.execute(req).await?;
+        let request_time = started.elapsed();
+        debug!("Warm-up GET /about took {:?}", request_time);
+
+        Ok(WarmUpReport {
+            addresses,
+            dns_time,
+            request_time,
+            about,
+        })
+    }
+
     /// Get Domain API
     pub fn domains(&self) -> DomainApi<'_> {
         DomainApi::new(self)
@@ -95,12 +762,60 @@ impl HsdsClient {
         method: reqwest::Method,
         path: &str,
     ) -> HsdsResult<RequestBuilder> {
-        let url = self.base_url.join(path)?;
-        let mut request = self.client.request(method, url);
+        let url = self.endpoint_url(path)?;
+        self.request_url(method, url).await
+    }
+
+    /// GET the resource an href points to and deserialize it
+    ///
+    /// Only the path and query of the href are used; they are resolved
+    /// against this client's base URL, since the server does not know about
+    /// proxies or base path prefixes in front of it.
+    pub async fn follow_href<T>(&self, href: &Href) -> HsdsResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let target = match Url::parse(&href.href) {
+            Ok(url) => url,
+            Err(_) => self.base_url.join(&href.href)?,
+        };
+        let mut url = self.endpoint_url(target.path())?;
+        url.set_query(target.query());
+        debug!("Following href rel={} to {}", href.rel, url);
+
+        let req = self.request_url(reqwest::Method::GET, url).await?;
+        self.execute(req).await
+    }
+
+    /// Build an authenticated request to a fully resolved URL
+    async fn request_url(
+        &self,
+        method: reqwest::Method,
+        url: Url,
+    ) -> HsdsResult<RequestBuilder> {
+        if self.read_only && is_mutating(&method, url.path()) {
+            return Err(HsdsError::ReadOnly(format!("{} {} is not allowed", method, url.path())));
+        }
+        let mut request = self.client.request(method.clone(), url.clone());
+        if let Some(timeout) = self.options.timeout {
+            request = request.timeout(timeout);
+        }
 
         // Apply authentication
         let mut headers = reqwest::header::HeaderMap::new();
         self.auth.apply_auth(&mut headers).await?;
+        match &self.impersonation {
+            Some(Impersonation::Header { name, user }) => {
+                headers.insert(name.clone(), user.parse().map_err(|e| {
+                    HsdsError::invalid_param(format!("Invalid acting user '{}': {}", user, e))
+                })?);
+            }
+            Some(Impersonation::Query { name, user }) => {
+                request = request.query(&[(name, user)]);
+            }
+            None => {}
+        }
+        debug!("HTTP {} {} headers={}", method, url, logging::redact_headers(&headers));
         
         for (name, value) in headers.iter() {
             request = request.header(name, value);
@@ -114,14 +829,321 @@ impl HsdsClient {
     where
         T: for<'de> Deserialize<'de>,
     {
-        let response = request.send().await?;
-        self.handle_response(response).await
+        match self.send(request).await? {
+            Sent::Response(response) => self.handle_response(response).await,
+            Sent::Recorded(planned) => Err(HsdsError::DryRun(format!(
+                "{} was recorded, not sent; it has no result", planned
+            ))),
+        }
     }
 
     /// Execute a request and return raw bytes
+    ///
+    /// In dry-run mode a recorded request returns an empty body.
     pub async fn execute_bytes(&self, request: RequestBuilder) -> HsdsResult<bytes::Bytes> {
-        let response = request.send().await?;
-        self.handle_response_bytes(response).await
+        match self.send(request).await? {
+            Sent::Response(response) => self.handle_response_bytes(response).await,
+            Sent::Recorded(_) => Ok(bytes::Bytes::new()),
+        }
+    }
+
+    /// Send a request, or record it if it mutates and dry-run mode is on
+    ///
+    /// Successful mutating requests are reported to the audit hook.
+    async fn send(&self, request: RequestBuilder) -> HsdsResult<Sent> {
+        self.cancellable(self.send_uncancelled(request)).await
+    }
+
+    async fn send_uncancelled(&self, request: RequestBuilder) -> HsdsResult<Sent> {
+        let request = self.apply_default_domain(request.build()?)?;
+        let (request, auth) = self.apply_route(request).await?;
+        if !is_mutating(request.method(), request.url().path()) {
+            return Ok(Sent::Response(self.send_read(request).await?));
+        }
+        if let Some(log) = &self.dry_run {
+            let planned = log.record(&request);
+            info!("Dry run: {}", planned);
+            return Ok(Sent::Recorded(planned));
+        }
+
+        let event = self.audit.as_ref().map(|_| AuditEvent::new(&request, auth.username(), self.acting_user()));
+        let response = self.execute_timed(request).await?;
+        if let (Some(hook), Some(mut event)) = (&self.audit, event) {
+            if response.status().is_success() {
+                event.status = response.status().as_u16();
+                event.timestamp = std::time::SystemTime::now();
+                hook.on_mutation(&event);
+            }
+        }
+        Ok(Sent::Response(response))
+    }
+
+    /// Fill in the default domain of a request made with an empty domain
+    fn apply_default_domain(&self, mut request: reqwest::Request) -> HsdsResult<reqwest::Request> {
+        let Some(query) = request.url().query() else {
+            return Ok(request);
+        };
+        if !query.split('&').any(|pair| pair == "domain=") {
+            return Ok(request);
+        }
+        let domain = self.default_domain.as_ref().ok_or_else(|| {
+            HsdsError::invalid_param("No domain given and the client has no default domain")
+        })?;
+        let query = query
+            .split('&')
+            .map(|pair| if pair == "domain=" { domain_pair(domain) } else { pair.to_string() })
+            .collect::<Vec<_>>()
+            .join("&");
+        request.url_mut().set_query(Some(&query));
+        Ok(request)
+    }
+
+    /// Redirect a request to the route serving its domain, if any
+    ///
+    /// The URL is moved to the route's endpoint and this client's
+    /// credentials are replaced by the route's. Returns the credentials the
+    /// request is sent with.
+    async fn apply_route(&self, mut request: reqwest::Request) -> HsdsResult<(reqwest::Request, &dyn Authentication)> {
+        let domain = request.url().query_pairs().find(|(k, _)| k == "domain").map(|(_, v)| v.into_owned());
+        let Some(route) = domain.as_deref().and_then(|d| self.route(d)) else {
+            return Ok((request, self.auth.as_ref()));
+        };
+
+        let endpoint = request.url().path().strip_prefix(self.base_url.path()).unwrap_or(request.url().path());
+        let mut url = route.base_url.join(endpoint)?;
+        url.set_query(request.url().query());
+        debug!("Routing {} {} to {}", request.method(), request.url().path(), route.base_url);
+        *request.url_mut() = url;
+
+        let mut default_headers = reqwest::header::HeaderMap::new();
+        self.auth.apply_auth(&mut default_headers).await?;
+        for name in default_headers.keys() {
+            request.headers_mut().remove(name);
+        }
+        let mut headers = reqwest::header::HeaderMap::new();
+        route.auth.apply_auth(&mut headers).await?;
+        request.headers_mut().extend(headers);
+        Ok((request, route.auth.as_ref()))
+    }
+
+    /// Send a request that does not modify server state, retrying transient failures
+    ///
+    /// Requests with a streamed body cannot be replayed and are sent once.
+    async fn send_read(&self, request: reqwest::Request) -> HsdsResult<Response> {
+        let mut backoff = self.options.backoff;
+        for _ in 0..self.options.max_retries {
+            let Some(attempt) = request.try_clone() else { break };
+            let failure = match self.execute_timed(attempt).await {
+                Ok(response) if is_transient(response.status()) => format!("HTTP {}", response.status()),
+                Err(e) if e.is_connect() || e.is_timeout() => e.to_string(),
+                outcome => return Ok(outcome?),
+            };
+            info!("{} {} failed ({}); retrying in {:?}", request.method(), request.url().path(), failure, backoff);
+            self.transfer_stats.iter().for_each(TransferStats::record_retry);
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+        Ok(self.execute_timed(request).await?)
+    }
+
+    /// Send a request once, recording it in the transfer stats
+    ///
+    /// Requests slower than the slow-request threshold are logged.
+    async fn execute_timed(&self, request: reqwest::Request) -> reqwest::Result<Response> {
+        if self.transfer_stats.is_empty() && self.options.slow_request_threshold.is_none() {
+            return self.client.execute(request).await;
+        }
+        let bytes_sent = request.body().and_then(|b| b.as_bytes()).map_or(0, |b| b.len() as u64);
+        let slow = self.options.slow_request_threshold.map(|threshold| (threshold, SlowRequest::new(&request, bytes_sent)));
+        let sent = Instant::now();
+        let result = self.client.execute(request).await;
+        let (received, failed) = match &result {
+            Ok(response) => (response.content_length(), !response.status().is_success()),
+            Err(_) => (None, true),
+        };
+        for stats in &self.transfer_stats {
+            stats.record(sent, bytes_sent, received, failed);
+        }
+        if let Some((threshold, slow)) = slow {
+            let elapsed = sent.elapsed();
+            if elapsed >= threshold {
+                let outcome = match &result {
+                    Ok(response) => response.status().to_string(),
+                    Err(e) => e.to_string(),
+                };
+                warn!("Slow request: {}", slow.summary(elapsed, &outcome, received));
+            }
+        }
+        result
+    }
+
+    /// Execute a request whose response body is not needed
+    ///
+    /// Succeeds on any 2xx status, including `204 No Content` and empty
+    /// bodies, without attempting to parse the body.
+    pub async fn execute_empty(&self, request: RequestBuilder) -> HsdsResult<()> {
+        self.execute_bytes(request).await.map(|_| ())
+    }
+
+    /// Execute a request whose response body may be empty
+    ///
+    /// Returns `None` for an empty (or whitespace-only) body, e.g. on
+    /// `204 No Content`, and the parsed JSON otherwise.
+    pub async fn execute_optional<T>(&self, request: RequestBuilder) -> HsdsResult<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let body = self.execute_bytes(request).await?;
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+        self.parse_json(&body).map(Some)
+    }
+
+    /// Execute a write, parsing its acknowledgement
+    ///
+    /// An empty body reads as [`WriteResult::default`], so writes recorded
+    /// in dry-run mode succeed.
+    pub(crate) async fn execute_write(&self, request: RequestBuilder) -> HsdsResult<WriteResult> {
+        Ok(self.execute_optional(request).await?.unwrap_or_default())
+    }
+
+    /// Execute a request, also returning the response's `ETag` header
+    pub(crate) async fn execute_tagged<T>(&self, request: RequestBuilder) -> HsdsResult<(T, Option<String>)>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let response = match self.send(request).await? {
+            Sent::Response(response) => response,
+            Sent::Recorded(planned) => {
+                return Err(HsdsError::DryRun(format!("{} was recorded, not sent; it has no result", planned)))
+            }
+        };
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        Ok((self.handle_response(response).await?, etag))
+    }
+
+    /// Execute a request and return the successful response with its body unread
+    ///
+    /// For callers that consume the body incrementally; error statuses are
+    /// handled as in [`execute`](Self::execute). A declared `Content-Length`
+    /// is checked against the size cap, but the caller must check the bytes
+    /// it receives with [`check_response_size`](Self::check_response_size).
+    pub(crate) async fn execute_streaming(&self, request: RequestBuilder) -> HsdsResult<Response> {
+        let response = match self.send(request).await? {
+            Sent::Response(response) => response,
+            Sent::Recorded(planned) => {
+                return Err(HsdsError::DryRun(format!("{} was recorded, not sent; it has no result", planned)))
+            }
+        };
+        match response.status() {
+            status if status.is_success() => {
+                self.check_response_size(response.content_length().unwrap_or(0))?;
+                Ok(response)
+            }
+            status => self.handle_error_response(status, response).await,
+        }
+    }
+
+    /// Execute an existence check
+    ///
+    /// Returns `true` on success and `false` on `404 Not Found` or
+    /// `410 Gone`; other failures are returned as errors.
+    pub async fn execute_exists(&self, request: RequestBuilder) -> HsdsResult<bool> {
+        let response = match self.send(request).await? {
+            Sent::Response(response) => response,
+            Sent::Recorded(planned) => {
+                return Err(HsdsError::DryRun(format!("{} was recorded, not sent", planned)))
+            }
+        };
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            StatusCode::NOT_FOUND | StatusCode::GONE => Ok(false),
+            status => self.handle_error_response(status, response).await,
+        }
+    }
+
+    /// Check whether a group, dataset or committed datatype exists
+    ///
+    /// The collection is derived from the id prefix (`g-`, `d-`, `t-`).
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    /// * `object_id` - UUID of the object
+    pub async fn object_exists(&self, domain: &str, object_id: &str) -> HsdsResult<bool> {
+        let collection = crate::apis::collection_for_id(object_id)?;
+        let path = format!("/{}/{}", collection, object_id);
+        let mut req = self.request(reqwest::Method::HEAD, &path).await?;
+        req = Self::with_domain(req, domain)?;
+
+        self.execute_exists(req).await
+    }
+
+    /// Execute a request negotiating the response format with a decoder
+    ///
+    /// The decoder's media type is sent as the `Accept` header and the
+    /// successful response body is handed to it for decoding. Compressed
+    /// responses are inflated first (see [`execute_decoded`](Self::execute_decoded)).
+    pub async fn execute_with<D>(&self, request: RequestBuilder, decoder: D) -> HsdsResult<D::Output>
+    where
+        D: ResponseDecoder,
+    {
+        self.execute_decoded(request, decoder).await.map(|(output, _)| output)
+    }
+
+    /// Execute a request with a decoder, also returning the content encoding
+    ///
+    /// With the `compression` feature, deflate and gzip responses are
+    /// accepted and inflated before decoding. The returned encoding is the
+    /// one the server applied (`None` for identity), so callers can tell
+    /// which transformations were undone.
+    pub async fn execute_decoded<D>(
+        &self,
+        request: RequestBuilder,
+        decoder: D,
+    ) -> HsdsResult<(D::Output, Option<String>)>
+    where
+        D: ResponseDecoder,
+    {
+        #[allow(unused_mut)]
+        let mut request = request.header(reqwest::header::ACCEPT, decoder.accept());
+        #[cfg(feature = "compression")]
+        {
+            request = request.header(reqwest::header::ACCEPT_ENCODING, crate::compression::ACCEPT_ENCODING);
+        }
+
+        let response = match self.send(request).await? {
+            Sent::Response(response) => response,
+            Sent::Recorded(planned) => {
+                return Err(HsdsError::DryRun(format!("{} was recorded, not sent; it has no result", planned)))
+            }
+        };
+        let encoding = response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .filter(|v| !v.is_empty() && v != "identity");
+        let body = self.handle_response_bytes(response).await?;
+
+        let body = match &encoding {
+            None => body,
+            #[cfg(feature = "compression")]
+            Some(encoding) => crate::compression::inflate(encoding, &body)?,
+            #[cfg(not(feature = "compression"))]
+            Some(encoding) => {
+                return Err(HsdsError::InvalidResponse(format!(
+                    "Response uses Content-Encoding '{}'; enable the `compression` feature", encoding
+                )))
+            }
+        };
+        // The cap applies to the inflated body too
+        self.check_response_size(body.len() as u64)?;
+        Ok((decoder.decode(body)?, encoding))
     }
 
     /// Handle response and deserialize JSON
@@ -132,27 +1154,67 @@ impl HsdsClient {
         let status = response.status();
         
         if status.is_success() {
-            let json = response.json::<T>().await?;
-            Ok(json)
+            let body = self.read_body(response).await?;
+            self.parse_json(&body)
         } else {
             self.handle_error_response(status, response).await
         }
     }
 
+    /// Deserialize a JSON body according to the client's [`Strictness`]
+    fn parse_json<T>(&self, body: &[u8]) -> HsdsResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        if !self.options.strictness.is_strict() {
+            return Ok(serde_json::from_slice(body)?);
+        }
+        let (parsed, ignored) = strict::from_value_strict(serde_json::from_slice(body)?)?;
+        if ignored.is_empty() {
+            Ok(parsed)
+        } else {
+            Err(HsdsError::InvalidResponse(format!(
+                "{} has no field for {}", std::any::type_name::<T>(), ignored.join(", ")
+            )))
+        }
+    }
+
     /// Handle response and return raw bytes
     async fn handle_response_bytes(&self, response: Response) -> HsdsResult<bytes::Bytes> {
         let status = response.status();
         
         if status.is_success() {
-            let bytes = response.bytes().await?;
-            Ok(bytes)
+            self.read_body(response).await
         } else {
             self.handle_error_response(status, response).await
         }
     }
 
+    /// Read a successful response's body, enforcing the size cap
+    async fn read_body(&self, response: Response) -> HsdsResult<bytes::Bytes> {
+        if self.options.max_response_size.is_none() {
+            return self.cancellable(async { Ok(response.bytes().await?) }).await;
+        }
+        self.check_response_size(response.content_length().unwrap_or(0))?;
+        self.cancellable(async {
+            let mut body = bytes::BytesMut::new();
+            let mut chunks = response.bytes_stream();
+            while let Some(chunk) = chunks.try_next().await? {
+                self.check_response_size((body.len() + chunk.len()) as u64)?;
+                body.extend_from_slice(&chunk);
+            }
+            Ok(body.freeze())
+        })
+        .await
+    }
+
     /// Handle error responses
+    ///
+    /// A `404` on a domain-level request (`/` or `/domains`) means the
+    /// domain itself is missing and maps to [`HsdsError::DomainNotFound`];
+    /// on any other path it is [`HsdsError::ObjectNotFound`].
     async fn handle_error_response<T>(&self, status: StatusCode, response: Response) -> HsdsResult<T> {
+        let domain_level = self.is_domain_request(response.url());
         // Try to parse error response
         let error_message = match response.json::<ErrorResponse>().await {
             Ok(error_resp) => {
@@ -166,15 +1228,49 @@ impl HsdsClient {
         match status {
             StatusCode::UNAUTHORIZED => Err(HsdsError::auth_error(error_message)),
             StatusCode::FORBIDDEN => Err(HsdsError::PermissionDenied(error_message)),
+            StatusCode::NOT_FOUND if domain_level => Err(HsdsError::DomainNotFound(error_message)),
             StatusCode::NOT_FOUND => Err(HsdsError::ObjectNotFound(error_message)),
             StatusCode::BAD_REQUEST => Err(HsdsError::invalid_param(error_message)),
+            StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED => Err(HsdsError::Conflict(error_message)),
             _ => Err(HsdsError::api_error(status.as_u16(), error_message)),
         }
     }
 
+    /// Whether a URL addresses a domain rather than an object in it
+    ///
+    /// The path is taken relative to the endpoint the request went to: the
+    /// most specific of the base URL and the route bases it lies under.
+    fn is_domain_request(&self, url: &Url) -> bool {
+        let base = std::iter::once(&self.base_url)
+            .chain(self.routes.iter().map(|route| &route.base_url))
+            .filter(|base| base.origin() == url.origin() && url.path().starts_with(base.path()))
+            .max_by_key(|base| base.path().len());
+        base.and_then(|base| url.path().strip_prefix(base.path()))
+            .is_some_and(|rest| matches!(rest, "" | "domains"))
+    }
+
     /// Add domain query parameter to request
-    pub fn with_domain(request: RequestBuilder, domain: &str) -> RequestBuilder {
-        request.query(&[("domain", domain)])
+    ///
+    /// The domain is validated and normalized as a [`DomainPath`] and
+    /// percent-encoded explicitly (spaces as `%20`, `+` as `%2B`). A
+    /// bucket-qualified domain also sets the `bucket` parameter. An empty
+    /// domain is replaced by the client's
+    /// [default domain](Self::with_default_domain) when the request is sent.
+    pub fn with_domain(request: RequestBuilder, domain: &str) -> HsdsResult<RequestBuilder> {
+        let pair = match domain {
+            "" => "domain=".to_string(),
+            _ => domain_pair(&DomainPath::parse(domain)?),
+        };
+        let (client, request) = request.build_split();
+        let mut request = request?;
+
+        let query = match request.url().query() {
+            Some(existing) if !existing.is_empty() => format!("{}&{}", existing, pair),
+            _ => pair,
+        };
+        request.url_mut().set_query(Some(&query));
+
+        Ok(RequestBuilder::from_parts(client, request))
     }
 
     /// Add pagination parameters to request
@@ -212,3 +1308,41 @@ impl HsdsClient {
         req
     }
 }
+
+/// Outcome of sending a request
+enum Sent {
+    Response(Response),
+    /// Recorded in dry-run mode instead of sent
+    Recorded(crate::dry_run::PlannedRequest),
+}
+
+/// Whether a failed read may succeed if sent again
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Query parameters selecting a domain (and its bucket, if qualified)
+fn domain_pair(domain: &DomainPath) -> String {
+    let mut pair = format!("domain={}", domain.encode());
+    if let Some(bucket) = domain.bucket() {
+        pair.push_str(&format!("&bucket={}", urlencoding::encode(bucket)));
+    }
+    pair
+}
+
+/// Whether a request modifies server state
+///
+/// `POST .../value` is a point selection read, not a write.
+pub(crate) fn is_mutating(method: &reqwest::Method, path: &str) -> bool {
+    match *method {
+        reqwest::Method::GET | reqwest::Method::HEAD | reqwest::Method::OPTIONS => false,
+        reqwest::Method::POST => !path.ends_with("/value"),
+        _ => true,
+    }
+}
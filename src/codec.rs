@@ -0,0 +1,290 @@
+//! Mapping of Rust element types to HSDS types and value encodings
+//!
+//! An [`ElementCodec`] knows the HSDS type JSON of a Rust type and how its
+//! values are written as JSON and, for fixed-size types, as the packed
+//! little-endian binary HSDS exchanges with `application/octet-stream`.
+//! Codecs are provided for the numeric primitives, `bool` (h5py's enum),
+//! `String` (variable-length UTF-8) and, with the `num-complex` feature,
+//! complex numbers. Structs become compounds with
+//! `#[derive(ElementCodec)]`; other types can implement the trait
+//! themselves.
+//!
+//! ```
+//! use hsds_client::ElementCodec;
+//!
+//! #[derive(ElementCodec)]
+//! struct Sample {
+//!     time: f64,
+//!     channel: u16,
+//! }
+//!
+//! assert_eq!(Sample::BINARY_SIZE, Some(10));
+//! assert_eq!(Sample::hsds_type()["fields"][1]["type"], "H5T_STD_U16LE");
+//! ```
+
+use crate::{
+    error::{HsdsError, HsdsResult},
+    values,
+};
+use serde_json::{json, Value};
+
+/// Buffer binary encodings are appended to (re-exported for implementors)
+pub use bytes::BufMut;
+
+/// Derive macro implementing [`ElementCodec`] for a struct as a compound
+pub use hsds_client_derive::ElementCodec;
+
+/// JSON values exchanged by codecs (re-exported for the derive macro)
+pub type JsonValue = Value;
+
+/// A Rust type stored as elements of HSDS datasets and attributes
+pub trait ElementCodec: Sized {
+    /// Size of one element in the binary encoding, `None` if it has none
+    /// (e.g. variable-length strings, which are only exchanged as JSON)
+    const BINARY_SIZE: Option<usize>;
+
+    /// HSDS type JSON, a predefined name or a type object
+    fn hsds_type() -> Value;
+
+    /// JSON form of one element, as HSDS writes it in a `value` array
+    fn to_json(&self) -> Value;
+
+    /// Element from its JSON form; the error says why the value does not fit
+    fn from_json(value: &Value) -> Result<Self, String>;
+
+    /// Append the binary encoding of the element
+    ///
+    /// Types without one ([`BINARY_SIZE`](Self::BINARY_SIZE) `None`) keep
+    /// the default, which fails.
+    fn put_binary<B: BufMut>(&self, _out: &mut B) -> Result<(), String> {
+        Err(format!("{} has no binary encoding", std::any::type_name::<Self>()))
+    }
+
+    /// Element from exactly [`BINARY_SIZE`](Self::BINARY_SIZE) bytes
+    ///
+    /// Types without a binary encoding keep the default, which fails.
+    fn from_binary(_bytes: &[u8]) -> Result<Self, String> {
+        Err(format!("{} has no binary encoding", std::any::type_name::<Self>()))
+    }
+}
+
+macro_rules! primitive_codec {
+    ($($ty:ty => $hsds:expr),*) => {
+        $(
+            impl ElementCodec for $ty {
+                const BINARY_SIZE: Option<usize> = Some(std::mem::size_of::<$ty>());
+
+                fn hsds_type() -> Value {
+                    Value::from($hsds)
+                }
+
+                fn to_json(&self) -> Value {
+                    json!(self)
+                }
+
+                fn from_json(value: &Value) -> Result<Self, String> {
+                    <$ty as serde::Deserialize>::deserialize(value).map_err(|e| e.to_string())
+                }
+
+                fn put_binary<B: BufMut>(&self, out: &mut B) -> Result<(), String> {
+                    out.put_slice(&self.to_le_bytes());
+                    Ok(())
+                }
+
+                fn from_binary(bytes: &[u8]) -> Result<Self, String> {
+                    let array = bytes
+                        .try_into()
+                        .map_err(|_| format!("expected {} bytes, found {}", std::mem::size_of::<$ty>(), bytes.len()))?;
+                    Ok(<$ty>::from_le_bytes(array))
+                }
+            }
+        )*
+    };
+}
+
+primitive_codec!(
+    i8 => "H5T_STD_I8LE",
+    u8 => "H5T_STD_U8LE",
+    i16 => "H5T_STD_I16LE",
+    u16 => "H5T_STD_U16LE",
+    i32 => "H5T_STD_I32LE",
+    u32 => "H5T_STD_U32LE",
+    i64 => "H5T_STD_I64LE",
+    u64 => "H5T_STD_U64LE",
+    f32 => "H5T_IEEE_F32LE",
+    f64 => "H5T_IEEE_F64LE"
+);
+
+/// Stored as [`values::bool_type`]; plain integers read as non-zero = true
+impl ElementCodec for bool {
+    const BINARY_SIZE: Option<usize> = Some(1);
+
+    fn hsds_type() -> Value {
+        values::bool_type()
+    }
+
+    fn to_json(&self) -> Value {
+        Value::from(u8::from(*self))
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Bool(b) => Ok(*b),
+            Value::Number(n) if n.as_i64().is_some() => Ok(n.as_i64() != Some(0)),
+            other => Err(format!("expected a boolean or integer, found {}", other)),
+        }
+    }
+
+    fn put_binary<B: BufMut>(&self, out: &mut B) -> Result<(), String> {
+        out.put_u8(u8::from(*self));
+        Ok(())
+    }
+
+    fn from_binary(bytes: &[u8]) -> Result<Self, String> {
+        match bytes {
+            [byte] => Ok(*byte != 0),
+            _ => Err(format!("expected 1 byte, found {}", bytes.len())),
+        }
+    }
+}
+
+/// Variable-length UTF-8 string, exchanged as JSON only
+impl ElementCodec for String {
+    const BINARY_SIZE: Option<usize> = None;
+
+    fn hsds_type() -> Value {
+        json!({
+            "class": "H5T_STRING",
+            "charSet": "H5T_CSET_UTF8",
+            "strPad": "H5T_STR_NULLTERM",
+            "length": "H5T_VARIABLE"
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        Value::from(self.as_str())
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        value.as_str().map(String::from).ok_or_else(|| format!("expected a string, found {}", value))
+    }
+}
+
+/// Stored as [`values::complex_type`], the h5py `r`/`i` compound
+#[cfg(feature = "num-complex")]
+impl<T: crate::apis::ComplexComponent> ElementCodec for num_complex::Complex<T> {
+    const BINARY_SIZE: Option<usize> = packed_size(&[T::BINARY_SIZE, T::BINARY_SIZE]);
+
+    fn hsds_type() -> Value {
+        compound_type(&[("r", T::hsds_type()), ("i", T::hsds_type())])
+    }
+
+    fn to_json(&self) -> Value {
+        json!([self.re.to_json(), self.im.to_json()])
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let fields = compound_fields(value, &["r", "i"])?;
+        Ok(Self::new(T::from_json(fields[0])?, T::from_json(fields[1])?))
+    }
+
+    fn put_binary<B: BufMut>(&self, out: &mut B) -> Result<(), String> {
+        self.re.put_binary(out)?;
+        self.im.put_binary(out)
+    }
+
+    fn from_binary(bytes: &[u8]) -> Result<Self, String> {
+        let (re, im) = bytes.split_at(bytes.len() / 2);
+        Ok(Self::new(T::from_binary(re)?, T::from_binary(im)?))
+    }
+}
+
+/// Total of the field sizes, `None` if a field has no binary encoding
+#[doc(hidden)]
+pub const fn packed_size(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut total = 0;
+    let mut i = 0;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) => total += size,
+            None => return None,
+        }
+        i += 1;
+    }
+    Some(total)
+}
+
+/// Compound type JSON of named fields
+pub fn compound_type(fields: &[(&str, Value)]) -> Value {
+    json!({
+        "class": "H5T_COMPOUND",
+        "fields": fields.iter().map(|(name, ty)| json!({"name": name, "type": ty})).collect::<Vec<_>>(),
+    })
+}
+
+/// Field values of a compound element, in the order of `names`
+///
+/// Accepts the array HSDS writes or an object keyed by field name.
+pub fn compound_fields<'v>(value: &'v Value, names: &[&str]) -> Result<Vec<&'v Value>, String> {
+    match value {
+        Value::Array(items) if items.len() == names.len() => Ok(items.iter().collect()),
+        Value::Array(items) => Err(format!("expected {} compound fields, found {}", names.len(), items.len())),
+        Value::Object(map) => names
+            .iter()
+            .map(|name| map.get(*name).ok_or_else(|| format!("missing compound field '{}'", name)))
+            .collect(),
+        other => Err(format!("expected a compound, found {}", other)),
+    }
+}
+
+/// Binary payload of elements, packed in order
+///
+/// Fails for types without a binary encoding.
+pub fn encode_binary<T: ElementCodec>(values: &[T]) -> HsdsResult<Vec<u8>> {
+    let size = binary_size::<T>()?;
+    let mut out = Vec::with_capacity(values.len() * size);
+    for (index, value) in values.iter().enumerate() {
+        value.put_binary(&mut out).map_err(|e| HsdsError::invalid_param(format!("element {}: {}", index, e)))?;
+    }
+    Ok(out)
+}
+
+/// Elements of a binary payload
+///
+/// Fails for types without a binary encoding and for payloads that are
+/// not a whole number of elements.
+pub fn decode_binary<T: ElementCodec>(bytes: &[u8]) -> HsdsResult<Vec<T>> {
+    let size = binary_size::<T>()?;
+    if size == 0 || !bytes.len().is_multiple_of(size) {
+        return Err(HsdsError::InvalidResponse(format!(
+            "{} bytes are not a whole number of {}-byte {} elements", bytes.len(), size, std::any::type_name::<T>()
+        )));
+    }
+    bytes
+        .chunks_exact(size)
+        .enumerate()
+        .map(|(index, element)| {
+            T::from_binary(element).map_err(|e| HsdsError::InvalidResponse(format!("element {}: {}", index, e)))
+        })
+        .collect()
+}
+
+/// Nested JSON array (row-major) of elements filling `shape`
+pub fn encode_json<T: ElementCodec>(values: &[T], shape: &[u64]) -> HsdsResult<Value> {
+    values::nest(values.iter().map(ElementCodec::to_json).collect(), shape)
+}
+
+/// Elements `rank` arrays deep in a nested JSON array, row-major
+///
+/// An element that does not fit `T` fails with [`HsdsError::Conversion`]
+/// carrying its index and value.
+pub fn decode_json<T: ElementCodec>(value: &Value, rank: usize) -> HsdsResult<Vec<T>> {
+    values::convert_nested_with(value, rank, T::from_json)
+}
+
+/// Binary element size of `T`, failing for types without a binary encoding
+pub(crate) fn binary_size<T: ElementCodec>() -> HsdsResult<usize> {
+    T::BINARY_SIZE.ok_or_else(|| {
+        HsdsError::invalid_param(format!("{} has no binary encoding", std::any::type_name::<T>()))
+    })
+}
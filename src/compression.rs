@@ -0,0 +1,92 @@
+//! Client-side compression of binary uploads
+//!
+//! Binary value writes can be sent with `Content-Encoding: deflate`; the
+//! server inflates the body before storing it, so highly compressible
+//! payloads (e.g. smooth simulation output) cost far fewer network bytes.
+//! Payloads that do not shrink are sent uncompressed.
+//!
+//! In the other direction, binary reads advertise [`ACCEPT_ENCODING`] and
+//! compressed responses are inflated with [`inflate`] before decoding.
+
+use std::io::{Read, Write};
+
+use bytes::Bytes;
+use flate2::{
+    read::{DeflateDecoder, GzDecoder, ZlibDecoder},
+    write::ZlibEncoder,
+    Compression,
+};
+
+use crate::{
+    error::{HsdsError, HsdsResult},
+    models::Dataset,
+};
+
+/// `Accept-Encoding` value sent on decoded reads
+pub const ACCEPT_ENCODING: &str = "deflate, gzip";
+
+/// Compression settings for binary uploads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadCompression {
+    /// Deflate level (1-9)
+    pub level: u32,
+    /// Payloads smaller than this are sent uncompressed
+    pub min_bytes: usize,
+}
+
+impl Default for UploadCompression {
+    fn default() -> Self {
+        Self {
+            level: 6,
+            min_bytes: 4096,
+        }
+    }
+}
+
+impl UploadCompression {
+    /// Compression with the given deflate level
+    pub fn with_level(level: u32) -> Self {
+        Self {
+            level: level.clamp(1, 9),
+            ..Self::default()
+        }
+    }
+
+    /// Compression matching a dataset's deflate filter, `None` if it has none
+    pub fn for_dataset(dataset: &Dataset) -> Option<Self> {
+        dataset.deflate_level().map(Self::with_level)
+    }
+
+    /// Compress a payload, `None` if it is too small or does not shrink
+    pub fn deflate(&self, data: &[u8]) -> Option<Bytes> {
+        if data.len() < self.min_bytes {
+            return None;
+        }
+        let mut encoder = ZlibEncoder::new(Vec::with_capacity(data.len() / 2), Compression::new(self.level));
+        encoder.write_all(data).ok()?;
+        let compressed = encoder.finish().ok()?;
+        (compressed.len() < data.len()).then(|| Bytes::from(compressed))
+    }
+}
+
+/// Inflate a response body compressed with the given `Content-Encoding`
+///
+/// "deflate" accepts both zlib-wrapped and raw deflate streams, since
+/// servers disagree on which one the name means.
+pub fn inflate(encoding: &str, data: &[u8]) -> HsdsResult<Bytes> {
+    let mut out = Vec::with_capacity(data.len() * 4);
+    let result = match encoding {
+        "gzip" | "x-gzip" => GzDecoder::new(data).read_to_end(&mut out),
+        "deflate" => ZlibDecoder::new(data).read_to_end(&mut out).or_else(|_| {
+            out.clear();
+            DeflateDecoder::new(data).read_to_end(&mut out)
+        }),
+        other => {
+            return Err(HsdsError::InvalidResponse(format!(
+                "Unsupported Content-Encoding '{}'", other
+            )))
+        }
+    };
+    result.map_err(|e| HsdsError::InvalidResponse(format!("Failed to inflate {} response: {}", encoding, e)))?;
+    Ok(Bytes::from(out))
+}
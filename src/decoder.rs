@@ -0,0 +1,106 @@
+use crate::error::HsdsResult;
+#[cfg(feature = "msgpack")]
+use crate::error::HsdsError;
+use bytes::Bytes;
+use serde::Deserialize;
+use std::marker::PhantomData;
+
+/// Content negotiation for response bodies
+///
+/// A decoder supplies the `Accept` header sent with the request and turns the
+/// raw response body into the requested output type.
+pub trait ResponseDecoder {
+    /// Decoded output type
+    type Output;
+
+    /// Media type sent in the `Accept` header
+    fn accept(&self) -> &str;
+
+    /// Decode a successful response body
+    fn decode(&self, body: Bytes) -> HsdsResult<Self::Output>;
+}
+
+/// Decodes JSON response bodies (`application/json`)
+pub struct JsonDecoder<T>(PhantomData<fn() -> T>);
+
+impl<T> JsonDecoder<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for JsonDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ResponseDecoder for JsonDecoder<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    type Output = T;
+
+    fn accept(&self) -> &str {
+        "application/json"
+    }
+
+    fn decode(&self, body: Bytes) -> HsdsResult<T> {
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// Returns the raw binary response body (`application/octet-stream`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BinaryDecoder;
+
+impl ResponseDecoder for BinaryDecoder {
+    type Output = Bytes;
+
+    fn accept(&self) -> &str {
+        "application/octet-stream"
+    }
+
+    fn decode(&self, body: Bytes) -> HsdsResult<Bytes> {
+        Ok(body)
+    }
+}
+
+/// Decodes MessagePack response bodies (`application/x-msgpack`)
+///
+/// Only useful against servers or proxies that can serve MessagePack; the
+/// payload layout is otherwise identical to the JSON response.
+#[cfg(feature = "msgpack")]
+pub struct MessagePackDecoder<T>(PhantomData<fn() -> T>);
+
+#[cfg(feature = "msgpack")]
+impl<T> MessagePackDecoder<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<T> Default for MessagePackDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<T> ResponseDecoder for MessagePackDecoder<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    type Output = T;
+
+    fn accept(&self) -> &str {
+        "application/x-msgpack"
+    }
+
+    fn decode(&self, body: Bytes) -> HsdsResult<T> {
+        rmp_serde::from_slice(&body)
+            .map_err(|e| HsdsError::InvalidResponse(format!("MessagePack decode failed: {}", e)))
+    }
+}
+
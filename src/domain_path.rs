@@ -0,0 +1,348 @@
+use crate::error::{HsdsError, HsdsResult};
+use crate::models::DomainKind;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// URI scheme prefix accepted by h5pyd (`hdf5://home/user/file.h5`)
+const HDF5_SCHEME: &str = "hdf5:/";
+
+/// Extensions that mark a path as a file domain
+const FILE_EXTENSIONS: [&str; 4] = [".h5", ".hdf5", ".he5", ".nc"];
+
+/// Validated and normalized HSDS domain path
+///
+/// Accepted forms (as used by h5pyd):
+/// - `/home/user/file.h5` - path in the server's default bucket
+/// - `mybucket/home/user/file.h5` - path qualified with a bucket
+/// - `hdf5://home/user/file.h5` - URI form of a plain path
+///
+/// Repeated slashes are collapsed, and a trailing slash marks a folder. The
+/// path is percent-encoded explicitly when sent as the `domain` query
+/// parameter, and a bucket, if present, is sent as the `bucket` parameter.
+///
+/// `DomainPath` dereferences to its original-style string form, so it can be
+/// passed to every API method that takes a domain and round-trips through
+/// serde unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DomainPath {
+    repr: String,
+    bucket: Option<String>,
+    path: String,
+}
+
+impl DomainPath {
+    /// Parse and normalize a domain path
+    pub fn parse(path: impl AsRef<str>) -> HsdsResult<Self> {
+        let raw = path.as_ref().trim();
+
+        if raw.is_empty() {
+            return Err(HsdsError::invalid_param("Domain path must not be empty"));
+        }
+        if let Some(c) = raw.chars().find(|c| c.is_control()) {
+            return Err(HsdsError::invalid_param(format!(
+                "Domain path '{}' contains control character {:?}", raw.escape_debug(), c
+            )));
+        }
+
+        if let Some(rest) = raw.strip_prefix(HDF5_SCHEME) {
+            if !rest.starts_with('/') {
+                return Err(HsdsError::invalid_param(format!(
+                    "Domain URI '{}' must start with 'hdf5://'", raw
+                )));
+            }
+            let path = Self::normalize_path(raw, rest)?;
+            return Ok(Self {
+                repr: format!("{}{}", HDF5_SCHEME, path),
+                bucket: None,
+                path,
+            });
+        }
+
+        if raw.starts_with('/') {
+            let path = Self::normalize_path(raw, raw)?;
+            return Ok(Self {
+                repr: path.clone(),
+                bucket: None,
+                path,
+            });
+        }
+
+        // Bucket-qualified form: "bucket/path"
+        let (bucket, rest) = raw.split_once('/').ok_or_else(|| {
+            HsdsError::invalid_param(format!(
+                "Domain path '{}' must start with '/' or be of the form 'bucket/path'", raw
+            ))
+        })?;
+        Self::validate_bucket(bucket)?;
+        let path = Self::normalize_path(raw, &format!("/{}", rest))?;
+        Ok(Self {
+            repr: format!("{}{}", bucket, path),
+            bucket: Some(bucket.to_string()),
+            path,
+        })
+    }
+
+    /// Parse a path that must refer to a file domain (no trailing slash)
+    pub fn file(path: impl AsRef<str>) -> HsdsResult<Self> {
+        let parsed = Self::parse(path)?;
+        if parsed.is_folder() {
+            return Err(HsdsError::invalid_param(format!(
+                "File domain path '{}' must not end with '/'", parsed
+            )));
+        }
+        Ok(parsed)
+    }
+
+    /// Parse a path as a folder (normalized with a trailing slash)
+    pub fn folder(path: impl AsRef<str>) -> HsdsResult<Self> {
+        let parsed = Self::parse(path)?;
+        if parsed.is_folder() {
+            return Ok(parsed);
+        }
+        let path = format!("{}/", parsed.path);
+        Ok(parsed.with_path(path))
+    }
+
+    /// Collapse repeated slashes and reject relative segments
+    fn normalize_path(raw: &str, path: &str) -> HsdsResult<String> {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if let Some(bad) = segments.iter().find(|s| **s == "." || **s == "..") {
+            return Err(HsdsError::invalid_param(format!(
+                "Domain path '{}' contains relative segment '{}'", raw, bad
+            )));
+        }
+
+        let mut normalized = format!("/{}", segments.join("/"));
+        if path.ends_with('/') && !segments.is_empty() {
+            normalized.push('/');
+        }
+        Ok(normalized)
+    }
+
+    /// Bucket names follow S3/Azure container naming rules
+    fn validate_bucket(bucket: &str) -> HsdsResult<()> {
+        let valid_chars = bucket
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.' | '_'));
+
+        if bucket.is_empty() || bucket.len() > 63 || !valid_chars {
+            return Err(HsdsError::invalid_param(format!(
+                "Invalid bucket name '{}': expected 1-63 lowercase letters, digits, '-', '.' or '_'",
+                bucket
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rebuild with a new path, keeping bucket and input style
+    fn with_path(&self, path: String) -> Self {
+        let repr = if self.repr.starts_with(HDF5_SCHEME) {
+            format!("{}{}", HDF5_SCHEME, path)
+        } else if let Some(bucket) = &self.bucket {
+            format!("{}{}", bucket, path)
+        } else {
+            path.clone()
+        };
+
+        Self {
+            repr,
+            bucket: self.bucket.clone(),
+            path,
+        }
+    }
+
+    /// Qualify this path with a bucket
+    pub fn with_bucket(&self, bucket: impl Into<String>) -> HsdsResult<Self> {
+        let bucket = bucket.into();
+        Self::validate_bucket(&bucket)?;
+        Ok(Self {
+            repr: format!("{}{}", bucket, self.path),
+            bucket: Some(bucket),
+            path: self.path.clone(),
+        })
+    }
+
+    /// The normalized string form, in the style it was written
+    pub fn as_str(&self) -> &str {
+        &self.repr
+    }
+
+    /// The path within the bucket (always starts with '/')
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The bucket, if the path is bucket-qualified
+    pub fn bucket(&self) -> Option<&str> {
+        self.bucket.as_deref()
+    }
+
+    /// Whether the path is written in folder form (trailing slash)
+    pub fn is_folder(&self) -> bool {
+        self.path.ends_with('/')
+    }
+
+    /// Kind suggested by the path itself, if any
+    ///
+    /// A trailing slash marks a folder and an HDF5 file extension (`.h5`,
+    /// `.hdf5`, `.he5`, `.nc`) marks a file. Other paths are ambiguous.
+    pub fn kind_hint(&self) -> Option<DomainKind> {
+        if self.is_folder() {
+            return Some(DomainKind::Folder);
+        }
+        let name = self.name()?.to_ascii_lowercase();
+        FILE_EXTENSIONS
+            .iter()
+            .any(|ext| name.ends_with(ext))
+            .then_some(DomainKind::File)
+    }
+
+    /// Check that the path can name a domain of the given kind
+    pub fn check_kind(&self, kind: DomainKind) -> HsdsResult<()> {
+        match (kind, self.kind_hint()) {
+            (DomainKind::Folder, Some(DomainKind::File)) => Err(HsdsError::invalid_param(format!(
+                "'{}' has a file extension and cannot be a folder; create it as a file domain", self
+            ))),
+            (DomainKind::File, Some(DomainKind::Folder)) => Err(HsdsError::invalid_param(format!(
+                "'{}' ends with '/' and names a folder; use create_folder or drop the trailing slash", self
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Last path segment (file or folder name), `None` for the root
+    pub fn name(&self) -> Option<&str> {
+        self.path.trim_end_matches('/').rsplit('/').next().filter(|s| !s.is_empty())
+    }
+
+    /// Parent folder of this path, `None` for the root
+    pub fn parent(&self) -> Option<DomainPath> {
+        let trimmed = self.path.trim_end_matches('/');
+        if trimmed.is_empty() {
+            return None;
+        }
+        let idx = trimmed.rfind('/')?;
+        Some(self.with_path(format!("{}/", &trimmed[..idx])))
+    }
+
+    /// Append a child name to this path
+    pub fn join(&self, child: &str) -> HsdsResult<DomainPath> {
+        let joined = format!("{}/{}", self.path.trim_end_matches('/'), child);
+        let path = Self::normalize_path(&joined, &joined)?;
+        Ok(self.with_path(path))
+    }
+
+    /// Whether an external link target names a domain relative to the linking one
+    ///
+    /// Targets starting with `./` or `../`, and bare names without any `/`
+    /// (e.g. `calib.h5`), are relative. Anything else is a domain path; a
+    /// relative target in a sub-folder must be written `./sub/file.h5`, since
+    /// `sub/file.h5` is the bucket-qualified form.
+    pub fn is_relative_target(target: &str) -> bool {
+        let target = target.trim();
+        matches!(target, "." | "..")
+            || target.starts_with("./")
+            || target.starts_with("../")
+            || (!target.is_empty() && !target.contains('/'))
+    }
+
+    /// Resolve an external link target against this domain
+    ///
+    /// As HDF5 resolves an external link's file name relative to the file
+    /// holding the link, a relative target (see
+    /// [`is_relative_target`](Self::is_relative_target)) is resolved against
+    /// the folder of this domain, or this domain itself if it is a folder,
+    /// keeping its bucket. Other targets are parsed as they are.
+    pub fn resolve(&self, target: &str) -> HsdsResult<DomainPath> {
+        let target = target.trim();
+        if !Self::is_relative_target(target) {
+            return Self::parse(target);
+        }
+
+        let base = if self.is_folder() { self.path.as_str() } else { self.parent_path() };
+        let mut segments: Vec<&str> = base.split('/').filter(|s| !s.is_empty()).collect();
+        for segment in target.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    if segments.pop().is_none() {
+                        return Err(HsdsError::invalid_param(format!(
+                            "External link target '{}' leaves the root folder from '{}'", target, self
+                        )));
+                    }
+                }
+                name => segments.push(name),
+            }
+        }
+
+        let mut path = format!("/{}", segments.join("/"));
+        if target.ends_with('/') && !segments.is_empty() {
+            path.push('/');
+        }
+        Ok(self.with_path(path))
+    }
+
+    /// Path of the folder holding this domain ("/" for top-level names)
+    fn parent_path(&self) -> &str {
+        let trimmed = self.path.trim_end_matches('/');
+        trimmed.rfind('/').map_or("/", |idx| &trimmed[..=idx])
+    }
+
+    /// Percent-encode the path for use in a URL query string
+    pub fn encode(&self) -> String {
+        urlencoding::encode(&self.path).into_owned()
+    }
+}
+
+impl Deref for DomainPath {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.repr
+    }
+}
+
+impl AsRef<str> for DomainPath {
+    fn as_ref(&self) -> &str {
+        &self.repr
+    }
+}
+
+impl fmt::Display for DomainPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.repr)
+    }
+}
+
+impl FromStr for DomainPath {
+    type Err = HsdsError;
+
+    fn from_str(s: &str) -> HsdsResult<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&str> for DomainPath {
+    type Error = HsdsError;
+
+    fn try_from(s: &str) -> HsdsResult<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for DomainPath {
+    type Error = HsdsError;
+
+    fn try_from(s: String) -> HsdsResult<Self> {
+        Self::parse(s)
+    }
+}
+
+impl From<DomainPath> for String {
+    fn from(path: DomainPath) -> String {
+        path.repr
+    }
+}
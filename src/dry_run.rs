@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+/// A mutating request that was recorded instead of sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedRequest {
+    /// HTTP method (e.g. "PUT", "DELETE")
+    pub method: String,
+    /// Request path, including any base path prefix
+    pub path: String,
+    /// Query string (including the domain parameter), if any
+    pub query: Option<String>,
+    /// Request body, if it is UTF-8 text
+    pub body: Option<String>,
+}
+
+impl std::fmt::Display for PlannedRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+        if let Some(query) = &self.query {
+            write!(f, "?{}", query)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shared record of the requests a dry-run client would have sent
+///
+/// Clones share the same record, so keep one clone to inspect the plan
+/// after passing another to [`HsdsClient::with_dry_run`](crate::HsdsClient::with_dry_run).
+#[derive(Debug, Clone, Default)]
+pub struct DryRunLog {
+    requests: Arc<Mutex<Vec<PlannedRequest>>>,
+}
+
+impl DryRunLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recorded requests, in the order they would have been sent
+    pub fn requests(&self) -> Vec<PlannedRequest> {
+        self.lock().clone()
+    }
+
+    /// Number of recorded requests
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no request has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Forget all recorded requests
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub(crate) fn record(&self, request: &reqwest::Request) -> PlannedRequest {
+        let planned = PlannedRequest {
+            method: request.method().to_string(),
+            path: request.url().path().to_string(),
+            query: request.url().query().map(String::from),
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .and_then(|b| std::str::from_utf8(b).ok())
+                .map(String::from),
+        };
+        self.lock().push(planned.clone());
+        planned
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<PlannedRequest>> {
+        // A panic while holding the lock cannot leave the Vec inconsistent
+        self.requests.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
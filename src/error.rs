@@ -35,8 +35,123 @@ pub enum HsdsError {
 
     #[error("Operation failed: {0}")]
     OperationFailed(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Read-only client: {0}")]
+    ReadOnly(String),
+
+    #[error("Dry run: {0}")]
+    DryRun(String),
+
+    #[error("Response too large: {size} bytes exceeds the limit of {limit} bytes")]
+    ResponseTooLarge { size: u64, limit: u64 },
+
+    #[error("Element {index:?} ({value}) could not be converted: {message}")]
+    Conversion {
+        /// Position of the element, one index per dimension (empty for a scalar)
+        index: Vec<u64>,
+        /// The element as received, truncated if long
+        value: String,
+        message: String,
+    },
+
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
+/// Stable, machine-readable kind of an [`HsdsError`]
+///
+/// Codes do not depend on server messages and keep their
+/// [string form](Self::as_str) across releases, so FFI layers, retry
+/// policies and localized messages can branch on them. New codes may be
+/// added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The server could not be reached or the connection failed
+    ConnectionFailed,
+    /// The request or the response body timed out
+    Timeout,
+    /// Other transport-level HTTP failure
+    Network,
+    /// Credentials were missing or rejected
+    AuthenticationFailed,
+    /// The user may not perform the operation
+    PermissionDenied,
+    /// A caller-supplied argument (name, selection, URL, ...) is invalid
+    InvalidParameter,
+    /// The domain does not exist
+    DomainNotFound,
+    /// The group, dataset, datatype, link or attribute does not exist
+    ObjectNotFound,
+    /// The request conflicts with the current state (exists, changed, ...)
+    Conflict,
+    /// The request body is larger than the server accepts
+    PayloadTooLarge,
+    /// The server asked the client to slow down
+    Throttled,
+    /// The server is temporarily unavailable
+    ServiceUnavailable,
+    /// The server failed to handle the request
+    ServerError,
+    /// Any other error status from the server
+    ApiError,
+    /// The response could not be parsed or lacks expected content
+    InvalidResponse,
+    /// The response exceeds the client's size limit
+    ResponseTooLarge,
+    /// A value could not be converted to the requested element type
+    ConversionFailed,
+    /// A multi-step operation could not complete
+    OperationFailed,
+    /// The client is read-only and the request would modify data
+    ReadOnly,
+    /// The request was only recorded by a dry-run client
+    DryRun,
+    /// The request was cancelled
+    Cancelled,
+}
+
+impl ErrorCode {
+    /// Snake-case name of the code, e.g. `"domain_not_found"`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ConnectionFailed => "connection_failed",
+            Self::Timeout => "timeout",
+            Self::Network => "network",
+            Self::AuthenticationFailed => "authentication_failed",
+            Self::PermissionDenied => "permission_denied",
+            Self::InvalidParameter => "invalid_parameter",
+            Self::DomainNotFound => "domain_not_found",
+            Self::ObjectNotFound => "object_not_found",
+            Self::Conflict => "conflict",
+            Self::PayloadTooLarge => "payload_too_large",
+            Self::Throttled => "throttled",
+            Self::ServiceUnavailable => "service_unavailable",
+            Self::ServerError => "server_error",
+            Self::ApiError => "api_error",
+            Self::InvalidResponse => "invalid_response",
+            Self::ResponseTooLarge => "response_too_large",
+            Self::ConversionFailed => "conversion_failed",
+            Self::OperationFailed => "operation_failed",
+            Self::ReadOnly => "read_only",
+            Self::DryRun => "dry_run",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Longest element text kept in a [`HsdsError::Conversion`]
+const MAX_CONVERSION_VALUE_CHARS: usize = 80;
+
 /// Result type for HSDS operations
 pub type HsdsResult<T> = Result<T, HsdsError>;
 
@@ -58,4 +173,62 @@ impl HsdsError {
     pub fn invalid_param(message: impl Into<String>) -> Self {
         Self::InvalidParameter(message.into())
     }
+
+    /// Create a conversion error for the element at `index`
+    pub fn conversion(index: Vec<u64>, value: impl std::fmt::Display, message: impl std::fmt::Display) -> Self {
+        let mut value = value.to_string();
+        if let Some((cut, _)) = value.char_indices().nth(MAX_CONVERSION_VALUE_CHARS) {
+            value.truncate(cut);
+            value.push_str("...");
+        }
+        Self::Conversion { index, value, message: message.to_string() }
+    }
+
+    /// Stable code of the error, independent of its message
+    ///
+    /// Server errors are classified by status: `401` is
+    /// [`ErrorCode::AuthenticationFailed`], `413`
+    /// [`ErrorCode::PayloadTooLarge`], `429` [`ErrorCode::Throttled`] and so on.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::Http(e) if e.is_timeout() => ErrorCode::Timeout,
+            Self::Http(e) if e.is_connect() => ErrorCode::ConnectionFailed,
+            Self::Http(_) => ErrorCode::Network,
+            Self::Json(_) => ErrorCode::InvalidResponse,
+            Self::Url(_) => ErrorCode::InvalidParameter,
+            Self::Auth(_) => ErrorCode::AuthenticationFailed,
+            Self::Api { status, .. } => match status {
+                401 => ErrorCode::AuthenticationFailed,
+                403 => ErrorCode::PermissionDenied,
+                400 => ErrorCode::InvalidParameter,
+                404 | 410 => ErrorCode::ObjectNotFound,
+                409 | 412 => ErrorCode::Conflict,
+                413 => ErrorCode::PayloadTooLarge,
+                429 => ErrorCode::Throttled,
+                503 => ErrorCode::ServiceUnavailable,
+                500..=599 => ErrorCode::ServerError,
+                _ => ErrorCode::ApiError,
+            },
+            Self::InvalidParameter(_) => ErrorCode::InvalidParameter,
+            Self::DomainNotFound(_) => ErrorCode::DomainNotFound,
+            Self::ObjectNotFound(_) => ErrorCode::ObjectNotFound,
+            Self::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            Self::InvalidResponse(_) => ErrorCode::InvalidResponse,
+            Self::OperationFailed(_) => ErrorCode::OperationFailed,
+            Self::Conflict(_) => ErrorCode::Conflict,
+            Self::ReadOnly(_) => ErrorCode::ReadOnly,
+            Self::DryRun(_) => ErrorCode::DryRun,
+            Self::ResponseTooLarge { .. } => ErrorCode::ResponseTooLarge,
+            Self::Conversion { .. } => ErrorCode::ConversionFailed,
+            Self::Cancelled => ErrorCode::Cancelled,
+        }
+    }
+
+    /// Index of the element that failed to convert, for conversion errors
+    pub fn element_index(&self) -> Option<&[u64]> {
+        match self {
+            Self::Conversion { index, .. } => Some(index),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,277 @@
+use crate::{
+    apis::dataset::value_read_request,
+    client::HsdsClient,
+    decoder::ResponseDecoder,
+    error::{HsdsError, HsdsResult},
+    models::{Dataset, FilterInfo, Group, Shape, StorageInfo},
+};
+use bytes::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+use std::ops::Range;
+
+/// Elements per region when prefetching a dataset without a chunk layout
+const PREFETCH_REGION_ELEMENTS: u64 = 1 << 20;
+
+/// Decoded dataset values with the transformations undone to produce them
+#[derive(Debug, Clone)]
+pub struct DecodedValues<T> {
+    pub values: T,
+    /// `Content-Encoding` the server applied to the response, `None` for identity
+    pub content_encoding: Option<String>,
+}
+
+/// One region of a sequential scan, see [`DatasetHandle::chunks_prefetched`]
+#[derive(Debug, Clone)]
+pub struct PrefetchedRegion {
+    /// Selection the region was read with
+    pub select: String,
+    /// Rows (along the first dimension) the region covers
+    pub rows: Range<u64>,
+    /// Values in HSDS binary format (little-endian, row-major)
+    pub data: Bytes,
+}
+
+/// A group opened in a domain, with its metadata cached
+///
+/// Obtained from [`GroupApi::open`](crate::GroupApi::open). The cached
+/// [`Group`] (including its attribute and link counts) is fetched once;
+/// call [`refresh`](Self::refresh) to pick up changes made since.
+#[derive(Clone)]
+pub struct GroupHandle<'a> {
+    client: &'a HsdsClient,
+    domain: String,
+    info: Group,
+}
+
+impl<'a> GroupHandle<'a> {
+    pub(crate) fn new(client: &'a HsdsClient, domain: &str, info: Group) -> Self {
+        Self {
+            client,
+            domain: domain.to_string(),
+            info,
+        }
+    }
+
+    /// UUID of the group
+    pub fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    /// Domain path the group lives in
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Cached group metadata
+    pub fn info(&self) -> &Group {
+        &self.info
+    }
+
+    /// Number of attributes, as of the last fetch (`None` if not reported)
+    pub fn attribute_count(&self) -> Option<u32> {
+        self.info.attribute_count
+    }
+
+    /// Number of links, as of the last fetch (`None` if not reported)
+    pub fn link_count(&self) -> Option<u32> {
+        self.info.link_count
+    }
+
+    /// Whether the group has attributes; `true` when the count is unknown
+    pub fn has_attributes(&self) -> bool {
+        self.info.attribute_count != Some(0)
+    }
+
+    /// Whether the group has links; `true` when the count is unknown
+    pub fn has_links(&self) -> bool {
+        self.info.link_count != Some(0)
+    }
+
+    /// Re-fetch the group metadata
+    pub async fn refresh(&mut self) -> HsdsResult<()> {
+        self.info = self.client.groups().get_group(&self.domain, &self.info.id, None).await?;
+        Ok(())
+    }
+}
+
+/// A dataset opened in a domain, with its metadata cached
+///
+/// Obtained from [`DatasetApi::open`](crate::DatasetApi::open). The cached
+/// [`Dataset`] is fetched once; call [`refresh`](Self::refresh) to pick up
+/// changes made by other clients. Handles opened through a read-only client
+/// (see [`HsdsClient::with_read_only`]) reject writes client-side.
+#[derive(Clone)]
+pub struct DatasetHandle<'a> {
+    client: &'a HsdsClient,
+    domain: String,
+    info: Dataset,
+}
+
+impl<'a> DatasetHandle<'a> {
+    pub(crate) fn new(client: &'a HsdsClient, domain: &str, info: Dataset) -> Self {
+        Self {
+            client,
+            domain: domain.to_string(),
+            info,
+        }
+    }
+
+    /// UUID of the dataset
+    pub fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    /// Domain path the dataset lives in
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Whether the handle comes from a read-only client
+    pub fn is_read_only(&self) -> bool {
+        self.client.is_read_only()
+    }
+
+    /// Cached dataset metadata
+    pub fn info(&self) -> &Dataset {
+        &self.info
+    }
+
+    /// Whether the dataset holds no values (null dataspace or a zero-length dimension)
+    pub fn is_empty(&self) -> bool {
+        self.info.shape.as_ref().is_some_and(Shape::is_empty)
+    }
+
+    /// Number of attributes, as of the last fetch of the metadata
+    ///
+    /// `None` if the server did not report it. Call
+    /// [`refresh`](Self::refresh) to pick up attributes added since.
+    pub fn attribute_count(&self) -> Option<u32> {
+        self.info.attribute_count
+    }
+
+    /// Whether the dataset has attributes, without listing them
+    ///
+    /// `true` when the count is unknown, so callers fall back to listing.
+    pub fn has_attributes(&self) -> bool {
+        self.info.attribute_count != Some(0)
+    }
+
+    /// Storage filters the server applies to the dataset's chunks
+    ///
+    /// These are undone by the server before values are returned; they are
+    /// exposed so callers know how the data is stored.
+    pub fn filters(&self) -> Vec<FilterInfo> {
+        self.info.filters()
+    }
+
+    /// Re-fetch the dataset metadata
+    pub async fn refresh(&mut self) -> HsdsResult<()> {
+        self.info = self.client.datasets().get_dataset(&self.domain, &self.info.id).await?;
+        Ok(())
+    }
+
+    /// Read values with a decoder, inflating compressed responses
+    ///
+    /// # Arguments
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `decoder` - Response decoder
+    pub async fn read_with<D>(&self, select: Option<&str>, decoder: D) -> HsdsResult<DecodedValues<D::Output>>
+    where
+        D: ResponseDecoder,
+    {
+        let req = value_read_request(self.client, &self.domain, &self.info.id, select, None, None).await?;
+        let (values, content_encoding) = self.client.execute_decoded(req, decoder).await?;
+        Ok(DecodedValues { values, content_encoding })
+    }
+
+    /// Scan a selection region by region, keeping `n` reads in flight ahead of the consumer
+    ///
+    /// The selection is split along its first dimension at the dataset's
+    /// chunk boundaries (blocks of about a million elements without a chunk
+    /// layout), and up to `n` regions are fetched concurrently while earlier
+    /// ones are processed. Regions are yielded in order. The first part of
+    /// `select` must be a row range (`"start:stop"`, a single row or empty);
+    /// the other parts apply to every region. Dropping the stream cancels
+    /// the reads in flight.
+    ///
+    /// # Arguments
+    /// * `select` - Optional selection string; whole dataset if `None`
+    /// * `n` - Number of regions read ahead (at least 1)
+    pub fn chunks_prefetched(
+        &self,
+        select: Option<&str>,
+        n: usize,
+    ) -> impl Stream<Item = HsdsResult<PrefetchedRegion>> + 'a {
+        let regions = match self.scan_regions(select) {
+            Ok(regions) => regions.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        };
+        let client = self.client;
+        let (domain, dataset_id) = (self.domain.clone(), self.info.id.clone());
+        stream::iter(regions)
+            .map(move |region| {
+                let (domain, dataset_id) = (domain.clone(), dataset_id.clone());
+                async move {
+                    let (select, rows) = region?;
+                    let data = client.datasets()
+                        .read_dataset_values(&domain, &dataset_id, Some(&select), None, None)
+                        .await?;
+                    Ok(PrefetchedRegion { select, rows, data })
+                }
+            })
+            .buffered(n.max(1))
+    }
+
+    /// Selections and row ranges of a prefetched scan
+    fn scan_regions(&self, select: Option<&str>) -> HsdsResult<Vec<(String, Range<u64>)>> {
+        let dims = self.info.shape.as_ref().and_then(|s| s.dims.clone()).unwrap_or_default();
+        let Some((&len, row_dims)) = dims.split_first() else {
+            return Err(HsdsError::invalid_param(format!("Dataset {} has no rows to scan", self.info.id)));
+        };
+        let parts: Vec<String> = match select {
+            None => vec![String::new(); dims.len()],
+            Some(selection) => {
+                let inner = selection.trim().trim_start_matches('[').trim_end_matches(']');
+                inner.split(',').map(|p| p.trim().to_string()).collect()
+            }
+        };
+        if parts.len() != dims.len() {
+            return Err(HsdsError::invalid_param(format!(
+                "Selection has {} dimensions; the dataset has {}", parts.len(), dims.len()
+            )));
+        }
+
+        let invalid = || HsdsError::invalid_param(format!("Unsupported row range '{}' for a scan", parts[0]));
+        let bound = |s: &str, default: u64| -> HsdsResult<u64> {
+            if s.trim().is_empty() { Ok(default) } else { s.trim().parse().map_err(|_| invalid()) }
+        };
+        let (start, stop) = match parts[0].split(':').collect::<Vec<_>>().as_slice() {
+            [row] if row.trim().is_empty() => (0, len),
+            [row] => {
+                let row = bound(row, 0)?;
+                (row, row + 1)
+            }
+            [start, stop] => (bound(start, 0)?, bound(stop, len)?),
+            _ => return Err(invalid()),
+        };
+
+        let chunk_rows = StorageInfo::from(&self.info)
+            .chunk_dims
+            .and_then(|chunks| chunks.first().copied())
+            .filter(|&rows| rows > 0)
+            .unwrap_or_else(|| (PREFETCH_REGION_ELEMENTS / row_dims.iter().product::<u64>().max(1)).max(1));
+        let trailing: String = parts[1..]
+            .iter()
+            .map(|p| if p.is_empty() { ",:".to_string() } else { format!(",{}", p) })
+            .collect();
+
+        let mut regions = Vec::new();
+        let mut first = start;
+        while first < stop.min(len) {
+            let last = ((first / chunk_rows + 1) * chunk_rows).min(stop).min(len);
+            regions.push((format!("[{}:{}{}]", first, last, trailing), first..last));
+            first = last;
+        }
+        Ok(regions)
+    }
+}
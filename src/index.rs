@@ -0,0 +1,282 @@
+//! Compact catalogs of domain contents for search and browse tools
+//!
+//! A [`DomainCatalog`] lists every object reachable from the root group by
+//! path, with its shape, datatype and a selection of attributes. Catalogs
+//! serialize to JSON, so tools can load one at startup instead of walking a
+//! large domain, and [`CatalogBuilder::refresh`] brings a stale catalog up
+//! to date by re-reading only objects whose `lastModified` changed.
+
+use crate::{
+    apis::{collection_for_id, search::object_attributes},
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::{LinkClass, LinkOrder},
+};
+use futures_util::TryStreamExt;
+use log::{debug, info};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// Kind of a cataloged object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogKind {
+    Group,
+    Dataset,
+    Datatype,
+}
+
+/// One object of a catalog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub kind: CatalogKind,
+    /// Dataset dimensions (empty for scalars, `None` for null dataspaces)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<Vec<u64>>,
+    /// Datatype: the base type name for numeric types, the full JSON otherwise
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub dtype: Option<Value>,
+    /// Values of the selected attributes the object has
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, Value>,
+    #[serde(rename = "lastModified", default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<f64>,
+}
+
+/// Catalog of a domain's objects by link path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomainCatalog {
+    pub domain: String,
+    /// Names of the attributes recorded for each object
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    /// Objects by path ("/" is the root group)
+    pub entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl DomainCatalog {
+    /// Parse a catalog from a JSON string
+    pub fn from_json_str(json: &str) -> HsdsResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize the catalog to a JSON string
+    pub fn to_json_string(&self) -> HsdsResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Load a catalog from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> HsdsResult<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            HsdsError::invalid_param(format!("Failed to read catalog {}: {}", path.display(), e))
+        })?;
+        Self::from_json_str(&json)
+    }
+
+    /// Save the catalog to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> HsdsResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json_string()?).map_err(|e| {
+            HsdsError::OperationFailed(format!("Failed to write catalog {}: {}", path.display(), e))
+        })
+    }
+
+    /// Entry at a path
+    pub fn get(&self, path: &str) -> Option<&CatalogEntry> {
+        self.entries.get(path)
+    }
+
+    /// Dataset entries with their paths
+    pub fn datasets(&self) -> impl Iterator<Item = (&str, &CatalogEntry)> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.kind == CatalogKind::Dataset)
+            .map(|(path, entry)| (path.as_str(), entry))
+    }
+}
+
+/// Work done by [`CatalogBuilder::refresh`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    /// Objects checked for changes
+    pub checked: usize,
+    /// Entries added or re-read because they changed
+    pub updated: usize,
+    /// Entries whose path no longer exists
+    pub removed: usize,
+}
+
+/// Builds and refreshes [`DomainCatalog`]s
+pub struct CatalogBuilder<'a> {
+    client: &'a HsdsClient,
+    attributes: Vec<String>,
+}
+
+impl<'a> CatalogBuilder<'a> {
+    pub fn new(client: &'a HsdsClient) -> Self {
+        Self { client, attributes: Vec::new() }
+    }
+
+    /// Record these attributes of every object (none by default)
+    pub fn with_attributes<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.attributes = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Catalog a domain by walking it from the root group
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path
+    pub async fn build(&self, domain: &str) -> HsdsResult<DomainCatalog> {
+        let mut catalog = DomainCatalog {
+            domain: domain.to_string(),
+            attributes: self.attributes.clone(),
+            entries: BTreeMap::new(),
+        };
+        self.refresh(&mut catalog).await?;
+        Ok(catalog)
+    }
+
+    /// Bring a catalog up to date with its domain
+    ///
+    /// Every object is fetched to compare its `lastModified`, but links and
+    /// attributes are only re-read for objects that changed: an unchanged
+    /// group keeps its cataloged children. The attribute selection stored
+    /// in the catalog is used, not this builder's.
+    ///
+    /// # Arguments
+    /// * `catalog` - Catalog to update in place
+    pub async fn refresh(&self, catalog: &mut DomainCatalog) -> HsdsResult<RefreshStats> {
+        info!("Refreshing catalog of domain: {}", catalog.domain);
+        let domain = catalog.domain.clone();
+        let root = self.client.domains().get_domain(&domain).await?.root.ok_or_else(|| {
+            HsdsError::invalid_param(format!("Domain '{}' has no root group (is it a folder?)", domain))
+        })?;
+
+        let mut stats = RefreshStats::default();
+        let mut entries = BTreeMap::new();
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut pending = VecDeque::from([("/".to_string(), root)]);
+
+        while let Some((path, id)) = pending.pop_front() {
+            stats.checked += 1;
+            let info = match self.get_object(&domain, &id).await {
+                Ok(info) => info,
+                // Deleted since its link was listed (or since the last refresh)
+                Err(HsdsError::ObjectNotFound(_)) if path != "/" => continue,
+                Err(e) => return Err(e),
+            };
+            let last_modified = info.get("lastModified").and_then(Value::as_f64);
+            let previous = catalog.entries.get(&path).filter(|e| e.id == id);
+            let unchanged = previous.is_some_and(|e| last_modified.is_some() && e.last_modified == last_modified);
+
+            let entry = match previous {
+                Some(previous) if unchanged => previous.clone(),
+                _ => {
+                    stats.updated += 1;
+                    self.entry(&domain, &id, &info, &catalog.attributes).await?
+                }
+            };
+
+            if entry.kind == CatalogKind::Group && visited.insert(id.clone()) {
+                let children: Vec<(String, String)> = if unchanged {
+                    child_entries(&catalog.entries, &path)
+                } else {
+                    self.child_links(&domain, &id, &path).await?
+                };
+                pending.extend(children);
+            }
+            entries.insert(path, entry);
+        }
+
+        stats.removed = catalog.entries.keys().filter(|path| !entries.contains_key(*path)).count();
+        catalog.entries = entries;
+        debug!("Catalog of {}: {:?}", domain, stats);
+        Ok(stats)
+    }
+
+    async fn get_object(&self, domain: &str, id: &str) -> HsdsResult<Value> {
+        let path = format!("/{}/{}", collection_for_id(id)?, id);
+        let mut req = self.client.request(Method::GET, &path).await?;
+        req = HsdsClient::with_domain(req, domain)?;
+        self.client.execute(req).await
+    }
+
+    /// Catalog entry for an object from its description
+    async fn entry(&self, domain: &str, id: &str, info: &Value, selected: &[String]) -> HsdsResult<CatalogEntry> {
+        let kind = match collection_for_id(id)? {
+            "groups" => CatalogKind::Group,
+            "datasets" => CatalogKind::Dataset,
+            _ => CatalogKind::Datatype,
+        };
+        let null = info.get("shape").and_then(|s| s.get("class")).and_then(Value::as_str) == Some("H5S_NULL");
+        let shape = match kind {
+            CatalogKind::Dataset if !null => Some(
+                info.get("shape")
+                    .and_then(|s| s.get("dims"))
+                    .and_then(|d| serde_json::from_value(d.clone()).ok())
+                    .unwrap_or_default(),
+            ),
+            _ => None,
+        };
+        let dtype = info.get("type").map(|t| match t.get("base") {
+            Some(Value::String(base)) if matches!(t.get("class").and_then(Value::as_str), Some("H5T_INTEGER" | "H5T_FLOAT")) => {
+                Value::String(base.clone())
+            }
+            _ => t.clone(),
+        });
+
+        let has_attributes = info.get("attributeCount").and_then(Value::as_u64) != Some(0);
+        let attributes = if selected.is_empty() || !has_attributes {
+            BTreeMap::new()
+        } else {
+            object_attributes(self.client, domain, id)
+                .await?
+                .into_iter()
+                .filter(|(name, _)| selected.contains(name))
+                .collect()
+        };
+
+        Ok(CatalogEntry {
+            id: id.to_string(),
+            kind,
+            shape,
+            dtype,
+            attributes,
+            last_modified: info.get("lastModified").and_then(Value::as_f64),
+        })
+    }
+
+    /// Hard-linked children of a group as (path, id)
+    async fn child_links(&self, domain: &str, group_id: &str, group_path: &str) -> HsdsResult<Vec<(String, String)>> {
+        let links: Vec<_> = self.client.links()
+            .links_stream(domain, group_id, LinkOrder::Name)
+            .try_collect()
+            .await?;
+        Ok(links
+            .into_iter()
+            .filter(|link| !matches!(link.class, Some(LinkClass::Soft) | Some(LinkClass::External)))
+            .filter_map(|link| Some((child_path(group_path, &link.title), link.id?)))
+            .collect())
+    }
+}
+
+fn child_path(group_path: &str, name: &str) -> String {
+    format!("{}/{}", group_path.trim_end_matches('/'), name)
+}
+
+/// Cataloged direct children of a group path as (path, id)
+fn child_entries(entries: &BTreeMap<String, CatalogEntry>, group_path: &str) -> Vec<(String, String)> {
+    let prefix = child_path(group_path, "");
+    entries
+        .range(prefix.clone()..)
+        .take_while(|(path, _)| path.starts_with(&prefix))
+        .filter(|(path, _)| path.len() > prefix.len() && !path[prefix.len()..].contains('/'))
+        .map(|(path, entry)| (path.clone(), entry.id.clone()))
+        .collect()
+}
@@ -0,0 +1,254 @@
+use crate::{
+    apis::search::object_attributes,
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::StorageInfo,
+};
+use log::debug;
+use serde_json::json;
+
+/// Attribute holding the row boundaries of an ingest plan as `[b0, b1, ..., bN]`
+///
+/// Partition `i` covers rows `b[i]..b[i+1]` along the first dimension.
+pub const INGEST_PLAN_ATTRIBUTE: &str = "__ingest_plan__";
+
+/// Prefix of the attributes marking partitions complete (`__ingest_done_<i>__`)
+///
+/// Each worker only writes its own attribute, so completions from concurrent
+/// processes never overwrite each other.
+pub const INGEST_DONE_PREFIX: &str = "__ingest_done_";
+
+/// Rows of a dataset assigned to one ingest worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngestPartition {
+    /// Position of the partition in the plan
+    pub index: usize,
+    /// First row along the first dimension
+    pub start_row: u64,
+    /// Row after the last one
+    pub stop_row: u64,
+    /// Rank of the dataset
+    rank: usize,
+}
+
+impl IngestPartition {
+    /// Number of rows in the partition
+    pub fn rows(&self) -> u64 {
+        self.stop_row - self.start_row
+    }
+
+    /// Selection covering the partition, e.g. "[0:128,:]"
+    pub fn selection(&self) -> String {
+        format!("[{}:{}{}]", self.start_row, self.stop_row, ",:".repeat(self.rank.saturating_sub(1)))
+    }
+}
+
+/// Completion state of an ingest plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngestStatus {
+    /// Number of partitions in the plan
+    pub partitions: usize,
+    /// Partitions marked complete, in ascending order
+    pub completed: Vec<usize>,
+}
+
+impl IngestStatus {
+    /// Partitions not marked complete yet, in ascending order
+    pub fn pending(&self) -> Vec<usize> {
+        (0..self.partitions).filter(|i| !self.completed.contains(i)).collect()
+    }
+
+    /// Whether every partition is marked complete
+    pub fn is_complete(&self) -> bool {
+        self.completed.len() == self.partitions
+    }
+}
+
+/// Split of a dataset into disjoint row ranges for parallel ingest
+///
+/// A coordinator calls [`plan`](Self::plan) once, which divides the first
+/// dimension into at most `workers` ranges of whole chunk rows, so no two
+/// workers ever write to the same chunk, and stores the boundaries in
+/// [`INGEST_PLAN_ATTRIBUTE`]. Workers in other processes call
+/// [`open`](Self::open), write the rows of their [`partition`](Self::partition)
+/// (e.g. with a [`BufferedWriter`](crate::BufferedWriter) or
+/// [`write_region_raw`](crate::DatasetApi::write_region_raw) on its
+/// selection) and then [`mark_complete`](Self::mark_complete) it. Anyone can
+/// follow progress with [`status`](Self::status).
+pub struct ParallelIngest<'a> {
+    client: &'a HsdsClient,
+    domain: String,
+    dataset_id: String,
+    partitions: Vec<IngestPartition>,
+}
+
+impl<'a> ParallelIngest<'a> {
+    /// Partition a dataset for `workers` workers and store the plan
+    ///
+    /// Fewer partitions are made when the first dimension has fewer chunk
+    /// rows than workers. Completion markers of an earlier plan are removed.
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    /// * `workers` - Number of workers to split the rows between
+    pub async fn plan(
+        client: &'a HsdsClient,
+        domain: &str,
+        dataset_id: &str,
+        workers: usize,
+    ) -> HsdsResult<ParallelIngest<'a>> {
+        if workers == 0 {
+            return Err(HsdsError::invalid_param("Parallel ingest needs at least one worker"));
+        }
+        let dataset = client.datasets().get_dataset(domain, dataset_id).await?;
+        let storage = StorageInfo::from(&dataset);
+        let dims = storage.shape.unwrap_or_default();
+        let Some(&rows) = dims.first().filter(|&&rows| rows > 0) else {
+            return Err(HsdsError::invalid_param(format!("Dataset {} has no rows to partition", dataset_id)));
+        };
+        let chunk_rows = storage.chunk_dims.as_ref().and_then(|c| c.first().copied()).unwrap_or(1);
+        let boundaries = partition_rows(rows, chunk_rows, workers);
+
+        let ingest = Self::from_boundaries(client, domain, dataset_id, dims.len(), &boundaries);
+        ingest.clear_markers().await?;
+        let plan = json!({
+            "type": "H5T_STD_U64LE",
+            "shape": [boundaries.len()],
+            "value": boundaries,
+        });
+        client.attributes()
+            .replace_attribute_raw(domain, "datasets", dataset_id, INGEST_PLAN_ATTRIBUTE, plan)
+            .await?;
+        debug!("Planned ingest of {} rows of {} in {} partitions", rows, dataset_id, ingest.partitions.len());
+        Ok(ingest)
+    }
+
+    /// Open the plan stored on a dataset by [`plan`](Self::plan)
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    pub async fn open(client: &'a HsdsClient, domain: &str, dataset_id: &str) -> HsdsResult<ParallelIngest<'a>> {
+        let dataset = client.datasets().get_dataset(domain, dataset_id).await?;
+        let rank = dataset.shape.and_then(|s| s.dims).map(|d| d.len()).unwrap_or_default();
+        let attr = client.attributes()
+            .get_attribute(domain, "datasets", dataset_id, INGEST_PLAN_ATTRIBUTE)
+            .await?;
+        let boundaries: Option<Vec<u64>> = attr.get("value").and_then(|v| serde_json::from_value(v.clone()).ok());
+
+        match boundaries {
+            Some(b) if b.len() >= 2 && b.windows(2).all(|w| w[0] < w[1]) => {
+                Ok(Self::from_boundaries(client, domain, dataset_id, rank, &b))
+            }
+            _ => Err(HsdsError::InvalidResponse(format!(
+                "Invalid {} attribute on dataset {}", INGEST_PLAN_ATTRIBUTE, dataset_id
+            ))),
+        }
+    }
+
+    fn from_boundaries(client: &'a HsdsClient, domain: &str, dataset_id: &str, rank: usize, boundaries: &[u64]) -> Self {
+        let partitions = boundaries
+            .windows(2)
+            .enumerate()
+            .map(|(index, w)| IngestPartition { index, start_row: w[0], stop_row: w[1], rank })
+            .collect();
+        Self {
+            client,
+            domain: domain.to_string(),
+            dataset_id: dataset_id.to_string(),
+            partitions,
+        }
+    }
+
+    /// UUID of the underlying dataset
+    pub fn dataset_id(&self) -> &str {
+        &self.dataset_id
+    }
+
+    /// All partitions, in row order
+    pub fn partitions(&self) -> &[IngestPartition] {
+        &self.partitions
+    }
+
+    /// Partition assigned to worker `index`, `None` if the plan has fewer partitions
+    pub fn partition(&self, index: usize) -> Option<&IngestPartition> {
+        self.partitions.get(index)
+    }
+
+    /// Record that all rows of a partition have been written
+    ///
+    /// # Arguments
+    /// * `index` - Partition index
+    pub async fn mark_complete(&self, index: usize) -> HsdsResult<()> {
+        let partition = self.partition(index).ok_or_else(|| {
+            HsdsError::invalid_param(format!("Ingest plan has no partition {} (of {})", index, self.partitions.len()))
+        })?;
+        let marker = json!({
+            "type": "H5T_STD_U64LE",
+            "shape": [2],
+            "value": [partition.start_row, partition.stop_row],
+        });
+        self.client.attributes()
+            .replace_attribute_raw(&self.domain, "datasets", &self.dataset_id, &done_attribute(index), marker)
+            .await?;
+        debug!("Ingest partition {} of {} complete", index, self.dataset_id);
+        Ok(())
+    }
+
+    /// Fetch which partitions have been marked complete
+    pub async fn status(&self) -> HsdsResult<IngestStatus> {
+        let attributes = object_attributes(self.client, &self.domain, &self.dataset_id).await?;
+        let completed = (0..self.partitions.len())
+            .filter(|&i| attributes.contains_key(&done_attribute(i)))
+            .collect();
+        Ok(IngestStatus { partitions: self.partitions.len(), completed })
+    }
+
+    /// Remove the plan and completion markers from the dataset
+    pub async fn finish(self) -> HsdsResult<()> {
+        self.clear_markers().await?;
+        self.client.attributes()
+            .delete_attribute(&self.domain, "datasets", &self.dataset_id, INGEST_PLAN_ATTRIBUTE)
+            .await
+            .or_else(ignore_missing)
+    }
+
+    /// Delete completion markers left on the dataset (of any earlier plan)
+    async fn clear_markers(&self) -> HsdsResult<()> {
+        let attributes = object_attributes(self.client, &self.domain, &self.dataset_id).await?;
+        for name in attributes.keys().filter(|name| name.starts_with(INGEST_DONE_PREFIX)) {
+            self.client.attributes()
+                .delete_attribute(&self.domain, "datasets", &self.dataset_id, name)
+                .await
+                .or_else(ignore_missing)?;
+        }
+        Ok(())
+    }
+}
+
+fn ignore_missing(e: HsdsError) -> HsdsResult<()> {
+    match e {
+        HsdsError::ObjectNotFound(_) => Ok(()),
+        e => Err(e),
+    }
+}
+
+fn done_attribute(index: usize) -> String {
+    format!("{}{}__", INGEST_DONE_PREFIX, index)
+}
+
+/// Row boundaries splitting `rows` into at most `workers` ranges of whole chunk rows
+///
+/// Chunk rows are spread as evenly as possible; the last range ends at `rows`.
+pub(crate) fn partition_rows(rows: u64, chunk_rows: u64, workers: usize) -> Vec<u64> {
+    let chunk_rows = chunk_rows.max(1);
+    let grid = rows.div_ceil(chunk_rows);
+    let parts = grid.min(workers as u64).max(1);
+    let (per_part, extra) = (grid / parts, grid % parts);
+    (0..=parts)
+        .map(|i| ((i * per_part + i.min(extra)) * chunk_rows).min(rows))
+        .collect()
+}
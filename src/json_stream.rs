@@ -0,0 +1,255 @@
+//! Incremental parsing of JSON value responses
+//!
+//! A value read returns `{"value": [[...], ...]}`, which for a large
+//! selection is a large body. [`ValueArrayParser`] is fed the body chunk by
+//! chunk and deserializes each element of the `value` array as soon as it is
+//! complete, so the raw body and a `serde_json::Value` tree of it never have
+//! to be held at the same time as the typed result.
+
+use crate::{
+    error::{HsdsError, HsdsResult},
+    values::SpecialFloats,
+};
+use serde::de::{value::F64Deserializer, DeserializeOwned, IntoDeserializer};
+
+/// Top-level key holding the values of a value response
+const VALUE_KEY: &[u8] = b"value";
+
+/// Push parser for the `value` array of a value response
+///
+/// Elements are the entries `rank` arrays deep (the value itself for a
+/// scalar dataset), so compound elements, which HSDS writes as arrays, are
+/// deserialized whole. Other top-level members (e.g. `hrefs`) are skipped.
+pub(crate) struct ValueArrayParser<T> {
+    rank: usize,
+    /// Containers open in the document (`[` or `{`)
+    stack: Vec<u8>,
+    in_string: bool,
+    escaped: bool,
+    /// Next string of the top-level object is a key
+    at_key: bool,
+    key: Vec<u8>,
+    /// The last top-level key read was `value`
+    at_value_key: bool,
+    capturing: bool,
+    captured: bool,
+    /// Bytes of the element being read
+    element: Vec<u8>,
+    in_element: bool,
+    /// Containers open inside the element
+    element_depth: usize,
+    /// Entries of the array open at each level of the `value` array
+    counts: Vec<u64>,
+    shape: Vec<Option<u64>>,
+    values: Vec<T>,
+    special_floats: SpecialFloats,
+}
+
+impl<T: DeserializeOwned> ValueArrayParser<T> {
+    pub(crate) fn new(rank: usize) -> Self {
+        Self {
+            rank,
+            stack: Vec::new(),
+            in_string: false,
+            escaped: false,
+            at_key: false,
+            key: Vec::new(),
+            at_value_key: false,
+            capturing: false,
+            captured: false,
+            element: Vec::new(),
+            in_element: false,
+            element_depth: 0,
+            counts: vec![0; rank],
+            shape: vec![None; rank],
+            values: Vec::new(),
+            special_floats: SpecialFloats::default(),
+        }
+    }
+
+    /// Read elements `T` rejects as special floats where they are accepted
+    pub(crate) fn with_special_floats(mut self, special_floats: SpecialFloats) -> Self {
+        self.special_floats = special_floats;
+        self
+    }
+
+    /// Parse the next chunk of the body
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> HsdsResult<()> {
+        chunk.iter().try_for_each(|&b| self.byte(b))
+    }
+
+    /// Finish the body, returning the values (row-major) and the shape of the `value` array
+    ///
+    /// Dimensions never entered (below an empty one) are reported as 0.
+    pub(crate) fn finish(mut self) -> HsdsResult<(Vec<T>, Vec<u64>)> {
+        if self.in_element && self.element_depth == 0 && !self.in_string {
+            self.finish_element()?;
+        }
+        if !self.captured || self.capturing || self.in_element {
+            return Err(HsdsError::InvalidResponse(
+                "Value response ended without a complete 'value' member".to_string(),
+            ));
+        }
+        Ok((self.values, self.shape.into_iter().map(|d| d.unwrap_or(0)).collect()))
+    }
+
+    /// Arrays open inside the `value` member
+    fn level(&self) -> usize {
+        self.stack.len() - 1
+    }
+
+    fn byte(&mut self, b: u8) -> HsdsResult<()> {
+        if self.in_element {
+            return self.element_byte(b);
+        }
+        if self.in_string {
+            let top_key = self.stack.len() == 1 && self.at_key;
+            if self.escaped {
+                self.escaped = false;
+            } else if b == b'\\' {
+                self.escaped = true;
+            } else if b == b'"' {
+                self.in_string = false;
+                if top_key {
+                    self.at_value_key = self.key == VALUE_KEY;
+                    self.at_key = false;
+                }
+                return Ok(());
+            }
+            if top_key {
+                self.key.push(b);
+            }
+            return Ok(());
+        }
+        if b.is_ascii_whitespace() {
+            return Ok(());
+        }
+
+        if self.capturing && self.level() == self.rank && !matches!(b, b',' | b']') {
+            if self.rank > 0 {
+                self.counts[self.rank - 1] += 1;
+            }
+            self.in_element = true;
+            return self.element_byte(b);
+        }
+        if self.capturing {
+            let expected = match self.level() {
+                0 => b == b'[',
+                _ => matches!(b, b'[' | b',' | b']'),
+            };
+            if !expected {
+                return Err(HsdsError::InvalidResponse(format!(
+                    "Expected a nested array of rank {} in 'value', found '{}'", self.rank, b as char
+                )));
+            }
+        }
+
+        match b {
+            b'"' => {
+                self.in_string = true;
+                self.key.clear();
+            }
+            b'{' | b'[' => {
+                if self.capturing {
+                    let level = self.level();
+                    if level > 0 {
+                        self.counts[level - 1] += 1;
+                    }
+                    self.counts[level] = 0;
+                }
+                self.stack.push(b);
+                if self.stack.len() == 1 {
+                    self.at_key = b == b'{';
+                }
+            }
+            b'}' | b']' => {
+                if self.capturing {
+                    let level = self.level();
+                    self.shape[level - 1].get_or_insert(self.counts[level - 1]);
+                }
+                self.stack.pop();
+                if self.capturing && self.stack.len() == 1 {
+                    self.capturing = false;
+                    self.captured = true;
+                }
+            }
+            b':' if self.stack.len() == 1 && self.at_value_key && !self.captured => {
+                self.capturing = true;
+            }
+            b',' if self.stack.len() == 1 => self.at_key = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn element_byte(&mut self, b: u8) -> HsdsResult<()> {
+        if self.in_string {
+            self.element.push(b);
+            if self.escaped {
+                self.escaped = false;
+            } else if b == b'\\' {
+                self.escaped = true;
+            } else if b == b'"' {
+                self.in_string = false;
+                if self.element_depth == 0 {
+                    self.finish_element()?;
+                }
+            }
+            return Ok(());
+        }
+        match b {
+            b'"' => {
+                self.element.push(b);
+                self.in_string = true;
+            }
+            b'[' | b'{' => {
+                self.element.push(b);
+                self.element_depth += 1;
+            }
+            b']' | b'}' if self.element_depth > 0 => {
+                self.element.push(b);
+                self.element_depth -= 1;
+                if self.element_depth == 0 {
+                    self.finish_element()?;
+                }
+            }
+            b',' | b']' | b'}' if self.element_depth == 0 => {
+                // End of a number or literal; the delimiter belongs to the enclosing array
+                self.finish_element()?;
+                return self.byte(b);
+            }
+            b if b.is_ascii_whitespace() && self.element_depth == 0 => self.finish_element()?,
+            b => self.element.push(b),
+        }
+        Ok(())
+    }
+
+    fn finish_element(&mut self) -> HsdsResult<()> {
+        let value = serde_json::from_slice(&self.element).or_else(|e| {
+            // Only elements the target type rejects are retried, as a plain f32/f64
+            let special = if self.special_floats.is_strict() {
+                None
+            } else {
+                serde_json::from_slice::<serde_json::Value>(&self.element)
+                    .ok()
+                    .and_then(|element| self.special_floats.parse(&element))
+            };
+            match special {
+                Some(x) => T::deserialize::<F64Deserializer<serde_json::Error>>(x.into_deserializer()),
+                None => Err(e),
+            }
+        }).map_err(|e| {
+            // Each level's count includes the element (or array) being read
+            let index = self.counts.iter().map(|&c| c.saturating_sub(1)).collect();
+            HsdsError::conversion(index, String::from_utf8_lossy(&self.element), e)
+        })?;
+        self.values.push(value);
+        self.element.clear();
+        self.in_element = false;
+        if self.rank == 0 {
+            self.capturing = false;
+            self.captured = true;
+        }
+        Ok(())
+    }
+}
@@ -2,22 +2,86 @@
  * HSDS OpenAPI Client - Generated Rust client for HDF Scalable Data Service
  */
 
+// Lets the derive macros' `::hsds_client` paths resolve inside this crate
+extern crate self as hsds_client;
+
 // Internal modules
 mod client;
 pub mod models;  // Make models public
 mod apis;
 mod error;
 mod auth;
+mod domain_path;
+mod navigation;
+pub mod decoder;
+pub mod logging;
+pub mod values;
+pub mod raw;
+mod ring_buffer;
+mod json_stream;
+mod record_log;
+mod buffered_writer;
+mod ingest;
+pub mod schema;
+pub mod template;
+mod timestamps;
+mod handle;
+mod dry_run;
+mod audit;
+mod strict;
+mod transfer;
+mod tuning;
+pub mod chunking;
+pub mod names;
+pub mod codec;
+pub mod types;
+pub mod snapshots;
+pub mod pagination;
+pub mod index;
+#[cfg(feature = "compression")]
+pub mod compression;
+
+// Integration test helpers for downstream crates (optional)
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export public types and interfaces
-pub use client::HsdsClient;
+pub use client::{ClientOptions, HsdsClient, WarmUpReport};
 pub use models::*;
 pub use apis::*;
-pub use error::{HsdsError, HsdsResult};
-pub use auth::{BasicAuth, BearerAuth, NoAuth};
+pub use error::{ErrorCode, HsdsError, HsdsResult};
+pub use auth::{BasicAuth, BearerAuth, Impersonation, NoAuth};
+pub use domain_path::DomainPath;
+pub use navigation::Navigable;
+pub use decoder::{ResponseDecoder, JsonDecoder, BinaryDecoder};
+#[cfg(feature = "msgpack")]
+pub use decoder::MessagePackDecoder;
+pub use logging::LogOptions;
+pub use ring_buffer::{RingBufferDataset, RING_HEAD_ATTRIBUTE};
+pub use record_log::{RecordLogOptions, RecordLogWriter};
+pub use buffered_writer::{BufferedWriter, BufferedWriterOptions};
+pub use ingest::{IngestPartition, IngestStatus, ParallelIngest, INGEST_DONE_PREFIX, INGEST_PLAN_ATTRIBUTE};
+pub use timestamps::Timestamped;
+pub use values::{ShapedValue, SpecialFloats};
+pub use handle::{DatasetHandle, DecodedValues, GroupHandle, PrefetchedRegion};
+pub use dry_run::{DryRunLog, PlannedRequest};
+pub use audit::{AuditEvent, AuditHook};
+pub use strict::Strictness;
+pub use transfer::{TransferReport, TransferStats};
+pub use tuning::{AdaptiveTuning, TuningState};
+pub use chunking::AccessPattern;
+pub use names::{validate_name, NameSanitizer};
+pub use codec::ElementCodec;
+pub use snapshots::{Snapshot, Snapshots};
+pub use pagination::{PageCursor, PaginationOptions};
+pub use index::{CatalogBuilder, DomainCatalog};
+#[cfg(feature = "compression")]
+pub use compression::UploadCompression;
+#[cfg(feature = "chrono")]
+pub use timestamps::{to_datetime, TimeRange};
 
 // Prelude module for convenient imports
 pub mod prelude {
@@ -25,16 +89,10 @@ pub mod prelude {
         HsdsClient, 
         BasicAuth, BearerAuth, NoAuth,
         HsdsError, HsdsResult,
+        DomainPath, Navigable, Timestamped,
         // Common model types
         Domain, Group, Dataset, Link,
         DatasetCreateRequest, DatasetValueRequest,
-        DataTypeSpec, ShapeSpec, LinkRequest,
+        H5Type, ShapeSpec, LinkRequest,
     };
 }
-
-// FFI exports for LabVIEW integration (optional)
-#[cfg(feature = "ffi")]
-pub mod ffi;
-
-#[cfg(feature = "ffi")]
-pub use ffi::*;
@@ -0,0 +1,143 @@
+use reqwest::header::{HeaderMap, HeaderName, AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE};
+use reqwest::{Method, Request};
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Placeholder written in place of masked values
+pub const REDACTED: &str = "<redacted>";
+
+/// Controls what request details end up in debug logs
+///
+/// Credentials are always masked. Request payloads (attribute values, dataset
+/// values, creation bodies) are only logged when explicitly enabled, and are
+/// truncated to `max_payload_len` bytes.
+#[derive(Debug, Clone)]
+pub struct LogOptions {
+    log_payloads: bool,
+    max_payload_len: usize,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            log_payloads: false,
+            max_payload_len: 1024,
+        }
+    }
+}
+
+impl LogOptions {
+    /// Create options with payload logging disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable logging of request payloads
+    pub fn with_payloads(mut self, enabled: bool) -> Self {
+        self.log_payloads = enabled;
+        self
+    }
+
+    /// Set the maximum number of bytes of a payload written to the log
+    pub fn with_max_payload_len(mut self, len: usize) -> Self {
+        self.max_payload_len = len;
+        self
+    }
+
+    /// Whether request payloads are logged
+    pub fn logs_payloads(&self) -> bool {
+        self.log_payloads
+    }
+
+    /// Render a payload for logging, honouring the opt-in and size cap
+    pub fn payload<T: Debug + ?Sized>(&self, value: &T) -> String {
+        if !self.log_payloads {
+            return REDACTED.to_string();
+        }
+
+        let rendered = format!("{:?}", value);
+        truncate(rendered, self.max_payload_len)
+    }
+}
+
+/// Headers whose values are never written to logs
+const SENSITIVE_HEADERS: [HeaderName; 4] = [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE];
+
+/// Render headers for logging with credential values masked
+pub fn redact_headers(headers: &HeaderMap) -> String {
+    let entries: Vec<String> = headers
+        .iter()
+        .map(|(name, value)| {
+            if SENSITIVE_HEADERS.contains(name) {
+                format!("{}: {}", name, REDACTED)
+            } else {
+                format!("{}: {}", name, value.to_str().unwrap_or("<binary>"))
+            }
+        })
+        .collect();
+
+    format!("{{{}}}", entries.join(", "))
+}
+
+/// Truncate a rendered payload on a char boundary, noting the original size
+fn truncate(rendered: String, max_len: usize) -> String {
+    if rendered.len() <= max_len {
+        return rendered;
+    }
+
+    let mut end = max_len;
+    while !rendered.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... ({} bytes total)", &rendered[..end], rendered.len())
+}
+
+/// Request details kept for the slow-request log, see
+/// [`ClientOptions::with_slow_request_threshold`](crate::ClientOptions::with_slow_request_threshold)
+#[derive(Debug, Clone)]
+pub(crate) struct SlowRequest {
+    method: Method,
+    path: String,
+    domain: Option<String>,
+    selection: Option<String>,
+    bytes_sent: u64,
+}
+
+impl SlowRequest {
+    pub(crate) fn new(request: &Request, bytes_sent: u64) -> Self {
+        let query = |name: &str| request.url().query_pairs().find(|(k, _)| k == name).map(|(_, v)| v.into_owned());
+        Self {
+            method: request.method().clone(),
+            path: request.url().path().to_string(),
+            domain: query("domain"),
+            selection: query("select"),
+            bytes_sent,
+        }
+    }
+
+    /// Object the request addresses, the last UUID in its path
+    fn object(&self) -> Option<&str> {
+        self.path
+            .split('/')
+            .rev()
+            .find(|segment| ["g-", "d-", "t-"].iter().any(|prefix| segment.starts_with(prefix)))
+    }
+
+    /// One-line summary of a request that took `elapsed`
+    ///
+    /// `outcome` is the response status, or the error if none arrived.
+    pub(crate) fn summary(&self, elapsed: Duration, outcome: &str, bytes_received: Option<u64>) -> String {
+        format!(
+            "{} {} took {:?}: domain={} object={} select={} sent={} bytes received={} outcome={}",
+            self.method,
+            self.path,
+            elapsed,
+            self.domain.as_deref().unwrap_or("-"),
+            self.object().unwrap_or("-"),
+            self.selection.as_deref().unwrap_or("-"),
+            self.bytes_sent,
+            bytes_received.map_or_else(|| "?".to_string(), |n| format!("{} bytes", n)),
+            outcome,
+        )
+    }
+}
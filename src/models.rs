@@ -1,16 +1,31 @@
+use crate::domain_path::DomainPath;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod h5type;
+
+pub use h5type::{
+    CompoundField, FilterInfo, H5Type, Layout, Shape, ShapeSpec, StringCharSet, StringLength, StringPadding,
+    TypeClass, TypeDef,
+};
+
 /// Access Control List for a single user
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Unset permissions are left out when the ACL is sent, so a partial ACL
+/// only changes the permissions it sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Acl {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub create: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub update: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delete: Option<bool>,
-    #[serde(rename = "updateACL")]
+    #[serde(rename = "updateACL", skip_serializing_if = "Option::is_none")]
     pub update_acl: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub read: Option<bool>,
-    #[serde(rename = "readACL")]
+    #[serde(rename = "readACL", skip_serializing_if = "Option::is_none")]
     pub read_acl: Option<bool>,
 }
 
@@ -21,6 +36,23 @@ pub struct Acls {
     pub users: HashMap<String, Acl>,
 }
 
+macro_rules! extra_fields {
+    ($($ty:ty),*) => {
+        $(
+            impl $ty {
+                /// Deserialize an unmodelled response field captured in `extra`
+                ///
+                /// Returns `None` if the field is absent or has a different shape.
+                pub fn extra_field<T: serde::de::DeserializeOwned>(&self, name: &str) -> Option<T> {
+                    self.extra.get(name).and_then(|v| T::deserialize(v).ok())
+                }
+            }
+        )*
+    };
+}
+
+extra_fields!(Domain, Group, Link, Dataset, Datatype, WriteResult);
+
 /// Domain information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Domain {
@@ -32,6 +64,78 @@ pub struct Domain {
     pub last_modified: Option<f64>,
     pub hrefs: Option<Vec<Href>>,
     pub acls: Option<Acls>,
+    /// Response fields not modelled by this crate (e.g. from newer servers)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Domain {
+    /// Whether this is a folder or a file domain
+    ///
+    /// Uses the `class` reported by the server, falling back to the presence
+    /// of a root group (only file domains have one).
+    pub fn kind(&self) -> DomainKind {
+        match self.class {
+            Some(DomainClass::Folder) => DomainKind::Folder,
+            Some(DomainClass::Domain) => DomainKind::File,
+            None if self.root.is_none() => DomainKind::Folder,
+            None => DomainKind::File,
+        }
+    }
+
+    /// Whether this domain is a folder
+    pub fn is_folder(&self) -> bool {
+        self.kind() == DomainKind::Folder
+    }
+}
+
+/// One page of a folder listing (`GET /domains`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainListing {
+    #[serde(default)]
+    pub domains: Vec<DomainEntry>,
+    pub hrefs: Option<Vec<Href>>,
+}
+
+/// A domain found in a folder listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainEntry {
+    /// Full domain path
+    pub name: String,
+    pub class: Option<DomainClass>,
+    pub owner: Option<String>,
+    pub created: Option<f64>,
+    #[serde(rename = "lastModified")]
+    pub last_modified: Option<f64>,
+    pub root: Option<String>,
+}
+
+impl DomainEntry {
+    /// Whether the entry is a folder or a file domain
+    pub fn kind(&self) -> DomainKind {
+        match self.class {
+            Some(DomainClass::Folder) => DomainKind::Folder,
+            Some(DomainClass::Domain) => DomainKind::File,
+            None if self.root.is_none() => DomainKind::Folder,
+            None => DomainKind::File,
+        }
+    }
+}
+
+/// Whether a domain is an HDF5 file or a folder of other domains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DomainKind {
+    File,
+    Folder,
+}
+
+impl std::fmt::Display for DomainKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DomainKind::File => f.write_str("file"),
+            DomainKind::Folder => f.write_str("folder"),
+        }
+    }
 }
 
 /// Domain class enumeration
@@ -49,12 +153,95 @@ pub struct Href {
     pub rel: String,
 }
 
+impl Href {
+    /// Typed relation kind of this href
+    pub fn rel_kind(&self) -> HrefRel {
+        HrefRel::from(self.rel.as_str())
+    }
+}
+
+/// Relation kinds returned in HSDS hrefs
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HrefRel {
+    /// The object itself ("self")
+    This,
+    Home,
+    Root,
+    Parent,
+    Owner,
+    Database,
+    Groups,
+    Datasets,
+    Datatypes,
+    Acls,
+    Attributes,
+    Links,
+    Shape,
+    Type,
+    Value,
+    /// Relation not known to this client version
+    Other(String),
+}
+
+impl HrefRel {
+    /// The rel string as sent by the server
+    pub fn as_str(&self) -> &str {
+        match self {
+            HrefRel::This => "self",
+            HrefRel::Home => "home",
+            HrefRel::Root => "root",
+            HrefRel::Parent => "parent",
+            HrefRel::Owner => "owner",
+            HrefRel::Database => "database",
+            HrefRel::Groups => "groups",
+            HrefRel::Datasets => "datasets",
+            HrefRel::Datatypes => "datatypes",
+            HrefRel::Acls => "acls",
+            HrefRel::Attributes => "attributes",
+            HrefRel::Links => "links",
+            HrefRel::Shape => "shape",
+            HrefRel::Type => "type",
+            HrefRel::Value => "value",
+            HrefRel::Other(rel) => rel,
+        }
+    }
+}
+
+impl From<&str> for HrefRel {
+    fn from(rel: &str) -> Self {
+        match rel {
+            "self" => HrefRel::This,
+            "home" => HrefRel::Home,
+            "root" => HrefRel::Root,
+            "parent" => HrefRel::Parent,
+            "owner" => HrefRel::Owner,
+            "database" => HrefRel::Database,
+            "groups" => HrefRel::Groups,
+            "datasets" => HrefRel::Datasets,
+            "datatypes" => HrefRel::Datatypes,
+            "acls" => HrefRel::Acls,
+            "attributes" => HrefRel::Attributes,
+            "links" => HrefRel::Links,
+            "shape" => HrefRel::Shape,
+            "type" => HrefRel::Type,
+            "value" => HrefRel::Value,
+            other => HrefRel::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for HrefRel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Group information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Group {
     pub id: String,
     pub root: Option<String>,
-    pub domain: Option<String>,
+    pub domain: Option<DomainPath>,
     pub alias: Option<Vec<String>>,
     pub created: Option<f64>,
     #[serde(rename = "lastModified")]
@@ -64,6 +251,9 @@ pub struct Group {
     #[serde(rename = "linkCount")]
     pub link_count: Option<u32>,
     pub hrefs: Option<Vec<Href>>,
+    /// Response fields not modelled by this crate (e.g. from newer servers)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Link information
@@ -78,6 +268,9 @@ pub struct Link {
     pub collection: Option<String>,
     pub h5path: Option<String>,
     pub h5domain: Option<String>,
+    /// Response fields not modelled by this crate (e.g. from newer servers)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Link class enumeration
@@ -91,6 +284,16 @@ pub enum LinkClass {
     External,
 }
 
+/// Order in which links of a group are listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkOrder {
+    /// Alphabetical by link name (server default)
+    #[default]
+    Name,
+    /// In the order the links were created
+    CreateOrder,
+}
+
 /// Links collection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Links {
@@ -98,47 +301,90 @@ pub struct Links {
     pub hrefs: Option<Vec<Href>>,
 }
 
+/// Links of one group, split by what they point to
+#[derive(Debug, Clone, Default)]
+pub struct GroupChildren {
+    /// Hard links to groups
+    pub groups: Vec<Link>,
+    /// Hard links to datasets
+    pub datasets: Vec<Link>,
+    /// Hard links to committed datatypes
+    pub datatypes: Vec<Link>,
+    /// Soft and external links, which are not resolved
+    pub unresolved: Vec<Link>,
+}
+
+impl GroupChildren {
+    /// Total number of links
+    pub fn len(&self) -> usize {
+        self.groups.len() + self.datasets.len() + self.datatypes.len() + self.unresolved.len()
+    }
+
+    /// Whether the group has no links
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /// Dataset information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dataset {
     pub id: String,
     pub root: Option<String>,
-    pub domain: Option<String>,
+    pub domain: Option<DomainPath>,
     pub created: Option<f64>,
     #[serde(rename = "lastModified")]
     pub last_modified: Option<f64>,
     #[serde(rename = "attributeCount")]
     pub attribute_count: Option<u32>,
     #[serde(rename = "type")]
-    pub data_type: Option<DataType>,
+    pub data_type: Option<H5Type>,
     pub shape: Option<Shape>,
-    pub layout: Option<serde_json::Value>,
+    pub layout: Option<Layout>,
     #[serde(rename = "creationProperties")]
     pub creation_properties: Option<serde_json::Value>,
     pub hrefs: Option<Vec<Href>>,
+    /// Response fields not modelled by this crate (e.g. from newer servers)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-/// Dataset collection
+/// Committed datatype information
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Datasets {
-    pub datasets: Vec<String>,
+pub struct Datatype {
+    pub id: String,
+    pub root: Option<String>,
+    pub created: Option<f64>,
+    #[serde(rename = "lastModified")]
+    pub last_modified: Option<f64>,
+    #[serde(rename = "attributeCount")]
+    pub attribute_count: Option<u32>,
+    /// Type definition (predefined name, or compound/enum/array object)
+    #[serde(rename = "type")]
+    pub data_type: Option<serde_json::Value>,
     pub hrefs: Option<Vec<Href>>,
+    /// Response fields not modelled by this crate (e.g. from newer servers)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-/// Data type information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DataType {
-    pub class: String,
-    pub base: Option<String>,
-    pub fields: Option<serde_json::Value>,
+/// Acknowledgement of a write (values, shape, attribute or link PUT)
+///
+/// HSDS answers most writes with an empty object or just `hrefs`; an empty
+/// body (e.g. `201 Created` without content) reads as the default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WriteResult {
+    pub hrefs: Option<Vec<Href>>,
+    /// Response fields not modelled by this crate (e.g. from newer servers)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-/// Shape information
+/// Dataset collection
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Shape {
-    pub class: String,
-    pub dims: Option<Vec<u64>>,
-    pub maxdims: Option<Vec<u64>>,
+pub struct Datasets {
+    pub datasets: Vec<String>,
+    pub hrefs: Option<Vec<Href>>,
 }
 
 /// Dataset value request
@@ -158,16 +404,224 @@ pub struct DatasetValueRequest {
     pub value_base64: Option<String>,
 }
 
+/// Selection read through `POST /datasets/{id}/value`
+///
+/// The selection travels in the request body, so it is not limited by URL
+/// length: either a list of point coordinates or a hyperslab selection
+/// string (e.g. "[0:100,5]"), optionally restricted to compound fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PointsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub points: Option<Vec<Vec<u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub select: Option<String>,
+    /// Compound field names separated by ':'
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<String>,
+}
+
+impl PointsRequest {
+    /// Read the elements at these coordinates
+    pub fn points(points: Vec<Vec<u64>>) -> Self {
+        Self { points: Some(points), select: None, fields: None }
+    }
+
+    /// Read a hyperslab selection
+    pub fn selection(select: impl Into<String>) -> Self {
+        Self { points: None, select: Some(select.into()), fields: None }
+    }
+
+    /// Read only these fields of a compound dataset
+    pub fn with_fields<S: AsRef<str>>(mut self, fields: &[S]) -> Self {
+        let fields: Vec<&str> = fields.iter().map(AsRef::as_ref).collect();
+        self.fields = Some(fields.join(":"));
+        self
+    }
+}
+
 /// Dataset shape update request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShapeUpdateRequest {
     pub shape: Vec<u64>,
 }
 
-/// Domain creation request
+/// Atomic dataset extension request (`extend` rows along `extend_dim`)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShapeExtendRequest {
+    pub extend: u64,
+    pub extend_dim: usize,
+}
+
+/// Direction of a dataset resize
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeKind {
+    /// New shape equals the current shape
+    Unchanged,
+    /// No dimension shrinks
+    Grow,
+    /// No dimension grows
+    Shrink,
+    /// Some dimensions grow and some shrink
+    Mixed,
+}
+
+impl ResizeKind {
+    /// Whether any dimension shrinks (data outside the new extent is lost)
+    pub fn shrinks(&self) -> bool {
+        matches!(self, ResizeKind::Shrink | ResizeKind::Mixed)
+    }
+}
+
+/// Result of a validated dataset resize
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResizeOutcome {
+    /// Shape before the resize
+    pub previous: Vec<u64>,
+    /// Effective shape reported by the server after the resize
+    pub shape: Vec<u64>,
+    pub kind: ResizeKind,
+}
+
+/// Storage statistics of a dataset (verbose dataset GET)
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageInfo {
+    /// Bytes allocated for the dataset's chunks on the server
+    pub allocated_size: Option<u64>,
+    /// Number of chunks that have been written
+    pub num_chunks: Option<u64>,
+    /// Layout class (e.g. "H5D_CHUNKED", "H5D_CONTIGUOUS")
+    pub layout_class: Option<String>,
+    /// Chunk dimensions for chunked layouts
+    pub chunk_dims: Option<Vec<u64>>,
+    /// Current dataset dimensions
+    pub shape: Option<Vec<u64>>,
+    /// Full layout description as returned by the server
+    pub layout: Option<Layout>,
+}
+
+impl StorageInfo {
+    /// Number of chunks needed to cover the current shape
+    pub fn chunk_grid_size(&self) -> Option<u64> {
+        let (shape, chunks) = (self.shape.as_ref()?, self.chunk_dims.as_ref()?);
+        if shape.len() != chunks.len() || chunks.contains(&0) {
+            return None;
+        }
+        Some(shape.iter().zip(chunks).map(|(d, c)| d.div_ceil(*c)).product())
+    }
+
+    /// Fraction of the chunk grid that has been written (0.0 to 1.0)
+    pub fn written_fraction(&self) -> Option<f64> {
+        let grid = self.chunk_grid_size()?;
+        if grid == 0 {
+            return Some(0.0);
+        }
+        Some(self.num_chunks? as f64 / grid as f64)
+    }
+
+    /// Average allocated bytes per written chunk
+    pub fn average_chunk_bytes(&self) -> Option<f64> {
+        match (self.allocated_size?, self.num_chunks?) {
+            (_, 0) => None,
+            (size, chunks) => Some(size as f64 / chunks as f64),
+        }
+    }
+}
+
+impl From<&Dataset> for StorageInfo {
+    fn from(dataset: &Dataset) -> Self {
+        let layout = dataset.layout.clone();
+        Self {
+            allocated_size: dataset.extra_field("allocated_size"),
+            num_chunks: dataset.extra_field("num_chunks"),
+            layout_class: layout.as_ref().map(|l| l.class().to_string()),
+            chunk_dims: layout.as_ref().and_then(Layout::chunk_dims).map(<[u64]>::to_vec),
+            shape: dataset.shape.as_ref().and_then(|s| s.dims.clone()),
+            layout,
+        }
+    }
+}
+
+impl Dataset {
+    /// Filter pipeline from the creation properties, in application order
+    pub fn filters(&self) -> Vec<FilterInfo> {
+        self.creation_properties
+            .as_ref()
+            .and_then(|p| p.get("filters"))
+            .and_then(|f| serde_json::from_value(f.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Deflate level of the dataset's filter pipeline, `None` if not deflated
+    pub fn deflate_level(&self) -> Option<u32> {
+        self.filters()
+            .into_iter()
+            .find(|f| f.class == "H5Z_FILTER_DEFLATE")
+            .map(|f| f.level.unwrap_or(6))
+    }
+}
+
+/// Domain creation request
+///
+/// Build with the `with_*` methods, e.g.
+/// `DomainCreateRequest::new().with_owner("alice")`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DomainCreateRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub folder: Option<u8>, // 0 or 1
+    /// Owner of the new domain (admin only; defaults to the requesting user)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Use an existing root group instead of creating one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_id: Option<String>,
+    /// Share the root group of another domain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_domain: Option<String>,
+    /// Bucket of `linked_domain`, if it lives in a different bucket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_bucket: Option<String>,
+}
+
+impl DomainCreateRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request for a folder instead of a file domain
+    pub fn folder() -> Self {
+        Self {
+            folder: Some(1),
+            ..Self::default()
+        }
+    }
+
+    /// Assign the domain to another user (requires admin rights)
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Attach an existing root group
+    pub fn with_root_id(mut self, root_id: impl Into<String>) -> Self {
+        self.root_id = Some(root_id.into());
+        self
+    }
+
+    /// Create the domain as a link to another domain's root group
+    ///
+    /// # Arguments
+    /// * `domain` - Domain path to link to
+    /// * `bucket` - Bucket of that domain, if different from the new one's
+    pub fn with_linked_domain(mut self, domain: impl Into<String>, bucket: Option<&str>) -> Self {
+        self.linked_domain = Some(domain.into());
+        self.linked_bucket = bucket.map(String::from);
+        self
+    }
+
+    /// Whether this requests a folder
+    pub fn is_folder(&self) -> bool {
+        self.folder == Some(1)
+    }
 }
 
 /// Group creation request
@@ -180,7 +634,7 @@ pub struct GroupCreateRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatasetCreateRequest {
     #[serde(rename = "type")]
-    pub data_type: DataTypeSpec,
+    pub data_type: H5Type,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub shape: Option<ShapeSpec>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -191,63 +645,6 @@ pub struct DatasetCreateRequest {
     pub link: Option<LinkRequest>,
 }
 
-/// String character set enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum StringCharSet {
-    #[serde(rename = "H5T_CSET_ASCII")]
-    Ascii,
-    #[serde(rename = "H5T_CSET_UTF8")]
-    Utf8,
-}
-
-/// String padding enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum StringPadding {
-    #[serde(rename = "H5T_STR_NULLPAD")]
-    NullPad,
-    #[serde(rename = "H5T_STR_NULLTERM")]
-    NullTerm,
-    #[serde(rename = "H5T_STR_SPACEPAD")]
-    SpacePad,
-}
-
-/// String length specification
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum StringLength {
-    Variable(String), // "H5T_VARIABLE"
-    Fixed(u32),
-}
-
-/// String data type specification
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StringDataType {
-    #[serde(rename = "class")]
-    pub class: String, // Always "H5T_STRING"
-    #[serde(rename = "charSet")]
-    pub char_set: StringCharSet,
-    #[serde(rename = "strPad")]
-    pub str_pad: StringPadding,
-    pub length: StringLength,
-}
-
-/// Data type specification (can be string or object)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum DataTypeSpec {
-    Predefined(String),
-    Custom(DataType),
-    String(StringDataType),
-}
-
-/// Shape specification (can be array or null)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum ShapeSpec {
-    Dimensions(Vec<u64>),
-    Null(String), // "H5S_NULL"
-}
-
 /// Link creation request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinkRequest {
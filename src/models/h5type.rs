@@ -0,0 +1,497 @@
+//! The HSDS JSON type system: datatypes, dataspaces, layouts and filters
+//!
+//! [`H5Type`] models every datatype the server sends or accepts: a
+//! predefined name (`"H5T_STD_I32LE"`), a committed type id, or a
+//! definition object tagged by its `class`, nested for compound, enum,
+//! array and variable-length types. Deserializing and serializing a
+//! value gives back the same JSON: properties this crate does not model
+//! are kept in each definition's `extra` map, and a definition of an
+//! unknown class is kept whole as [`H5Type::Other`].
+//!
+//! ```
+//! use hsds_client::{H5Type, TypeClass, TypeDef};
+//! use serde_json::json;
+//!
+//! let json = json!({"class": "H5T_ARRAY", "base": "H5T_IEEE_F32LE", "dims": [3]});
+//! let parsed: H5Type = serde_json::from_value(json.clone()).unwrap();
+//! assert!(matches!(&parsed, H5Type::Defined(TypeDef::Array { dims, .. }) if dims == &[3]));
+//! assert_eq!(parsed.class(), Some(TypeClass::Array));
+//! assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Class of an HDF5 datatype (`H5T_*`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypeClass {
+    #[serde(rename = "H5T_INTEGER")]
+    Integer,
+    #[serde(rename = "H5T_FLOAT")]
+    Float,
+    #[serde(rename = "H5T_STRING")]
+    String,
+    #[serde(rename = "H5T_COMPOUND")]
+    Compound,
+    #[serde(rename = "H5T_ENUM")]
+    Enum,
+    #[serde(rename = "H5T_ARRAY")]
+    Array,
+    #[serde(rename = "H5T_VLEN")]
+    Vlen,
+    #[serde(rename = "H5T_OPAQUE")]
+    Opaque,
+    #[serde(rename = "H5T_REFERENCE")]
+    Reference,
+    #[serde(rename = "H5T_BITFIELD")]
+    Bitfield,
+    #[serde(rename = "H5T_TIME")]
+    Time,
+}
+
+impl TypeClass {
+    /// Every class, in HDF5 order
+    pub const ALL: [TypeClass; 11] = [
+        Self::Integer, Self::Float, Self::Time, Self::String, Self::Bitfield, Self::Opaque,
+        Self::Compound, Self::Reference, Self::Enum, Self::Vlen, Self::Array,
+    ];
+
+    /// The `H5T_*` class name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Integer => "H5T_INTEGER",
+            Self::Float => "H5T_FLOAT",
+            Self::String => "H5T_STRING",
+            Self::Compound => "H5T_COMPOUND",
+            Self::Enum => "H5T_ENUM",
+            Self::Array => "H5T_ARRAY",
+            Self::Vlen => "H5T_VLEN",
+            Self::Opaque => "H5T_OPAQUE",
+            Self::Reference => "H5T_REFERENCE",
+            Self::Bitfield => "H5T_BITFIELD",
+            Self::Time => "H5T_TIME",
+        }
+    }
+
+    /// Class of a class name (`"H5T_FLOAT"`) or predefined type name (`"H5T_IEEE_F64LE"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(class) = Self::ALL.into_iter().find(|class| class.as_str() == name) {
+            return Some(class);
+        }
+        let predefined = [
+            ("H5T_STD_I", Self::Integer),
+            ("H5T_STD_U", Self::Integer),
+            ("H5T_IEEE_F", Self::Float),
+            ("H5T_STD_B", Self::Bitfield),
+            ("H5T_STD_REF", Self::Reference),
+            ("H5T_UNIX_D", Self::Time),
+        ];
+        predefined.into_iter().find(|(prefix, _)| name.starts_with(prefix)).map(|(_, class)| class)
+    }
+}
+
+impl fmt::Display for TypeClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An HSDS datatype, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum H5Type {
+    /// Predefined type name (`"H5T_STD_I32LE"`) or committed type id (`"t-..."`)
+    Named(String),
+    /// Type definition object
+    Defined(TypeDef),
+    /// Definition of a class this crate does not know, kept as sent
+    Other(Value),
+}
+
+impl H5Type {
+    /// Predefined type by name
+    pub fn named(name: impl Into<String>) -> Self {
+        Self::Named(name.into())
+    }
+
+    /// Class of the type; `None` for committed type ids and unknown classes
+    pub fn class(&self) -> Option<TypeClass> {
+        match self {
+            Self::Named(name) => TypeClass::from_name(name),
+            Self::Defined(definition) => Some(definition.class()),
+            Self::Other(_) => None,
+        }
+    }
+
+    /// Id of the committed type this refers to, if it is one
+    pub fn committed_id(&self) -> Option<&str> {
+        match self {
+            Self::Named(name) if name.starts_with("t-") => Some(name),
+            Self::Defined(definition) => definition.extra().get("id").and_then(Value::as_str),
+            _ => None,
+        }
+    }
+
+    /// Compound type of `(field name, predefined type)` pairs, in storage order
+    pub fn compound(fields: &[(&str, &str)]) -> Self {
+        let fields = fields
+            .iter()
+            .map(|(name, field_type)| CompoundField {
+                name: name.to_string(),
+                field_type: Self::named(*field_type),
+                extra: serde_json::Map::new(),
+            })
+            .collect();
+        TypeDef::Compound { fields, extra: serde_json::Map::new() }.into()
+    }
+
+    /// String type with the given character set, padding and length
+    pub fn string(char_set: StringCharSet, str_pad: StringPadding, length: StringLength) -> Self {
+        TypeDef::String { char_set, str_pad, length, extra: serde_json::Map::new() }.into()
+    }
+
+    /// Variable-length UTF-8 string
+    pub fn variable_utf8() -> Self {
+        Self::string(StringCharSet::Utf8, StringPadding::NullPad, StringLength::Variable("H5T_VARIABLE".to_string()))
+    }
+
+    /// Fixed-length UTF-8 string of `length` bytes
+    pub fn fixed_utf8(length: u32) -> Self {
+        Self::string(StringCharSet::Utf8, StringPadding::NullPad, StringLength::Fixed(length))
+    }
+
+    /// Variable-length ASCII string
+    pub fn variable_ascii() -> Self {
+        Self::string(StringCharSet::Ascii, StringPadding::NullPad, StringLength::Variable("H5T_VARIABLE".to_string()))
+    }
+
+    /// Fixed-length ASCII string of `length` bytes
+    pub fn fixed_ascii(length: u32) -> Self {
+        Self::string(StringCharSet::Ascii, StringPadding::NullPad, StringLength::Fixed(length))
+    }
+
+    /// Field names of a compound type, in storage order; empty for other types
+    pub fn field_names(&self) -> Vec<String> {
+        match self {
+            Self::Defined(TypeDef::Compound { fields, .. }) => fields.iter().map(|f| f.name.clone()).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl From<TypeDef> for H5Type {
+    fn from(definition: TypeDef) -> Self {
+        Self::Defined(definition)
+    }
+}
+
+/// Datatype definition object, tagged by its `class`
+///
+/// Each variant's `extra` holds the properties not modelled here (e.g.
+/// the `id` of a committed type in a dataset's type).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "class")]
+pub enum TypeDef {
+    #[serde(rename = "H5T_INTEGER")]
+    Integer {
+        /// Predefined type, e.g. "H5T_STD_I32LE"
+        base: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_FLOAT")]
+    Float {
+        /// Predefined type, e.g. "H5T_IEEE_F64LE"
+        base: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_STRING")]
+    String {
+        #[serde(rename = "charSet")]
+        char_set: StringCharSet,
+        #[serde(rename = "strPad")]
+        str_pad: StringPadding,
+        length: StringLength,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_COMPOUND")]
+    Compound {
+        fields: Vec<CompoundField>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_ENUM")]
+    Enum {
+        /// Integer type of the stored values
+        base: Box<H5Type>,
+        /// Member names and their values
+        mapping: BTreeMap<String, i64>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_ARRAY")]
+    Array {
+        base: Box<H5Type>,
+        dims: Vec<u64>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_VLEN")]
+    Vlen {
+        base: Box<H5Type>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_OPAQUE")]
+    Opaque {
+        /// Element size in bytes
+        size: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_REFERENCE")]
+    Reference {
+        /// "H5T_STD_REF_OBJ" or "H5T_STD_REF_DSETREG"
+        base: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_BITFIELD")]
+    Bitfield {
+        base: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5T_TIME")]
+    Time {
+        base: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+}
+
+impl TypeDef {
+    pub fn class(&self) -> TypeClass {
+        match self {
+            Self::Integer { .. } => TypeClass::Integer,
+            Self::Float { .. } => TypeClass::Float,
+            Self::String { .. } => TypeClass::String,
+            Self::Compound { .. } => TypeClass::Compound,
+            Self::Enum { .. } => TypeClass::Enum,
+            Self::Array { .. } => TypeClass::Array,
+            Self::Vlen { .. } => TypeClass::Vlen,
+            Self::Opaque { .. } => TypeClass::Opaque,
+            Self::Reference { .. } => TypeClass::Reference,
+            Self::Bitfield { .. } => TypeClass::Bitfield,
+            Self::Time { .. } => TypeClass::Time,
+        }
+    }
+
+    /// Properties of the definition not modelled by this crate
+    pub fn extra(&self) -> &serde_json::Map<String, Value> {
+        match self {
+            Self::Integer { extra, .. }
+            | Self::Float { extra, .. }
+            | Self::String { extra, .. }
+            | Self::Compound { extra, .. }
+            | Self::Enum { extra, .. }
+            | Self::Array { extra, .. }
+            | Self::Vlen { extra, .. }
+            | Self::Opaque { extra, .. }
+            | Self::Reference { extra, .. }
+            | Self::Bitfield { extra, .. }
+            | Self::Time { extra, .. } => extra,
+        }
+    }
+}
+
+/// Member of a compound type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompoundField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: H5Type,
+    /// Field properties not modelled by this crate
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// Storage layout of a dataset, tagged by its `class`
+///
+/// A layout of a class this crate does not know is kept as sent in
+/// [`Layout::Other`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "class")]
+pub enum Layout {
+    #[serde(rename = "H5D_CONTIGUOUS")]
+    Contiguous {
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5D_COMPACT")]
+    Compact {
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    #[serde(rename = "H5D_CHUNKED")]
+    Chunked {
+        /// Chunk dimensions
+        dims: Vec<u64>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    /// Contiguous data in an external HDF5 file
+    #[serde(rename = "H5D_CONTIGUOUS_REF")]
+    ContiguousRef {
+        file_uri: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    /// Chunks in an external HDF5 file, located by a `chunks` map
+    #[serde(rename = "H5D_CHUNKED_REF")]
+    ChunkedRef {
+        file_uri: String,
+        dims: Vec<u64>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    /// Chunks in external HDF5 files, located by a chunk table dataset
+    #[serde(rename = "H5D_CHUNKED_REF_INDIRECT")]
+    ChunkedRefIndirect {
+        dims: Vec<u64>,
+        chunk_table: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, Value>,
+    },
+    /// Layout of a class this crate does not know, kept as sent
+    #[serde(untagged)]
+    Other(Value),
+}
+
+impl Layout {
+    /// The `H5D_*` layout class name (empty if an unknown layout has none)
+    pub fn class(&self) -> &str {
+        match self {
+            Self::Contiguous { .. } => "H5D_CONTIGUOUS",
+            Self::Compact { .. } => "H5D_COMPACT",
+            Self::Chunked { .. } => "H5D_CHUNKED",
+            Self::ContiguousRef { .. } => "H5D_CONTIGUOUS_REF",
+            Self::ChunkedRef { .. } => "H5D_CHUNKED_REF",
+            Self::ChunkedRefIndirect { .. } => "H5D_CHUNKED_REF_INDIRECT",
+            Self::Other(layout) => layout.get("class").and_then(Value::as_str).unwrap_or_default(),
+        }
+    }
+
+    /// Chunk dimensions of chunked layouts
+    pub fn chunk_dims(&self) -> Option<&[u64]> {
+        match self {
+            Self::Chunked { dims, .. } | Self::ChunkedRef { dims, .. } | Self::ChunkedRefIndirect { dims, .. } => Some(dims),
+            _ => None,
+        }
+    }
+}
+
+/// Shape information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shape {
+    /// "H5S_NULL", "H5S_SCALAR" or "H5S_SIMPLE"
+    pub class: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dims: Option<Vec<u64>>,
+    /// Maximum dimensions, `0` for unlimited ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxdims: Option<Vec<u64>>,
+}
+
+impl Shape {
+    /// Whether this is a null dataspace (`H5S_NULL`), which holds no values
+    pub fn is_null(&self) -> bool {
+        self.class == "H5S_NULL"
+    }
+
+    /// Whether this is a scalar dataspace holding a single value
+    pub fn is_scalar(&self) -> bool {
+        self.class == "H5S_SCALAR"
+    }
+
+    /// Number of elements: 0 for null dataspaces and zero-length dimensions
+    pub fn element_count(&self) -> u64 {
+        match (&self.dims, self.is_null()) {
+            (_, true) => 0,
+            (Some(dims), false) if !self.is_scalar() => dims.iter().product(),
+            _ => 1,
+        }
+    }
+
+    /// Whether the dataspace holds no values (null, or a zero-length dimension)
+    pub fn is_empty(&self) -> bool {
+        self.element_count() == 0
+    }
+}
+
+/// One stage of a dataset's filter pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterInfo {
+    /// Filter class (e.g. "H5Z_FILTER_DEFLATE", "H5Z_FILTER_SHUFFLE")
+    pub class: String,
+    /// HDF5 filter id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Compression level, for filters that have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<u32>,
+    /// Filter parameters not modelled by this crate (e.g. szip or scale-offset options)
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// String character set enumeration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StringCharSet {
+    #[serde(rename = "H5T_CSET_ASCII")]
+    Ascii,
+    #[serde(rename = "H5T_CSET_UTF8")]
+    Utf8,
+}
+
+/// String padding enumeration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StringPadding {
+    #[serde(rename = "H5T_STR_NULLPAD")]
+    NullPad,
+    #[serde(rename = "H5T_STR_NULLTERM")]
+    NullTerm,
+    #[serde(rename = "H5T_STR_SPACEPAD")]
+    SpacePad,
+}
+
+/// String length specification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StringLength {
+    Variable(String), // "H5T_VARIABLE"
+    Fixed(u32),
+}
+
+/// Shape specification (can be array or null)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ShapeSpec {
+    Dimensions(Vec<u64>),
+    Null(String), // "H5S_NULL"
+}
+
+impl ShapeSpec {
+    /// Null dataspace, for placeholder datasets without values
+    pub fn null() -> Self {
+        Self::Null("H5S_NULL".to_string())
+    }
+}
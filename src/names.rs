@@ -0,0 +1,100 @@
+//! Validation and sanitizing of link and attribute names
+//!
+//! HSDS answers a name it cannot store with a bare `400 Bad Request`.
+//! [`validate_name`] checks names client-side, before anything is sent, and
+//! says what is wrong; create calls for links, attributes, groups and
+//! datasets run it on the names they are given. Tools importing names they
+//! do not control (e.g. raw instrument channel names) can map them to valid
+//! names with a [`NameSanitizer`] instead of failing the upload.
+
+use crate::error::{HsdsError, HsdsResult};
+
+/// Longest name accepted, in bytes of UTF-8
+pub const MAX_NAME_BYTES: usize = 1024;
+
+/// Check that a link or attribute name can be stored by HSDS
+///
+/// A name must not be empty, `.` or `..`, must not contain `/` (which
+/// separates path components) or control characters, and must be at most
+/// [`MAX_NAME_BYTES`] long.
+pub fn validate_name(name: &str) -> HsdsResult<()> {
+    let problem = if name.is_empty() {
+        "must not be empty".to_string()
+    } else if name == "." || name == ".." {
+        "is reserved for path navigation".to_string()
+    } else if name.contains('/') {
+        "must not contain '/'".to_string()
+    } else if let Some(c) = name.chars().find(|c| c.is_control()) {
+        format!("must not contain control character {:?}", c)
+    } else if name.len() > MAX_NAME_BYTES {
+        format!("is {} bytes long; at most {} are allowed", name.len(), MAX_NAME_BYTES)
+    } else {
+        return Ok(());
+    };
+    Err(HsdsError::invalid_param(format!("Name '{}' {}", name.escape_debug(), problem)))
+}
+
+/// Maps arbitrary strings to names passing [`validate_name`]
+///
+/// Disallowed characters are replaced, overlong names are cut at
+/// [`MAX_NAME_BYTES`] (on a character boundary) and empty or reserved names
+/// become the replacement. Valid names are returned unchanged.
+#[derive(Debug, Clone)]
+pub struct NameSanitizer {
+    replacement: String,
+}
+
+impl Default for NameSanitizer {
+    fn default() -> Self {
+        Self { replacement: "_".to_string() }
+    }
+}
+
+impl NameSanitizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Text put in place of each disallowed character (default `_`)
+    ///
+    /// Characters of the replacement that are themselves disallowed are
+    /// dropped.
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = replacement.into().chars().filter(|&c| is_allowed(c)).collect();
+        self
+    }
+
+    /// Sanitized form of `name`
+    pub fn sanitize(&self, name: &str) -> String {
+        if validate_name(name).is_ok() {
+            return name.to_string();
+        }
+        let mut sanitized = String::with_capacity(name.len());
+        for c in name.chars() {
+            if is_allowed(c) {
+                sanitized.push(c);
+            } else {
+                sanitized.push_str(&self.replacement);
+            }
+        }
+        if sanitized.len() > MAX_NAME_BYTES {
+            let mut end = MAX_NAME_BYTES;
+            while !sanitized.is_char_boundary(end) {
+                end -= 1;
+            }
+            sanitized.truncate(end);
+        }
+        if validate_name(&sanitized).is_ok() {
+            return sanitized;
+        }
+        // Empty or reserved, even after replacing
+        match validate_name(&self.replacement) {
+            Ok(()) => self.replacement.clone(),
+            Err(_) => "_".to_string(),
+        }
+    }
+}
+
+fn is_allowed(c: char) -> bool {
+    c != '/' && !c.is_control()
+}
@@ -0,0 +1,63 @@
+use crate::{
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::{Dataset, Datasets, Domain, Group, Href, HrefRel, Links},
+};
+use serde::de::DeserializeOwned;
+
+/// HATEOAS-style navigation over the hrefs returned with HSDS responses
+#[async_trait::async_trait]
+pub trait Navigable: Sync {
+    /// All hrefs attached to the response
+    fn hrefs(&self) -> &[Href];
+
+    /// Find the href with the given relation
+    fn href(&self, rel: &HrefRel) -> Option<&Href> {
+        self.hrefs().iter().find(|h| &h.rel_kind() == rel)
+    }
+
+    /// GET the resource referenced by the given relation
+    ///
+    /// # Arguments
+    /// * `client` - Client used to issue the authenticated request
+    /// * `rel` - Relation to follow (e.g. `HrefRel::Root`)
+    async fn follow_href<T>(&self, client: &HsdsClient, rel: HrefRel) -> HsdsResult<T>
+    where
+        T: DeserializeOwned + Send,
+    {
+        let href = self.href(&rel).ok_or_else(|| {
+            HsdsError::ObjectNotFound(format!("No href with rel '{}' in response", rel))
+        })?;
+        client.follow_href(href).await
+    }
+}
+
+impl Navigable for Domain {
+    fn hrefs(&self) -> &[Href] {
+        self.hrefs.as_deref().unwrap_or(&[])
+    }
+}
+
+impl Navigable for Group {
+    fn hrefs(&self) -> &[Href] {
+        self.hrefs.as_deref().unwrap_or(&[])
+    }
+}
+
+impl Navigable for Dataset {
+    fn hrefs(&self) -> &[Href] {
+        self.hrefs.as_deref().unwrap_or(&[])
+    }
+}
+
+impl Navigable for Links {
+    fn hrefs(&self) -> &[Href] {
+        self.hrefs.as_deref().unwrap_or(&[])
+    }
+}
+
+impl Navigable for Datasets {
+    fn hrefs(&self) -> &[Href] {
+        self.hrefs.as_deref().unwrap_or(&[])
+    }
+}
@@ -0,0 +1,222 @@
+//! Adaptive page sizing for auto-paginating streams
+//!
+//! Listing streams such as [`LinkApi::links_stream_with`](crate::LinkApi::links_stream_with)
+//! start with a configured page size and adjust it to the observed response
+//! latency: slow pages halve the size, fast full pages double it. Throttled
+//! requests (`429 Too Many Requests`, `503 Service Unavailable`) are retried
+//! by the client's own retry policy
+//! ([`ClientOptions::with_max_retries`](crate::ClientOptions::with_max_retries)),
+//! not again here; the backoff counts towards the page latency, so a page
+//! that needed retries shrinks the next one and traversals of millions of
+//! links ease off on a busy shared cluster.
+//!
+//! The resumable variants of the streams pair each item with a
+//! [`PageCursor`] that continues the listing after it. Persist the cursor
+//! of the last item processed and pass it back with
+//! [`PaginationOptions::with_cursor`] to pick up an interrupted traversal
+//! where it stopped.
+
+use crate::error::HsdsResult;
+use futures_util::{stream, Stream, TryStreamExt};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Page sizing and limit knobs for listing streams
+#[derive(Debug, Clone)]
+pub struct PaginationOptions {
+    initial_page_size: u32,
+    min_page_size: u32,
+    max_page_size: u32,
+    target_latency: Duration,
+    max_pages: Option<usize>,
+    cursor: PageCursor,
+}
+
+impl Default for PaginationOptions {
+    fn default() -> Self {
+        Self {
+            initial_page_size: 1000,
+            min_page_size: 100,
+            max_page_size: 10_000,
+            target_latency: Duration::from_secs(1),
+            max_pages: None,
+            cursor: PageCursor::start(),
+        }
+    }
+}
+
+impl PaginationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Page size of the first request (clamped to the size bounds)
+    pub fn with_initial_page_size(mut self, size: u32) -> Self {
+        self.initial_page_size = size;
+        self
+    }
+
+    /// Bounds the page size is adapted within
+    pub fn with_page_size_bounds(mut self, min: u32, max: u32) -> Self {
+        self.min_page_size = min.max(1);
+        self.max_page_size = max.max(self.min_page_size);
+        self
+    }
+
+    /// Page latency the size is adapted towards
+    pub fn with_target_latency(mut self, latency: Duration) -> Self {
+        self.target_latency = latency;
+        self
+    }
+
+    /// Stop after this many pages (a warning is logged if more remain)
+    pub fn with_max_pages(mut self, pages: usize) -> Self {
+        self.max_pages = Some(pages);
+        self
+    }
+
+    /// Start the listing after `cursor` instead of at the beginning
+    pub fn with_cursor(mut self, cursor: PageCursor) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Use a fixed page size, without adaptation
+    pub fn fixed(size: u32) -> Self {
+        Self::default()
+            .with_page_size_bounds(size, size)
+            .with_initial_page_size(size)
+    }
+
+    /// Page size after a page of `len` items took `elapsed`
+    pub(crate) fn adapt(&self, size: u32, len: usize, elapsed: Duration) -> u32 {
+        let next = if elapsed > self.target_latency {
+            size / 2
+        } else if elapsed < self.target_latency / 2 && len >= size as usize {
+            size.saturating_mul(2)
+        } else {
+            size
+        };
+        next.clamp(self.min_page_size, self.max_page_size)
+    }
+}
+
+/// Position in a paginated listing, the marker of the last item seen
+///
+/// Cursors serialize to the marker string (or `null` at the start), so
+/// they can be stored with the progress of a long job.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PageCursor {
+    marker: Option<String>,
+}
+
+impl PageCursor {
+    /// The beginning of the listing
+    pub fn start() -> Self {
+        Self::default()
+    }
+
+    /// Just after the item with this marker (a link or attribute name)
+    pub fn after(marker: impl Into<String>) -> Self {
+        Self { marker: Some(marker.into()) }
+    }
+
+    /// The marker the listing continues after, `None` at the start
+    pub fn marker(&self) -> Option<&str> {
+        self.marker.as_deref()
+    }
+
+    /// Whether this cursor is at the beginning of the listing
+    pub fn is_start(&self) -> bool {
+        self.marker.is_none()
+    }
+}
+
+struct Pages<T, F> {
+    fetch: F,
+    ready: VecDeque<T>,
+    marker: Option<String>,
+    page_size: u32,
+    pages: usize,
+    done: bool,
+}
+
+/// Stream the items of a paginated listing
+///
+/// `fetch(limit, marker)` returns one page; `marker_of` gives the marker
+/// that continues the listing after an item. A page shorter than the
+/// requested size ends the listing, which starts after the cursor of
+/// `options`. A failed page (after the client's retries) is yielded as an error and
+/// ends the stream.
+pub(crate) fn paginate<'a, T, F, Fut>(
+    options: PaginationOptions,
+    fetch: F,
+    marker_of: fn(&T) -> String,
+) -> impl Stream<Item = HsdsResult<T>> + 'a
+where
+    T: 'a,
+    F: FnMut(u32, Option<String>) -> Fut + 'a,
+    Fut: Future<Output = HsdsResult<Vec<T>>> + 'a,
+{
+    let state = Pages {
+        fetch,
+        ready: VecDeque::new(),
+        marker: options.cursor.marker.clone(),
+        page_size: options.initial_page_size.clamp(options.min_page_size, options.max_page_size),
+        pages: 0,
+        done: false,
+    };
+
+    stream::unfold(state, move |mut state| {
+        let options = options.clone();
+        async move {
+            loop {
+                if let Some(item) = state.ready.pop_front() {
+                    return Some((Ok(item), state));
+                }
+                if state.done {
+                    return None;
+                }
+                if options.max_pages.is_some_and(|max| state.pages >= max) {
+                    warn!("Listing stopped after {} pages (max_pages reached)", state.pages);
+                    return None;
+                }
+
+                let started = Instant::now();
+                let page = match (state.fetch)(state.page_size, state.marker.clone()).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        state.done = true;
+                        return Some((Err(e), state));
+                    }
+                };
+                let elapsed = started.elapsed();
+
+                state.pages += 1;
+                state.done = page.len() < state.page_size as usize;
+                state.marker = page.last().map(marker_of);
+                let next = options.adapt(state.page_size, page.len(), elapsed);
+                if next != state.page_size {
+                    debug!("Page of {} items took {:?}; page size {} -> {}", page.len(), elapsed, state.page_size, next);
+                    state.page_size = next;
+                }
+                state.ready.extend(page);
+            }
+        }
+    })
+}
+
+/// Pair each item of a [`paginate`] stream with the cursor that resumes after it
+pub(crate) fn with_cursors<'a, T: 'a>(
+    items: impl Stream<Item = HsdsResult<T>> + 'a,
+    marker_of: fn(&T) -> String,
+) -> impl Stream<Item = HsdsResult<(T, PageCursor)>> + 'a {
+    items.map_ok(move |item| {
+        let cursor = PageCursor::after(marker_of(&item));
+        (item, cursor)
+    })
+}
@@ -0,0 +1,49 @@
+use crate::{
+    codec::{self, ElementCodec},
+    error::{HsdsError, HsdsResult},
+};
+use bytes::{Bytes, BytesMut};
+
+/// Reusable buffer for encoding binary write payloads
+///
+/// Each call to [`encode`](Self::encode) hands out a frozen `Bytes` view of
+/// the buffer. Once the previous payload has been sent and dropped, the next
+/// call reuses the same allocation instead of allocating per write.
+#[derive(Debug, Default)]
+pub struct RawWriteBuffer {
+    buf: BytesMut,
+}
+
+impl RawWriteBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a buffer with room for `capacity` bytes
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: BytesMut::with_capacity(capacity),
+        }
+    }
+
+    /// Encode elements as their packed binary payload
+    ///
+    /// Fails for types without a binary encoding (see
+    /// [`ElementCodec::BINARY_SIZE`]).
+    pub fn encode<T: ElementCodec>(&mut self, values: &[T]) -> HsdsResult<Bytes> {
+        let size = codec::binary_size::<T>()?;
+        self.buf.clear();
+        self.buf.reserve(values.len() * size);
+        for (index, value) in values.iter().enumerate() {
+            value
+                .put_binary(&mut self.buf)
+                .map_err(|e| HsdsError::invalid_param(format!("element {}: {}", index, e)))?;
+        }
+        Ok(self.buf.split().freeze())
+    }
+
+    /// Current capacity of the underlying allocation
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    apis::AppendOptions,
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::{DatasetCreateRequest, H5Type, LinkRequest, ShapeSpec},
+};
+use log::{debug, warn};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Batching and backpressure limits for a [`RecordLogWriter`]
+#[derive(Debug, Clone)]
+pub struct RecordLogOptions {
+    batch_records: usize,
+    batch_age: Duration,
+    max_buffered: usize,
+}
+
+impl Default for RecordLogOptions {
+    fn default() -> Self {
+        Self {
+            batch_records: 1000,
+            batch_age: Duration::from_secs(1),
+            max_buffered: 100_000,
+        }
+    }
+}
+
+impl RecordLogOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flush once this many records are buffered
+    pub fn with_batch_records(mut self, records: usize) -> Self {
+        self.batch_records = records.max(1);
+        self
+    }
+
+    /// Flush once the oldest buffered record is this old
+    pub fn with_batch_age(mut self, age: Duration) -> Self {
+        self.batch_age = age;
+        self
+    }
+
+    /// Maximum number of records held while flushes fail or lag behind
+    pub fn with_max_buffered(mut self, records: usize) -> Self {
+        self.max_buffered = records.max(1);
+        self
+    }
+}
+
+/// Append-only writer of compound records to an unlimited 1D dataset
+///
+/// Records are buffered client-side and appended in batches when either the
+/// batch size or the batch age is reached. A flush first allocates rows for
+/// the batch, then writes them; if the write fails the records stay
+/// buffered together with their allocated rows, and the next flush rewrites
+/// them into the same rows instead of extending again, so a retried batch
+/// leaves neither gaps nor duplicates. Once `max_buffered` records are held,
+/// [`push`](Self::push) waits for a flush and [`try_push`](Self::try_push)
+/// refuses new records, so producers slow down instead of growing memory.
+///
+/// Batches are only age-checked when records are pushed; call
+/// [`flush_if_due`](Self::flush_if_due) from a timer for quiet periods, and
+/// [`flush`](Self::flush) before dropping the writer.
+pub struct RecordLogWriter<'a> {
+    client: &'a HsdsClient,
+    domain: String,
+    dataset_id: String,
+    fields: Vec<String>,
+    options: RecordLogOptions,
+    buffer: Vec<Value>,
+    /// Rows allocated for the first `stop - start` buffered records, not written yet
+    allocated: Option<(u64, u64)>,
+    oldest: Option<Instant>,
+    written: u64,
+}
+
+impl<'a> RecordLogWriter<'a> {
+    /// Create an unlimited compound dataset and a writer for it
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the group to link the dataset into
+    /// * `name` - Link name of the dataset
+    /// * `fields` - Record fields as `(name, HSDS type)` pairs
+    /// * `options` - Batching and backpressure limits
+    pub async fn create(
+        client: &'a HsdsClient,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        fields: &[(&str, &str)],
+        options: RecordLogOptions,
+    ) -> HsdsResult<RecordLogWriter<'a>> {
+        if fields.is_empty() {
+            return Err(HsdsError::invalid_param("Record log needs at least one field"));
+        }
+
+        let request = DatasetCreateRequest {
+            data_type: H5Type::compound(fields),
+            shape: Some(ShapeSpec::Dimensions(vec![0])),
+            maxdims: Some(vec![0]),
+            creation_properties: None,
+            link: Some(LinkRequest {
+                id: parent_group_id.to_string(),
+                name: name.to_string(),
+            }),
+        };
+        let dataset = client.datasets().create_dataset(domain, request).await?;
+
+        Ok(Self::new(client, domain, dataset.id, fields.iter().map(|(n, _)| n.to_string()).collect(), options))
+    }
+
+    /// Open a writer for an existing compound dataset
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of an unlimited 1D compound dataset
+    /// * `options` - Batching and backpressure limits
+    pub async fn open(
+        client: &'a HsdsClient,
+        domain: &str,
+        dataset_id: &str,
+        options: RecordLogOptions,
+    ) -> HsdsResult<RecordLogWriter<'a>> {
+        let dataset = client.datasets().get_dataset(domain, dataset_id).await?;
+        let fields = dataset.data_type.map(|t| t.field_names()).unwrap_or_default();
+        if fields.is_empty() {
+            return Err(HsdsError::invalid_param(format!(
+                "Dataset {} does not have a compound type", dataset_id
+            )));
+        }
+
+        Ok(Self::new(client, domain, dataset.id, fields, options))
+    }
+
+    fn new(client: &'a HsdsClient, domain: &str, dataset_id: String, fields: Vec<String>, options: RecordLogOptions) -> Self {
+        Self {
+            client,
+            domain: domain.to_string(),
+            dataset_id,
+            fields,
+            options,
+            buffer: Vec::new(),
+            allocated: None,
+            oldest: None,
+            written: 0,
+        }
+    }
+
+    /// UUID of the underlying dataset
+    pub fn dataset_id(&self) -> &str {
+        &self.dataset_id
+    }
+
+    /// Number of records waiting to be flushed
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Number of records written to the server
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Whether the buffer has reached `max_buffered`
+    pub fn is_saturated(&self) -> bool {
+        self.buffer.len() >= self.options.max_buffered
+    }
+
+    /// Buffer a record, flushing when a batch is complete
+    ///
+    /// Waits for a flush first if the buffer is saturated.
+    ///
+    /// # Arguments
+    /// * `record` - Struct or map with the record fields, or a field-ordered array
+    pub async fn push<T: Serialize>(&mut self, record: &T) -> HsdsResult<()> {
+        if self.is_saturated() {
+            self.flush().await?;
+        }
+        self.buffer_record(record)?;
+        self.flush_if_due().await
+    }
+
+    /// Buffer a record without waiting
+    ///
+    /// Returns `Ok(false)` without buffering when the writer is saturated.
+    pub fn try_push<T: Serialize>(&mut self, record: &T) -> HsdsResult<bool> {
+        if self.is_saturated() {
+            return Ok(false);
+        }
+        self.buffer_record(record)?;
+        Ok(true)
+    }
+
+    /// Flush if the batch size or age limit has been reached
+    pub async fn flush_if_due(&mut self) -> HsdsResult<()> {
+        let full = self.buffer.len() >= self.options.batch_records;
+        let aged = self.oldest.is_some_and(|t| t.elapsed() >= self.options.batch_age);
+        if full || aged {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Append all buffered records
+    ///
+    /// On failure the records stay buffered and the error is returned; rows
+    /// already allocated for them are reused by the next flush.
+    pub async fn flush(&mut self) -> HsdsResult<()> {
+        while !self.buffer.is_empty() {
+            let datasets = self.client.datasets();
+            let (start, stop) = match self.allocated {
+                Some(region) => region,
+                None => {
+                    let rows = self.buffer.len() as u64;
+                    let (start, stop, _) = datasets
+                        .allocate_rows(&self.domain, &self.dataset_id, rows, &AppendOptions::default())
+                        .await?;
+                    self.allocated = Some((start, stop));
+                    (start, stop)
+                }
+            };
+
+            let rows = (stop - start) as usize;
+            let batch = Value::Array(self.buffer[..rows].to_vec());
+            datasets.write_rows(&self.domain, &self.dataset_id, start, stop, &batch).await?;
+            debug!("Flushed {} records to {} at [{}:{}]", rows, self.dataset_id, start, stop);
+
+            self.buffer.drain(..rows);
+            self.allocated = None;
+            self.written += rows as u64;
+        }
+        self.oldest = None;
+        Ok(())
+    }
+
+    /// Convert a record to a field-ordered array and buffer it
+    fn buffer_record<T: Serialize>(&mut self, record: &T) -> HsdsResult<()> {
+        let row = match serde_json::to_value(record)? {
+            Value::Object(mut map) => {
+                let row = self.fields
+                    .iter()
+                    .map(|name| map.remove(name).ok_or_else(|| {
+                        HsdsError::invalid_param(format!("Record is missing field '{}'", name))
+                    }))
+                    .collect::<HsdsResult<Vec<_>>>()?;
+                Value::Array(row)
+            }
+            Value::Array(row) if row.len() == self.fields.len() => Value::Array(row),
+            other => return Err(HsdsError::invalid_param(format!(
+                "Record must be an object or an array of {} fields, got {}", self.fields.len(), other
+            ))),
+        };
+
+        self.buffer.push(row);
+        self.oldest.get_or_insert_with(Instant::now);
+        Ok(())
+    }
+}
+
+impl Drop for RecordLogWriter<'_> {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            warn!(
+                "RecordLogWriter for {} dropped with {} unflushed records",
+                self.dataset_id,
+                self.buffer.len()
+            );
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::{DatasetCreateRequest, DatasetValueRequest},
+};
+use serde_json::Value;
+
+/// Attribute holding the ring state as `[head, count]`
+///
+/// `head` is the row the next push writes to and `count` the number of valid
+/// rows (at most the capacity). Readers in other processes use it to find the
+/// newest data.
+pub const RING_HEAD_ATTRIBUTE: &str = "__ring_head__";
+
+/// Fixed-length dataset used as a ring buffer of rows
+///
+/// The dataset is 1D (`[capacity]`) or 2D (`[capacity, row_len]`) and is
+/// continuously overwritten; the write position is stored in
+/// [`RING_HEAD_ATTRIBUTE`]. Intended for rolling windows of live data, e.g.
+/// the last ten minutes of a monitoring signal.
+///
+/// Pushes update the cached head, so a buffer should have a single writer.
+/// Reads always fetch the current head from the server.
+#[derive(Clone)]
+pub struct RingBufferDataset<'a> {
+    client: &'a HsdsClient,
+    domain: String,
+    dataset_id: String,
+    capacity: u64,
+    row_len: Option<u64>,
+    head: u64,
+    count: u64,
+}
+
+impl<'a> RingBufferDataset<'a> {
+    /// Create a new ring buffer dataset linked into a group
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    /// * `parent_group_id` - UUID of the group to link the dataset into
+    /// * `name` - Link name of the dataset
+    /// * `hsds_type` - Element type (e.g. "H5T_IEEE_F64LE")
+    /// * `capacity` - Number of rows kept
+    /// * `row_len` - Row length for a 2D buffer, `None` for 1D
+    pub async fn create(
+        client: &'a HsdsClient,
+        domain: &str,
+        parent_group_id: &str,
+        name: &str,
+        hsds_type: &str,
+        capacity: u64,
+        row_len: Option<u64>,
+    ) -> HsdsResult<RingBufferDataset<'a>> {
+        if capacity == 0 {
+            return Err(HsdsError::invalid_param("Ring buffer capacity must be at least 1"));
+        }
+
+        let dims = std::iter::once(capacity).chain(row_len).collect();
+        let request = DatasetCreateRequest::from_hsds_type_with_link(hsds_type, dims, parent_group_id, name);
+        let dataset = client.datasets().create_dataset(domain, request).await?;
+
+        let ring = Self {
+            client,
+            domain: domain.to_string(),
+            dataset_id: dataset.id,
+            capacity,
+            row_len,
+            head: 0,
+            count: 0,
+        };
+        ring.store_head().await?;
+        Ok(ring)
+    }
+
+    /// Open an existing ring buffer dataset
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    /// * `dataset_id` - UUID of the dataset
+    pub async fn open(client: &'a HsdsClient, domain: &str, dataset_id: &str) -> HsdsResult<RingBufferDataset<'a>> {
+        let dataset = client.datasets().get_dataset(domain, dataset_id).await?;
+        let dims = dataset.shape
+            .and_then(|s| s.dims)
+            .ok_or_else(|| HsdsError::InvalidResponse(format!("Dataset {} has no dims", dataset_id)))?;
+
+        let (capacity, row_len) = match dims.as_slice() {
+            [capacity] => (*capacity, None),
+            [capacity, row_len] => (*capacity, Some(*row_len)),
+            _ => return Err(HsdsError::invalid_param(format!(
+                "Ring buffer dataset {} must be 1D or 2D, found dims {:?}", dataset_id, dims
+            ))),
+        };
+
+        let mut ring = Self {
+            client,
+            domain: domain.to_string(),
+            dataset_id: dataset_id.to_string(),
+            capacity,
+            row_len,
+            head: 0,
+            count: 0,
+        };
+        (ring.head, ring.count) = ring.load_head().await?;
+        Ok(ring)
+    }
+
+    /// UUID of the underlying dataset
+    pub fn dataset_id(&self) -> &str {
+        &self.dataset_id
+    }
+
+    /// Number of rows the buffer holds
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Number of valid rows as of the last push or open
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether nothing has been pushed yet
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Append rows, overwriting the oldest ones once the buffer is full
+    ///
+    /// If more rows than the capacity are pushed, only the newest are kept.
+    ///
+    /// # Arguments
+    /// * `rows` - Rows to append (elements for 1D, arrays of `row_len` for 2D)
+    pub async fn push(&mut self, rows: Vec<Value>) -> HsdsResult<()> {
+        if let Some(row_len) = self.row_len {
+            if let Some(bad) = rows.iter().find(|r| r.as_array().map(|a| a.len() as u64) != Some(row_len)) {
+                return Err(HsdsError::invalid_param(format!(
+                    "Ring buffer rows must be arrays of length {}, got {}", row_len, bad
+                )));
+            }
+        }
+
+        let pushed = rows.len() as u64;
+        let skip = pushed.saturating_sub(self.capacity);
+        let kept = pushed - skip;
+        let start = (self.head + skip) % self.capacity;
+
+        let mut rows = rows.into_iter().skip(skip as usize);
+        for (seg_start, seg_stop) in ring_segments(start, kept, self.capacity) {
+            let segment: Vec<Value> = rows.by_ref().take((seg_stop - seg_start) as usize).collect();
+            let (start, stop) = self.bounds(seg_start, seg_stop);
+            let request = DatasetValueRequest {
+                start: Some(start),
+                stop: Some(stop),
+                step: None,
+                points: None,
+                value: Some(Value::Array(segment)),
+                value_base64: None,
+            };
+            self.client.datasets().write_dataset_values(&self.domain, &self.dataset_id, request).await?;
+        }
+
+        self.head = (self.head + pushed) % self.capacity;
+        self.count = (self.count + pushed).min(self.capacity);
+        self.store_head().await
+    }
+
+    /// Read the newest `n` rows, oldest first
+    ///
+    /// # Arguments
+    /// * `n` - Number of rows (capped at the number of valid rows)
+    pub async fn latest(&self, n: u64) -> HsdsResult<Vec<Value>> {
+        let (head, count) = self.load_head().await?;
+        let n = n.min(count);
+        let start = (head + self.capacity - n) % self.capacity;
+        self.read_rows(start, n).await
+    }
+
+    /// Iterate over all valid rows, oldest first
+    pub async fn iter_chronological(&self) -> HsdsResult<std::vec::IntoIter<Value>> {
+        let (head, count) = self.load_head().await?;
+        let start = (head + self.capacity - count) % self.capacity;
+        Ok(self.read_rows(start, count).await?.into_iter())
+    }
+
+    /// Read `len` rows starting at ring position `start`
+    async fn read_rows(&self, start: u64, len: u64) -> HsdsResult<Vec<Value>> {
+        let mut rows = Vec::with_capacity(len as usize);
+        for (seg_start, seg_stop) in ring_segments(start, len, self.capacity) {
+            let select = match self.row_len {
+                Some(row_len) => format!("[{}:{},0:{}]", seg_start, seg_stop, row_len),
+                None => format!("[{}:{}]", seg_start, seg_stop),
+            };
+            let response = self.client.datasets()
+                .read_dataset_values_json(&self.domain, &self.dataset_id, Some(&select), None, None)
+                .await?;
+            match response.get("value") {
+                Some(Value::Array(values)) => rows.extend(values.iter().cloned()),
+                // A single-row selection of a 1D dataset may come back as a scalar
+                Some(value) if seg_stop - seg_start == 1 => rows.push(value.clone()),
+                _ => return Err(HsdsError::InvalidResponse(format!(
+                    "Unexpected value response for selection {}", select
+                ))),
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Start/stop coordinates for a row range
+    fn bounds(&self, start: u64, stop: u64) -> (Vec<u64>, Vec<u64>) {
+        match self.row_len {
+            Some(row_len) => (vec![start, 0], vec![stop, row_len]),
+            None => (vec![start], vec![stop]),
+        }
+    }
+
+    /// Fetch `[head, count]` from the state attribute
+    async fn load_head(&self) -> HsdsResult<(u64, u64)> {
+        let attr = self.client.attributes()
+            .get_attribute(&self.domain, "datasets", &self.dataset_id, RING_HEAD_ATTRIBUTE)
+            .await?;
+        let state: Option<Vec<u64>> = attr.get("value").and_then(|v| serde_json::from_value(v.clone()).ok());
+
+        match state.as_deref() {
+            Some(&[head, count]) if head < self.capacity && count <= self.capacity => Ok((head, count)),
+            _ => Err(HsdsError::InvalidResponse(format!(
+                "Invalid {} attribute on dataset {}", RING_HEAD_ATTRIBUTE, self.dataset_id
+            ))),
+        }
+    }
+
+    /// Write `[head, count]` to the state attribute, replacing the old value
+    async fn store_head(&self) -> HsdsResult<()> {
+        let state = serde_json::json!({
+            "type": "H5T_STD_U64LE",
+            "shape": [2],
+            "value": [self.head, self.count],
+        });
+        self.client.attributes()
+            .replace_attribute_raw(&self.domain, "datasets", &self.dataset_id, RING_HEAD_ATTRIBUTE, state)
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Split `len` rows starting at `start` into contiguous `[start, stop)` ranges
+/// of a ring of `capacity` rows (at most two)
+pub(crate) fn ring_segments(start: u64, len: u64, capacity: u64) -> Vec<(u64, u64)> {
+    if len == 0 {
+        return Vec::new();
+    }
+    let first_stop = (start + len).min(capacity);
+    let mut segments = vec![(start, first_stop)];
+    let wrapped = len - (first_stop - start);
+    if wrapped > 0 {
+        segments.push((0, wrapped));
+    }
+    segments
+}
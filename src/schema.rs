@@ -0,0 +1,205 @@
+//! Registry of committed datatypes shared by the datasets of a domain
+//!
+//! Record types derive [`Schema`] next to their
+//! [`ElementCodec`](crate::ElementCodec), which gives the compound layout. A
+//! [`SchemaRegistry`] commits each schema once per domain, records the
+//! committed type ids in the [`SCHEMA_ATTRIBUTE`] attribute of the root
+//! group, and reuses them when creating datasets, so every dataset of a
+//! record type shares one named type instead of carrying its own copy.
+//!
+//! ```
+//! use hsds_client::{schema::Schema, ElementCodec};
+//!
+//! #[derive(ElementCodec, Schema)]
+//! #[schema(name = "Reading")]
+//! struct SensorReading {
+//!     sensor: u32,
+//!     value: f64,
+//! }
+//!
+//! assert_eq!(SensorReading::NAME, "Reading");
+//! ```
+
+use crate::{
+    apis::conditional::UpdateOptions,
+    client::HsdsClient,
+    codec::ElementCodec,
+    error::{HsdsError, HsdsResult},
+    models::{Dataset, DatasetCreateRequest, H5Type, LinkRequest, ShapeSpec},
+};
+use log::{info, warn};
+use std::collections::BTreeMap;
+
+/// Root group attribute mapping schema names to committed type ids
+///
+/// Stored as a JSON object string, e.g. `{"Event": "t-1234..."}`.
+pub const SCHEMA_ATTRIBUTE: &str = "__schemas__";
+
+/// A record type with a fixed compound layout, usually derived
+///
+/// The committed type is the element type's [`ElementCodec::hsds_type`].
+pub trait Schema: ElementCodec {
+    /// Name the schema is registered under (unique within a domain)
+    const NAME: &'static str;
+}
+
+/// Derive macro implementing [`Schema`], named after the type or `#[schema(name = "...")]`
+pub use hsds_client_derive::Schema;
+
+/// Committed schema types of one domain
+///
+/// Concurrent registries committing the same new schema may each commit a
+/// type; the first one recorded wins, the others adopt its id and their
+/// own commits stay unused. Names registered by other writers since the
+/// registry was loaded are picked up whenever it records a schema.
+pub struct SchemaRegistry<'a> {
+    client: &'a HsdsClient,
+    domain: String,
+    root_id: String,
+    ids: BTreeMap<String, String>,
+}
+
+impl<'a> SchemaRegistry<'a> {
+    /// Load the registry of a domain
+    ///
+    /// # Arguments
+    /// * `client` - HSDS client
+    /// * `domain` - Domain path
+    pub async fn load(client: &'a HsdsClient, domain: &str) -> HsdsResult<SchemaRegistry<'a>> {
+        let root_id = client.domains().get_domain(domain).await?
+            .root
+            .ok_or_else(|| HsdsError::InvalidResponse(format!("Domain {} has no root group", domain)))?;
+
+        let ids = match client.attributes().get_attribute(domain, "groups", &root_id, SCHEMA_ATTRIBUTE).await {
+            Ok(attr) => schema_ids(&attr)?,
+            Err(HsdsError::ObjectNotFound(_)) => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            client,
+            domain: domain.to_string(),
+            root_id,
+            ids,
+        })
+    }
+
+    /// Committed type id of a registered schema
+    pub fn type_id(&self, name: &str) -> Option<&str> {
+        self.ids.get(name).map(String::as_str)
+    }
+
+    /// Names of all registered schemas
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.ids.keys().map(String::as_str)
+    }
+
+    /// Commit the schema's type if not yet registered and return its id
+    ///
+    /// The id returned is the one recorded in the domain, which is another
+    /// writer's if it registered the schema first.
+    pub async fn commit<S: Schema>(&mut self) -> HsdsResult<String> {
+        if let Some(id) = self.ids.get(S::NAME) {
+            return Ok(id.clone());
+        }
+
+        let id = self.client.datatypes()
+            .commit_datatype(&self.domain, serde_json::json!({"type": S::hsds_type()}))
+            .await?
+            .id;
+
+        self.record(S::NAME, id).await
+    }
+
+    /// Create a 1D dataset of the schema's committed type
+    ///
+    /// # Arguments
+    /// * `parent_group_id` - UUID of the group to link the dataset into
+    /// * `name` - Link name of the dataset
+    /// * `dims` - Dataset dimensions
+    /// * `maxdims` - Optional maximum dimensions (0 for unlimited)
+    pub async fn create_dataset<S: Schema>(
+        &mut self,
+        parent_group_id: &str,
+        name: &str,
+        dims: Vec<u64>,
+        maxdims: Option<Vec<u64>>,
+    ) -> HsdsResult<Dataset> {
+        let type_id = self.commit::<S>().await?;
+        let request = DatasetCreateRequest {
+            data_type: H5Type::named(type_id),
+            shape: Some(ShapeSpec::Dimensions(dims)),
+            maxdims,
+            creation_properties: None,
+            link: Some(LinkRequest {
+                id: parent_group_id.to_string(),
+                name: name.to_string(),
+            }),
+        };
+
+        self.client.datasets().create_dataset(&self.domain, request).await
+    }
+
+    /// Record a schema in the root group attribute, returning the id it maps to
+    ///
+    /// The stored map is read, merged with this registry's names and written
+    /// back with `If-Match` unless it already holds them all; a write losing
+    /// to a concurrent one re-reads the map and merges again. A name already
+    /// recorded keeps its stored id.
+    async fn record(&mut self, name: &str, id: String) -> HsdsResult<String> {
+        let attributes = self.client.attributes();
+        let options = UpdateOptions::default();
+        let mut backoff = options.backoff;
+        let mut attempts = 0;
+        loop {
+            let stored = attributes.get_attribute_tagged(&self.domain, "groups", &self.root_id, SCHEMA_ATTRIBUTE).await?;
+            let (mut ids, existing) = match stored {
+                Some((attr, etag)) => (schema_ids(&attr)?, Some(etag)),
+                None => (BTreeMap::new(), None),
+            };
+            let stored_count = ids.len();
+            for (known, known_id) in &self.ids {
+                ids.entry(known.clone()).or_insert_with(|| known_id.clone());
+            }
+            let recorded = ids.entry(name.to_string()).or_insert_with(|| id.clone()).clone();
+            if ids.len() == stored_count {
+                self.ids = ids;
+                return Ok(recorded);
+            }
+            if existing == Some(None) {
+                warn!("No ETag for the schema map of {}; writing without If-Match", self.domain);
+            }
+
+            let attr = serde_json::json!({
+                "type": String::hsds_type(),
+                "value": serde_json::to_string(&ids)?,
+            });
+            let existing = existing.as_ref().map(Option::as_deref);
+            if attributes
+                .put_attribute_if_match(&self.domain, "groups", &self.root_id, SCHEMA_ATTRIBUTE, attr, existing)
+                .await?
+            {
+                self.ids = ids;
+                return Ok(recorded);
+            }
+            if attempts >= options.max_retries {
+                return Err(HsdsError::Conflict(format!(
+                    "Schema map of {} kept changing while recording '{}'", self.domain, name
+                )));
+            }
+            attempts += 1;
+            info!("Schema map of {} changed while recording '{}'; retrying in {:?}", self.domain, name, backoff);
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+}
+
+/// Name → id map held by a schema attribute
+fn schema_ids(attr: &serde_json::Value) -> HsdsResult<BTreeMap<String, String>> {
+    Ok(attr.get("value")
+        .and_then(|v| v.as_str())
+        .map(serde_json::from_str)
+        .transpose()?
+        .unwrap_or_default())
+}
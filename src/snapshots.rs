@@ -0,0 +1,175 @@
+//! Client-side snapshots of a domain
+//!
+//! HSDS has no native snapshotting, so [`Snapshots`] follows a convention:
+//! the snapshots of `/home/user/file.h5` are full copies stored in the
+//! sibling folder `/home/user/file.h5.snapshots/`, one domain per snapshot,
+//! named by their UTC creation time (e.g. `2024-06-01T12:00:00Z`).
+//!
+//! Copies are made with [`GroupApi`](crate::GroupApi)'s group copy, so a
+//! snapshot costs as much as reading and rewriting the whole domain.
+
+use crate::{
+    apis::copy::{CopyJournal, CopyOptions},
+    client::HsdsClient,
+    domain_path::DomainPath,
+    error::{HsdsError, HsdsResult},
+};
+use futures_util::TryStreamExt;
+use log::{info, warn};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Suffix appended to a domain path to form its snapshot folder
+pub const SNAPSHOT_FOLDER_SUFFIX: &str = ".snapshots";
+
+/// A stored snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Snapshot name (last path segment)
+    pub name: String,
+    /// Full domain path of the snapshot
+    pub domain: String,
+    /// Creation time reported by the server, in seconds since the epoch
+    pub created: Option<f64>,
+}
+
+/// Snapshots of one domain
+pub struct Snapshots<'a> {
+    client: &'a HsdsClient,
+    domain: DomainPath,
+}
+
+impl<'a> Snapshots<'a> {
+    /// Snapshots of a file domain
+    pub fn new(client: &'a HsdsClient, domain: &str) -> HsdsResult<Self> {
+        Ok(Self { client, domain: DomainPath::file(domain)? })
+    }
+
+    /// Folder the snapshots are stored in (ends with '/')
+    pub fn folder(&self) -> String {
+        format!("{}{}/", self.domain, SNAPSHOT_FOLDER_SUFFIX)
+    }
+
+    /// Domain path of a named snapshot
+    pub fn snapshot_domain(&self, name: &str) -> HsdsResult<String> {
+        if name.is_empty() || name.contains('/') {
+            return Err(HsdsError::invalid_param(format!("Invalid snapshot name '{}'", name)));
+        }
+        Ok(format!("{}{}", self.folder(), name))
+    }
+
+    /// Snapshot the domain under its current UTC time
+    pub async fn create(&self) -> HsdsResult<Snapshot> {
+        self.create_named(&timestamp_name(SystemTime::now())).await
+    }
+
+    /// Snapshot the domain under a given name
+    ///
+    /// # Arguments
+    /// * `name` - Snapshot name; fails with `Conflict` if it already exists
+    pub async fn create_named(&self, name: &str) -> HsdsResult<Snapshot> {
+        let target = self.snapshot_domain(name)?;
+        info!("Creating snapshot {} of {}", name, self.domain);
+
+        match self.client.domains().create_folder(&self.folder()).await {
+            Ok(_) | Err(HsdsError::Conflict(_)) => {}
+            Err(e) => return Err(e),
+        }
+        let created = copy_domain(self.client, &self.domain, &target).await?;
+        Ok(Snapshot { name: name.to_string(), domain: target, created })
+    }
+
+    /// List the snapshots, oldest name first
+    ///
+    /// A domain without a snapshot folder has no snapshots.
+    pub async fn list(&self) -> HsdsResult<Vec<Snapshot>> {
+        if !self.client.domains().domain_exists(&self.folder()).await? {
+            return Ok(Vec::new());
+        }
+        let mut snapshots: Vec<Snapshot> = self.client.domains()
+            .find_domains(&self.folder(), None, false)
+            .map_ok(|entry| Snapshot {
+                name: entry.name.rsplit('/').next().unwrap_or_default().to_string(),
+                domain: entry.name,
+                created: entry.created,
+            })
+            .try_collect()
+            .await?;
+        snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(snapshots)
+    }
+
+    /// Replace the domain's contents with a snapshot
+    ///
+    /// The current state is snapshotted first and returned, so a restore
+    /// can itself be undone. The domain is then deleted, recreated and
+    /// filled from the snapshot; object ids change in the process.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the snapshot to restore
+    pub async fn restore(&self, name: &str) -> HsdsResult<Snapshot> {
+        let source = self.snapshot_domain(name)?;
+        if !self.client.domains().domain_exists(&source).await? {
+            return Err(HsdsError::DomainNotFound(format!("Snapshot '{}' of {}", name, self.domain)));
+        }
+        info!("Restoring {} from snapshot {}", self.domain, name);
+
+        let backup = self.create().await?;
+        self.client.domains().delete_domain(&self.domain).await?;
+        copy_domain(self.client, &source, &self.domain).await?;
+        Ok(backup)
+    }
+
+    /// Delete a snapshot
+    ///
+    /// # Arguments
+    /// * `name` - Name of the snapshot to delete
+    pub async fn delete(&self, name: &str) -> HsdsResult<()> {
+        self.client.domains().delete_domain(&self.snapshot_domain(name)?).await
+    }
+}
+
+/// Copy a whole domain into a new one, deleting the new domain on failure
+async fn copy_domain(client: &HsdsClient, source: &str, target: &str) -> HsdsResult<Option<f64>> {
+    let source_root = client.domains().get_domain(source).await?.root
+        .ok_or_else(|| HsdsError::InvalidResponse(format!("Domain {} has no root group", source)))?;
+    let created = client.domains().create_domain(target, None).await?;
+    let target_root = created.root
+        .ok_or_else(|| HsdsError::InvalidResponse(format!("Domain {} has no root group", target)))?;
+
+    let mut journal = CopyJournal::new();
+    let copied = match client.domains().await_created(target).await {
+        Ok(()) => client.groups()
+            .copy_contents(source, &source_root, target, &target_root, &mut journal, &CopyOptions::default())
+            .await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = copied {
+        if let Err(cleanup) = client.domains().delete_domain(target).await {
+            warn!("Failed to delete partial copy {}: {}", target, cleanup);
+        }
+        return Err(e);
+    }
+    Ok(created.created)
+}
+
+/// Snapshot name for a point in time, `YYYY-MM-DDTHH:MM:SSZ` in UTC
+pub(crate) fn timestamp_name(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60
+    )
+}
@@ -0,0 +1,143 @@
+//! Strict deserialization of responses
+//!
+//! By default responses are read leniently: fields a model does not know
+//! are dropped (or kept in its `extra` map), so a client keeps working
+//! against newer servers. With [`Strictness::Strict`] every JSON response
+//! is deserialized through a wrapper that notes each field the target type
+//! ignores, and the response fails with the full list. Missing required
+//! fields and unknown enum variants fail in both modes. Run CI in strict
+//! mode to learn about server changes before they are silently dropped.
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+
+/// How response bodies are matched against the types they are read into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Ignore fields the target type does not have
+    #[default]
+    Lenient,
+    /// Fail on fields the target type does not have
+    Strict,
+}
+
+impl Strictness {
+    pub fn is_strict(self) -> bool {
+        self == Strictness::Strict
+    }
+}
+
+/// Deserialize `value`, returning the paths (e.g. `links[0].created`) of fields `T` ignored
+pub(crate) fn from_value_strict<T: DeserializeOwned>(value: Value) -> Result<(T, Vec<String>), serde_json::Error> {
+    let ignored = RefCell::new(Vec::new());
+    let result = T::deserialize(Tracked { value, path: String::new(), ignored: &ignored })?;
+    Ok((result, ignored.into_inner()))
+}
+
+/// A JSON value whose ignored fields are recorded
+struct Tracked<'r> {
+    value: Value,
+    path: String,
+    ignored: &'r RefCell<Vec<String>>,
+}
+
+impl<'r> Tracked<'r> {
+    fn child(&self, value: Value, path: String) -> Self {
+        Tracked { value, path, ignored: self.ignored }
+    }
+
+    fn visit_object<'de, V: Visitor<'de>>(self, map: Map<String, Value>, visitor: V) -> Result<V::Value, serde_json::Error> {
+        visitor.visit_map(TrackedMap { entries: map.into_iter(), value: None, parent: self })
+    }
+
+    fn visit_array<'de, V: Visitor<'de>>(self, items: Vec<Value>, visitor: V) -> Result<V::Value, serde_json::Error> {
+        visitor.visit_seq(TrackedSeq { items: items.into_iter().enumerate(), parent: self })
+    }
+}
+
+impl<'de, 'r> de::Deserializer<'de> for Tracked<'r> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        match std::mem::take(&mut self.value) {
+            Value::Object(map) => self.visit_object(map, visitor),
+            Value::Array(items) => self.visit_array(items, visitor),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.ignored.borrow_mut().push(self.path);
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+struct TrackedMap<'r> {
+    entries: serde_json::map::IntoIter,
+    value: Option<(String, Value)>,
+    parent: Tracked<'r>,
+}
+
+impl<'de, 'r> MapAccess<'de> for TrackedMap<'r> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let result = seed.deserialize(IntoDeserializer::<serde_json::Error>::into_deserializer(key.as_str()))?;
+        self.value = Some((key, value));
+        Ok(Some(result))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (key, value) = self.value.take().ok_or_else(|| de::Error::custom("value requested before its key"))?;
+        let path = if self.parent.path.is_empty() { key } else { format!("{}.{}", self.parent.path, key) };
+        seed.deserialize(self.parent.child(value, path))
+    }
+}
+
+struct TrackedSeq<'r> {
+    items: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    parent: Tracked<'r>,
+}
+
+impl<'de, 'r> SeqAccess<'de> for TrackedSeq<'r> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        match self.items.next() {
+            Some((i, value)) => {
+                let path = format!("{}[{}]", self.parent.path, i);
+                seed.deserialize(self.parent.child(value, path)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
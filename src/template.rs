@@ -0,0 +1,186 @@
+//! Declarative domain layouts
+//!
+//! A [`DomainTemplate`] describes the groups, datasets and attributes a
+//! domain should contain. Templates are plain serde structures, so they can
+//! be built in code or loaded from JSON:
+//!
+//! ```json
+//! {
+//!   "attributes": {"facility": {"value": "NT-1"}},
+//!   "groups": {
+//!     "diagnostics": {
+//!       "datasets": {
+//!         "ip": {"type": "H5T_IEEE_F64LE", "shape": [0], "maxdims": [0],
+//!                "attributes": {"units": {"type": "H5T_STRING", "value": "A"}}},
+//!         "calibration": {"type": "H5T_IEEE_F64LE", "shape": "H5S_NULL"}
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! The same template can check an existing domain with
+//! `DomainApi::validate_domain`, which returns a [`ValidationReport`].
+
+use crate::{
+    error::{HsdsError, HsdsResult},
+    models::{DatasetCreateRequest, H5Type, LinkRequest, ShapeSpec},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Layout of a whole domain, rooted at its root group
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DomainTemplate {
+    #[serde(flatten)]
+    pub root: GroupTemplate,
+}
+
+/// Attributes and children of a group
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroupTemplate {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, AttributeTemplate>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, GroupTemplate>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub datasets: BTreeMap<String, DatasetTemplate>,
+}
+
+/// Type, shape and attributes of a dataset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetTemplate {
+    /// Predefined type name, committed type id or type definition
+    #[serde(rename = "type")]
+    pub data_type: serde_json::Value,
+    /// Dimensions, or `"H5S_NULL"` for a placeholder dataset without values
+    pub shape: ShapeSpec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maxdims: Option<Vec<u64>>,
+    #[serde(rename = "creationProperties", default, skip_serializing_if = "Option::is_none")]
+    pub creation_properties: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, AttributeTemplate>,
+}
+
+/// Initial value (and optionally type and shape) of an attribute
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeTemplate {
+    /// Attribute type; inferred from the value when omitted
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<Vec<u64>>,
+    pub value: serde_json::Value,
+}
+
+impl DomainTemplate {
+    /// Parse a template from a JSON string
+    pub fn from_json_str(json: &str) -> HsdsResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a template from a JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> HsdsResult<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            HsdsError::invalid_param(format!("Failed to read template {}: {}", path.display(), e))
+        })?;
+        Self::from_json_str(&json)
+    }
+}
+
+impl DatasetTemplate {
+    /// Dataset creation request linking the dataset as `name` under `parent_group_id`
+    pub fn create_request(&self, parent_group_id: &str, name: &str) -> HsdsResult<DatasetCreateRequest> {
+        Ok(DatasetCreateRequest {
+            data_type: self.type_spec()?,
+            shape: Some(self.shape.clone()),
+            maxdims: self.maxdims.clone(),
+            creation_properties: self.creation_properties.clone(),
+            link: Some(LinkRequest {
+                id: parent_group_id.to_string(),
+                name: name.to_string(),
+            }),
+        })
+    }
+
+    /// Typed form of the template's type
+    pub fn type_spec(&self) -> HsdsResult<H5Type> {
+        Ok(serde_json::from_value(self.data_type.clone())?)
+    }
+}
+
+impl AttributeTemplate {
+    /// Attribute body for `set_attribute_raw`, or `None` if the type is inferred
+    pub fn attribute_body(&self) -> Option<serde_json::Value> {
+        let data_type = self.data_type.as_ref()?;
+        let mut body = serde_json::json!({"type": data_type, "value": self.value});
+        if let Some(shape) = &self.shape {
+            body["shape"] = serde_json::json!(shape);
+        }
+        Some(body)
+    }
+}
+
+/// Kind of difference between a domain and its template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// A required group or dataset does not exist
+    MissingObject,
+    /// The link exists but refers to the wrong kind of object
+    WrongObjectKind,
+    /// The dataset type or shape does not match
+    DatasetMismatch,
+    /// A required attribute does not exist
+    MissingAttribute,
+    /// The attribute exists with a different type
+    AttributeTypeMismatch,
+}
+
+/// One difference found by template validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Path of the object (`/group/dataset`), with `@name` for attributes
+    pub path: String,
+    pub kind: ViolationKind,
+    pub detail: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.detail)
+    }
+}
+
+/// Result of validating a domain against a template
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Whether the domain matches the template
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, path: impl Into<String>, kind: ViolationKind, detail: impl Into<String>) {
+        self.violations.push(Violation {
+            path: path.into(),
+            kind,
+            detail: detail.into(),
+        });
+    }
+}
+
+/// Describe how an attribute's type differs from the template, if it does
+///
+/// Types are compared with [`types::mismatch`](crate::types::mismatch), so a
+/// type name matches the same type spelled as a class/base object and a
+/// bare class name matches any type of that class.
+pub(crate) fn attribute_type_mismatch(expected: &serde_json::Value, actual: &serde_json::Value) -> Option<String> {
+    crate::types::mismatch(expected, actual)
+}
@@ -0,0 +1,132 @@
+use crate::{
+    auth::{BasicAuth, NoAuth},
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+};
+use log::{debug, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Endpoint used when `HS_ENDPOINT` is not set
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:5101";
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Create a client from the h5pyd-style environment variables
+///
+/// * `HS_ENDPOINT` - server URL (defaults to `http://localhost:5101`)
+/// * `HS_USERNAME` / `HS_PASSWORD` - basic auth credentials (no auth if unset)
+pub fn client_from_env() -> HsdsResult<HsdsClient> {
+    let endpoint = std::env::var("HS_ENDPOINT").unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string());
+
+    match std::env::var("HS_USERNAME") {
+        Ok(username) => {
+            let password = std::env::var("HS_PASSWORD").unwrap_or_default();
+            HsdsClient::new(endpoint, BasicAuth::new(username, password))
+        }
+        Err(_) => HsdsClient::new(endpoint, NoAuth),
+    }
+}
+
+/// Generate a unique domain path in a folder
+///
+/// Names combine a nanosecond timestamp with a process-wide counter, so
+/// tests running in parallel never collide.
+///
+/// # Arguments
+/// * `folder` - Parent folder (e.g. "/home/admin")
+/// * `prefix` - File name prefix (e.g. "test_dataset")
+pub fn unique_domain_name(folder: &str, prefix: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}/{}_{}_{}.h5", folder.trim_end_matches('/'), prefix, timestamp, seq)
+}
+
+/// Temporary domain that is deleted when dropped, even if the test panics
+pub struct TestDomain {
+    client: HsdsClient,
+    path: String,
+    root: String,
+    keep: bool,
+}
+
+impl TestDomain {
+    /// Create a uniquely named domain in `folder`
+    ///
+    /// # Arguments
+    /// * `client` - Client used to create (and later delete) the domain
+    /// * `folder` - Parent folder (e.g. "/home/admin")
+    /// * `prefix` - File name prefix
+    pub async fn create(client: &HsdsClient, folder: &str, prefix: &str) -> HsdsResult<Self> {
+        let path = unique_domain_name(folder, prefix);
+        let domain = client.domains().create_domain(&path, None).await?;
+        client.domains().await_created(&path).await?;
+        let root = domain.root.ok_or_else(|| {
+            HsdsError::InvalidResponse(format!("Created domain '{}' has no root group", path))
+        })?;
+        debug!("Created test domain {}", path);
+
+        Ok(Self {
+            client: client.clone(),
+            path,
+            root,
+            keep: false,
+        })
+    }
+
+    /// Domain path
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Root group id
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Client the domain was created with
+    pub fn client(&self) -> &HsdsClient {
+        &self.client
+    }
+
+    /// Keep the domain after the guard is dropped (useful when debugging)
+    pub fn keep(mut self) -> Self {
+        self.keep = true;
+        self
+    }
+}
+
+impl Drop for TestDomain {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+
+        // Drop cannot be async and may run inside a current-thread runtime, so
+        // delete from a separate thread with its own runtime and connections.
+        let client = match self.client.with_new_connection_pool() {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to clean up test domain {}: {}", self.path, e);
+                return;
+            }
+        };
+        let path = self.path.clone();
+        let cleanup = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| HsdsError::OperationFailed(e.to_string()))?;
+            runtime.block_on(client.domains().delete_domain(&path))
+        });
+
+        match cleanup.join() {
+            Ok(Ok(())) => debug!("Deleted test domain {}", self.path),
+            Ok(Err(e)) => warn!("Failed to clean up test domain {}: {}", self.path, e),
+            Err(_) => warn!("Cleanup of test domain {} panicked", self.path),
+        }
+    }
+}
@@ -0,0 +1,996 @@
+use crate::{
+    apis::dataset::check_resize,
+    apis::domain::wildcard_match,
+    auth::{BasicAuth, BearerAuth, NoAuth},
+    client::{is_mutating, HsdsClient},
+    domain_path::DomainPath,
+    error::{ErrorCode, HsdsError},
+    logging::{redact_headers, LogOptions, SlowRequest, REDACTED},
+    models::{DatasetCreateRequest, Domain, DomainKind, H5Type, ResizeKind, Shape, ShapeSpec, TypeDef},
+    raw::RawWriteBuffer,
+    template::{attribute_type_mismatch, DomainTemplate},
+    ring_buffer::ring_segments,
+    strict::from_value_strict,
+    ingest::partition_rows,
+    values,
+};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+
+/// Credentials never appear in Debug output of auth types
+#[test]
+fn test_auth_debug_is_redacted() {
+    let basic = format!("{:?}", BasicAuth::new("admin", "s3cret"));
+    assert!(basic.contains("admin"));
+    assert!(!basic.contains("s3cret"));
+
+    let bearer = format!("{:?}", BearerAuth::new("tok-123"));
+    assert!(!bearer.contains("tok-123"));
+}
+
+/// Authorization headers are masked while other headers are kept
+#[test]
+fn test_redact_headers() {
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic YWRtaW46YWRtaW4="));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let rendered = redact_headers(&headers);
+    assert!(!rendered.contains("YWRtaW46YWRtaW4="));
+    assert!(rendered.contains(REDACTED));
+    assert!(rendered.contains("application/json"));
+}
+
+/// Payload logging is opt-in and capped in size
+#[test]
+fn test_payload_logging_opt_in_and_capped() {
+    let value = vec![42u32; 1000];
+
+    assert_eq!(LogOptions::default().payload(&value), REDACTED);
+
+    let options = LogOptions::new().with_payloads(true).with_max_payload_len(16);
+    let rendered = options.payload(&value);
+    assert!(rendered.starts_with("[42, 42, 42, 42,"));
+    assert!(rendered.contains("bytes total"));
+}
+
+/// Domain paths are normalized and folder/file forms are distinguished
+#[test]
+fn test_domain_path_normalization() {
+    let path = DomainPath::parse("//home//user/my file.h5").unwrap();
+    assert_eq!(path.as_str(), "/home/user/my file.h5");
+    assert!(!path.is_folder());
+    assert_eq!(path.name(), Some("my file.h5"));
+    assert_eq!(path.parent().unwrap().as_str(), "/home/user/");
+
+    let folder = DomainPath::folder("/home/user").unwrap();
+    assert_eq!(folder.as_str(), "/home/user/");
+    assert!(folder.is_folder());
+    assert_eq!(folder.join("shot_1.h5").unwrap().as_str(), "/home/user/shot_1.h5");
+
+    assert_eq!(DomainPath::parse("/").unwrap().as_str(), "/");
+    assert!(DomainPath::parse("/").unwrap().parent().is_none());
+}
+
+/// Invalid domain paths are rejected with a clear error
+#[test]
+fn test_domain_path_validation() {
+    assert!(DomainPath::parse("").is_err());
+    assert!(DomainPath::parse("file.h5").is_err());
+    assert!(DomainPath::parse("My Bucket/home/file.h5").is_err());
+    assert!(DomainPath::parse("hdf5:home/file.h5").is_err());
+    assert!(DomainPath::parse("/home/../etc").is_err());
+    assert!(DomainPath::parse("/home/a\nb").is_err());
+    assert!(DomainPath::file("/home/user/").is_err());
+}
+
+/// Domain paths are percent-encoded for the query string
+#[test]
+fn test_domain_path_encoding() {
+    let path = DomainPath::parse("/home/user/a b+c \u{e9}.h5").unwrap();
+    assert_eq!(path.encode(), "%2Fhome%2Fuser%2Fa%20b%2Bc%20%C3%A9.h5");
+}
+
+/// Bucket-qualified and hdf5:// forms are split and round-trip unchanged
+#[test]
+fn test_domain_path_bucket_forms() {
+    let qualified = DomainPath::parse("mybucket/home/user/file.h5").unwrap();
+    assert_eq!(qualified.bucket(), Some("mybucket"));
+    assert_eq!(qualified.path(), "/home/user/file.h5");
+    assert_eq!(qualified.as_str(), "mybucket/home/user/file.h5");
+    assert_eq!(qualified.parent().unwrap().as_str(), "mybucket/home/user/");
+
+    let uri = DomainPath::parse("hdf5://home/user/file.h5").unwrap();
+    assert_eq!(uri.bucket(), None);
+    assert_eq!(uri.path(), "/home/user/file.h5");
+    assert_eq!(uri.to_string(), "hdf5://home/user/file.h5");
+
+    let json = serde_json::to_string(&qualified).unwrap();
+    assert_eq!(json, "\"mybucket/home/user/file.h5\"");
+    let back: DomainPath = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, qualified);
+
+    let rebucketed = DomainPath::parse("/home/user/file.h5").unwrap().with_bucket("other").unwrap();
+    assert_eq!(rebucketed.as_str(), "other/home/user/file.h5");
+}
+
+/// Generated test domain names are unique and placed in the folder
+#[cfg(feature = "test-util")]
+/// Relative external link targets resolve against the linking domain's folder
+#[test]
+fn test_domain_path_resolve_relative_target() {
+    let linking = DomainPath::parse("mybucket/home/user/shot_1.h5").unwrap();
+    assert_eq!(linking.resolve("calib.h5").unwrap().as_str(), "mybucket/home/user/calib.h5");
+    assert_eq!(linking.resolve("./cal/gain.h5").unwrap().as_str(), "mybucket/home/user/cal/gain.h5");
+    assert_eq!(linking.resolve("../shared/base.h5").unwrap().as_str(), "mybucket/home/shared/base.h5");
+    assert_eq!(linking.resolve("/other/file.h5").unwrap().as_str(), "/other/file.h5");
+    assert_eq!(linking.resolve("other/dir/file.h5").unwrap().bucket(), Some("other"));
+    assert!(linking.resolve("../../../up.h5").is_err());
+
+    let folder = DomainPath::folder("/home/user").unwrap();
+    assert_eq!(folder.resolve("a.h5").unwrap().as_str(), "/home/user/a.h5");
+    assert!(DomainPath::is_relative_target("a.h5"));
+    assert!(!DomainPath::is_relative_target("bucket/a.h5"));
+}
+
+/// HTTPS base URLs are only accepted when a TLS backend is compiled in
+#[test]
+fn test_https_requires_tls_feature() {
+    let tls = cfg!(any(feature = "rustls-tls", feature = "native-tls"));
+    assert!(HsdsClient::new("http://localhost:5101", NoAuth).is_ok());
+    assert_eq!(HsdsClient::new("https://hsds.example.org", NoAuth).is_ok(), tls);
+    let custom = reqwest::Client::new();
+    assert_eq!(HsdsClient::with_client(custom, "https://hsds.example.org", NoAuth).is_ok(), tls);
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_unique_domain_name() {
+    use crate::test_util::unique_domain_name;
+
+    let a = unique_domain_name("/home/admin/", "test");
+    let b = unique_domain_name("/home/admin", "test");
+    assert_ne!(a, b);
+    assert!(a.starts_with("/home/admin/test_"));
+    assert!(a.ends_with(".h5"));
+    assert!(DomainPath::file(&a).is_ok());
+}
+
+/// Flat elements are nested row-major and flatten back unchanged
+#[test]
+fn test_values_nest_and_flatten() {
+    let flat: Vec<serde_json::Value> = (0..6).map(serde_json::Value::from).collect();
+    let nested = values::nest(flat.clone(), &[2, 3]).unwrap();
+    assert_eq!(nested, serde_json::json!([[0, 1, 2], [3, 4, 5]]));
+    assert_eq!(values::flatten(&nested), flat);
+    assert_eq!(values::shape_of(&nested), vec![2, 3]);
+
+    assert_eq!(values::nest(vec![serde_json::json!(7)], &[]).unwrap(), serde_json::json!(7));
+    assert_eq!(values::nest(vec![], &[2, 0]).unwrap(), serde_json::json!([[], []]));
+    assert!(values::nest(flat, &[4, 2]).is_err());
+}
+
+#[cfg(feature = "arbitrary")]
+proptest::proptest! {
+    /// Any generated case nests to its shape and flattens back to its elements
+    #[test]
+    fn prop_values_nest_round_trip(case in values::arbitrary::value_case(3, 5)) {
+        let nested = case.to_json();
+        proptest::prop_assert_eq!(values::shape_of(&nested), case.shape.clone());
+        proptest::prop_assert!(
+            values::arbitrary::compare_values(case.element_type, &nested, &serde_json::Value::Array(case.elements.clone())).is_ok()
+        );
+    }
+}
+
+/// Raw payloads are little-endian and the buffer allocation is reused
+#[test]
+fn test_raw_write_buffer_reuse() {
+    let mut buffer = RawWriteBuffer::with_capacity(8);
+
+    let first = buffer.encode(&[1i32, -2]).unwrap();
+    assert_eq!(first.as_ref(), &[1, 0, 0, 0, 0xfe, 0xff, 0xff, 0xff]);
+    let first_ptr = first.as_ptr();
+    drop(first);
+
+    let second = buffer.encode(&[1.5f64]).unwrap();
+    assert_eq!(second.as_ref(), &1.5f64.to_le_bytes());
+    assert_eq!(second.as_ptr(), first_ptr, "Allocation should be reused once the payload is dropped");
+    drop(second);
+
+    assert!(buffer.encode(&["no binary form".to_string()]).is_err());
+}
+
+/// Resizes are checked against rank and maxdims and classified
+#[test]
+fn test_check_resize() {
+    let unlimited = [0u64, 8];
+    assert_eq!(check_resize(&[4, 4], Some(&unlimited), &[10, 8]).unwrap(), ResizeKind::Grow);
+    assert_eq!(check_resize(&[4, 4], Some(&unlimited), &[2, 4]).unwrap(), ResizeKind::Shrink);
+    assert_eq!(check_resize(&[4, 4], Some(&unlimited), &[10, 2]).unwrap(), ResizeKind::Mixed);
+    assert_eq!(check_resize(&[4, 4], None, &[4, 4]).unwrap(), ResizeKind::Unchanged);
+
+    assert!(check_resize(&[4, 4], Some(&unlimited), &[4, 9]).is_err(), "Exceeds maxdims");
+    assert!(check_resize(&[4, 4], Some(&unlimited), &[4]).is_err(), "Rank mismatch");
+    assert!(check_resize(&[4, 4], None, &[5, 4]).is_err(), "Not resizable");
+}
+
+/// Ring ranges wrap at the capacity into at most two segments
+#[test]
+fn test_ring_segments() {
+    assert_eq!(ring_segments(2, 3, 10), vec![(2, 5)]);
+    assert_eq!(ring_segments(8, 5, 10), vec![(8, 10), (0, 3)]);
+    assert_eq!(ring_segments(0, 10, 10), vec![(0, 10)]);
+    assert!(ring_segments(4, 0, 10).is_empty());
+}
+
+/// Ingest partitions are whole chunk rows spread evenly, ending at the last row
+#[test]
+fn test_partition_rows() {
+    assert_eq!(partition_rows(100, 10, 4), vec![0, 30, 60, 80, 100]);
+    assert_eq!(partition_rows(95, 10, 2), vec![0, 50, 95]);
+    // Fewer chunk rows than workers
+    assert_eq!(partition_rows(25, 10, 8), vec![0, 10, 20, 25]);
+    assert_eq!(partition_rows(7, 0, 3), vec![0, 3, 5, 7]);
+    assert_eq!(partition_rows(7, 100, 3), vec![0, 7]);
+}
+
+/// The AIMD controller doubles in slow start, then grows additively and halves once per round
+#[test]
+fn test_aimd_controller() {
+    use crate::tuning::{AdaptiveTuning, AimdController, TuningState};
+    use std::time::Duration;
+
+    let fast = Duration::from_millis(10);
+    let slow = Duration::from_secs(5);
+    let tuning = AdaptiveTuning::new()
+        .with_initial_request_bytes(100)
+        .with_request_bytes_bounds(50, 1000)
+        .with_max_in_flight(4)
+        .with_target_latency(Duration::from_secs(1));
+    let mut aimd = AimdController::new(tuning);
+    assert_eq!(aimd.state(), TuningState { request_bytes: 100, in_flight: 1 });
+
+    aimd.on_success(aimd.generation(), fast);
+    assert_eq!(aimd.state(), TuningState { request_bytes: 200, in_flight: 2 });
+    // A round is as many fast requests as are in flight
+    aimd.on_success(aimd.generation(), fast);
+    assert_eq!(aimd.state().in_flight, 2);
+    aimd.on_success(aimd.generation(), fast);
+    assert_eq!(aimd.state(), TuningState { request_bytes: 400, in_flight: 4 });
+
+    // Requests of the same round congested together halve only once
+    let generation = aimd.generation();
+    aimd.on_success(generation, slow);
+    aimd.on_congestion(generation);
+    assert_eq!(aimd.state(), TuningState { request_bytes: 200, in_flight: 2 });
+
+    // After congestion growth is additive, by the initial size
+    aimd.on_success(aimd.generation(), fast);
+    aimd.on_success(aimd.generation(), fast);
+    assert_eq!(aimd.state(), TuningState { request_bytes: 300, in_flight: 3 });
+
+    for _ in 0..3 {
+        aimd.on_congestion(aimd.generation());
+    }
+    assert_eq!(aimd.state(), TuningState { request_bytes: 50, in_flight: 1 });
+}
+
+/// Element codecs map Rust types to HSDS types and round-trip JSON and binary values
+#[test]
+fn test_element_codecs() {
+    use crate::codec::{self, ElementCodec};
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, ElementCodec)]
+    struct Reading {
+        time: f64,
+        channel: u16,
+        valid: bool,
+    }
+
+    #[derive(Debug, PartialEq, ElementCodec)]
+    struct Labelled {
+        label: String,
+        value: i32,
+    }
+
+    assert_eq!(i16::hsds_type(), json!("H5T_STD_I16LE"));
+    assert_eq!(codec::decode_binary::<u32>(&codec::encode_binary(&[1u32, 70_000]).unwrap()).unwrap(), vec![1, 70_000]);
+    assert_eq!(bool::from_json(&json!(1)), Ok(true));
+    assert!(codec::encode_binary(&["a".to_string()]).is_err());
+
+    assert_eq!(Reading::BINARY_SIZE, Some(11));
+    assert_eq!(Reading::hsds_type()["class"], "H5T_COMPOUND");
+    assert_eq!(Reading::hsds_type()["fields"][2]["type"]["class"], "H5T_ENUM");
+    let readings = vec![
+        Reading { time: 0.5, channel: 3, valid: true },
+        Reading { time: 1.5, channel: 4, valid: false },
+    ];
+    let bytes = codec::encode_binary(&readings).unwrap();
+    assert_eq!(bytes.len(), 22);
+    assert_eq!(codec::decode_binary::<Reading>(&bytes).unwrap(), readings);
+    assert!(codec::decode_binary::<Reading>(&bytes[..21]).is_err());
+
+    let nested = codec::encode_json(&readings, &[2, 1]).unwrap();
+    assert_eq!(nested, json!([[[0.5, 3, 1]], [[1.5, 4, 0]]]));
+    assert_eq!(codec::decode_json::<Reading>(&nested, 2).unwrap(), readings);
+    let keyed = json!([{"time": 2.0, "channel": 1, "valid": true}]);
+    assert_eq!(codec::decode_json::<Reading>(&keyed, 1).unwrap()[0].channel, 1);
+
+    assert_eq!(Labelled::BINARY_SIZE, None);
+    assert!(Labelled::from_binary(&[0; 8]).is_err());
+    let error = codec::decode_json::<Labelled>(&json!([["a", 1], ["b", "x"]]), 1).unwrap_err();
+    assert_eq!(error.element_index(), Some(&[1][..]));
+    assert!(error.to_string().contains("field 'value'"), "{}", error);
+}
+
+/// Names HSDS cannot store are rejected with a reason, or mapped to valid ones
+#[test]
+fn test_validate_and_sanitize_names() {
+    use crate::names::{validate_name, NameSanitizer, MAX_NAME_BYTES};
+
+    assert!(validate_name("ch 01 [mV] µ").is_ok());
+    for bad in ["", ".", "..", "a/b", "tab\there", "nul\0"] {
+        let error = validate_name(bad).unwrap_err().to_string();
+        assert!(error.starts_with("Invalid parameter: Name '"), "{}", error);
+    }
+    assert!(validate_name(&"x".repeat(MAX_NAME_BYTES + 1)).is_err());
+
+    let sanitizer = NameSanitizer::new();
+    assert_eq!(sanitizer.sanitize("ch 01 [mV]"), "ch 01 [mV]");
+    assert_eq!(sanitizer.sanitize("in/out\n"), "in_out_");
+    assert_eq!(sanitizer.sanitize(".."), "_");
+    assert_eq!(NameSanitizer::new().with_replacement("-/-").sanitize("a/b"), "a--b");
+    assert_eq!(NameSanitizer::new().with_replacement("").sanitize("/"), "_");
+    let long = sanitizer.sanitize(&"é".repeat(MAX_NAME_BYTES));
+    assert!(long.len() <= MAX_NAME_BYTES && validate_name(&long).is_ok());
+}
+
+/// Templates parse from JSON with nested groups, typed datasets and attributes
+#[test]
+fn test_domain_template_from_json() {
+    let template = DomainTemplate::from_json_str(r#"{
+        "attributes": {"facility": {"value": "NT-1"}},
+        "groups": {
+            "diagnostics": {
+                "datasets": {
+                    "ip": {"type": "H5T_IEEE_F64LE", "shape": [0], "maxdims": [0]},
+                    "calibration": {"type": "H5T_IEEE_F64LE", "shape": "H5S_NULL"},
+                    "label": {"type": {"class": "H5T_STRING", "charSet": "H5T_CSET_UTF8",
+                              "strPad": "H5T_STR_NULLTERM", "length": "H5T_VARIABLE"}, "shape": [4]}
+                }
+            }
+        }
+    }"#).unwrap();
+
+    assert!(template.root.attributes["facility"].attribute_body().is_none(), "Type is inferred");
+    let diagnostics = &template.root.groups["diagnostics"];
+    let request = diagnostics.datasets["ip"].create_request("g-1", "ip").unwrap();
+    assert!(matches!(request.data_type, H5Type::Named(ref t) if t == "H5T_IEEE_F64LE"));
+    assert_eq!(request.link.unwrap().name, "ip");
+    assert!(matches!(diagnostics.datasets["label"].type_spec().unwrap(), H5Type::Defined(TypeDef::String { .. })));
+    let placeholder = diagnostics.datasets["calibration"].create_request("g-1", "calibration").unwrap();
+    assert_eq!(placeholder.shape, Some(ShapeSpec::null()));
+
+    assert!(DomainTemplate::from_json_str(r#"{"datasets": {"x": {"shape": [1]}}}"#).is_err());
+}
+
+/// Null and zero-length dataspaces hold no values; scalars hold one
+#[test]
+fn test_shape_element_count() {
+    let shape = |class: &str, dims: Option<Vec<u64>>| Shape { class: class.to_string(), dims, maxdims: None };
+    assert!(shape("H5S_NULL", None).is_empty());
+    assert!(shape("H5S_SIMPLE", Some(vec![0, 8])).is_empty());
+    assert_eq!(shape("H5S_SCALAR", None).element_count(), 1);
+    assert_eq!(shape("H5S_SIMPLE", Some(vec![3, 4])).element_count(), 12);
+    assert!(!shape("H5S_SCALAR", None).is_null());
+}
+
+/// The h5py boolean enum and 8-bit integers hold booleans; wider types do not
+#[test]
+fn test_is_bool_type() {
+    assert!(values::is_bool_type(&values::bool_type()));
+    assert!(values::is_bool_type(&serde_json::json!({"class": "H5T_INTEGER", "base": "H5T_STD_U8LE"})));
+    assert!(values::is_bool_type(&serde_json::json!("H5T_STD_I8LE")));
+    assert!(!values::is_bool_type(&serde_json::json!({"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"})));
+    let mut colors = values::bool_type();
+    colors["mapping"] = serde_json::json!({"RED": 0, "GREEN": 1});
+    assert!(!values::is_bool_type(&colors));
+}
+
+/// Complex types are {r, i} compounds of one float type
+#[test]
+fn test_complex_base() {
+    assert_eq!(values::complex_base(&values::complex_type("H5T_IEEE_F32LE")), Some("H5T_IEEE_F32LE"));
+    let defined = serde_json::json!({"class": "H5T_COMPOUND", "fields": [
+        {"name": "r", "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"}},
+        {"name": "i", "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"}}
+    ]});
+    assert_eq!(values::complex_base(&defined), Some("H5T_IEEE_F64LE"));
+    let swapped = serde_json::json!({"class": "H5T_COMPOUND", "fields": [
+        {"name": "i", "type": "H5T_IEEE_F32LE"}, {"name": "r", "type": "H5T_IEEE_F32LE"}
+    ]});
+    assert_eq!(values::complex_base(&swapped), None);
+    let mixed = serde_json::json!({"class": "H5T_COMPOUND", "fields": [
+        {"name": "r", "type": "H5T_IEEE_F32LE"}, {"name": "i", "type": "H5T_IEEE_F64LE"}
+    ]});
+    assert_eq!(values::complex_base(&mixed), None);
+}
+
+/// Time encodings round-trip instants and are named by their units
+#[cfg(feature = "chrono")]
+#[test]
+fn test_time_encoding_round_trip() {
+    use crate::TimeEncoding;
+    use chrono::{TimeZone, Utc};
+
+    let time = Utc.timestamp_opt(1_714_564_800, 250_000_000).unwrap();
+    for encoding in [TimeEncoding::EpochSeconds, TimeEncoding::EpochNanoseconds, TimeEncoding::Iso8601] {
+        let stored = encoding.encode(&time).unwrap();
+        assert_eq!(encoding.decode(&stored).unwrap(), time, "{:?}", encoding);
+        assert_eq!(TimeEncoding::from_units(encoding.units()), Some(encoding));
+    }
+    assert_eq!(TimeEncoding::Iso8601.encode(&time).unwrap(), serde_json::json!("2024-05-01T12:00:00.250Z"));
+    assert_eq!(TimeEncoding::from_units("seconds since 1970-01-01 00:00:00"), Some(TimeEncoding::EpochSeconds));
+    assert_eq!(TimeEncoding::from_units("ns"), Some(TimeEncoding::EpochNanoseconds));
+    assert_eq!(TimeEncoding::from_units("days since 2000-01-01"), None);
+}
+
+/// Scaling applies scale_factor then add_offset, defaulting to the identity
+#[test]
+fn test_scaling_from_attributes() {
+    use crate::Scaling;
+    use std::collections::HashMap;
+
+    let mut attributes = HashMap::new();
+    assert!(Scaling::from_attributes(&attributes).unwrap().is_identity());
+    attributes.insert("scale_factor".to_string(), serde_json::json!([0.5]));
+    attributes.insert("add_offset".to_string(), serde_json::json!(10));
+    let scaling = Scaling::from_attributes(&attributes).unwrap();
+    assert_eq!(scaling, Scaling::new(0.5, 10.0));
+    assert_eq!(scaling.apply(4.0), 12.0);
+
+    let mut nested = serde_json::json!([[2, 4], [null, 6]]);
+    scaling.apply_json(&mut nested);
+    assert_eq!(nested, serde_json::json!([[11.0, 12.0], [null, 13.0]]));
+
+    attributes.insert("scale_factor".to_string(), serde_json::json!("half"));
+    assert!(Scaling::from_attributes(&attributes).is_err());
+}
+
+/// The value parser yields elements across arbitrary chunk boundaries
+#[test]
+fn test_value_array_parser_chunks() {
+    use crate::json_stream::ValueArrayParser;
+
+    let body = br#"{"hrefs": [{"rel": "self", "href": "value"}], "value": [[1, -2.5e1], [3 , 4]]}"#;
+    for chunk_size in [1, 3, 7, body.len()] {
+        let mut parser = ValueArrayParser::<f64>::new(2);
+        body.chunks(chunk_size).try_for_each(|chunk| parser.feed(chunk)).unwrap();
+        let (values, dims) = parser.finish().unwrap();
+        assert_eq!(values, vec![1.0, -25.0, 3.0, 4.0]);
+        assert_eq!(dims, vec![2, 2]);
+    }
+
+    // Compound elements are arrays at the dataset's rank
+    let mut parser = ValueArrayParser::<(i32, String)>::new(1);
+    parser.feed(br#"{"value": [[1, "a,]"], [2, "\"b\""]]}"#).unwrap();
+    assert_eq!(parser.finish().unwrap().0, vec![(1, "a,]".to_string()), (2, "\"b\"".to_string())]);
+
+    let mut parser = ValueArrayParser::<i64>::new(0);
+    parser.feed(br#"{"value": 42}"#).unwrap();
+    assert_eq!(parser.finish().unwrap(), (vec![42], vec![]));
+
+    let mut parser = ValueArrayParser::<i64>::new(1);
+    parser.feed(br#"{"value": [1, 2"#).unwrap();
+    assert!(parser.finish().is_err());
+    let mut parser = ValueArrayParser::<i64>::new(2);
+    assert!(parser.feed(br#"{"value": [1, 2]}"#).is_err());
+}
+
+/// Conversion errors report the multi-dimensional index and the offending element
+#[test]
+fn test_conversion_error_index() {
+    use crate::json_stream::ValueArrayParser;
+
+    let nested = serde_json::json!([[1, 2, 3], [4, "x", 6]]);
+    let error = values::convert_nested::<u8>(&nested, 2).unwrap_err();
+    assert_eq!(error.element_index(), Some(&[1, 1][..]));
+    assert!(error.to_string().contains("\"x\""), "{}", error);
+    assert_eq!(values::convert_nested::<u8>(&nested[0], 1).unwrap(), vec![1, 2, 3]);
+    assert_eq!(values::convert_nested::<(u8, u8)>(&serde_json::json!([[1, 2]]), 1).unwrap(), vec![(1, 2)]);
+    assert!(values::convert_nested::<u8>(&serde_json::json!([1, 2]), 2).is_err());
+
+    let mut parser = ValueArrayParser::<u8>::new(2);
+    let error = parser.feed(br#"{"value": [[1, 2], [3, 4], [5, 300]]}"#).unwrap_err();
+    assert_eq!(error.element_index(), Some(&[2, 1][..]));
+    assert!(matches!(error, HsdsError::Conversion { ref value, .. } if value == "300"));
+
+    assert_eq!(values::unravel_index(5, &[2, 3]), vec![1, 2]);
+    assert_eq!(values::unravel_index(0, &[]), Vec::<u64>::new());
+    let long = HsdsError::conversion(vec![0], "y".repeat(500), "bad");
+    assert!(long.to_string().len() < 200);
+}
+
+/// Special floats encoded as strings or null are read where configured
+#[test]
+fn test_value_array_parser_special_floats() {
+    use crate::json_stream::ValueArrayParser;
+    use crate::values::SpecialFloats;
+
+    let body = br#"{"value": [1.5, "NaN", null, "-Infinity"]}"#;
+    let mut strict = ValueArrayParser::<f64>::new(1);
+    assert!(strict.feed(body).is_err());
+
+    let mut tolerant = ValueArrayParser::<f32>::new(1).with_special_floats(SpecialFloats::tolerant());
+    tolerant.feed(body).unwrap();
+    let (values, _) = tolerant.finish().unwrap();
+    assert_eq!(values[0], 1.5);
+    assert!(values[1].is_nan() && values[2].is_nan());
+    assert_eq!(values[3], f32::NEG_INFINITY);
+
+    // Null stays None for optional elements; integers never accept special floats
+    let mut optional = ValueArrayParser::<Option<f64>>::new(1).with_special_floats(SpecialFloats::tolerant());
+    optional.feed(br#"{"value": [null, 2.0]}"#).unwrap();
+    assert_eq!(optional.finish().unwrap().0, vec![None, Some(2.0)]);
+    let mut integers = ValueArrayParser::<i64>::new(1).with_special_floats(SpecialFloats::tolerant());
+    assert!(integers.feed(br#"{"value": [1, "NaN"]}"#).is_err());
+
+    let strings_only = SpecialFloats::new().with_strings(true);
+    assert!(strings_only.parse(&serde_json::Value::Null).is_none());
+    assert_eq!(strings_only.parse(&serde_json::json!("Infinity")), Some(f64::INFINITY));
+    assert_eq!(SpecialFloats::new().with_null_as(0.0).parse(&serde_json::Value::Null), Some(0.0));
+}
+
+/// Attribute types match by name against the base type, or by class
+#[test]
+fn test_attribute_type_mismatch() {
+    let actual = serde_json::json!({"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"});
+    assert!(attribute_type_mismatch(&serde_json::json!("H5T_STD_I32LE"), &actual).is_none());
+    assert!(attribute_type_mismatch(&serde_json::json!({"class": "H5T_INTEGER"}), &actual).is_none());
+    assert!(attribute_type_mismatch(&serde_json::json!("H5T_IEEE_F64LE"), &actual).is_some());
+    assert!(attribute_type_mismatch(&serde_json::json!({"class": "H5T_STRING"}), &actual).is_some());
+}
+
+/// Paths hint at their domain kind and reject contradicting requests
+#[test]
+fn test_domain_kind_hints() {
+    let file = DomainPath::parse("/home/user/shot.H5").unwrap();
+    assert_eq!(file.kind_hint(), Some(DomainKind::File));
+    assert!(file.check_kind(DomainKind::Folder).is_err());
+    assert!(file.check_kind(DomainKind::File).is_ok());
+
+    let folder = DomainPath::parse("/home/user/runs/").unwrap();
+    assert_eq!(folder.kind_hint(), Some(DomainKind::Folder));
+    assert!(folder.check_kind(DomainKind::File).is_err());
+
+    let ambiguous = DomainPath::parse("/home/user/runs").unwrap();
+    assert_eq!(ambiguous.kind_hint(), None);
+    assert!(ambiguous.check_kind(DomainKind::Folder).is_ok());
+    assert!(ambiguous.check_kind(DomainKind::File).is_ok());
+
+    let folder_domain: Domain = serde_json::from_value(serde_json::json!({"owner": "admin"})).unwrap();
+    assert!(folder_domain.is_folder());
+    let file_domain: Domain = serde_json::from_value(serde_json::json!({"root": "g-1"})).unwrap();
+    assert_eq!(file_domain.kind(), DomainKind::File);
+}
+
+/// Domain name patterns support '*' and '?' wildcards
+#[test]
+fn test_wildcard_match() {
+    assert!(wildcard_match("*.h5", "shot_1.h5"));
+    assert!(wildcard_match("shot_?.h5", "shot_1.h5"));
+    assert!(wildcard_match("*_*.h5", "a_b_c.h5"));
+    assert!(wildcard_match("*", ""));
+    assert!(!wildcard_match("*.h5", "shot_1.hdf5"));
+    assert!(!wildcard_match("shot_?.h5", "shot_10.h5"));
+    assert!(wildcard_match("chan[0-9]*", "chan12"));
+    assert!(!wildcard_match("chan[0-9]*", "chan_x"));
+    assert!(wildcard_match("cal_[!xy]", "cal_z"));
+    assert!(!wildcard_match("cal_[!xy]", "cal_x"));
+    assert!(wildcard_match("[]]", "]"));
+    assert!(wildcard_match("a[b", "a[b"));
+}
+
+/// Epoch-second timestamps convert to UTC and filter by time range
+#[cfg(feature = "chrono")]
+#[test]
+fn test_timestamps_and_ranges() {
+    use crate::{TimeRange, Timestamped};
+    use chrono::{TimeZone, Utc};
+
+    let domain: Domain = serde_json::from_value(serde_json::json!({
+        "root": "g-1", "created": 1704067200.5, "lastModified": 1704153600.0
+    })).unwrap();
+    let created = domain.created_at().unwrap();
+    assert_eq!(created, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + chrono::Duration::milliseconds(500));
+
+    let january = TimeRange::between(
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap(),
+    );
+    assert!(january.contains(domain.last_modified_at().unwrap()));
+    assert!(!january.contains(Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap()), "End is exclusive");
+    assert!(TimeRange::default().contains(created));
+    assert!(crate::to_datetime(f64::NAN).is_none());
+}
+
+/// Unknown response fields are kept and survive a serde round trip
+#[test]
+fn test_models_capture_extra_fields() {
+    let json = serde_json::json!({
+        "id": "g-1",
+        "linkCount": 2,
+        "compressors": ["blosclz", "zstd"],
+        "futureFlag": true
+    });
+    let group: crate::models::Group = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(group.link_count, Some(2));
+    assert!(!group.extra.contains_key("linkCount"), "Modelled fields are not duplicated");
+    assert_eq!(group.extra_field::<Vec<String>>("compressors"), Some(vec!["blosclz".to_string(), "zstd".to_string()]));
+    assert_eq!(group.extra_field::<bool>("futureFlag"), Some(true));
+    assert_eq!(group.extra_field::<u32>("futureFlag"), None);
+
+    let back = serde_json::to_value(&group).unwrap();
+    assert_eq!(back["compressors"], json["compressors"]);
+}
+
+/// Chunk recommendations stay near the target size for both access patterns
+#[test]
+fn test_chunking_recommend() {
+    use crate::chunking::{recommend, type_size, AccessPattern};
+
+    // Rows of 1000 f64 values: whole rows, as many as fit in 1 MiB
+    let rows = recommend(&[0, 1000], 8, 1024 * 1024, AccessPattern::RowStreaming);
+    assert_eq!(rows, vec![131, 1000]);
+
+    // Small datasets are a single chunk
+    assert_eq!(recommend(&[10, 10], 4, 1024 * 1024, AccessPattern::Hyperslab), vec![10, 10]);
+    assert_eq!(recommend(&[10, 10], 4, 1024 * 1024, AccessPattern::RowStreaming), vec![10, 10]);
+
+    // Large volumes are split evenly across axes
+    let cube = recommend(&[4096, 4096, 4096], 4, 1024 * 1024, AccessPattern::Hyperslab);
+    assert_eq!(cube, vec![64, 64, 64]);
+    assert!(cube.iter().product::<u64>() * 4 <= 1024 * 1024);
+
+    assert!(recommend(&[], 8, 1024, AccessPattern::Hyperslab).is_empty());
+    assert_eq!(recommend(&[100], 8, 1, AccessPattern::Hyperslab), vec![1]);
+
+    assert_eq!(type_size(&H5Type::named("H5T_IEEE_F64LE")), Some(8));
+    assert_eq!(type_size(&H5Type::named("H5T_STD_U16BE")), Some(2));
+    assert_eq!(type_size(&H5Type::fixed_ascii(12)), Some(12));
+    assert_eq!(type_size(&H5Type::variable_utf8()), None);
+
+    let request = DatasetCreateRequest::from_hsds_type("H5T_IEEE_F32LE", vec![1_000_000, 64])
+        .auto_chunk(AccessPattern::RowStreaming);
+    let layout = &request.creation_properties.unwrap()["layout"];
+    assert_eq!(layout["class"], "H5D_CHUNKED");
+    assert_eq!(layout["dims"], serde_json::json!([8192, 64]));
+}
+
+/// Point selection POSTs are reads; everything else but GET/HEAD mutates
+#[test]
+fn test_is_mutating() {
+    use reqwest::Method;
+
+    assert!(!is_mutating(&Method::GET, "/datasets/d-1/value"));
+    assert!(!is_mutating(&Method::HEAD, "/"));
+    assert!(!is_mutating(&Method::POST, "/datasets/d-1/value"));
+    assert!(is_mutating(&Method::POST, "/datasets"));
+    assert!(is_mutating(&Method::PUT, "/datasets/d-1/value"));
+    assert!(is_mutating(&Method::DELETE, "/"));
+}
+
+/// Domain creation options serialize only what was set
+#[test]
+fn test_domain_create_request_builder() {
+    use crate::models::DomainCreateRequest;
+
+    assert_eq!(serde_json::to_value(DomainCreateRequest::new()).unwrap(), serde_json::json!({}));
+    assert_eq!(serde_json::to_value(DomainCreateRequest::folder()).unwrap(), serde_json::json!({"folder": 1}));
+
+    let request = DomainCreateRequest::new()
+        .with_owner("alice")
+        .with_linked_domain("/shared/base.h5", Some("archive"));
+    assert!(!request.is_folder());
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        serde_json::json!({"owner": "alice", "linked_domain": "/shared/base.h5", "linked_bucket": "archive"})
+    );
+}
+
+/// Snapshot names are UTC timestamps that sort chronologically
+#[test]
+fn test_snapshot_timestamp_name() {
+    use crate::snapshots::timestamp_name;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    assert_eq!(timestamp_name(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    assert_eq!(timestamp_name(UNIX_EPOCH + Duration::from_secs(1_717_243_200)), "2024-06-01T12:00:00Z");
+    assert_eq!(timestamp_name(UNIX_EPOCH + Duration::from_secs(951_782_399)), "2000-02-28T23:59:59Z");
+    assert_eq!(timestamp_name(UNIX_EPOCH + Duration::from_secs(951_868_800)), "2000-03-01T00:00:00Z");
+}
+
+/// Page sizes shrink on slow pages, grow on fast full ones, within bounds
+#[test]
+fn test_pagination_adapt() {
+    use crate::pagination::PaginationOptions;
+    use std::time::Duration;
+
+    let options = PaginationOptions::new()
+        .with_page_size_bounds(100, 1000)
+        .with_target_latency(Duration::from_millis(200));
+    assert_eq!(options.adapt(400, 400, Duration::from_millis(50)), 800);
+    assert_eq!(options.adapt(800, 800, Duration::from_millis(50)), 1000);
+    assert_eq!(options.adapt(400, 10, Duration::from_millis(50)), 400);
+    assert_eq!(options.adapt(400, 400, Duration::from_millis(150)), 400);
+    assert_eq!(options.adapt(400, 400, Duration::from_millis(500)), 200);
+    assert_eq!(options.adapt(150, 150, Duration::from_secs(2)), 100);
+    assert_eq!(PaginationOptions::fixed(50).adapt(50, 50, Duration::ZERO), 50);
+}
+
+/// Attribute predicates compare by type and unwrap one-element arrays
+#[test]
+fn test_attribute_predicate_matches() {
+    use crate::AttributePredicate;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    let attributes: HashMap<String, serde_json::Value> = HashMap::from([
+        ("sensor".to_string(), json!("bolometer")),
+        ("gain".to_string(), json!([3])),
+        ("calibrated".to_string(), json!(true)),
+    ]);
+
+    assert!(AttributePredicate::eq("sensor", "bolometer").matches(&attributes));
+    assert!(AttributePredicate::gt("gain", 2.5).and(AttributePredicate::le("gain", 3)).matches(&attributes));
+    assert!(AttributePredicate::eq("calibrated", true).matches(&attributes));
+    assert!(AttributePredicate::contains("sensor", "bolo").matches(&attributes));
+    assert!(AttributePredicate::exists("missing").or(AttributePredicate::exists("gain")).matches(&attributes));
+    assert!(!AttributePredicate::gt("calibrated", false).matches(&attributes));
+    assert!(!AttributePredicate::ne("sensor", 1).matches(&attributes));
+    assert!(AttributePredicate::exists("missing").negate().matches(&attributes));
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn assert_send<T: Send>(_: &T) {}
+
+/// The client, its API wrappers and handles can be shared across tasks
+#[test]
+fn test_client_and_handles_are_send_sync() {
+    use crate::*;
+
+    assert_send_sync::<HsdsClient>();
+    assert_send_sync::<ClientOptions>();
+    assert_send_sync::<HsdsError>();
+    assert_send_sync::<DomainApi<'static>>();
+    assert_send_sync::<GroupApi<'static>>();
+    assert_send_sync::<LinkApi<'static>>();
+    assert_send_sync::<DatasetApi<'static>>();
+    assert_send_sync::<DatatypeApi<'static>>();
+    assert_send_sync::<AttributeApi<'static>>();
+    assert_send_sync::<DatasetHandle<'static>>();
+    assert_send_sync::<GroupHandle<'static>>();
+    assert_send_sync::<PrefetchedRegion>();
+    assert_send_sync::<BufferedWriter<'static, f64>>();
+    assert_send_sync::<RecordLogWriter<'static>>();
+    assert_send_sync::<RingBufferDataset<'static>>();
+    assert_send_sync::<ParallelIngest<'static>>();
+    assert_send_sync::<UploadHandle>();
+    assert_send_sync::<Snapshots<'static>>();
+    assert_send_sync::<CatalogBuilder<'static>>();
+    assert_send_sync::<schema::SchemaRegistry<'static>>();
+    assert_send_sync::<DryRunLog>();
+    assert_send_sync::<TransferStats>();
+    assert_send_sync::<ExternalLinkOptions>();
+    assert_send_sync::<DomainPath>();
+}
+
+/// Futures and streams of a shared client can be moved into spawned tasks
+#[test]
+fn test_client_futures_are_send() {
+    use crate::models::LinkOrder;
+
+    let client = HsdsClient::new("http://localhost:5101", NoAuth).unwrap();
+    assert_send(&client.domains().get_domain("/home/test/file.h5"));
+    assert_send(&client.domains().find_domains("/home/", Some("*.h5"), true));
+    assert_send(&client.datasets().open("/home/test/file.h5", "d-1"));
+    assert_send(&client.datasets().read_dataset_values_streamed::<f64>("/home/test/file.h5", "d-1", None));
+    assert_send(&client.datasets().write_region_raw("/home/test/file.h5", "d-1", None, Vec::new(), "application/octet-stream"));
+    assert_send(&client.links().links_stream("/home/test/file.h5", "g-1", LinkOrder::Name));
+    assert_send(&client.attributes().get_attribute("/home/test/file.h5", "groups", "g-1", "a"));
+    assert_send(&client.groups().copy_group(
+        "/a.h5", "g-1", "/b.h5", "g-2", "copy", &mut Default::default(), &Default::default(),
+    ));
+}
+
+/// Strict deserialization reports ignored fields by path and keeps `extra` maps
+#[test]
+fn test_from_value_strict() {
+    use crate::models::{Href, Link};
+    use serde_json::json;
+
+    let (hrefs, ignored) = from_value_strict::<Vec<Href>>(json!([
+        {"href": "http://h/groups/g-1", "rel": "self"},
+        {"href": "http://h/", "rel": "home", "title": {"nested": [1, 2]}}
+    ])).unwrap();
+    assert_eq!(hrefs.len(), 2);
+    assert_eq!(ignored, vec!["[1].title"]);
+
+    let (link, ignored) = from_value_strict::<Option<Link>>(json!({
+        "class": "H5L_TYPE_HARD", "title": "data", "id": "d-1", "linkVersion": 2
+    })).unwrap();
+    let link = link.unwrap();
+    assert!(ignored.is_empty());
+    assert_eq!(link.extra["linkVersion"], 2);
+
+    // Missing required fields and unknown variants fail regardless
+    assert!(from_value_strict::<Href>(json!({"href": "x"})).is_err());
+    assert!(from_value_strict::<Link>(json!({"class": "H5L_TYPE_NEW", "title": "t"})).is_err());
+}
+
+/// Error codes classify errors by kind and status, not message
+#[test]
+fn test_error_codes() {
+    assert_eq!(HsdsError::DomainNotFound("/a.h5".into()).error_code(), ErrorCode::DomainNotFound);
+    assert_eq!(HsdsError::api_error(413, "too big").error_code(), ErrorCode::PayloadTooLarge);
+    assert_eq!(HsdsError::api_error(429, "slow down").error_code(), ErrorCode::Throttled);
+    assert_eq!(HsdsError::api_error(502, "").error_code(), ErrorCode::ServerError);
+    assert_eq!(HsdsError::api_error(418, "").error_code(), ErrorCode::ApiError);
+    assert_eq!(HsdsError::conversion(vec![1], "x", "not a number").error_code(), ErrorCode::ConversionFailed);
+    assert_eq!(HsdsError::Cancelled.error_code().to_string(), "cancelled");
+    assert_eq!(ErrorCode::PayloadTooLarge.as_str(), "payload_too_large");
+}
+
+/// Type spellings normalize to one form and compare on shared properties
+#[test]
+fn test_type_compatibility() {
+    use crate::models::H5Type;
+    use crate::types::{compatible, compatible_json, normalize};
+    use serde_json::json;
+
+    assert_eq!(normalize(&json!("H5T_IEEE_F64LE")), json!({"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"}));
+    assert!(compatible_json(&json!("H5T_STD_I32LE"), &json!({"class": "H5T_INTEGER", "base": "H5T_STD_I32LE", "id": "t-1"})));
+    assert!(!compatible_json(&json!("H5T_STD_I32LE"), &json!("H5T_STD_I64LE")));
+
+    let compound = H5Type::compound(&[("time", "H5T_IEEE_F64LE"), ("value", "H5T_STD_I16LE")]);
+    let expanded: H5Type = serde_json::from_value(json!({"class": "H5T_COMPOUND", "fields": [
+        {"name": "time", "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"}},
+        {"name": "value", "type": {"class": "H5T_INTEGER", "base": "H5T_STD_I16LE"}}
+    ]})).unwrap();
+    assert!(compound.is_compatible_with(&expanded));
+    let renamed = H5Type::compound(&[("time", "H5T_IEEE_F64LE"), ("level", "H5T_STD_I16LE")]);
+    assert!(!renamed.is_compatible_with(&expanded));
+
+    // String details only one side states do not count against it
+    let utf8 = H5Type::fixed_utf8(8);
+    let existing: H5Type = serde_json::from_value(json!({"class": "H5T_STRING"})).unwrap();
+    assert!(existing.is_compatible_with(&utf8));
+    assert!(!compatible(&utf8, &H5Type::variable_utf8()));
+}
+
+/// Values convert with the returned shape, indexed dimensions keeping length 1
+#[test]
+fn test_shaped_value() {
+    use crate::apis::coords::selection_shape;
+    use crate::values::ShapedValue;
+    use serde_json::json;
+
+    assert_eq!(selection_shape(Some("[3,:]"), &[10, 4]).unwrap(), vec![1, 4]);
+    assert_eq!(selection_shape(Some("[3:4,0:4:2]"), &[10, 4]).unwrap(), vec![1, 2]);
+    assert_eq!(selection_shape(Some("[2,1]"), &[10, 4]).unwrap(), vec![1, 1]);
+    assert_eq!(selection_shape(None, &[10, 4]).unwrap(), vec![10, 4]);
+
+    let row = ShapedValue::from_response(json!({"value": [[1, 2, 3, 4]]}), vec![1, 4]);
+    assert_eq!(row.to_vec::<i32>().unwrap(), vec![1, 2, 3, 4]);
+    let point = ShapedValue::from_response(json!({"value": [[7.5]]}), vec![1, 1]);
+    assert_eq!(point.to_vec::<f64>().unwrap(), vec![7.5]);
+    let scalar = ShapedValue::from_response(json!({"value": 7.5}), Vec::new());
+    assert_eq!(scalar.to_vec::<f64>().unwrap(), vec![7.5]);
+
+    // A shape reported by the server wins over the selection's
+    let reported = ShapedValue::from_response(json!({"value": [[1, 2], [3, 4]], "shape": [2, 2]}), vec![4]);
+    assert_eq!(reported.shape(), &[2, 2]);
+    assert_eq!(reported.to_vec::<u8>().unwrap(), vec![1, 2, 3, 4]);
+    #[cfg(feature = "ndarray")]
+    assert_eq!(reported.to_ndarray::<u8>().unwrap()[[1, 0]], 3);
+
+    let short = ShapedValue::new(json!([1, 2, 3]), vec![4]);
+    assert!(matches!(short.to_vec::<i32>(), Err(HsdsError::InvalidResponse(_))));
+}
+
+/// Slow-request summaries name the domain, object and selection
+#[test]
+fn test_slow_request_summary() {
+    let url = "http://hsds/datasets/d-1234/value?domain=%2Fhome%2Fa.h5&select=%5B0%3A100%5D";
+    let request = reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap());
+    let summary = SlowRequest::new(&request, 0).summary(std::time::Duration::from_millis(1500), "200 OK", Some(400));
+    assert_eq!(
+        summary,
+        "GET /datasets/d-1234/value took 1.5s: domain=/home/a.h5 object=d-1234 select=[0:100] sent=0 bytes received=400 bytes outcome=200 OK"
+    );
+
+    let request = reqwest::Request::new(reqwest::Method::GET, "http://hsds/".parse().unwrap());
+    let summary = SlowRequest::new(&request, 0).summary(std::time::Duration::from_secs(2), "timed out", None);
+    assert!(summary.contains("domain=- object=- select=- sent=0 bytes received=? outcome=timed out"));
+}
+
+/// Server type, shape, layout and filter JSON survives a serde round trip
+#[test]
+fn test_h5type_round_trip() {
+    use crate::models::{FilterInfo, H5Type, Layout, Shape, TypeClass};
+    use serde::{de::DeserializeOwned, Serialize};
+    use serde_json::Value;
+
+    fn round_trip<T: DeserializeOwned + Serialize>(fixture: &Value) -> T {
+        let parsed: T = serde_json::from_value(fixture.clone())
+            .unwrap_or_else(|e| panic!("{} does not parse: {}", fixture, e));
+        assert_eq!(&serde_json::to_value(&parsed).unwrap(), fixture);
+        parsed
+    }
+
+    let fixtures: Value = serde_json::from_str(include_str!("../tests/fixtures/h5type.json")).unwrap();
+    let types: Vec<H5Type> = fixtures["types"].as_array().unwrap().iter().map(round_trip).collect();
+    fixtures["shapes"].as_array().unwrap().iter().for_each(|s| drop(round_trip::<Shape>(s)));
+    let layouts: Vec<Layout> = fixtures["layouts"].as_array().unwrap().iter().map(round_trip).collect();
+    let filters: Vec<FilterInfo> = fixtures["filters"].as_array().unwrap().iter().map(round_trip).collect();
+
+    let classes: Vec<_> = types.iter().map(H5Type::class).collect();
+    assert_eq!(classes[..3], [Some(TypeClass::Integer), Some(TypeClass::Float), None]);
+    assert_eq!(types[2].committed_id(), Some("t-be3c1c9a-7a5c11ee-8b2e-0242ac120006"));
+    assert_eq!(types[5].committed_id(), types[2].committed_id());
+    assert!(TypeClass::ALL.iter().all(|class| classes.contains(&Some(*class)) || *class == TypeClass::Time));
+
+    // A class this crate does not know yet is kept whole
+    let future = serde_json::json!({"class": "H5T_FUTURE_CLASS", "base": "H5T_STD_I32LE"});
+    assert!(matches!(round_trip::<H5Type>(&future), H5Type::Other(_)));
+
+    assert_eq!(layouts[2].chunk_dims(), Some(&[64, 512][..]));
+    assert_eq!(layouts.iter().map(Layout::class).collect::<Vec<_>>()[3..], ["H5D_CONTIGUOUS_REF", "H5D_CHUNKED_REF", "H5D_CHUNKED_REF_INDIRECT"]);
+    assert_eq!(filters[3].extra["scaleType"], "H5Z_SO_INT");
+}
+
+/// GET /datatypes/{id} and GET /datasets/{id} bodies for each structured class parse into the typed models
+///
+/// `datatype_integration_test::test_type_fixtures_match_server` checks these
+/// fixtures against a running server.
+#[test]
+fn test_datatype_responses() {
+    use crate::models::{Dataset, Datatype, H5Type, TypeClass};
+    use serde_json::Value;
+
+    let fixtures: Value = serde_json::from_str(include_str!("../tests/fixtures/datatypes.json")).unwrap();
+    let mut classes = Vec::new();
+    for fixture in fixtures["datatypes"].as_array().unwrap() {
+        let datatype: Datatype = serde_json::from_value(fixture.clone()).unwrap();
+        let type_json = datatype.data_type.unwrap();
+        let parsed: H5Type = serde_json::from_value(type_json.clone()).unwrap();
+        assert!(matches!(parsed, H5Type::Defined(_)), "{} is not modelled", type_json);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), type_json);
+        assert_eq!(datatype.extra.keys().collect::<Vec<_>>(), ["domain"]);
+        classes.push(parsed.class().unwrap());
+    }
+    assert_eq!(classes, [TypeClass::Compound, TypeClass::Enum, TypeClass::Vlen, TypeClass::Reference, TypeClass::Array]);
+
+    let dataset: Dataset = serde_json::from_value(fixtures["committed_dataset"].clone()).unwrap();
+    let data_type = dataset.data_type.as_ref().unwrap();
+    assert_eq!(data_type.committed_id(), fixtures["datatypes"][0]["id"].as_str());
+    assert_eq!(data_type.field_names(), ["timestamp", "channel", "label", "gain"]);
+    assert_eq!(serde_json::to_value(data_type).unwrap(), fixtures["committed_dataset"]["type"]);
+}
+
+/// The GET /datasets/{id} example of hsds_openapi.yaml parses into the typed models
+#[test]
+fn test_dataset_response_types() {
+    use crate::models::{Dataset, H5Type, TypeClass};
+
+    let dataset: Dataset = serde_json::from_str(include_str!("../tests/fixtures/dataset.json")).unwrap();
+    let data_type = dataset.data_type.as_ref().unwrap();
+    assert_eq!(data_type.class(), Some(TypeClass::Integer));
+    assert!(data_type.is_compatible_with(&H5Type::named("H5T_STD_U32LE")));
+    let layout = dataset.layout.as_ref().unwrap();
+    assert_eq!(layout.class(), "H5D_CHUNKED");
+    assert_eq!(layout.chunk_dims(), Some(&[4, 4, 4][..]));
+    assert!(dataset.extra.is_empty());
+}
@@ -0,0 +1,96 @@
+use crate::models::{Dataset, Domain, DomainEntry, Group, Link};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Objects carrying HSDS creation/modification times (epoch seconds)
+///
+/// With the `chrono` feature (on by default) the times are also available
+/// as `DateTime<Utc>`.
+pub trait Timestamped {
+    /// Creation time in seconds since the Unix epoch
+    fn created_secs(&self) -> Option<f64>;
+
+    /// Last modification time in seconds since the Unix epoch
+    fn last_modified_secs(&self) -> Option<f64> {
+        None
+    }
+
+    /// Creation time
+    #[cfg(feature = "chrono")]
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_secs().and_then(to_datetime)
+    }
+
+    /// Last modification time
+    #[cfg(feature = "chrono")]
+    fn last_modified_at(&self) -> Option<DateTime<Utc>> {
+        self.last_modified_secs().and_then(to_datetime)
+    }
+}
+
+macro_rules! timestamped {
+    ($ty:ty) => {
+        impl Timestamped for $ty {
+            fn created_secs(&self) -> Option<f64> {
+                self.created
+            }
+
+            fn last_modified_secs(&self) -> Option<f64> {
+                self.last_modified
+            }
+        }
+    };
+}
+
+timestamped!(Domain);
+timestamped!(DomainEntry);
+timestamped!(Group);
+timestamped!(Dataset);
+
+impl Timestamped for Link {
+    fn created_secs(&self) -> Option<f64> {
+        self.created
+    }
+}
+
+/// Convert fractional epoch seconds to a UTC timestamp
+#[cfg(feature = "chrono")]
+pub fn to_datetime(secs: f64) -> Option<DateTime<Utc>> {
+    if !secs.is_finite() {
+        return None;
+    }
+    let whole = secs.floor();
+    let nanos = ((secs - whole) * 1e9).round().min(999_999_999.0) as u32;
+    Utc.timestamp_opt(whole as i64, nanos).single()
+}
+
+/// Half-open time interval `[start, end)`; a missing bound is unbounded
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "chrono")]
+impl TimeRange {
+    /// Interval between two instants
+    pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self { start: Some(start), end: Some(end) }
+    }
+
+    /// Everything at or after `start`
+    pub fn since(start: DateTime<Utc>) -> Self {
+        Self { start: Some(start), end: None }
+    }
+
+    /// Everything before `end`
+    pub fn until(end: DateTime<Utc>) -> Self {
+        Self { start: None, end: Some(end) }
+    }
+
+    /// Whether the instant lies in the range
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.start.is_none_or(|start| time >= start) && self.end.is_none_or(|end| time < end)
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Timing breakdown of the requests recorded by a [`TransferStats`]
+///
+/// Byte counts cover request bodies known up front and response bodies
+/// announced by `Content-Length`, so streamed bodies are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferReport {
+    /// HTTP exchanges made, including retried attempts
+    pub requests: u64,
+    /// Attempts repeated after a transient failure
+    pub retries: u64,
+    /// Exchanges that failed to connect or returned an error status
+    pub failures: u64,
+    /// Request body bytes sent
+    pub bytes_sent: u64,
+    /// Response body bytes received
+    pub bytes_received: u64,
+    /// Wall time from the first request to the last response
+    pub elapsed: Duration,
+    /// Mean request latency
+    pub mean_latency: Duration,
+    /// Median request latency
+    pub p50_latency: Duration,
+    /// 95th percentile request latency
+    pub p95_latency: Duration,
+    /// 99th percentile request latency
+    pub p99_latency: Duration,
+    /// Slowest request
+    pub max_latency: Duration,
+}
+
+impl TransferReport {
+    /// Bytes sent and received per second of wall time (0 if nothing was timed)
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            (self.bytes_sent + self.bytes_received) as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+impl std::fmt::Display for TransferReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} requests ({} retries, {} failed), {} B sent, {} B received in {:.3?} ({:.0} B/s); \
+             latency mean {:.3?}, p50 {:.3?}, p95 {:.3?}, p99 {:.3?}, max {:.3?}",
+            self.requests, self.retries, self.failures, self.bytes_sent, self.bytes_received,
+            self.elapsed, self.throughput(),
+            self.mean_latency, self.p50_latency, self.p95_latency, self.p99_latency, self.max_latency
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct StatsState {
+    first_sent: Option<Instant>,
+    last_received: Option<Instant>,
+    latencies: Vec<Duration>,
+    retries: u64,
+    failures: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+/// Shared recorder of request timings and sizes
+///
+/// Clones share the same record, so keep one clone to read the
+/// [`report`](Self::report) after passing another to
+/// [`HsdsClient::with_transfer_stats`](crate::HsdsClient::with_transfer_stats).
+/// Copies and imports record their own requests and leave the report in
+/// [`CopyJournal::transfer`](crate::CopyJournal::transfer) and
+/// [`ImportReport::transfer`](crate::ImportReport::transfer).
+#[derive(Debug, Clone, Default)]
+pub struct TransferStats {
+    state: Arc<Mutex<StatsState>>,
+}
+
+impl TransferStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Summary of the requests recorded so far
+    pub fn report(&self) -> TransferReport {
+        let state = self.lock();
+        let mut latencies = state.latencies.clone();
+        latencies.sort_unstable();
+        let total: Duration = latencies.iter().sum();
+        let percentile = |p: usize| {
+            // Nearest-rank percentile
+            latencies.len().checked_sub(1)
+                .map(|last| latencies[(p * latencies.len()).div_ceil(100).saturating_sub(1).min(last)])
+                .unwrap_or_default()
+        };
+
+        TransferReport {
+            requests: latencies.len() as u64,
+            retries: state.retries,
+            failures: state.failures,
+            bytes_sent: state.bytes_sent,
+            bytes_received: state.bytes_received,
+            elapsed: match (state.first_sent, state.last_received) {
+                (Some(first), Some(last)) => last.saturating_duration_since(first),
+                _ => Duration::ZERO,
+            },
+            mean_latency: total.checked_div(latencies.len() as u32).unwrap_or_default(),
+            p50_latency: percentile(50),
+            p95_latency: percentile(95),
+            p99_latency: percentile(99),
+            max_latency: latencies.last().copied().unwrap_or_default(),
+        }
+    }
+
+    /// Forget everything recorded
+    pub fn reset(&self) {
+        *self.lock() = StatsState::default();
+    }
+
+    /// Record one HTTP exchange that started at `sent`
+    pub(crate) fn record(&self, sent: Instant, bytes_sent: u64, bytes_received: Option<u64>, failed: bool) {
+        let now = Instant::now();
+        let mut state = self.lock();
+        state.first_sent = Some(state.first_sent.map_or(sent, |first| first.min(sent)));
+        state.last_received = Some(now);
+        state.latencies.push(now.saturating_duration_since(sent));
+        state.bytes_sent += bytes_sent;
+        state.bytes_received += bytes_received.unwrap_or(0);
+        if failed {
+            state.failures += 1;
+        }
+    }
+
+    /// Record that a failed attempt is about to be repeated
+    pub(crate) fn record_retry(&self) {
+        self.lock().retries += 1;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatsState> {
+        // Every update leaves the counters consistent, even if a later one panics
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,187 @@
+//! Adaptive request sizing and concurrency for bulk transfers
+//!
+//! [`AdaptiveTuning`] drives an AIMD controller, the scheme TCP uses for its
+//! congestion window. A transfer starts with one request in flight and a
+//! small payload. While requests come back faster than the target latency,
+//! payload size and in-flight count double every round (slow start); after
+//! the first sign of congestion they grow by one step per round instead.
+//! A request slower than the target, throttled (429/503) or timed out
+//! halves both, once per round. The transfer settles just below the point
+//! where the server starts to push back, without tuning per deployment.
+
+use crate::error::HsdsError;
+use log::debug;
+use std::time::Duration;
+
+/// Bounds and targets of adaptive transfers
+#[derive(Debug, Clone)]
+pub struct AdaptiveTuning {
+    initial_request_bytes: usize,
+    min_request_bytes: usize,
+    max_request_bytes: usize,
+    max_in_flight: usize,
+    target_latency: Duration,
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl Default for AdaptiveTuning {
+    fn default() -> Self {
+        Self {
+            initial_request_bytes: 256 * 1024,
+            min_request_bytes: 64 * 1024,
+            max_request_bytes: 64 * 1024 * 1024,
+            max_in_flight: 8,
+            target_latency: Duration::from_secs(2),
+            max_retries: 5,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl AdaptiveTuning {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Payload size of the first request (clamped to the size bounds)
+    ///
+    /// Also the step payloads grow by after slow start.
+    pub fn with_initial_request_bytes(mut self, bytes: usize) -> Self {
+        self.initial_request_bytes = bytes.max(1);
+        self
+    }
+
+    /// Bounds the payload size is adapted within
+    pub fn with_request_bytes_bounds(mut self, min: usize, max: usize) -> Self {
+        self.min_request_bytes = min.max(1);
+        self.max_request_bytes = max.max(self.min_request_bytes);
+        self
+    }
+
+    /// Most requests in flight at once (at least 1)
+    pub fn with_max_in_flight(mut self, requests: usize) -> Self {
+        self.max_in_flight = requests.max(1);
+        self
+    }
+
+    /// Request latency above which the server counts as congested
+    pub fn with_target_latency(mut self, latency: Duration) -> Self {
+        self.target_latency = latency;
+        self
+    }
+
+    /// Maximum number of retries of a throttled or timed-out request
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Initial backoff before retrying a request (doubled on each retry)
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub(crate) fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Delay before retry number `attempt` (1-based)
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+    }
+}
+
+/// Current settings of an adaptive transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TuningState {
+    /// Target payload size of the next request
+    pub request_bytes: usize,
+    /// Requests allowed in flight
+    pub in_flight: usize,
+}
+
+/// AIMD controller for request size and in-flight count
+#[derive(Debug, Clone)]
+pub(crate) struct AimdController {
+    options: AdaptiveTuning,
+    state: TuningState,
+    slow_start: bool,
+    /// Fast completions since the last change
+    fast: usize,
+    /// Bumped on every decrease; requests sent before it are not counted again
+    generation: u64,
+}
+
+impl AimdController {
+    pub(crate) fn new(options: AdaptiveTuning) -> Self {
+        let request_bytes = options.initial_request_bytes.clamp(options.min_request_bytes, options.max_request_bytes);
+        Self {
+            options,
+            state: TuningState { request_bytes, in_flight: 1 },
+            slow_start: true,
+            fast: 0,
+            generation: 0,
+        }
+    }
+
+    pub(crate) fn state(&self) -> TuningState {
+        self.state
+    }
+
+    /// Generation to tag a request with when sending it
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// A request of `generation` succeeded after `latency`
+    pub(crate) fn on_success(&mut self, generation: u64, latency: Duration) {
+        if latency > self.options.target_latency {
+            self.on_congestion(generation);
+            return;
+        }
+        self.fast += 1;
+        if self.fast < self.state.in_flight {
+            return;
+        }
+        // A full round of fast requests
+        self.fast = 0;
+        let options = &self.options;
+        let previous = self.state;
+        if self.slow_start {
+            self.state.request_bytes = self.state.request_bytes.saturating_mul(2);
+            self.state.in_flight = self.state.in_flight.saturating_mul(2);
+        } else {
+            self.state.request_bytes = self.state.request_bytes.saturating_add(options.initial_request_bytes);
+            self.state.in_flight += 1;
+        }
+        self.state.request_bytes = self.state.request_bytes.min(options.max_request_bytes);
+        self.state.in_flight = self.state.in_flight.min(options.max_in_flight);
+        if self.state != previous {
+            debug!("Transfer tuning up: {:?}", self.state);
+        }
+    }
+
+    /// A request of `generation` was slow, throttled or timed out
+    pub(crate) fn on_congestion(&mut self, generation: u64) {
+        if generation < self.generation {
+            return;
+        }
+        self.generation += 1;
+        self.slow_start = false;
+        self.fast = 0;
+        self.state.request_bytes = (self.state.request_bytes / 2).max(self.options.min_request_bytes);
+        self.state.in_flight = (self.state.in_flight / 2).max(1);
+        debug!("Transfer tuning down: {:?}", self.state);
+    }
+}
+
+/// Whether a failed request signals an overloaded server and may be retried
+pub(crate) fn is_congestion(error: &HsdsError) -> bool {
+    match error {
+        HsdsError::Api { status, .. } => matches!(status, 429 | 503),
+        HsdsError::Http(e) => e.is_timeout(),
+        _ => false,
+    }
+}
@@ -0,0 +1,149 @@
+//! Normalization and comparison of HSDS datatypes
+//!
+//! HSDS accepts and returns the same type in several spellings: a
+//! predefined name (`"H5T_STD_I32LE"`), a class/base object
+//! (`{"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"}`), or a bare class
+//! name used as a loose constraint (`"H5T_STRING"`). [`normalize`] brings
+//! them to one object form, and [`compatible`] compares two types in that
+//! form. Properties only one side states (e.g. a string length given as
+//! a bare `"H5T_STRING"` constraint) do not count against compatibility.
+//!
+//! ```
+//! use hsds_client::{types, H5Type};
+//! use serde_json::json;
+//!
+//! let name = H5Type::named("H5T_STD_I32LE");
+//! let object: H5Type = serde_json::from_value(json!({"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"})).unwrap();
+//! assert!(types::compatible(&name, &object));
+//! ```
+
+use crate::models::H5Type;
+use serde_json::{json, Map, Value};
+
+/// Type classes that may stand alone as a type name
+const CLASSES: &[&str] = &[
+    "H5T_INTEGER", "H5T_FLOAT", "H5T_STRING", "H5T_COMPOUND", "H5T_ENUM", "H5T_ARRAY",
+    "H5T_VLEN", "H5T_OPAQUE", "H5T_REFERENCE", "H5T_TIME", "H5T_BITFIELD",
+];
+
+/// Whether two types describe compatible types
+///
+/// See the [module documentation](self) for what counts as compatible.
+pub fn compatible(a: &H5Type, b: &H5Type) -> bool {
+    mismatch(&type_json(a), &type_json(b)).is_none()
+}
+
+/// Whether two HSDS type JSON values describe compatible types
+pub fn compatible_json(a: &Value, b: &Value) -> bool {
+    mismatch(a, b).is_none()
+}
+
+/// Describe how `actual` differs from `expected`, if the types are not compatible
+pub fn mismatch(expected: &Value, actual: &Value) -> Option<String> {
+    differs(&normalize(expected), &normalize(actual))
+        .map(|what| format!("type {} does not match {}: {}", actual, expected, what))
+}
+
+/// Type JSON in object form, with predefined names expanded to class and base
+///
+/// Compound fields, array and enum bases are normalized recursively. Keys
+/// that describe the object rather than the type (`id`, `hrefs`, ...) are
+/// dropped.
+pub fn normalize(datatype: &Value) -> Value {
+    match datatype {
+        Value::String(name) if CLASSES.contains(&name.as_str()) => json!({"class": name}),
+        Value::String(name) => match predefined_class(name) {
+            Some(class) => json!({"class": class, "base": name}),
+            // A committed type id or an unknown name only matches itself
+            None => json!({"base": name}),
+        },
+        Value::Object(map) => {
+            let mut normalized = Map::new();
+            for (key, value) in map {
+                let value = match key.as_str() {
+                    "class" | "charSet" | "strPad" | "length" | "dims" | "mapping" | "size" | "order" | "tag" => value.clone(),
+                    "base" if value.is_string() => value.clone(),
+                    "base" => normalize(value),
+                    "fields" => Value::Array(
+                        value
+                            .as_array()
+                            .map(|fields| {
+                                fields
+                                    .iter()
+                                    .map(|f| json!({"name": f.get("name").cloned().unwrap_or(Value::Null), "type": normalize(f.get("type").unwrap_or(&Value::Null))}))
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                    ),
+                    _ => continue,
+                };
+                normalized.insert(key.clone(), value);
+            }
+            if let (None, Some(class)) = (normalized.get("class"), normalized.get("base").and_then(Value::as_str).and_then(predefined_class)) {
+                normalized.insert("class".to_string(), Value::from(class));
+            }
+            Value::Object(normalized)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Class of a predefined type name such as `H5T_IEEE_F64LE`
+fn predefined_class(name: &str) -> Option<&'static str> {
+    if name.starts_with("H5T_STD_I") || name.starts_with("H5T_STD_U") || name.starts_with("H5T_NATIVE_INT") {
+        Some("H5T_INTEGER")
+    } else if name.starts_with("H5T_IEEE_F") || name.starts_with("H5T_NATIVE_FLOAT") || name.starts_with("H5T_NATIVE_DOUBLE") {
+        Some("H5T_FLOAT")
+    } else if name.starts_with("H5T_STD_REF") {
+        Some("H5T_REFERENCE")
+    } else {
+        None
+    }
+}
+
+/// First property both normalized types state differently
+fn differs(a: &Value, b: &Value) -> Option<String> {
+    let (Value::Object(a), Value::Object(b)) = (a, b) else {
+        return (a != b).then(|| "different types".to_string());
+    };
+    for (key, left) in a {
+        let Some(right) = b.get(key) else { continue };
+        let difference = match key.as_str() {
+            "base" if left.is_string() && right.is_string() => {
+                (left != right).then(|| format!("base {} vs {}", left, right))
+            }
+            "base" => differs(&normalize(left), &normalize(right)),
+            "fields" => fields_differ(left, right),
+            _ => (left != right).then(|| format!("{} {} vs {}", key, left, right)),
+        };
+        if difference.is_some() {
+            return difference;
+        }
+    }
+    None
+}
+
+fn fields_differ(a: &Value, b: &Value) -> Option<String> {
+    let (a, b) = (a.as_array()?, b.as_array()?);
+    if a.len() != b.len() {
+        return Some(format!("{} fields vs {}", a.len(), b.len()));
+    }
+    a.iter().zip(b).find_map(|(left, right)| {
+        if left.get("name") != right.get("name") {
+            return Some(format!("field {} vs {}", left["name"], right["name"]));
+        }
+        differs(&left["type"], &right["type"]).map(|what| format!("field {}: {}", left["name"], what))
+    })
+}
+
+/// JSON form of a type
+fn type_json(datatype: &H5Type) -> Value {
+    serde_json::to_value(datatype).unwrap_or(Value::Null)
+}
+
+impl H5Type {
+    /// Whether this type is compatible with another, see [`compatible`]
+    pub fn is_compatible_with(&self, other: &H5Type) -> bool {
+        compatible(self, other)
+    }
+}
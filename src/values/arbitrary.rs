@@ -0,0 +1,178 @@
+use crate::{
+    client::HsdsClient,
+    error::{HsdsError, HsdsResult},
+    models::{DatasetCreateRequest, DatasetValueRequest},
+    values::{flatten, nest},
+};
+use proptest::prelude::*;
+use serde_json::Value;
+
+/// Predefined HSDS element types covered by the generators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+impl ElementType {
+    /// All generated element types
+    pub const ALL: [ElementType; 10] = [
+        ElementType::I8, ElementType::U8, ElementType::I16, ElementType::U16,
+        ElementType::I32, ElementType::U32, ElementType::I64, ElementType::U64,
+        ElementType::F32, ElementType::F64,
+    ];
+
+    /// HSDS predefined type name
+    pub fn hsds_type(&self) -> &'static str {
+        match self {
+            ElementType::I8 => "H5T_STD_I8LE",
+            ElementType::U8 => "H5T_STD_U8LE",
+            ElementType::I16 => "H5T_STD_I16LE",
+            ElementType::U16 => "H5T_STD_U16LE",
+            ElementType::I32 => "H5T_STD_I32LE",
+            ElementType::U32 => "H5T_STD_U32LE",
+            ElementType::I64 => "H5T_STD_I64LE",
+            ElementType::U64 => "H5T_STD_U64LE",
+            ElementType::F32 => "H5T_IEEE_F32LE",
+            ElementType::F64 => "H5T_IEEE_F64LE",
+        }
+    }
+}
+
+/// A generated dataset: element type, shape and row-major elements
+#[derive(Debug, Clone)]
+pub struct ValueCase {
+    pub element_type: ElementType,
+    pub shape: Vec<u64>,
+    pub elements: Vec<Value>,
+}
+
+impl ValueCase {
+    /// Elements as a nested JSON array matching the shape
+    pub fn to_json(&self) -> Value {
+        nest(self.elements.clone(), &self.shape).unwrap_or(Value::Null)
+    }
+
+    /// Dataset creation request for this case
+    pub fn create_request(&self) -> DatasetCreateRequest {
+        DatasetCreateRequest::from_hsds_type(self.element_type.hsds_type(), self.shape.clone())
+    }
+}
+
+/// Strategy over the supported element types
+pub fn element_type() -> impl Strategy<Value = ElementType> {
+    proptest::sample::select(ElementType::ALL.to_vec())
+}
+
+/// Strategy over shapes of rank 1..=max_rank with dims 1..=max_dim
+pub fn shape(max_rank: usize, max_dim: u64) -> impl Strategy<Value = Vec<u64>> {
+    proptest::collection::vec(1..=max_dim.max(1), 1..=max_rank.max(1))
+}
+
+/// Strategy over `count` JSON elements representable in the element type
+pub fn elements(element_type: ElementType, count: usize) -> BoxedStrategy<Vec<Value>> {
+    use proptest::collection::vec;
+
+    match element_type {
+        ElementType::I8 => vec(any::<i8>().prop_map(Value::from), count).boxed(),
+        ElementType::U8 => vec(any::<u8>().prop_map(Value::from), count).boxed(),
+        ElementType::I16 => vec(any::<i16>().prop_map(Value::from), count).boxed(),
+        ElementType::U16 => vec(any::<u16>().prop_map(Value::from), count).boxed(),
+        ElementType::I32 => vec(any::<i32>().prop_map(Value::from), count).boxed(),
+        ElementType::U32 => vec(any::<u32>().prop_map(Value::from), count).boxed(),
+        ElementType::I64 => vec(any::<i64>().prop_map(Value::from), count).boxed(),
+        ElementType::U64 => vec(any::<u64>().prop_map(Value::from), count).boxed(),
+        ElementType::F32 => {
+            let finite = proptest::num::f32::NORMAL | proptest::num::f32::ZERO | proptest::num::f32::SUBNORMAL;
+            vec(finite.prop_map(|v| Value::from(v as f64)), count).boxed()
+        }
+        ElementType::F64 => {
+            let finite = proptest::num::f64::NORMAL | proptest::num::f64::ZERO | proptest::num::f64::SUBNORMAL;
+            vec(finite.prop_map(Value::from), count).boxed()
+        }
+    }
+}
+
+/// Strategy over complete value cases (type, shape and matching elements)
+///
+/// # Arguments
+/// * `max_rank` - Maximum number of dimensions
+/// * `max_dim` - Maximum extent of each dimension
+pub fn value_case(max_rank: usize, max_dim: u64) -> impl Strategy<Value = ValueCase> {
+    (element_type(), shape(max_rank, max_dim)).prop_flat_map(|(element_type, shape)| {
+        let count = shape.iter().product::<u64>() as usize;
+        elements(element_type, count).prop_map(move |elements| ValueCase {
+            element_type,
+            shape: shape.clone(),
+            elements,
+        })
+    })
+}
+
+/// Compare expected and returned values element by element
+///
+/// Integers must match exactly; floats are compared at the precision of the
+/// element type. Returns a description of the first mismatch.
+pub fn compare_values(element_type: ElementType, expected: &Value, actual: &Value) -> Result<(), String> {
+    let expected = flatten(expected);
+    let actual = flatten(actual);
+    if expected.len() != actual.len() {
+        return Err(format!("expected {} elements, got {}", expected.len(), actual.len()));
+    }
+
+    for (index, (e, a)) in expected.iter().zip(&actual).enumerate() {
+        let equal = match element_type {
+            ElementType::F32 => match (e.as_f64(), a.as_f64()) {
+                (Some(e), Some(a)) => (e as f32) == (a as f32),
+                _ => false,
+            },
+            ElementType::F64 => e.as_f64() == a.as_f64(),
+            _ => e == a,
+        };
+        if !equal {
+            return Err(format!("element {} differs: expected {}, got {}", index, e, a));
+        }
+    }
+    Ok(())
+}
+
+/// Write a case to a new dataset, read it back and compare
+///
+/// # Arguments
+/// * `client` - HSDS client
+/// * `domain` - Domain path
+/// * `case` - Generated value case
+pub async fn assert_round_trip(client: &HsdsClient, domain: &str, case: &ValueCase) -> HsdsResult<()> {
+    let dataset = client.datasets().create_dataset(domain, case.create_request()).await?;
+    let expected = case.to_json();
+
+    let write = DatasetValueRequest {
+        start: None,
+        stop: None,
+        step: None,
+        points: None,
+        value: Some(expected.clone()),
+        value_base64: None,
+    };
+    client.datasets().write_dataset_values(domain, &dataset.id, write).await?;
+
+    let response = client.datasets()
+        .read_dataset_values_json(domain, &dataset.id, None, None, None)
+        .await?;
+    let actual = response.get("value").cloned().unwrap_or(Value::Null);
+
+    let result = compare_values(case.element_type, &expected, &actual);
+    client.datasets().delete_dataset(domain, &dataset.id).await.ok();
+
+    result.map_err(|mismatch| HsdsError::OperationFailed(format!(
+        "Round trip of {} {:?} failed: {}", case.element_type.hsds_type(), case.shape, mismatch
+    )))
+}
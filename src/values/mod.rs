@@ -0,0 +1,338 @@
+use crate::{
+    codec::{self, ElementCodec},
+    error::{HsdsError, HsdsResult},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+
+/// Build a nested JSON array (row-major) from flat elements and a shape
+///
+/// A zero-rank shape returns the single element as a scalar.
+///
+/// # Arguments
+/// * `flat` - Elements in row-major order
+/// * `shape` - Dimensions of the array
+pub fn nest(flat: Vec<Value>, shape: &[u64]) -> HsdsResult<Value> {
+    let expected: u64 = shape.iter().product();
+    if flat.len() as u64 != expected {
+        return Err(HsdsError::invalid_param(format!(
+            "{} elements do not fill shape {:?} ({} elements)", flat.len(), shape, expected
+        )));
+    }
+
+    let mut elements = flat.into_iter();
+    Ok(nest_level(&mut elements, shape))
+}
+
+fn nest_level(elements: &mut impl Iterator<Item = Value>, shape: &[u64]) -> Value {
+    match shape.split_first() {
+        None => elements.next().unwrap_or(Value::Null),
+        Some((&dim, rest)) => Value::Array((0..dim).map(|_| nest_level(elements, rest)).collect()),
+    }
+}
+
+/// Flatten a (possibly nested) JSON array into its leaf elements, row-major
+pub fn flatten(value: &Value) -> Vec<Value> {
+    let mut out = Vec::new();
+    flatten_into(value, &mut out);
+    out
+}
+
+fn flatten_into(value: &Value, out: &mut Vec<Value>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| flatten_into(item, out)),
+        other => out.push(other.clone()),
+    }
+}
+
+/// Convert the elements `rank` arrays deep in a nested JSON array, row-major
+///
+/// An element that does not deserialize into `T` fails with
+/// [`HsdsError::Conversion`] carrying its multi-dimensional index and
+/// value. Compound elements, which HSDS writes as arrays, are converted
+/// whole.
+///
+/// # Arguments
+/// * `value` - Nested arrays of a value read (the element itself for rank 0)
+/// * `rank` - Number of array levels above the elements
+pub fn convert_nested<T: DeserializeOwned>(value: &Value, rank: usize) -> HsdsResult<Vec<T>> {
+    convert_nested_with(value, rank, |element| T::deserialize(element).map_err(|e| e.to_string()))
+}
+
+/// [`convert_nested`] with a custom element conversion
+pub(crate) fn convert_nested_with<T>(
+    value: &Value,
+    rank: usize,
+    convert: impl Fn(&Value) -> Result<T, String>,
+) -> HsdsResult<Vec<T>> {
+    let mut out = Vec::new();
+    let mut index = Vec::with_capacity(rank);
+    convert_into(value, rank, &convert, &mut index, &mut out)?;
+    Ok(out)
+}
+
+fn convert_into<T>(
+    value: &Value,
+    depth: usize,
+    convert: &impl Fn(&Value) -> Result<T, String>,
+    index: &mut Vec<u64>,
+    out: &mut Vec<T>,
+) -> HsdsResult<()> {
+    if depth == 0 {
+        let element = convert(value).map_err(|e| HsdsError::conversion(index.clone(), value, e))?;
+        out.push(element);
+        return Ok(());
+    }
+    let Value::Array(items) = value else {
+        return Err(HsdsError::conversion(index.clone(), value, format!("expected {} more array levels", depth)));
+    };
+    for (i, item) in items.iter().enumerate() {
+        index.push(i as u64);
+        convert_into(item, depth - 1, convert, index, out)?;
+        index.pop();
+    }
+    Ok(())
+}
+
+/// Multi-dimensional index of the element at a row-major position
+pub fn unravel_index(position: u64, shape: &[u64]) -> Vec<u64> {
+    let mut index = vec![0; shape.len()];
+    let mut rest = position;
+    for (i, &dim) in shape.iter().enumerate().rev() {
+        if dim > 0 {
+            index[i] = rest % dim;
+            rest /= dim;
+        }
+    }
+    index
+}
+
+/// Infer the shape of a nested JSON array from its first elements
+pub fn shape_of(value: &Value) -> Vec<u64> {
+    let mut shape = Vec::new();
+    let mut current = value;
+    while let Value::Array(items) = current {
+        shape.push(items.len() as u64);
+        match items.first() {
+            Some(first) => current = first,
+            None => break,
+        }
+    }
+    shape
+}
+
+/// Values of a read together with the shape they were returned in
+///
+/// The JSON is kept as received and only converted when an element type
+/// is chosen, so one read can be converted more than once. The shape is
+/// the one the server reported, or otherwise the exact shape of the
+/// selection, in which a dimension selected by a single index keeps
+/// length 1: reading `[3,:]` of a 10×4 dataset gives 4 values of shape
+/// `[1, 4]`, nested as HSDS returns them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapedValue {
+    value: Value,
+    shape: Vec<u64>,
+}
+
+impl ShapedValue {
+    /// Nested JSON array (the element itself for an empty shape) of a known shape
+    pub fn new(value: Value, shape: Vec<u64>) -> Self {
+        Self { value, shape }
+    }
+
+    /// Values of a read response
+    ///
+    /// The shape is the response's `shape` (a list of dimensions or an
+    /// object with `dims`) when it has one, `selection_shape` otherwise.
+    ///
+    /// # Arguments
+    /// * `response` - JSON body of a value read
+    /// * `selection_shape` - Shape of the selection that was read
+    pub fn from_response(mut response: Value, selection_shape: Vec<u64>) -> Self {
+        let shape = match response.get("shape") {
+            Some(Value::Object(shape)) if shape.get("class").and_then(Value::as_str) == Some("H5S_SCALAR") => Some(Vec::new()),
+            Some(Value::Object(shape)) => shape.get("dims").and_then(|dims| serde_json::from_value(dims.clone()).ok()),
+            Some(dims) => serde_json::from_value(dims.clone()).ok(),
+            None => None,
+        };
+        let value = response.get_mut("value").map(Value::take).unwrap_or(Value::Null);
+        Self { value, shape: shape.unwrap_or(selection_shape) }
+    }
+
+    pub fn shape(&self) -> &[u64] {
+        &self.shape
+    }
+
+    pub fn rank(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Number of elements the shape holds
+    pub fn len(&self) -> u64 {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The values as received
+    pub fn json(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn into_json(self) -> Value {
+        self.value
+    }
+
+    /// The values as `T` elements, flat (row-major)
+    ///
+    /// Fails with [`HsdsError::InvalidResponse`] if the values do not fill
+    /// the shape.
+    pub fn to_vec<T: ElementCodec>(&self) -> HsdsResult<Vec<T>> {
+        let elements = if self.is_empty() { Vec::new() } else { codec::decode_json(&self.value, self.rank())? };
+        if elements.len() as u64 != self.len() {
+            return Err(HsdsError::InvalidResponse(format!(
+                "{} values do not fill shape {:?}", elements.len(), self.shape
+            )));
+        }
+        Ok(elements)
+    }
+
+    /// The values as an array of `T` elements with this shape
+    #[cfg(feature = "ndarray")]
+    pub fn to_ndarray<T: ElementCodec>(&self) -> HsdsResult<ndarray::ArrayD<T>> {
+        let dims: Vec<usize> = self.shape.iter().map(|&dim| dim as usize).collect();
+        ndarray::ArrayD::from_shape_vec(ndarray::IxDyn(&dims), self.to_vec()?)
+            .map_err(|e| HsdsError::InvalidResponse(format!("values do not fit shape {:?}: {}", self.shape, e)))
+    }
+}
+
+/// Datatype of boolean datasets and attributes
+///
+/// Follows h5py's convention: an 8-bit signed enum with `FALSE = 0` and
+/// `TRUE = 1`, which h5py and the HDF5 tools read back as numpy booleans.
+pub fn bool_type() -> Value {
+    serde_json::json!({
+        "class": "H5T_ENUM",
+        "base": {"class": "H5T_INTEGER", "base": "H5T_STD_I8LE"},
+        "mapping": {"FALSE": 0, "TRUE": 1}
+    })
+}
+
+/// Whether a datatype can hold booleans as single bytes
+///
+/// Accepts the [`bool_type`] enum (any 8-bit base with `FALSE = 0` and
+/// `TRUE = 1`) and plain 8-bit integers, which are read as non-zero = true.
+pub fn is_bool_type(dtype: &Value) -> bool {
+    let is_byte = |name: Option<&str>| matches!(name, Some("H5T_STD_I8LE" | "H5T_STD_U8LE" | "H5T_STD_I8BE" | "H5T_STD_U8BE"));
+    match dtype.get("class").and_then(Value::as_str) {
+        Some("H5T_ENUM") => {
+            let mapping = dtype.get("mapping");
+            is_byte(dtype.get("base").and_then(|b| b.get("base")).and_then(Value::as_str))
+                && mapping.and_then(|m| m.get("FALSE")) == Some(&Value::from(0))
+                && mapping.and_then(|m| m.get("TRUE")) == Some(&Value::from(1))
+        }
+        Some("H5T_INTEGER") => is_byte(dtype.get("base").and_then(Value::as_str)),
+        _ => is_byte(dtype.as_str()),
+    }
+}
+
+/// Datatype of complex numbers with components of a float type
+///
+/// Follows h5py's convention: a compound of the real part `r` and the
+/// imaginary part `i`, e.g. `complex_type("H5T_IEEE_F32LE")` for complex64.
+pub fn complex_type(float_type: &str) -> Value {
+    serde_json::json!({
+        "class": "H5T_COMPOUND",
+        "fields": [
+            {"name": "r", "type": float_type},
+            {"name": "i", "type": float_type}
+        ]
+    })
+}
+
+/// Component float type of a complex datatype following [`complex_type`]
+///
+/// Returns `None` unless the type is a compound of exactly the fields `r`
+/// and `i`, in that order, of the same floating-point type (by name or as
+/// an `H5T_FLOAT` definition).
+pub fn complex_base(dtype: &Value) -> Option<&str> {
+    if dtype.get("class").and_then(Value::as_str) != Some("H5T_COMPOUND") {
+        return None;
+    }
+    fn float_name(field: &Value) -> Option<&str> {
+        let ty = field.get("type")?;
+        let name = ty.as_str().or_else(|| ty.get("base").and_then(Value::as_str))?;
+        name.starts_with("H5T_IEEE_F").then_some(name)
+    }
+    match dtype.get("fields").and_then(Value::as_array).map(Vec::as_slice) {
+        Some([real, imag])
+            if real.get("name").and_then(Value::as_str) == Some("r")
+                && imag.get("name").and_then(Value::as_str) == Some("i") =>
+        {
+            let base = float_name(real)?;
+            (float_name(imag)? == base).then_some(base)
+        }
+        _ => None,
+    }
+}
+
+/// Non-numeric encodings of special float values accepted by typed reads
+///
+/// JSON has no NaN or infinity, so depending on its configuration HSDS
+/// writes them as strings (`"NaN"`, `"Infinity"`, `"-Infinity"`) or as
+/// `null`. By default typed float reads reject both; set on a client with
+/// [`ClientOptions::with_special_floats`](crate::ClientOptions::with_special_floats).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpecialFloats {
+    strings: bool,
+    null_as: Option<f64>,
+}
+
+impl SpecialFloats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept both encodings, reading `null` as NaN
+    pub fn tolerant() -> Self {
+        Self { strings: true, null_as: Some(f64::NAN) }
+    }
+
+    /// Accept `"NaN"`, `"Infinity"`, `"-Infinity"` (and `"inf"`, any case) as floats
+    pub fn with_strings(mut self, accept: bool) -> Self {
+        self.strings = accept;
+        self
+    }
+
+    /// Read `null` as this value (typically `f64::NAN`)
+    pub fn with_null_as(mut self, value: f64) -> Self {
+        self.null_as = Some(value);
+        self
+    }
+
+    /// Whether any non-numeric encoding is accepted
+    pub fn is_strict(&self) -> bool {
+        !self.strings && self.null_as.is_none()
+    }
+
+    /// Float value of a JSON element, numbers included
+    pub fn parse(&self, value: &Value) -> Option<f64> {
+        match value {
+            Value::Number(n) => n.as_f64(),
+            Value::Null => self.null_as,
+            Value::String(s) if self.strings => match s.trim().to_ascii_lowercase().as_str() {
+                "nan" | "+nan" | "-nan" => Some(f64::NAN),
+                "inf" | "+inf" | "infinity" | "+infinity" => Some(f64::INFINITY),
+                "-inf" | "-infinity" => Some(f64::NEG_INFINITY),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use hsds_client::{HsdsClient, BasicAuth};
-use hsds_client::models::{DatasetCreateRequest, DataTypeSpec, ShapeSpec, LinkRequest, GroupCreateRequest};
+use hsds_client::models::{DatasetCreateRequest, H5Type, ShapeSpec, LinkRequest, GroupCreateRequest};
 use std::env;
 
+mod common;
+
 fn create_test_client() -> Result<HsdsClient, Box<dyn std::error::Error>> {
     let endpoint = env::var("HSDS_ENDPOINT").unwrap_or_else(|_| "http://localhost:5101".to_string());
     let username = env::var("HSDS_USERNAME").unwrap_or_else(|_| "admin".to_string());
@@ -23,7 +25,7 @@ async fn create_test_dataset(
     name: &str,
 ) -> Result<String, Box<dyn std::error::Error>> {
     let create_request = DatasetCreateRequest {
-        data_type: DataTypeSpec::Predefined("H5T_STD_I32LE".to_string()),
+        data_type: H5Type::named("H5T_STD_I32LE"),
         shape: Some(ShapeSpec::Dimensions(vec![10])),
         maxdims: None,
         creation_properties: None,
@@ -80,10 +82,10 @@ async fn test_set_attribute_api() {
     client.attributes().set_attribute(&domain_path, &root_group_id, "root_description", "This is the root group").await
         .expect("Failed to set root group attribute");
     
-    client.attributes().set_attribute(&domain_path, &child_group_id, "child_description", "This is a child group").await
+    client.attributes().set_attribute(&domain_path, child_group_id, "child_description", "This is a child group").await
         .expect("Failed to set child group attribute");
     
-    client.attributes().set_attribute(&domain_path, &child_group_id, "creation_time", 1642694400i64).await
+    client.attributes().set_attribute(&domain_path, child_group_id, "creation_time", 1642694400i64).await
         .expect("Failed to set group timestamp");
     
     // Dataset attribute (d- prefix)
@@ -116,7 +118,7 @@ async fn test_set_attribute_api() {
     assert_eq!(root_desc.get("value").unwrap().as_str().unwrap(), "This is the root group");
     
     // Check child group attributes  
-    let child_attrs = client.attributes().list_group_attributes(&domain_path, &child_group_id).await
+    let child_attrs = client.attributes().list_group_attributes(&domain_path, child_group_id).await
         .expect("Failed to list child group attributes");
     
     if let Some(attrs) = child_attrs.get("attributes") {
@@ -152,17 +154,17 @@ async fn test_set_attribute_api() {
     // Test 4: Create multiple attributes to show the unified API in action
     
     // Create attributes using the unified API for different types
-    client.attributes().set_attribute(&domain_path, &child_group_id, "unified_method", "set by unified API").await
+    client.attributes().set_attribute(&domain_path, child_group_id, "unified_method", "set by unified API").await
         .expect("Failed with unified method");
     
-    client.attributes().set_attribute(&domain_path, &child_group_id, "another_attr", 999i32).await
+    client.attributes().set_attribute(&domain_path, child_group_id, "another_attr", 999i32).await
         .expect("Failed to set integer attribute");
     
     // Verify both exist and work correctly
-    let unified_attr = client.attributes().get_attribute(&domain_path, "groups", &child_group_id, "unified_method").await
+    let unified_attr = client.attributes().get_attribute(&domain_path, "groups", child_group_id, "unified_method").await
         .expect("Failed to get unified attribute");
     
-    let int_attr = client.attributes().get_attribute(&domain_path, "groups", &child_group_id, "another_attr").await
+    let int_attr = client.attributes().get_attribute(&domain_path, "groups", child_group_id, "another_attr").await
         .expect("Failed to get integer attribute");
     
     assert_eq!(
@@ -206,7 +208,7 @@ async fn test_attribute_api_type_inference() {
     client.attributes().set_attribute(&domain_path, &root_group_id, "int_attr", 42i32).await
         .expect("Failed to set integer");
     
-    client.attributes().set_attribute(&domain_path, &root_group_id, "float_attr", 3.14159f64).await
+    client.attributes().set_attribute(&domain_path, &root_group_id, "float_attr", 2.5f64).await
         .expect("Failed to set float");
     
     client.attributes().set_attribute(&domain_path, &root_group_id, "bool_attr", true).await
@@ -268,7 +270,7 @@ async fn test_attribute_list_operations() {
     // Create child group and dataset for testing
     let child_group = client.groups().create_group(&domain_path, None).await
         .expect("Failed to create child group");
-    let child_group_id = child_group.id;
+    let child_group_id = &child_group.id;
     
     let dataset_id = create_test_dataset(&client, &domain_path, &root_group_id, "test_dataset").await
         .expect("Failed to create test dataset");
@@ -276,7 +278,7 @@ async fn test_attribute_list_operations() {
     println!("Testing attribute list operations...");
     
     // Test 1: List attributes on empty objects (should be empty)
-    let empty_group_attrs = client.attributes().list_group_attributes(&domain_path, &child_group_id).await
+    let empty_group_attrs = client.attributes().list_group_attributes(&domain_path, child_group_id).await
         .expect("Failed to list empty group attributes");
     
     if let Some(attrs) = empty_group_attrs.get("attributes") {
@@ -293,20 +295,20 @@ async fn test_attribute_list_operations() {
     }
     
     // Test 2: Add multiple attributes and verify listing
-    client.attributes().set_attribute(&domain_path, &child_group_id, "name", "Test Group").await
+    client.attributes().set_attribute(&domain_path, child_group_id, "name", "Test Group").await
         .expect("Failed to set name attribute");
     
-    client.attributes().set_attribute(&domain_path, &child_group_id, "version", 42i32).await
+    client.attributes().set_attribute(&domain_path, child_group_id, "version", 42i32).await
         .expect("Failed to set version attribute");
     
-    client.attributes().set_attribute(&domain_path, &child_group_id, "temperature", 23.5f64).await
+    client.attributes().set_attribute(&domain_path, child_group_id, "temperature", 23.5f64).await
         .expect("Failed to set temperature attribute");
     
-    client.attributes().set_attribute(&domain_path, &child_group_id, "active", true).await
+    client.attributes().set_attribute(&domain_path, child_group_id, "active", true).await
         .expect("Failed to set active attribute");
     
     // Test 3: List and verify all attributes
-    let group_attrs = client.attributes().list_group_attributes(&domain_path, &child_group_id).await
+    let group_attrs = client.attributes().list_group_attributes(&domain_path, child_group_id).await
         .expect("Failed to list group attributes");
     
     if let Some(attrs) = group_attrs.get("attributes") {
@@ -384,7 +386,7 @@ async fn test_attribute_get_operations() {
     client.attributes().set_attribute(&domain_path, &group_id, "integer_value", 12345i64).await
         .expect("Failed to set integer attribute");
     
-    client.attributes().set_attribute(&domain_path, &group_id, "float_value", 3.14159f64).await
+    client.attributes().set_attribute(&domain_path, &group_id, "float_value", 2.5f64).await
         .expect("Failed to set float attribute");
     
     client.attributes().set_attribute(&domain_path, &group_id, "boolean_value", false).await
@@ -422,7 +424,7 @@ async fn test_attribute_get_operations() {
     let float_attr = client.attributes().get_attribute(&domain_path, "groups", &group_id, "float_value").await
         .expect("Failed to get float attribute");
     
-    assert!((float_attr.get("value").unwrap().as_f64().unwrap() - 3.14159).abs() < 1e-10);
+    assert!((float_attr.get("value").unwrap().as_f64().unwrap() - 2.5).abs() < 1e-10);
     if let Some(attr_type) = float_attr.get("type") {
         assert_eq!(attr_type.get("class").unwrap().as_str().unwrap(), "H5T_FLOAT");
         assert_eq!(attr_type.get("base").unwrap().as_str().unwrap(), "H5T_IEEE_F64LE");
@@ -437,7 +439,7 @@ async fn test_attribute_get_operations() {
     let boolean_value = boolean_attr.get("value").unwrap();
     let is_false = boolean_value.as_u64().unwrap_or(0) == 0 || 
                    boolean_value.as_i64().unwrap_or(0) == 0 ||
-                   boolean_value.as_bool().unwrap_or(true) == false;
+                   !boolean_value.as_bool().unwrap_or(true);
     assert!(is_false, "Boolean false should be represented as 0 or false, got: {:?}", boolean_value);
     
     if let Some(attr_type) = boolean_attr.get("type") {
@@ -626,3 +628,557 @@ async fn test_attribute_delete_operations() {
     // Clean up
     client.domains().delete_domain(&domain_path).await.ok();
 }
+
+/// Test that a multi-megabyte calibration table round-trips through binary transport
+#[tokio::test]
+async fn test_large_array_attribute_binary_round_trip() {
+    env_logger::try_init().ok();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.unwrap();
+    
+    let table: Vec<f64> = (0..256 * 1024).map(|i| i as f64 * 0.25).collect();
+    client.attributes()
+        .set_attribute_array(&domain_path, "groups", &root_group_id, "calibration", &table, Some(vec![1024, 256]))
+        .await
+        .expect("Failed to set large attribute");
+    
+    let bytes = client.attributes()
+        .get_attribute_binary(&domain_path, "groups", &root_group_id, "calibration")
+        .await
+        .expect("Failed to get large attribute");
+    let expected: Vec<u8> = table.iter().flat_map(|v| v.to_le_bytes()).collect();
+    assert_eq!(bytes.as_ref(), expected.as_slice());
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+#[tokio::test]
+async fn test_large_attribute_spill_to_dataset() {
+    use hsds_client::{AttributeStorage, LargeAttributeOptions};
+    
+    env_logger::try_init().ok();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.unwrap();
+    
+    let values: Vec<i32> = (0..1000).collect();
+    let attr_data = serde_json::json!({"type": "H5T_STD_I32LE", "shape": [1000], "value": values});
+    let options = LargeAttributeOptions::new().with_max_inline_bytes(1024);
+    let storage = client.attributes()
+        .set_large_attribute(&domain_path, "groups", &root_group_id, "blob", attr_data, &options)
+        .await
+        .expect("Failed to set large attribute");
+    assert!(matches!(storage, AttributeStorage::Dataset(_)));
+    
+    let attribute = client.attributes()
+        .get_large_attribute(&domain_path, "groups", &root_group_id, "blob")
+        .await
+        .expect("Failed to get large attribute");
+    assert_eq!(attribute["value"], serde_json::json!(values));
+    
+    let storage = client.attributes()
+        .set_large_attribute(&domain_path, "groups", &root_group_id, "blob", serde_json::json!({"type": "H5T_STD_I32LE", "value": 7}), &options)
+        .await
+        .expect("Failed to overwrite attribute");
+    assert_eq!(storage, AttributeStorage::Inline);
+    
+    client.attributes().delete_large_attribute(&domain_path, "groups", &root_group_id, "blob").await
+        .expect("Failed to delete attribute");
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Tests against a mock server, runnable without HSDS
+mod mock {
+    use super::common;
+    use hsds_client::{HsdsClient, HsdsError, NoAuth};
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    /// Test that large array attributes are sent base64-encoded and small ones as JSON
+    #[tokio::test]
+    async fn test_attribute_array_transport_selection() {
+        use base64::{Engine, engine::general_purpose};
+        use hsds_client::BINARY_ATTRIBUTE_THRESHOLD;
+        use wiremock::matchers::body_partial_json;
+
+        let large: Vec<f64> = (0..BINARY_ATTRIBUTE_THRESHOLD / 8 + 1).map(|i| i as f64).collect();
+        let encoded: Vec<u8> = large.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let encoded = general_purpose::STANDARD.encode(encoded);
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/attributes/small"))
+            .and(body_partial_json(json!({"type": "H5T_STD_I32LE", "shape": [2, 2], "value": [[1, 2], [3, 4]]})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/attributes/large"))
+            .and(body_partial_json(json!({"type": "H5T_IEEE_F64LE", "encoding": "base64", "value": encoded})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/attributes/large"))
+            .and(query_param("encoding", "base64"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": "AQIDBA=="})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let attributes = client.attributes();
+        attributes.set_attribute_array("/home/test/file.h5", "datasets", "d-1", "small", &[1i32, 2, 3, 4], Some(vec![2, 2])).await
+            .expect("Failed to set small attribute");
+        attributes.set_attribute_array("/home/test/file.h5", "datasets", "d-1", "large", &large, None).await
+            .expect("Failed to set large attribute");
+
+        let bytes = attributes.get_attribute_binary("/home/test/file.h5", "datasets", "d-1", "large").await
+            .expect("Failed to get binary attribute");
+        assert_eq!(bytes.as_ref(), &[1u8, 2, 3, 4]);
+
+        let mismatch = attributes.set_attribute_array("/home/test/file.h5", "datasets", "d-1", "bad", &[1i32, 2, 3], Some(vec![2, 2])).await;
+        assert!(matches!(mismatch, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that an attribute rejected as too large is stored in a dataset
+    #[tokio::test]
+    async fn test_large_attribute_spill() {
+        use hsds_client::{AttributeStorage, LargeAttributeOptions};
+        use wiremock::matchers::body_partial_json;
+
+        let server = common::mock_server().await;
+        let attr_path = "/groups/g-root/attributes/calibration";
+        Mock::given(method("GET"))
+            .and(path(attr_path))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"message": "not found"})))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(attr_path))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "calibration", "type": {"class": "H5T_STRING"}, "value": "hsds-large-attribute:d-big"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(attr_path))
+            .and(body_partial_json(json!({"value": "hsds-large-attribute:d-big"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(attr_path))
+            .respond_with(ResponseTemplate::new(413).set_body_json(json!({"message": "too large"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/__large_attributes__"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"link": {"id": "g-big", "class": "H5L_TYPE_HARD"}})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datasets"))
+            .and(body_partial_json(json!({"type": "H5T_IEEE_F64LE", "shape": [3]})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "d-big"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-big/links/d-big"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-big/value"))
+            .and(body_partial_json(json!({"value": [1.0, 2.0, 3.0]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-big/links/d-big"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"link": {"id": "d-big", "class": "H5L_TYPE_HARD"}})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-big"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-big", "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [3]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-big/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1.0, 2.0, 3.0]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let domain = "/home/test/file.h5";
+        let attr_data = json!({"type": "H5T_IEEE_F64LE", "shape": [3], "value": [1.0, 2.0, 3.0]});
+        let storage = client.attributes()
+            .set_large_attribute(domain, "groups", "g-root", "calibration", attr_data, &LargeAttributeOptions::new())
+            .await
+            .expect("Failed to write attribute");
+        assert_eq!(storage, AttributeStorage::Dataset("d-big".to_string()));
+
+        let attribute = client.attributes()
+            .get_large_attribute(domain, "groups", "g-root", "calibration")
+            .await
+            .expect("Failed to read attribute");
+        assert_eq!(attribute["value"], json!([1.0, 2.0, 3.0]));
+        assert_eq!(attribute["shape"]["dims"], json!([3]));
+    }
+
+    /// Test that a spilled value is written in row slices, splitting a slice the server rejects
+    #[tokio::test]
+    async fn test_large_attribute_spill_in_slices() {
+        use hsds_client::{AttributeStorage, LargeAttributeOptions};
+        use wiremock::matchers::body_partial_json;
+
+        let server = common::mock_server().await;
+        let attr_path = "/groups/g-root/attributes/table";
+        Mock::given(method("GET"))
+            .and(path(attr_path))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"message": "not found"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(attr_path))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/__large_attributes__"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"link": {"id": "g-big", "class": "H5L_TYPE_HARD"}})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datasets"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "d-big"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-big/links/d-big"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+        // The server takes at most one row per request
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-big/value"))
+            .and(body_partial_json(json!({"start": [0, 0], "stop": [2, 2]})))
+            .respond_with(ResponseTemplate::new(413).set_body_json(json!({"message": "too large"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        for (row, value) in [(0, json!([[1, 2]])), (1, json!([[3, 4]])), (2, json!([[5, 6]]))] {
+            Mock::given(method("PUT"))
+                .and(path("/datasets/d-big/value"))
+                .and(body_partial_json(json!({"start": [row, 0], "stop": [row + 1, 2], "value": value})))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = common::mock_client(&server);
+        // Two rows fit the 16-byte limit, three do not
+        let options = LargeAttributeOptions::new().with_max_inline_bytes(16);
+        let attr_data = json!({"type": "H5T_STD_I32LE", "shape": [3, 2], "value": [[1, 2], [3, 4], [5, 6]]});
+        let storage = client.attributes()
+            .set_large_attribute("/home/test/file.h5", "groups", "g-root", "table", attr_data, &options)
+            .await
+            .expect("Failed to write attribute");
+        assert_eq!(storage, AttributeStorage::Dataset("d-big".to_string()));
+    }
+
+    /// Test that a reference-like string is not trusted unless the dataset is linked as a spill
+    #[tokio::test]
+    async fn test_large_attribute_unlinked_reference() {
+        use hsds_client::{AttributeStorage, LargeAttributeOptions};
+        use wiremock::matchers::path_regex;
+
+        let server = common::mock_server().await;
+        let attr_path = "/groups/g-root/attributes/note";
+        Mock::given(method("GET"))
+            .and(path(attr_path))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "note", "type": {"class": "H5T_STRING"}, "value": "hsds-large-attribute:d-victim"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(attr_path))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/__large_attributes__"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"message": "not found"})))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/__large_attributes__"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"link": {"id": "g-big", "class": "H5L_TYPE_HARD"}})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-big/links/d-victim"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"message": "not found"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/groups"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "g-new"})))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path_regex("^/(datasets|groups)/.*"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let attributes = client.attributes();
+        let attr_data = json!({"type": "H5T_STD_I32LE", "value": 7});
+        let options = LargeAttributeOptions::new();
+        for _ in 0..2 {
+            let storage = attributes
+                .set_large_attribute("/home/test/file.h5", "groups", "g-root", "note", attr_data.clone(), &options)
+                .await
+                .expect("Failed to write attribute");
+            assert_eq!(storage, AttributeStorage::Inline);
+        }
+    }
+
+    /// Test that copy_attributes filters by name and applies the overwrite policy
+    #[tokio::test]
+    async fn test_copy_attributes() {
+        use hsds_client::{AttributeOverwrite, CopyOptions};
+        use wiremock::matchers::path_regex;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-a/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "cal_gain"}, {"name": "cal_offset"}, {"name": "note"}
+            ]})))
+            .mount(&server)
+            .await;
+        for name in ["cal_gain", "cal_offset", "note"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/groups/g-a/attributes/{}", name)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "name": name,
+                    "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"},
+                    "shape": {"class": "H5S_SCALAR"},
+                    "value": 1.5
+                })))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-b/attributes/cal_gain"))
+            .and(query_param("replace", "1"))
+            .respond_with(ResponseTemplate::new(200))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-b/attributes/cal_gain"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({"message": "exists"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path_regex(r"^/datasets/d-b/attributes/(cal_offset|note)$"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let copy = |options: CopyOptions| {
+            let client = client.clone();
+            async move { client.attributes().copy_attributes("/home/a.h5", "g-a", "/home/b.h5", "d-b", &options).await }
+        };
+
+        let skip = CopyOptions::new().with_include_attributes(["cal_*"]).with_attribute_overwrite(AttributeOverwrite::Skip);
+        assert_eq!(copy(skip).await.unwrap(), vec!["cal_offset"]);
+
+        let fail = CopyOptions::new().with_include_attributes(["cal_*"]).with_attribute_overwrite(AttributeOverwrite::Fail);
+        assert!(matches!(copy(fail).await, Err(HsdsError::Conflict(_))));
+
+        let replace = CopyOptions::new().with_exclude_attributes(["note"]);
+        assert_eq!(copy(replace).await.unwrap(), vec!["cal_gain", "cal_offset"]);
+    }
+
+    /// Test that diff_attributes reports added, removed and changed attributes across domains
+    #[tokio::test]
+    async fn test_diff_attributes() {
+        let scalar = json!({"class": "H5S_SCALAR"});
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/attributes"))
+            .and(query_param("domain", "/shots/1.h5"))
+            .and(query_param("IncludeData", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "gain", "type": "H5T_IEEE_F64LE", "shape": scalar, "value": 1.5},
+                {"name": "operator", "type": "H5T_STD_I32LE", "shape": scalar, "value": 7},
+                {"name": "offsets", "type": "H5T_STD_I32LE", "shape": {"class": "H5S_SIMPLE", "dims": [2]}, "value": [1, 2]}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-2/attributes"))
+            .and(query_param("domain", "/shots/2.h5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "gain", "type": "H5T_IEEE_F64LE", "shape": scalar, "value": 1.5},
+                {"name": "offsets", "type": "H5T_STD_I32LE", "shape": {"class": "H5S_SIMPLE", "dims": [2]}, "value": [1, 3]},
+                {"name": "probe", "type": "H5T_STD_U8LE", "shape": scalar, "value": 4}
+            ]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let diff = client.attributes().diff_attributes("/shots/1.h5", "g-1", "/shots/2.h5", "g-2").await
+            .expect("Diff failed");
+
+        assert_eq!(diff.added.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(), vec!["probe"]);
+        assert_eq!(diff.removed.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(), vec!["operator"]);
+        assert_eq!(diff.changed.len(), 1);
+        let change = &diff.changed[0];
+        assert_eq!(change.name, "offsets");
+        assert!(change.value_changed() && !change.type_changed() && !change.shape_changed());
+        assert_eq!(change.after.elements::<i32>().expect("Bad elements"), vec![1, 3]);
+        assert!(!diff.is_empty());
+    }
+
+    /// Test that a conditional attribute update re-checks the predicate after losing a race
+    #[tokio::test]
+    async fn test_update_attribute_if() {
+        use hsds_client::{AttributeSwap, HsdsError, UpdateOptions};
+        use std::time::Duration;
+        use wiremock::matchers::body_json;
+
+        let status = |value: &str| json!({"name": "status", "type": {"class": "H5T_STRING"}, "shape": {"class": "H5S_SCALAR"}, "value": value});
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/attributes/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status("queued")).insert_header("ETag", "\"v1\""))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/attributes/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status("running")).insert_header("ETag", "\"v2\""))
+            .mount(&server)
+            .await;
+        // Another writer got in between the read and the write
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-1/attributes/status"))
+            .and(header("If-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(412).set_body_json(json!({"message": "ETag mismatch"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-1/attributes/status"))
+            .and(header("If-Match", "\"v2\""))
+            .and(query_param("replace", "1"))
+            .and(body_json(json!({"type": {"class": "H5T_STRING"}, "value": "done"})))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let attributes = client.attributes();
+        let domain = "/home/test/file.h5";
+        let options = UpdateOptions::new().with_backoff(Duration::from_millis(1));
+
+        let claimed = attributes
+            .update_attribute_if_with(domain, "groups", "g-1", "status", &options, |v| v == "queued", json!("running"))
+            .await
+            .expect("Conditional update failed");
+        assert_eq!(claimed, AttributeSwap::Rejected { current: json!("running") });
+
+        let finished = attributes
+            .update_attribute_if_with(domain, "groups", "g-1", "status", &options, |v| v == "running", json!("done"))
+            .await
+            .expect("Conditional update failed");
+        assert!(finished.is_swapped());
+
+        // Without an ETag the write is unconditional unless one is required
+        let unversioned = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/attributes/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status("queued")))
+            .mount(&unversioned)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-1/attributes/status"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&unversioned)
+            .await;
+
+        let client = HsdsClient::new(unversioned.uri(), NoAuth).expect("Failed to create client");
+        let attributes = client.attributes();
+        let swapped = attributes
+            .update_attribute_if(domain, "groups", "g-1", "status", |v| v == "queued", json!("running"))
+            .await
+            .expect("Unconditional fallback failed");
+        assert!(swapped.is_swapped());
+        let strict = UpdateOptions::new().with_require_etag(true);
+        let err = attributes
+            .update_attribute_if_with(domain, "groups", "g-1", "status", &strict, |v| v == "queued", json!("running"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HsdsError::OperationFailed(_)), "got {:?}", err);
+    }
+}
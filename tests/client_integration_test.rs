@@ -0,0 +1,887 @@
+use hsds_client::{HsdsClient, HsdsError, NoAuth, JsonDecoder};
+use hsds_client::{Group, HrefRel, Navigable};
+use reqwest::Method;
+use serde_json::json;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, ResponseTemplate};
+
+mod common;
+
+/// Test that execute_with sends the decoder's Accept type and decodes JSON
+#[tokio::test]
+async fn test_execute_with_json_decoder() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/datasets/d-1234/value"))
+        .and(header("Accept", "application/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1, 2, 3]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let req = client.request(Method::GET, "/datasets/d-1234/value").await
+        .expect("Failed to build request");
+
+    let value: serde_json::Value = client.execute_with(req, JsonDecoder::new()).await
+        .expect("Failed to execute request");
+
+    assert_eq!(value["value"], json!([1, 2, 3]));
+}
+
+/// Test that a base URL path is kept as a prefix for all endpoints
+#[tokio::test]
+async fn test_base_path_prefix() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/api/hsds/"))
+        .and(query_param("domain", "/home/test/file.h5"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-1", "owner": "test"})))
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/hsds/groups/g-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-1"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // With and without trailing slash on the base URL
+    for base in [format!("{}/api/hsds", server.uri()), format!("{}/api/hsds/", server.uri())] {
+        let client = HsdsClient::new(&base, NoAuth).expect("Failed to create client");
+        assert_eq!(client.base_url().path(), "/api/hsds/");
+
+        let domain = client.domains().get_domain("/home/test/file.h5").await
+            .expect("Failed to get domain");
+        assert_eq!(domain.root.as_deref(), Some("g-1"));
+    }
+
+    let client = HsdsClient::new(format!("{}/api/hsds", server.uri()), NoAuth)
+        .expect("Failed to create client");
+    let group = client.groups().get_group("/home/test/file.h5", "g-1", None).await
+        .expect("Failed to get group");
+    assert_eq!(group.id, "g-1");
+}
+
+/// Test that a 404 on a domain-level request is DomainNotFound, elsewhere ObjectNotFound
+#[tokio::test]
+async fn test_not_found_errors() {
+    use hsds_client::ErrorCode;
+
+    let server = common::mock_server().await;
+    for endpoint in ["/api/hsds/", "/api/hsds/domains", "/api/hsds/groups/g-1"] {
+        Mock::given(method("GET"))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"message": "not found"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let client = HsdsClient::new(format!("{}/api/hsds", server.uri()), NoAuth)
+        .expect("Failed to create client");
+    let err = client.domains().get_domain("/home/test/missing.h5").await.unwrap_err();
+    assert!(matches!(err, HsdsError::DomainNotFound(_)), "got {:?}", err);
+    assert_eq!(err.error_code(), ErrorCode::DomainNotFound);
+
+    let req = client.request(Method::GET, "/domains").await.expect("Failed to build request");
+    let req = HsdsClient::with_domain(req, "/home/missing/").expect("Invalid domain");
+    let err = client.execute::<serde_json::Value>(req).await.unwrap_err();
+    assert!(matches!(err, HsdsError::DomainNotFound(_)), "got {:?}", err);
+
+    let err = client.groups().get_group("/home/test/file.h5", "g-1", None).await.unwrap_err();
+    assert!(matches!(err, HsdsError::ObjectNotFound(_)), "got {:?}", err);
+    assert_eq!(err.error_code(), ErrorCode::ObjectNotFound);
+}
+
+/// Test that domain-level 404s are recognized relative to a routed, path-prefixed endpoint
+#[tokio::test]
+async fn test_not_found_errors_through_route() {
+    let server = common::mock_server().await;
+    for endpoint in ["/cluster/hsds/", "/cluster/hsds/groups/g-1"] {
+        Mock::given(method("GET"))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"message": "not found"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let client = HsdsClient::new(format!("{}/api/hsds", server.uri()), NoAuth)
+        .expect("Failed to create client")
+        .with_route("/cluster", format!("{}/cluster/hsds", server.uri()), NoAuth)
+        .unwrap();
+    let err = client.domains().get_domain("/cluster/missing.h5").await.unwrap_err();
+    assert!(matches!(err, HsdsError::DomainNotFound(_)), "got {:?}", err);
+
+    let err = client.groups().get_group("/cluster/file.h5", "g-1", None).await.unwrap_err();
+    assert!(matches!(err, HsdsError::ObjectNotFound(_)), "got {:?}", err);
+}
+
+/// Test following a typed href relation through the client's base URL
+#[tokio::test]
+async fn test_follow_href() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/api/hsds/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "root": "g-1",
+            "hrefs": [
+                {"rel": "self", "href": "http://hsds.internal/?domain=/home/test/file.h5"},
+                {"rel": "root", "href": "http://hsds.internal/groups/g-1?domain=/home/test/file.h5"}
+            ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/hsds/groups/g-1"))
+        .and(query_param("domain", "/home/test/file.h5"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-1", "linkCount": 2})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = HsdsClient::new(format!("{}/api/hsds", server.uri()), NoAuth)
+        .expect("Failed to create client");
+    let domain = client.domains().get_domain("/home/test/file.h5").await
+        .expect("Failed to get domain");
+
+    assert_eq!(domain.href(&HrefRel::This).map(|h| h.rel_kind()), Some(HrefRel::This));
+    assert!(domain.href(&HrefRel::Acls).is_none());
+
+    let root: Group = domain.follow_href(&client, HrefRel::Root).await
+        .expect("Failed to follow root href");
+    assert_eq!(root.id, "g-1");
+    assert_eq!(root.link_count, Some(2));
+}
+
+/// Test that a TestDomain guard deletes its domain when dropped
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn test_test_domain_deleted_on_drop() {
+    use hsds_client::test_util::TestDomain;
+
+    let server = common::mock_server().await;
+    Mock::given(method("PUT"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({"root": "g-1"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    {
+        let domain = TestDomain::create(&client, "/home/test", "guard").await
+            .expect("Failed to create test domain");
+        assert_eq!(domain.root(), "g-1");
+        assert!(domain.path().starts_with("/home/test/guard_"));
+    }
+
+    server.verify().await;
+}
+
+/// Test that deletes accept 204/empty bodies and execute_optional maps them to None
+#[tokio::test]
+async fn test_empty_responses() {
+    let server = common::mock_server().await;
+    Mock::given(method("DELETE"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/groups/g-1/links/old"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"hrefs": []})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/empty"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/json"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "x"})))
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    client.datasets().delete_dataset("/home/test/file.h5", "d-1").await
+        .expect("Empty 204 should be a successful delete");
+    client.links().delete_link("/home/test/file.h5", "g-1", "old").await
+        .expect("JSON body should be ignored on delete");
+
+    let empty: Option<serde_json::Value> = client.execute_optional(client.request(Method::PUT, "/empty").await.unwrap()).await
+        .expect("Empty body should not fail");
+    assert!(empty.is_none());
+    let body: Option<serde_json::Value> = client.execute_optional(client.request(Method::PUT, "/json").await.unwrap()).await
+        .expect("JSON body should parse");
+    assert_eq!(body, Some(json!({"id": "x"})));
+}
+
+/// Test that existence checks use HEAD and map 404 to false
+#[tokio::test]
+async fn test_exists_helpers() {
+    let server = common::mock_server().await;
+    Mock::given(method("HEAD"))
+        .and(path("/"))
+        .and(query_param("domain", "/home/test/file.h5"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/"))
+        .and(query_param("domain", "/home/test/missing.h5"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/groups/g-1/links/missing"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/groups/g-1/attributes/secret"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let domain = "/home/test/file.h5";
+    assert!(client.domains().domain_exists(domain).await.unwrap());
+    assert!(!client.domains().domain_exists("/home/test/missing.h5").await.unwrap());
+    assert!(client.object_exists(domain, "d-1").await.unwrap());
+    assert!(!client.links().link_exists(domain, "g-1", "missing").await.unwrap());
+
+    let denied = client.attributes().attribute_exists(domain, "groups", "g-1", "secret").await;
+    assert!(matches!(denied, Err(HsdsError::PermissionDenied(_))), "Other errors are not mapped to false");
+    assert!(matches!(client.object_exists(domain, "x-1").await, Err(HsdsError::InvalidParameter(_))));
+}
+
+/// Test that warm_up resolves the host and calls /about
+#[tokio::test]
+async fn test_warm_up() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/about"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "state": "READY",
+            "hsds_version": "0.9.0"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let report = client.warm_up().await.expect("Warm-up failed");
+
+    assert!(!report.addresses.is_empty());
+    assert_eq!(report.about["state"], "READY");
+}
+
+/// Test that a read-only client rejects writes without contacting the server
+#[tokio::test]
+async fn test_read_only_client() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "d-1"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let archive = client.clone().with_read_only(true);
+    assert!(!client.is_read_only());
+
+    let handle = archive.datasets().open("/home/test/archive.h5", "d-1").await.expect("Reads are allowed");
+    assert!(handle.is_read_only());
+
+    let deleted = archive.datasets().delete_dataset("/home/test/archive.h5", "d-1").await;
+    assert!(matches!(deleted, Err(HsdsError::ReadOnly(_))), "Got {:?}", deleted);
+    let created = archive.groups().create_group("/home/test/archive.h5", None).await;
+    assert!(matches!(created, Err(HsdsError::ReadOnly(_))));
+}
+
+/// Test that a dry-run client records mutating calls instead of sending them
+#[tokio::test]
+async fn test_dry_run_records_mutations() {
+    use hsds_client::DryRunLog;
+
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "d-1"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let log = DryRunLog::new();
+    let client = HsdsClient::new(server.uri(), NoAuth)
+        .expect("Failed to create client")
+        .with_dry_run(log.clone());
+    assert!(client.is_dry_run());
+
+    client.datasets().get_dataset("/home/test/file.h5", "d-1").await.expect("Reads are sent");
+
+    let results = client.domains().delete_domains(&["/home/test/a.h5", "/home/test/b.h5"], 2).await;
+    assert!(results.iter().all(|(_, r)| r.is_ok()));
+
+    let created = client.groups().create_group("/home/test/file.h5", None).await;
+    assert!(matches!(created, Err(HsdsError::DryRun(_))), "Got {:?}", created);
+
+    let planned = log.requests();
+    assert_eq!(planned.len(), 3);
+    assert_eq!(planned[0].method, "DELETE");
+    assert!(planned[0].query.as_deref().unwrap().contains("a.h5"));
+    assert_eq!(planned[2].method, "POST");
+    assert_eq!(planned[2].path, "/groups");
+}
+
+/// Test that the audit hook sees successful mutations only
+#[tokio::test]
+async fn test_audit_hook() {
+    use hsds_client::{AuditEvent, BasicAuth};
+    use std::sync::{Arc, Mutex};
+
+    let server = common::mock_server().await;
+    Mock::given(method("DELETE"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/datasets/d-2"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(json!({"message": "forbidden"})))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "d-1"})))
+        .mount(&server)
+        .await;
+
+    let events: Arc<Mutex<Vec<AuditEvent>>> = Arc::default();
+    let sink = events.clone();
+    let client = HsdsClient::new(server.uri(), BasicAuth::new("alice", "secret"))
+        .expect("Failed to create client")
+        .with_audit_hook(move |event: &AuditEvent| sink.lock().unwrap().push(event.clone()));
+
+    let domain = "/home/alice/run1.h5";
+    client.datasets().get_dataset(domain, "d-1").await.expect("Failed to get dataset");
+    client.datasets().delete_dataset(domain, "d-1").await.expect("Failed to delete dataset");
+    assert!(client.datasets().delete_dataset(domain, "d-2").await.is_err());
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1, "Reads and failed writes are not audited");
+    assert_eq!(events[0].operation, "DELETE");
+    assert_eq!(events[0].domain.as_deref(), Some(domain));
+    assert_eq!(events[0].object_id.as_deref(), Some("d-1"));
+    assert_eq!(events[0].user.as_deref(), Some("alice"));
+    assert_eq!(events[0].status, 200);
+}
+
+/// Test that impersonation is sent with every request and audited
+#[tokio::test]
+async fn test_impersonation() {
+    use hsds_client::{AuditEvent, BasicAuth, Impersonation};
+    use std::sync::{Arc, Mutex};
+
+    let server = common::mock_server().await;
+    Mock::given(method("DELETE"))
+        .and(path("/datasets/d-1"))
+        .and(header("X-Acting-User", "bob"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/datasets/d-2"))
+        .and(query_param("acting_user", "carol"))
+        .and(query_param("domain", "/home/bob/run1.h5"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let events: Arc<Mutex<Vec<AuditEvent>>> = Arc::default();
+    let sink = events.clone();
+    let service = HsdsClient::new(server.uri(), BasicAuth::new("ingest", "secret"))
+        .expect("Failed to create client")
+        .with_audit_hook(move |event: &AuditEvent| sink.lock().unwrap().push(event.clone()));
+    assert!(Impersonation::header("bad header", "bob").is_err());
+
+    let as_bob = service.clone()
+        .with_impersonation(Impersonation::header("X-Acting-User", "bob").expect("Invalid header"));
+    let as_carol = service.clone().with_impersonation(Impersonation::query("acting_user", "carol"));
+    assert_eq!(as_bob.acting_user(), Some("bob"));
+    assert_eq!(service.acting_user(), None);
+
+    let domain = "/home/bob/run1.h5";
+    as_bob.datasets().delete_dataset(domain, "d-1").await.expect("Failed to delete as bob");
+    as_carol.datasets().delete_dataset(domain, "d-2").await.expect("Failed to delete as carol");
+
+    let events = events.lock().unwrap();
+    let users: Vec<_> = events.iter().map(|e| (e.user.as_deref(), e.acting_user.as_deref())).collect();
+    assert_eq!(users, vec![(Some("ingest"), Some("bob")), (Some("ingest"), Some("carol"))]);
+}
+
+/// Test that responses larger than the configured cap are rejected
+#[tokio::test]
+async fn test_max_response_size() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "d-1",
+            "shape": {"class": "H5S_SIMPLE", "dims": [1000]}
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/datasets/d-1/value"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": vec![12345; 1000]})))
+        .mount(&server)
+        .await;
+
+    let client = HsdsClient::new(server.uri(), NoAuth)
+        .expect("Failed to create client")
+        .with_max_response_size(1024);
+    assert_eq!(client.max_response_size(), Some(1024));
+    let datasets = client.datasets();
+
+    let result = datasets.read_dataset_values_json("/home/test/file.h5", "d-1", None, None, None).await;
+    assert!(matches!(result, Err(HsdsError::ResponseTooLarge { limit: 1024, size }) if size > 1024));
+    let result = datasets.read_dataset_values_streamed::<u32>("/home/test/file.h5", "d-1", None).await;
+    assert!(matches!(result, Err(HsdsError::ResponseTooLarge { .. })));
+
+    // Small responses are unaffected
+    let dataset = datasets.get_dataset("/home/test/file.h5", "d-1").await.unwrap();
+    assert_eq!(dataset.id, "d-1");
+}
+
+/// Test that scoped options retry reads and apply timeouts without affecting the parent client
+#[tokio::test]
+async fn test_client_with_options() {
+    use std::time::Duration;
+
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-1"})))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-slow"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"id": "g-slow"}))
+                .set_delay(Duration::from_millis(500)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let patient = client.with_options(|o| o.with_max_retries(2).with_backoff(Duration::from_millis(10)));
+    assert_eq!(patient.options().max_retries(), 2);
+    assert_eq!(client.options().max_retries(), 0);
+
+    let group: serde_json::Value = patient.execute(patient.request(reqwest::Method::GET, "/groups/g-1").await.unwrap()).await.unwrap();
+    assert_eq!(group["id"], "g-1");
+
+    let hasty = client.with_options(|o| o.with_timeout(Duration::from_millis(50)));
+    let result: Result<serde_json::Value, _> =
+        hasty.execute(hasty.request(reqwest::Method::GET, "/groups/g-slow").await.unwrap()).await;
+    assert!(matches!(result, Err(HsdsError::Http(e)) if e.is_timeout()));
+    let group: serde_json::Value = client.execute(client.request(reqwest::Method::GET, "/groups/g-slow").await.unwrap()).await.unwrap();
+    assert_eq!(group["id"], "g-slow");
+}
+
+/// Test that a cancellation token aborts requests in flight and stops listing streams
+#[tokio::test]
+async fn test_cancellation() {
+    use futures_util::StreamExt;
+    use hsds_client::{LinkOrder, PaginationOptions};
+    use std::time::{Duration, Instant};
+    use tokio_util::sync::CancellationToken;
+
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-slow"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"id": "g-slow"}))
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1/links"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+            {"title": "a", "class": "H5L_TYPE_HARD"},
+            {"title": "b", "class": "H5L_TYPE_HARD"}
+        ]})))
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+
+    // A request in flight is aborted when the token is cancelled
+    let token = CancellationToken::new();
+    let scoped = client.with_cancellation(token.clone());
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        canceller.cancel();
+    });
+    let started = Instant::now();
+    let req = scoped.request(reqwest::Method::GET, "/groups/g-slow").await.unwrap();
+    let result: Result<serde_json::Value, _> = scoped.execute(req).await;
+    assert!(matches!(result, Err(HsdsError::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // Helpers stop at their next request; nothing is sent once cancelled
+    let token = CancellationToken::new();
+    let scoped = client.with_cancellation(token.clone());
+    let links = scoped.links();
+    let mut stream = Box::pin(links.links_stream_with("/home/test/file.h5", "g-1", LinkOrder::Name, PaginationOptions::fixed(2)));
+    assert_eq!(stream.next().await.unwrap().unwrap().title, "a");
+    assert_eq!(stream.next().await.unwrap().unwrap().title, "b");
+    token.cancel();
+    assert!(matches!(stream.next().await, Some(Err(HsdsError::Cancelled))));
+    let sent = server.received_requests().await.unwrap().iter().filter(|r| r.url.path() == "/groups/g-1/links").count();
+    assert_eq!(sent, 1);
+
+    // Dropping a stream cancels it too: pages are only requested when polled
+    let links = client.links();
+    let mut stream = Box::pin(links.links_stream_with("/home/test/file.h5", "g-1", LinkOrder::Name, PaginationOptions::fixed(2)));
+    assert!(stream.next().await.unwrap().is_ok());
+    drop(stream);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let sent = server.received_requests().await.unwrap().iter().filter(|r| r.url.path() == "/groups/g-1/links").count();
+    assert_eq!(sent, 2);
+}
+
+/// Test that writes return typed acknowledgements, including for empty bodies
+#[tokio::test]
+async fn test_write_results() {
+    let server = common::mock_server().await;
+    Mock::given(method("PUT"))
+        .and(path("/datasets/d-1/value"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/groups/g-1/attributes/gain"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+            "hrefs": [{"rel": "self", "href": "/groups/g-1/attributes/gain"}]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/datatypes"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+            "id": "t-1", "attributeCount": 0, "created": 1700000000.0, "root": "g-root"
+        })))
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let request = hsds_client::DatasetValueRequest {
+        start: None,
+        stop: None,
+        step: None,
+        points: None,
+        value: Some(json!([1, 2])),
+        value_base64: None,
+    };
+    let written = client.datasets().write_dataset_values("/home/test/file.h5", "d-1", request).await.unwrap();
+    assert!(written.hrefs.is_none() && written.extra.is_empty());
+
+    let written = client.attributes().set_attribute("/home/test/file.h5", "g-1", "gain", 2.5).await.unwrap();
+    assert_eq!(written.hrefs.unwrap()[0].rel, "self");
+
+    let datatype = client.datatypes()
+        .commit_datatype("/home/test/file.h5", json!({"type": "H5T_STD_I32LE"}))
+        .await
+        .unwrap();
+    assert_eq!((datatype.id.as_str(), datatype.attribute_count), ("t-1", Some(0)));
+}
+
+/// Test that routes send requests to the endpoint and credentials of the domain's prefix
+#[tokio::test]
+async fn test_endpoint_routes() {
+    use hsds_client::BasicAuth;
+
+    let public = common::mock_server().await;
+    let internal = common::mock_server().await;
+    for server in [&public, &internal] {
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-1"})))
+            .mount(server)
+            .await;
+    }
+
+    let client = HsdsClient::new(public.uri(), NoAuth)
+        .expect("Failed to create client")
+        .with_route("/internal/", format!("{}/", internal.uri()), BasicAuth::new("svc", "s3cret"))
+        .unwrap();
+    assert_eq!(client.base_url_for("/internal/data/a.h5").as_str(), format!("{}/", internal.uri()));
+    assert_eq!(client.base_url_for("/internals.h5"), client.base_url());
+
+    for domain in ["/public/a.h5", "/internal/data/a.h5", "/internals.h5", "/internal"] {
+        client.groups().get_group(domain, "g-1", None).await.unwrap();
+    }
+
+    let routed = internal.received_requests().await.unwrap();
+    let domains: Vec<String> = routed
+        .iter()
+        .map(|r| r.url.query_pairs().find(|(k, _)| k == "domain").unwrap().1.into_owned())
+        .collect();
+    assert_eq!(domains, vec!["/internal/data/a.h5", "/internal"]);
+    assert!(routed.iter().all(|r| r.headers.get("authorization").is_some()));
+    let direct = public.received_requests().await.unwrap();
+    assert_eq!(direct.len(), 2);
+    assert!(direct.iter().all(|r| r.headers.get("authorization").is_none()));
+}
+
+/// Test that transfer stats count requests, retries, bytes and latencies
+#[tokio::test]
+async fn test_transfer_stats() {
+    use hsds_client::TransferStats;
+    use std::time::Duration;
+
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(r#"{"id": "g-1"}"#)
+                .set_delay(Duration::from_millis(50)),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/datasets/d-1/value"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let stats = TransferStats::new();
+    let client = HsdsClient::new(server.uri(), NoAuth)
+        .expect("Failed to create client")
+        .with_options(|o| o.with_max_retries(1).with_backoff(Duration::from_millis(1)))
+        .with_transfer_stats(stats.clone());
+
+    let _: serde_json::Value = client.execute(client.request(Method::GET, "/groups/g-1").await.unwrap()).await.unwrap();
+    client.datasets()
+        .write_region_raw("/home/test/a.h5", "d-1", None, vec![0u8; 16], "application/octet-stream")
+        .await
+        .unwrap();
+
+    let report = stats.report();
+    assert_eq!(report.requests, 3);
+    assert_eq!(report.retries, 1);
+    assert_eq!(report.failures, 1);
+    assert_eq!(report.bytes_sent, 16);
+    assert_eq!(report.bytes_received, 13);
+    assert!(report.max_latency >= Duration::from_millis(50));
+    assert!(report.p50_latency <= report.p95_latency && report.p99_latency <= report.max_latency);
+    assert!(report.elapsed >= report.max_latency);
+    assert!(report.to_string().starts_with("3 requests (1 retries, 1 failed)"));
+
+    stats.reset();
+    assert_eq!(stats.report(), Default::default());
+}
+
+/// Test that strict clients reject response fields the models do not have
+#[tokio::test]
+async fn test_strict_deserialization() {
+    use hsds_client::Strictness;
+
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/domains"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "domains": [{"name": "/exp/a.h5", "class": "domain", "root": "g-1", "quota": 10}],
+            "hrefs": [],
+            "total": 1
+        })))
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let listing = client.domains().list_folder("/exp", None, None).await
+        .expect("Lenient listing failed");
+    assert_eq!(listing.domains[0].name, "/exp/a.h5");
+
+    let strict = client.with_options(|o| o.with_strictness(Strictness::Strict));
+    match strict.domains().list_folder("/exp", None, None).await {
+        Err(HsdsError::InvalidResponse(message)) => {
+            assert!(message.contains("domains[0].quota, total"), "{}", message);
+        }
+        other => panic!("Expected an invalid response, got {:?}", other),
+    }
+}
+
+/// Test that an empty domain path stands for the client's default domain
+#[tokio::test]
+async fn test_default_domain() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .and(query_param("domain", "/home/test/run 42.h5"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-1", "root": "g-1"})))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    match client.groups().get_group("", "g-1", None).await {
+        Err(HsdsError::InvalidParameter(_)) => {}
+        other => panic!("Expected a missing domain error, got {:?}", other),
+    }
+
+    let client = client.with_default_domain("/home/test/run 42.h5").expect("Invalid domain");
+    assert_eq!(client.default_domain().map(|d| d.as_str()), Some("/home/test/run 42.h5"));
+    let group = client.groups().get_group("", "g-1", None).await.expect("Default domain read failed");
+    assert_eq!(group.id, "g-1");
+    // An explicit domain still wins
+    client.groups().get_group("/home/test/run 42.h5", "g-1", None).await.expect("Explicit domain read failed");
+}
+
+/// Test that handles expose cached attribute and link counts and refresh them
+#[tokio::test]
+async fn test_handle_counts() {
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "g-1", "root": "g-1", "attributeCount": 0, "linkCount": 3
+        })))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "g-1", "root": "g-1", "attributeCount": 2, "linkCount": 3
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/datasets/d-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "d-1", "type": {"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"},
+            "shape": {"class": "H5S_SIMPLE", "dims": [4]}
+        })))
+        .mount(&server)
+        .await;
+
+    let client = common::mock_client(&server);
+    let mut group = client.groups().open("/home/test/file.h5", "g-1").await.expect("Open failed");
+    assert_eq!(group.link_count(), Some(3));
+    assert!(group.has_links());
+    assert!(!group.has_attributes());
+
+    group.refresh().await.expect("Refresh failed");
+    assert_eq!(group.attribute_count(), Some(2));
+    assert!(group.has_attributes());
+
+    // Without a reported count, callers are told to look
+    let dataset = client.datasets().open("/home/test/file.h5", "d-1").await.expect("Open failed");
+    assert_eq!(dataset.attribute_count(), None);
+    assert!(dataset.has_attributes());
+}
+
+/// Test that with HTTP/2 prior knowledge concurrent requests share one connection
+#[cfg(feature = "http2")]
+#[tokio::test]
+async fn test_http2_prior_knowledge_multiplexes() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let server = common::mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/groups/g-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-1"})).set_delay(Duration::from_millis(200)))
+        .expect(20)
+        .mount(&server)
+        .await;
+
+    // Count the TCP connections the client opens by proxying them to the mock
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = format!("http://{}", listener.local_addr().unwrap());
+    let upstream = *server.address();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((mut inbound, _)) = listener.accept().await {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut outbound = tokio::net::TcpStream::connect(upstream).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+            });
+        }
+    });
+
+    let client = HsdsClient::new(&proxy, NoAuth)
+        .expect("Failed to create client")
+        .with_http2_prior_knowledge()
+        .expect("Failed to enable HTTP/2");
+    assert!(client.uses_http2_prior_knowledge());
+
+    // Open the connection first so the concurrent requests find it in the pool
+    let groups = client.groups();
+    groups.get_group("/home/test/file.h5", "g-1", None).await.expect("First request failed");
+    let requests = (0..19).map(|_| groups.get_group("/home/test/file.h5", "g-1", None));
+    for result in futures_util::future::join_all(requests).await {
+        result.expect("Multiplexed request failed");
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
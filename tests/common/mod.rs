@@ -0,0 +1,15 @@
+//! Setup shared by the tests that run against a mock HSDS server
+
+use hsds_client::{HsdsClient, NoAuth};
+use wiremock::MockServer;
+
+/// Start a mock HSDS server, setting up logging for the test
+pub async fn mock_server() -> MockServer {
+    let _ = env_logger::try_init();
+    MockServer::start().await
+}
+
+/// Client without credentials talking to `server`
+pub fn mock_client(server: &MockServer) -> HsdsClient {
+    HsdsClient::new(server.uri(), NoAuth).expect("Failed to create client")
+}
@@ -1,9 +1,11 @@
-use hsds_client::{HsdsClient, BasicAuth, HsdsResult};
-use hsds_client::models::{DatasetCreateRequest, DataTypeSpec, ShapeSpec, LinkRequest, DatasetValueRequest, ShapeUpdateRequest};
+use hsds_client::{HsdsClient, BasicAuth, HsdsError, HsdsResult, AppendOptions, RingBufferDataset};
+use hsds_client::models::{DatasetCreateRequest, H5Type, ShapeSpec, LinkRequest, DatasetValueRequest, ShapeUpdateRequest, ResizeKind};
 use std::time::{SystemTime, UNIX_EPOCH};
 use serde_json::json;
 use base64::{Engine as _, engine::general_purpose};
 
+mod common;
+
 /// Helper to create a test client
 fn create_test_client() -> HsdsResult<HsdsClient> {
     HsdsClient::new(
@@ -24,7 +26,7 @@ fn create_test_domain_name() -> String {
 /// Helper to create a simple dataset creation request
 fn create_simple_dataset_request(root_group_id: String) -> DatasetCreateRequest {
     DatasetCreateRequest {
-        data_type: DataTypeSpec::Predefined("H5T_STD_I32LE".to_string()),
+        data_type: H5Type::named("H5T_STD_I32LE"),
         shape: Some(ShapeSpec::Dimensions(vec![10, 10])),
         maxdims: None,
         creation_properties: None,
@@ -38,7 +40,7 @@ fn create_simple_dataset_request(root_group_id: String) -> DatasetCreateRequest
 /// Helper to create a 1D dataset creation request
 fn create_1d_dataset_request(root_group_id: String) -> DatasetCreateRequest {
     DatasetCreateRequest {
-        data_type: DataTypeSpec::Predefined("H5T_IEEE_F64LE".to_string()),
+        data_type: H5Type::named("H5T_IEEE_F64LE"),
         shape: Some(ShapeSpec::Dimensions(vec![5])),
         maxdims: None,
         creation_properties: None,
@@ -52,7 +54,7 @@ fn create_1d_dataset_request(root_group_id: String) -> DatasetCreateRequest {
 /// Helper to create an unlimited dimension dataset request
 fn create_unlimited_dataset_request(root_group_id: String) -> DatasetCreateRequest {
     DatasetCreateRequest {
-        data_type: DataTypeSpec::Predefined("H5T_STD_I32LE".to_string()),
+        data_type: H5Type::named("H5T_STD_I32LE"),
         shape: Some(ShapeSpec::Dimensions(vec![0])), // Start with 0 size
         maxdims: Some(vec![0]), // 0 means unlimited
         creation_properties: None,
@@ -289,7 +291,7 @@ async fn test_delete_dataset() {
         .expect("Failed to get dataset before deletion");
     
     // Delete the dataset
-    let _result = client.datasets().delete_dataset(&domain_path, &dataset.id).await
+    client.datasets().delete_dataset(&domain_path, &dataset.id).await
         .expect("Failed to delete dataset");
     
     // Verify the dataset no longer exists
@@ -539,7 +541,7 @@ async fn test_write_binary_data() {
     
     // Create a dataset suitable for binary data (uint8)
     let dataset_request = DatasetCreateRequest {
-        data_type: DataTypeSpec::Predefined("H5T_STD_U8LE".to_string()),
+        data_type: H5Type::named("H5T_STD_U8LE"),
         shape: Some(ShapeSpec::Dimensions(vec![10])),
         maxdims: None,
         creation_properties: None,
@@ -627,3 +629,2096 @@ async fn test_multiple_dataset_operations() {
     client.datasets().delete_dataset(&domain_path, &dataset3.id).await.ok();
     client.domains().delete_domain(&domain_path).await.ok();
 }
+
+/// Test get_or_create_dataset reuses a compatible dataset and rejects an incompatible one
+#[tokio::test]
+async fn test_get_or_create_dataset() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let request = DatasetCreateRequest::from_hsds_type("H5T_STD_I32LE", vec![10]);
+    
+    // First call creates, second call returns the same dataset
+    let first = client.datasets()
+        .get_or_create_dataset(&domain_path, &root_group_id, "channel_1", request.clone()).await
+        .expect("Failed to create dataset");
+    let second = client.datasets()
+        .get_or_create_dataset(&domain_path, &root_group_id, "channel_1", request).await
+        .expect("Failed to get existing dataset");
+    assert_eq!(first.id, second.id, "Re-running should return the existing dataset");
+    
+    // Incompatible type is rejected
+    let incompatible = DatasetCreateRequest::from_hsds_type("H5T_IEEE_F64LE", vec![10]);
+    let result = client.datasets()
+        .get_or_create_dataset(&domain_path, &root_group_id, "channel_1", incompatible).await;
+    assert!(matches!(result, Err(HsdsError::Conflict(_))), "Incompatible dataset should conflict");
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Property-based write/read round trip over random types and shapes
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_random_value_round_trip() {
+    use hsds_client::values::arbitrary::{assert_round_trip, value_case};
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+    
+    let _ = env_logger::try_init();
+    
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    runtime.block_on(client.domains().create_domain(&domain_path, None))
+        .expect("Failed to create test domain");
+    
+    let mut runner = TestRunner::new(Config::with_cases(16));
+    let result = runner.run(&value_case(3, 8), |case| {
+        runtime.block_on(assert_round_trip(&client, &domain_path, &case))
+            .map_err(|e| TestCaseError::fail(e.to_string()))
+    });
+    
+    // Clean up
+    runtime.block_on(client.domains().delete_domain(&domain_path)).ok();
+    result.expect("Round trip property failed");
+}
+
+/// Test validated resize: grow within maxdims, reject shrink without the flag
+#[tokio::test]
+async fn test_resize_dataset_guardrails() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let dataset = client.datasets().create_dataset(&domain_path, create_unlimited_dataset_request(root_group_id)).await
+        .expect("Failed to create dataset");
+    
+    let grown = client.datasets().resize_dataset(&domain_path, &dataset.id, vec![20], false).await
+        .expect("Failed to grow dataset");
+    assert_eq!(grown.kind, ResizeKind::Grow);
+    assert_eq!(grown.shape, vec![20]);
+    
+    let refused = client.datasets().resize_dataset(&domain_path, &dataset.id, vec![5], false).await;
+    assert!(matches!(refused, Err(HsdsError::InvalidParameter(_))), "Shrink should require allow_shrink");
+    
+    let shrunk = client.datasets().resize_dataset(&domain_path, &dataset.id, vec![5], true).await
+        .expect("Failed to shrink dataset");
+    assert_eq!(shrunk.previous, vec![20]);
+    assert_eq!(shrunk.shape, vec![5]);
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test that concurrent coordinated appends land in disjoint regions
+#[tokio::test]
+async fn test_append_coordinated_concurrent() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let dataset = client.datasets().create_dataset(&domain_path, create_unlimited_dataset_request(root_group_id)).await
+        .expect("Failed to create dataset");
+    
+    let datasets = client.datasets();
+    let writers = (0..4).map(|writer| {
+        let options = AppendOptions::new().with_lock(std::time::Duration::from_secs(30));
+        datasets.append_coordinated(&domain_path, &dataset.id, 3, json!([writer, writer, writer]), options)
+    });
+    let mut results: Vec<_> = futures_util::future::join_all(writers).await
+        .into_iter()
+        .collect::<HsdsResult<_>>()
+        .expect("Coordinated append failed");
+    
+    results.sort_by_key(|r| r.start);
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.start, i as u64 * 3);
+        assert_eq!(result.stop, result.start + 3);
+    }
+    
+    let values = client.datasets().read_dataset_values_json(&domain_path, &dataset.id, None, None, None).await
+        .expect("Failed to read values");
+    assert_eq!(values["value"].as_array().map(|v| v.len()), Some(12));
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test that a ring buffer wraps and returns rows in chronological order
+#[tokio::test]
+async fn test_ring_buffer_dataset() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let mut ring = RingBufferDataset::create(&client, &domain_path, &root_group_id, "window", "H5T_STD_I32LE", 5, Some(2)).await
+        .expect("Failed to create ring buffer");
+    
+    ring.push((0..3).map(|i| json!([i, i])).collect()).await.expect("Failed to push");
+    ring.push((3..7).map(|i| json!([i, i])).collect()).await.expect("Failed to push");
+    assert_eq!(ring.len(), 5);
+    
+    let reopened = RingBufferDataset::open(&client, &domain_path, ring.dataset_id()).await
+        .expect("Failed to open ring buffer");
+    let latest = reopened.latest(3).await.expect("Failed to read latest rows");
+    assert_eq!(latest, vec![json!([4, 4]), json!([5, 5]), json!([6, 6])]);
+    
+    let all: Vec<_> = reopened.iter_chronological().await.expect("Failed to read rows").collect();
+    assert_eq!(all, (2..7).map(|i| json!([i, i])).collect::<Vec<_>>());
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test that a record log writer appends batches to an unlimited compound dataset
+#[tokio::test]
+async fn test_record_log_writer() {
+    use hsds_client::{RecordLogOptions, RecordLogWriter};
+    
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let fields = [("ts", "H5T_STD_I64LE"), ("value", "H5T_IEEE_F64LE")];
+    let options = RecordLogOptions::new().with_batch_records(4);
+    let mut writer = RecordLogWriter::create(&client, &domain_path, &root_group_id, "events", &fields, options).await
+        .expect("Failed to create record log");
+    
+    for ts in 0..10 {
+        writer.push(&json!({"ts": ts, "value": ts as f64 * 0.5})).await
+            .expect("Failed to push record");
+    }
+    writer.flush().await.expect("Failed to flush");
+    assert_eq!(writer.written(), 10);
+    
+    let shape = client.datasets().get_dataset(&domain_path, writer.dataset_id()).await
+        .expect("Failed to get dataset")
+        .shape.and_then(|s| s.dims);
+    assert_eq!(shape, Some(vec![10]));
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test reading storage statistics after writing part of a dataset
+#[tokio::test]
+async fn test_dataset_storage_info() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let request = DatasetCreateRequest::from_hsds_type_with_link("H5T_STD_I32LE", vec![100], &root_group_id, "stats");
+    let dataset = client.datasets().create_dataset(&domain_path, request).await
+        .expect("Failed to create dataset");
+    let write = DatasetValueRequest {
+        start: Some(vec![0]),
+        stop: Some(vec![10]),
+        step: None,
+        points: None,
+        value: Some(json!((0..10).collect::<Vec<i32>>())),
+        value_base64: None,
+    };
+    client.datasets().write_dataset_values(&domain_path, &dataset.id, write).await
+        .expect("Failed to write values");
+    
+    let info = client.datasets().storage_info(&domain_path, &dataset.id).await
+        .expect("Failed to get storage info");
+    assert_eq!(info.shape, Some(vec![100]));
+    assert!(info.layout_class.is_some(), "Layout should be reported");
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test that placeholder datasets with a null dataspace read as empty
+#[tokio::test]
+async fn test_null_dataspace_dataset() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let request = DatasetCreateRequest {
+        data_type: H5Type::named("H5T_IEEE_F64LE"),
+        shape: Some(ShapeSpec::null()),
+        maxdims: None,
+        creation_properties: None,
+        link: Some(LinkRequest { id: root_group_id, name: "placeholder".to_string() }),
+    };
+    let dataset = client.datasets().create_dataset(&domain_path, request).await
+        .expect("Failed to create null dataspace dataset");
+    let values = client.datasets().read_dataset_values_json(&domain_path, &dataset.id, None, None, None).await
+        .expect("Reading a null dataspace should give an empty result");
+    assert_eq!(values["value"], json!([]));
+    
+    let handle = client.datasets().open(&domain_path, &dataset.id).await
+        .expect("Failed to open dataset");
+    assert!(handle.is_empty());
+    assert!(handle.info().shape.as_ref().is_some_and(|s| s.is_null()));
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Tests against a mock server, runnable without HSDS
+mod mock {
+    use super::common;
+    use hsds_client::{HsdsError, BinaryDecoder};
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    /// Test that the binary decoder negotiates octet-stream and returns raw bytes
+    #[tokio::test]
+    async fn test_read_dataset_values_binary() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1234/value"))
+            .and(query_param("domain", "/home/test/file.h5"))
+            .and(header("Accept", "application/octet-stream"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![1u8, 0, 0, 0, 2, 0, 0, 0]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let bytes = client.datasets()
+            .read_dataset_values_with("/home/test/file.h5", "d-1234", None, None, None, BinaryDecoder)
+            .await
+            .expect("Failed to read values");
+
+        assert_eq!(bytes.as_ref(), &[1u8, 0, 0, 0, 2, 0, 0, 0]);
+    }
+
+    /// Test that raw region writes send the payload untouched with its content type
+    #[tokio::test]
+    async fn test_write_region_raw() {
+        use hsds_client::raw::RawWriteBuffer;
+        use wiremock::matchers::body_bytes;
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1234/value"))
+            .and(query_param("select", "[0:2]"))
+            .and(header("Content-Type", "application/octet-stream"))
+            .and(body_bytes(vec![1u8, 0, 2, 0]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let mut buffer = RawWriteBuffer::new();
+        client.datasets()
+            .write_region_raw("/home/test/file.h5", "d-1234", Some("[0:2]"), buffer.encode(&[1u16, 2]).unwrap(), "application/octet-stream")
+            .await
+            .expect("Failed to write raw region");
+    }
+
+    /// Test that a coordinated append retries after a conflict and writes the extended region
+    #[tokio::test]
+    async fn test_append_coordinated_retries_conflict() {
+        use hsds_client::AppendOptions;
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1234/shape"))
+            .and(body_json(json!({"extend": 2, "extend_dim": 0})))
+            .respond_with(ResponseTemplate::new(409).set_body_string("conflict"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1234/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"selection": "[4:6,0:3]"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1234"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1234",
+                "shape": {"class": "H5S_SIMPLE", "dims": [6, 3], "maxdims": [0, 3]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1234/value"))
+            .and(body_json(json!({"start": [4, 0], "stop": [6, 3], "value": [[1, 2, 3], [4, 5, 6]]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let options = AppendOptions::new().with_backoff(std::time::Duration::from_millis(1));
+        let result = client.datasets()
+            .append_coordinated("/home/test/file.h5", "d-1234", 2, json!([[1, 2, 3], [4, 5, 6]]), options)
+            .await
+            .expect("Append should succeed after retry");
+
+        assert_eq!((result.start, result.stop, result.attempts), (4, 6, 2));
+    }
+
+    /// Test that a write conflict after a successful extend rewrites the same region without extending again
+    #[tokio::test]
+    async fn test_append_coordinated_never_re_extends() {
+        use hsds_client::AppendOptions;
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"selection": "[8:10]"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [10], "maxdims": [0]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(409))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-2/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-3/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"selection": "[10:8]"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let options = AppendOptions::new().with_backoff(std::time::Duration::from_millis(1));
+        let result = datasets
+            .append_coordinated("/home/test/file.h5", "d-1", 2, json!([1, 2]), options.clone())
+            .await
+            .expect("Append should succeed after rewriting");
+        assert_eq!((result.start, result.stop, result.attempts), (8, 10, 2));
+
+        // Without the allocated selection the region is unknown
+        let err = datasets
+            .append_coordinated("/home/test/file.h5", "d-2", 2, json!([1, 2]), options.clone())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HsdsError::InvalidResponse(_)), "got {:?}", err);
+
+        // An inverted region is rejected, not subtracted
+        let err = datasets
+            .append_coordinated("/home/test/file.h5", "d-3", 2, json!([1, 2]), options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HsdsError::InvalidResponse(_)), "got {:?}", err);
+    }
+
+    /// Test that a stale append lock is broken only with If-Match on the ETag it was read with
+    #[tokio::test]
+    async fn test_append_lock_compare_and_delete() {
+        use hsds_client::{AppendOptions, APPEND_LOCK_ATTRIBUTE};
+
+        let server = common::mock_server().await;
+        let lock_path = format!("/datasets/d-1/attributes/{}", APPEND_LOCK_ATTRIBUTE);
+        Mock::given(method("PUT"))
+            .and(path(lock_path.clone()))
+            .respond_with(ResponseTemplate::new(409))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(lock_path.clone()))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(lock_path.clone()))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"stale\"")
+                .set_body_json(json!({"value": "{\"owner\": \"other\", \"acquired\": 0}"})))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(lock_path.clone()))
+            .and(header("If-Match", "\"stale\""))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // Our own lock, read back on release: deleted only if unchanged
+        Mock::given(method("GET"))
+            .and(path(lock_path.clone()))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"selection": "[0:1]"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [1], "maxdims": [0]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let options = AppendOptions::new()
+            .with_backoff(std::time::Duration::from_millis(1))
+            .with_lock(std::time::Duration::from_secs(60));
+        client.datasets()
+            .append_coordinated("/home/test/file.h5", "d-1", 1, json!([7]), options)
+            .await
+            .expect("Append should succeed after breaking the stale lock");
+    }
+
+    /// Test that the record log writer batches records into field-ordered appends
+    #[tokio::test]
+    async fn test_record_log_writer_batches() {
+        use hsds_client::{RecordLogOptions, RecordLogWriter};
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-log"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-log",
+                "type": {"class": "H5T_COMPOUND", "fields": [
+                    {"name": "ts", "type": "H5T_STD_I64LE"},
+                    {"name": "level", "type": "H5T_STD_U8LE"}
+                ]},
+                "shape": {"class": "H5S_SIMPLE", "dims": [2], "maxdims": [0]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-log/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"selection": "[0:2]"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-log/value"))
+            .and(body_json(json!({"start": [0], "stop": [2], "value": [[10, 1], [11, 2]]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let options = RecordLogOptions::new().with_batch_records(2).with_max_buffered(3);
+        let mut writer = RecordLogWriter::open(&client, "/home/test/file.h5", "d-log", options).await
+            .expect("Failed to open record log");
+
+        writer.push(&json!({"level": 1, "ts": 10})).await.expect("Failed to push");
+        writer.push(&json!([11, 2])).await.expect("Failed to push");
+        assert_eq!((writer.written(), writer.buffered()), (2, 0));
+
+        assert!(writer.push(&json!({"ts": 12})).await.is_err(), "Missing field should be rejected");
+        for ts in 12..15 {
+            assert!(writer.try_push(&json!([ts, 0])).expect("Failed to buffer"));
+        }
+        assert!(writer.is_saturated());
+        assert!(!writer.try_push(&json!([15, 0])).expect("Failed to check capacity"));
+        assert_eq!(writer.buffered(), 3);
+    }
+
+    /// Test that a flush retried after a failed write reuses its allocated rows
+    #[tokio::test]
+    async fn test_record_log_writer_retry_reuses_rows() {
+        use hsds_client::{RecordLogOptions, RecordLogWriter};
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-log"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-log",
+                "type": {"class": "H5T_COMPOUND", "fields": [{"name": "ts", "type": "H5T_STD_I64LE"}]},
+                "shape": {"class": "H5S_SIMPLE", "dims": [4], "maxdims": [0]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-log/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"selection": "[4:6]"})))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-log/shape"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"selection": "[6:7]"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-log/value"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-log/value"))
+            .and(body_json(json!({"start": [4], "stop": [6], "value": [[1], [2]]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-log/value"))
+            .and(body_json(json!({"start": [6], "stop": [7], "value": [[3]]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let options = RecordLogOptions::new().with_batch_records(10);
+        let mut writer = RecordLogWriter::open(&client, "/home/test/file.h5", "d-log", options).await
+            .expect("Failed to open record log");
+
+        writer.push(&json!([1])).await.expect("Failed to push");
+        writer.push(&json!([2])).await.expect("Failed to push");
+        assert!(writer.flush().await.is_err(), "First write should fail");
+        assert_eq!((writer.written(), writer.buffered()), (0, 2));
+
+        // Records pushed meanwhile get rows of their own after the retried batch
+        writer.push(&json!([3])).await.expect("Failed to push");
+        writer.flush().await.expect("Retried flush should succeed");
+        assert_eq!((writer.written(), writer.buffered()), (3, 0));
+    }
+
+    /// Test that storage_info requests verbose output and types the statistics
+    #[tokio::test]
+    async fn test_dataset_storage_info() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .and(query_param("verbose", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [1000, 100]},
+                "layout": {"class": "H5D_CHUNKED", "dims": [100, 100]},
+                "num_chunks": 5,
+                "allocated_size": 200000
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let info = client.datasets().storage_info("/home/test/file.h5", "d-1").await
+            .expect("Failed to get storage info");
+
+        assert_eq!(info.layout_class.as_deref(), Some("H5D_CHUNKED"));
+        assert_eq!(info.chunk_dims, Some(vec![100, 100]));
+        assert_eq!(info.chunk_grid_size(), Some(10));
+        assert_eq!(info.written_fraction(), Some(0.5));
+        assert_eq!(info.average_chunk_bytes(), Some(40000.0));
+    }
+
+    /// Test that compressible uploads are deflated and small ones are sent as-is
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_write_region_compressed() {
+        use hsds_client::{Dataset, UploadCompression};
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .and(header("Content-Encoding", "deflate"))
+            .and(|req: &wiremock::Request| req.body.len() < 1000)
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .and(|req: &wiremock::Request| !req.headers.contains_key("content-encoding"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dataset: Dataset = serde_json::from_value(json!({
+            "id": "d-1",
+            "creationProperties": {"filters": [{"class": "H5Z_FILTER_DEFLATE", "id": 1, "level": 9}]}
+        })).unwrap();
+        let compression = UploadCompression::for_dataset(&dataset).expect("Dataset has a deflate filter");
+        assert_eq!(compression.level, 9);
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+
+        let sent = datasets.write_region_compressed(domain, "d-1", None, vec![0u8; 64 * 1024], &compression).await
+            .expect("Compressed write failed");
+        assert!(sent < 1000, "Zeros compress well, sent {} bytes", sent);
+
+        let sent = datasets.write_region_compressed(domain, "d-1", Some("[0:4]"), vec![1u8; 16], &compression).await
+            .expect("Small write failed");
+        assert_eq!(sent, 16);
+    }
+
+    /// Test that deflated value responses are inflated and filters are exposed on the handle
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_dataset_handle_inflates_responses() {
+        use hsds_client::UploadCompression;
+
+        let payload: Vec<u8> = (0..4096u32).flat_map(|i| (i % 16).to_le_bytes()).collect();
+        let compression = UploadCompression { level: 6, min_bytes: 0 };
+        let deflated = compression.deflate(&payload).expect("Payload compresses");
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "creationProperties": {"filters": [
+                    {"class": "H5Z_FILTER_SHUFFLE", "id": 2, "name": "shuffle"},
+                    {"class": "H5Z_FILTER_DEFLATE", "id": 1, "name": "deflate", "level": 4}
+                ]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .and(|req: &wiremock::Request| {
+                req.headers.get("accept-encoding").and_then(|v| v.to_str().ok()) == Some("deflate, gzip")
+            })
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "deflate")
+                .set_body_bytes(deflated.to_vec()))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let handle = client.datasets().open("/home/test/file.h5", "d-1").await.expect("Failed to open dataset");
+
+        let filters = handle.filters();
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].class, "H5Z_FILTER_SHUFFLE");
+        assert_eq!(handle.info().deflate_level(), Some(4));
+
+        let read = handle.read_with(None, BinaryDecoder).await.expect("Failed to read values");
+        assert_eq!(read.content_encoding.as_deref(), Some("deflate"));
+        assert_eq!(read.values.as_ref(), payload.as_slice());
+    }
+
+    /// Test that a catalog refresh only re-reads changed objects
+    #[tokio::test]
+    async fn test_catalog_refresh() {
+        use hsds_client::{CatalogBuilder, DomainCatalog};
+        use hsds_client::index::RefreshStats;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-root", "attributeCount": 0, "lastModified": 1.0})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "ip", "class": "H5L_TYPE_HARD", "id": "d-ip"},
+                {"title": "alias", "class": "H5L_TYPE_SOFT", "h5path": "/ip"}
+            ]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-ip"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-ip", "attributeCount": 1, "lastModified": 1.0,
+                "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [100]}
+            })))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-ip"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-ip", "attributeCount": 1, "lastModified": 2.0,
+                "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [200]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-ip/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "units", "value": "A"}, {"name": "comment", "value": "not indexed"}
+            ]})))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let builder = CatalogBuilder::new(&client).with_attributes(["units"]);
+        let catalog = builder.build("/home/test/shot.h5").await.expect("Failed to build catalog");
+        let ip = catalog.get("/ip").expect("Dataset not cataloged");
+        assert_eq!(ip.shape, Some(vec![100]));
+        assert_eq!(ip.dtype, Some(json!("H5T_IEEE_F64LE")));
+        assert_eq!(ip.attributes.get("units"), Some(&json!("A")));
+        assert!(!ip.attributes.contains_key("comment"));
+        assert_eq!(catalog.entries.len(), 2);
+
+        let mut catalog = DomainCatalog::from_json_str(&catalog.to_json_string().unwrap()).expect("Round trip failed");
+        let stats = builder.refresh(&mut catalog).await.expect("Failed to refresh catalog");
+        assert_eq!(stats, RefreshStats { checked: 2, updated: 1, removed: 0 });
+        assert_eq!(catalog.get("/ip").unwrap().shape, Some(vec![200]));
+        assert_eq!(catalog.datasets().count(), 1);
+    }
+
+    /// Test that nearby points are read as one hyperslab and the rest by point selection
+    #[tokio::test]
+    async fn test_read_points_coalesced() {
+        use hsds_client::CoalesceOptions;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .and(query_param("select", "[0:2,1:3]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[1, 2], [11, 12]]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1005]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let points = vec![vec![100, 5], vec![1, 2], vec![0, 1]];
+        let values = client.datasets()
+            .read_points_coalesced("/home/test/file.h5", "d-1", &points, &CoalesceOptions::new())
+            .await
+            .expect("Failed to read points");
+        assert_eq!(values, vec![json!(1005), json!(12), json!(1)]);
+    }
+
+    /// Test that POST value reads send points or a selection with fields in the body
+    #[tokio::test]
+    async fn test_read_values_post() {
+        use hsds_client::PointsRequest;
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("POST"))
+            .and(path("/datasets/d-1/value"))
+            .and(body_json(json!({"select": "[0:2,3]", "fields": "time:ip"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[0.0, 1.5], [0.1, 1.6]]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datasets/d-1/value"))
+            .and(body_json(json!({"points": [[1, 3], [4, 5]]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [7, 9]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let domain = "/home/test/file.h5";
+        let request = PointsRequest::selection("[0:2,3]").with_fields(&["time", "ip"]);
+        let values = client.datasets().read_values_post(domain, "d-1", &request).await.expect("Selection read failed");
+        assert_eq!(values["value"], json!([[0.0, 1.5], [0.1, 1.6]]));
+
+        let values = client.datasets().read_dataset_points(domain, "d-1", vec![vec![1, 3], vec![4, 5]]).await.expect("Point read failed");
+        assert_eq!(values["value"], json!([7, 9]));
+
+        let empty = PointsRequest { points: None, select: None, fields: None };
+        assert!(matches!(client.datasets().read_values_post(domain, "d-1", &empty).await, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that selections too long for the URL are sent in a POST body
+    #[tokio::test]
+    async fn test_long_selection_uses_post() {
+        use wiremock::matchers::body_json;
+
+        let long_selection = format!("[{}]", vec!["0:10:2"; 400].join(","));
+        let server = common::mock_server().await;
+        Mock::given(method("POST"))
+            .and(path("/datasets/d-1/value"))
+            .and(body_json(json!({"select": long_selection})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1, 2]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .and(query_param("select", "[0:2]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1, 2]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let domain = "/home/test/file.h5";
+        let long = client.datasets()
+            .read_dataset_values_json(domain, "d-1", Some(&long_selection), None, None)
+            .await
+            .expect("Long selection read failed");
+        let short = client.datasets()
+            .read_dataset_values_json(domain, "d-1", Some("[0:2]"), None, None)
+            .await
+            .expect("Short selection read failed");
+        assert_eq!(long, short);
+    }
+
+    /// Test that update_region writes back with If-Match and retries on 412
+    #[cfg(feature = "ndarray")]
+    #[tokio::test]
+    async fn test_update_region() {
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "d-1", "shape": {"class": "H5S_SIMPLE", "dims": [4, 3]}})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .and(query_param("select", "[1:3,0:2]"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(json!({"value": [[1.0, 2.0], [3.0, 4.0]]})),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .and(header("If-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(412).set_body_json(json!({"message": "precondition failed"})))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .and(query_param("select", "[1:3,0:2]"))
+            .and(header("If-Match", "\"v1\""))
+            .and(body_json(json!({"value": [[2.0, 4.0], [6.0, 8.0]]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let mut calls = 0;
+        client.datasets()
+            .update_region("/home/test/file.h5", "d-1", Some("[1:3,0:2]"), |region: &mut ndarray::ArrayViewMutD<f64>| {
+                calls += 1;
+                assert_eq!(region.shape(), &[2, 2]);
+                *region *= 2.0;
+            })
+            .await
+            .expect("Failed to update region");
+        assert_eq!(calls, 2);
+    }
+
+    /// Test that reads and empty writes of a null-dataspace dataset give empty results
+    #[tokio::test]
+    async fn test_null_dataspace_values() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "d-1", "shape": {"class": "H5S_NULL"}})))
+            .mount(&server)
+            .await;
+        Mock::given(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({"message": "Null space datasets can not be used as target for GET value"})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let values = datasets.read_dataset_values_json("/home/test/file.h5", "d-1", None, None, None).await.unwrap();
+        assert_eq!(values, json!({"value": []}));
+        assert!(datasets.read_dataset_values("/home/test/file.h5", "d-1", None, None, None).await.unwrap().is_empty());
+        datasets.write_region_raw("/home/test/file.h5", "d-1", None, Vec::new(), "application/octet-stream").await.unwrap();
+        let err = datasets.write_region_raw("/home/test/file.h5", "d-1", None, vec![1u8], "application/octet-stream").await;
+        assert!(matches!(err, Err(HsdsError::InvalidParameter(_))), "Non-empty writes still fail");
+        let err = datasets.resize_dataset("/home/test/file.h5", "d-1", vec![4], false).await;
+        assert!(matches!(err, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that booleans round-trip as 0/1 bytes of an h5py-style enum dataset
+    #[tokio::test]
+    async fn test_bool_dataset_values() {
+        use wiremock::matchers::body_bytes;
+
+        let server = common::mock_server().await;
+        let bool_type = json!({
+            "class": "H5T_ENUM",
+            "base": {"class": "H5T_INTEGER", "base": "H5T_STD_I8LE"},
+            "mapping": {"FALSE": 0, "TRUE": 1}
+        });
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1", "type": bool_type, "shape": {"class": "H5S_SIMPLE", "dims": [4]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-2", "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"}, "shape": {"class": "H5S_SIMPLE", "dims": [4]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .and(body_bytes(vec![1u8, 0, 0, 1]))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .and(header("Accept", "application/octet-stream"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8, 1, 2, 0]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        datasets.write_bool("/home/test/file.h5", "d-1", None, &[true, false, false, true]).await.unwrap();
+        let mask = datasets.read_bool("/home/test/file.h5", "d-1", None).await.unwrap();
+        assert_eq!(mask, vec![false, true, true, false]);
+        let err = datasets.write_bool("/home/test/file.h5", "d-2", None, &[true]).await;
+        assert!(matches!(err, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that complex values round-trip as packed {r, i} pairs
+    #[cfg(feature = "num-complex")]
+    #[tokio::test]
+    async fn test_complex_dataset_values() {
+        use num_complex::Complex;
+        use wiremock::matchers::body_bytes;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "type": {"class": "H5T_COMPOUND", "fields": [
+                    {"name": "r", "type": "H5T_IEEE_F32LE"}, {"name": "i", "type": "H5T_IEEE_F32LE"}
+                ]},
+                "shape": {"class": "H5S_SIMPLE", "dims": [2]}
+            })))
+            .mount(&server)
+            .await;
+        let packed: Vec<u8> = [1.0f32, -2.0, 0.5, 4.0].iter().flat_map(|x| x.to_le_bytes()).collect();
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .and(body_bytes(packed.clone()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(packed))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let spectrum = vec![Complex::new(1.0f32, -2.0), Complex::new(0.5, 4.0)];
+        datasets.write_complex("/home/test/file.h5", "d-1", None, &spectrum).await.unwrap();
+        assert_eq!(datasets.read_complex::<f32>("/home/test/file.h5", "d-1", None).await.unwrap(), spectrum);
+        let err = datasets.read_complex::<f64>("/home/test/file.h5", "d-1", None).await;
+        assert!(matches!(err, Err(HsdsError::InvalidParameter(_))), "Component type must match");
+    }
+
+    /// Test that timestamps are written with a units attribute and decoded by it
+    #[cfg(feature = "chrono")]
+    #[tokio::test]
+    async fn test_timestamps_round_trip() {
+        use chrono::{TimeZone, Utc};
+        use hsds_client::TimeEncoding;
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .and(body_json(json!({"value": [1_714_564_800_000_000_000i64, 1_714_564_801_500_000_000i64]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/attributes/units"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/attributes/units"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "units", "value": "nanoseconds since 1970-01-01T00:00:00Z"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "value": [1_714_564_800_000_000_000i64, 1_714_564_801_500_000_000i64]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let times = vec![
+            Utc.timestamp_opt(1_714_564_800, 0).unwrap(),
+            Utc.timestamp_opt(1_714_564_801, 500_000_000).unwrap(),
+        ];
+        client.datasets()
+            .write_timestamps("/home/test/file.h5", "d-1", None, &times, TimeEncoding::EpochNanoseconds)
+            .await
+            .expect("Failed to write timestamps");
+        let read = client.datasets().read_timestamps("/home/test/file.h5", "d-1", None).await.unwrap();
+        assert_eq!(read, times);
+    }
+
+    /// Test that scaled reads apply scale_factor and add_offset attributes
+    #[tokio::test]
+    async fn test_scaled_reads() {
+        use hsds_client::ReadOptions;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/attributes"))
+            .and(query_param("IncludeData", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "units", "value": "A"},
+                {"name": "scale_factor", "value": 0.25},
+                {"name": "add_offset", "value": [-1.0]}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[4, 8], [12, 16]]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let physical = datasets.get_scaled::<f64>("/home/test/file.h5", "d-1", None).await.unwrap();
+        assert_eq!(physical, vec![0.0, 1.0, 2.0, 3.0]);
+
+        let options = ReadOptions::new().with_scaling(true);
+        let nested = datasets.read_values("/home/test/file.h5", "d-1", None, &options).await.unwrap();
+        assert_eq!(nested, json!([[0.0, 1.0], [2.0, 3.0]]));
+        let raw = datasets.read_values("/home/test/file.h5", "d-1", None, &ReadOptions::new()).await.unwrap();
+        assert_eq!(raw, json!([[4, 8], [12, 16]]));
+    }
+
+    /// Test that read_with_coords pairs values with dimension scale coordinates
+    #[tokio::test]
+    async fn test_read_with_coords() {
+        use hsds_client::ReadOptions;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1", "shape": {"class": "H5S_SIMPLE", "dims": [3, 2]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "units", "value": "V"},
+                {"name": "DIMENSION_LIST", "value": [["datasets/d-t"], []]}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-t/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "NAME", "value": "time"},
+                {"name": "units", "value": "s"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .and(query_param("select", "[0:2,1]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[5], [7]]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-t/value"))
+            .and(query_param("select", "[0:2]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [0.0, 0.1]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let read = client.datasets()
+            .read_with_coords("/home/test/file.h5", "d-1", Some("[0:2,1]"), &ReadOptions::new())
+            .await
+            .expect("Failed to read with coordinates");
+        assert_eq!(read.values, json!([[5], [7]]));
+        assert_eq!(read.units.as_deref(), Some("V"));
+        assert_eq!(read.axes[0].scale_id.as_deref(), Some("d-t"));
+        assert_eq!(read.axes[0].name.as_deref(), Some("time"));
+        assert_eq!(read.axes[0].units.as_deref(), Some("s"));
+        assert_eq!(read.axes[0].values, vec![json!(0.0), json!(0.1)]);
+        assert_eq!(read.axes[1].scale_id, None);
+        assert_eq!(read.axes[1].values, vec![json!(1)]);
+    }
+
+    /// Test that streamed reads parse typed values from the JSON response
+    #[tokio::test]
+    async fn test_streamed_value_read() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [2, 3]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .and(header("Accept", "application/json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "hrefs": [{"rel": "self", "href": "/datasets/d-1/value"}],
+                "value": [[1, 2, 3], [4, 5, 6]]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-2",
+                "shape": {"class": "H5S_SIMPLE", "dims": [2]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-2/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[1, 2], [3]]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let values: Vec<u16> = client
+            .datasets()
+            .read_dataset_values_streamed("/home/test/file.h5", "d-1", None)
+            .await
+            .unwrap();
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+
+        // Elements one level too deep for the dataset's rank fail to convert
+        let result = client.datasets().read_dataset_values_streamed::<u16>("/home/test/file.h5", "d-2", None).await;
+        let error = result.unwrap_err();
+        assert!(matches!(&error, HsdsError::Conversion { value, .. } if value == "[1,2]"), "{}", error);
+        assert_eq!(error.element_index(), Some(&[0][..]));
+    }
+
+    /// Test that spawn_upload writes row batches in the background and can be aborted
+    #[tokio::test]
+    async fn test_spawn_upload() {
+        use hsds_client::{UploadOptions, UploadStatus};
+        use std::time::Duration;
+
+        let server = common::mock_server().await;
+        for (id, delay) in [("d-1", 0), ("d-slow", 5000)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/datasets/{}", id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "id": id,
+                    "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F32LE"},
+                    "shape": {"class": "H5S_SIMPLE", "dims": [10, 2]}
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("PUT"))
+                .and(path(format!("/datasets/{}/value", id)))
+                .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(delay)))
+                .mount(&server)
+                .await;
+        }
+
+        let client = common::mock_client(&server);
+        let data: Vec<f32> = (0..16).map(|i| i as f32).collect();
+        let handle = client.datasets().spawn_upload(
+            "/home/test/file.h5", "d-1", 2, data.clone(), UploadOptions::new().with_rows_per_request(3),
+        );
+        let progress = handle.wait().await.unwrap();
+        assert_eq!((progress.rows_written, progress.total_rows, progress.bytes_sent), (8, 8, 64));
+
+        let requests = server.received_requests().await.unwrap();
+        let selections: Vec<String> = requests
+            .iter()
+            .filter(|r| r.method.as_str() == "PUT")
+            .map(|r| r.url.query_pairs().find(|(k, _)| k == "select").unwrap().1.into_owned())
+            .collect();
+        assert_eq!(selections, vec!["[2:5,:]", "[5:8,:]", "[8:10,:]"]);
+
+        let handle = client.datasets().spawn_upload("/home/test/file.h5", "d-slow", 0, data, UploadOptions::new());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(handle.status(), UploadStatus::Running);
+        handle.abort();
+        assert_eq!(handle.status(), UploadStatus::Aborted);
+        assert!(matches!(handle.wait().await, Err(HsdsError::Cancelled)));
+
+        let handle = client.datasets().spawn_upload("/home/test/file.h5", "d-1", 0, vec![1.0f32; 3], UploadOptions::new());
+        assert!(matches!(handle.wait().await, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that an adaptive upload retries throttled writes and grows its requests
+    #[tokio::test]
+    async fn test_spawn_upload_adaptive() {
+        use hsds_client::{AdaptiveTuning, UploadOptions};
+        use std::time::Duration;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F32LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [100, 2]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let tuning = AdaptiveTuning::new()
+            .with_initial_request_bytes(32)
+            .with_request_bytes_bounds(16, 256)
+            .with_max_in_flight(4)
+            .with_backoff(Duration::from_millis(1));
+        let data: Vec<f32> = (0..200).map(|i| i as f32).collect();
+        let handle = client.datasets().spawn_upload(
+            "/home/test/file.h5", "d-1", 0, data, UploadOptions::new().with_adaptive(tuning),
+        );
+        let progress = handle.wait().await.unwrap();
+        assert_eq!((progress.rows_written, progress.total_rows, progress.bytes_sent), (100, 100, 800));
+
+        let requests = server.received_requests().await.unwrap();
+        let mut ranges: Vec<(u64, u64)> = requests
+            .iter()
+            .filter(|r| r.method.as_str() == "PUT")
+            .map(|r| {
+                let select = r.url.query_pairs().find(|(k, _)| k == "select").unwrap().1.into_owned();
+                let (start, stop) = select.trim_start_matches('[').trim_end_matches(",:]").split_once(':').unwrap();
+                (start.parse().unwrap(), stop.parse().unwrap())
+            })
+            .collect();
+        // The throttled first write of 4 rows is sent again
+        assert_eq!(&ranges[..2], &[(0, 4), (0, 4)]);
+        ranges.dedup();
+        ranges.sort();
+        assert!(ranges.windows(2).all(|w| w[0].1 == w[1].0), "{:?}", ranges);
+        assert_eq!(ranges.last().unwrap().1, 100);
+        assert!(ranges.iter().any(|(start, stop)| stop - start > 4), "requests grow after the retry");
+    }
+
+    /// Test that BufferedWriter consolidates consecutive small writes into region writes
+    #[tokio::test]
+    async fn test_buffered_writer() {
+        use hsds_client::{BufferedWriter, BufferedWriterOptions};
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [100, 2]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let options = BufferedWriterOptions::new().with_max_rows(4);
+        let mut writer = BufferedWriter::<i16>::open(&client, "/home/test/file.h5", "d-1", options).await.unwrap();
+
+        for i in 0..5 {
+            writer.push(&[i, -i]).await.unwrap();
+        }
+        assert_eq!((writer.requests(), writer.written_rows(), writer.buffered_rows()), (1, 4, 1));
+
+        // A write elsewhere flushes the buffered run first
+        writer.write(50, &[7, 7, 8, 8]).await.unwrap();
+        assert_eq!((writer.requests(), writer.buffered_rows(), writer.next_row()), (2, 2, 52));
+        assert!(writer.push(&[1, 2, 3]).await.is_err());
+        writer.flush().await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let puts: Vec<_> = requests.iter().filter(|r| r.method.as_str() == "PUT").collect();
+        let selections: Vec<String> = puts
+            .iter()
+            .map(|r| r.url.query_pairs().find(|(k, _)| k == "select").unwrap().1.into_owned())
+            .collect();
+        assert_eq!(selections, vec!["[0:4,:]", "[4:5,:]", "[50:52,:]"]);
+        assert_eq!(puts[0].body, vec![0, 0, 0, 0, 1, 0, 255, 255, 2, 0, 254, 255, 3, 0, 253, 255]);
+    }
+
+    /// Test that chunks_prefetched scans a selection in chunk-aligned regions, in order
+    #[tokio::test]
+    async fn test_chunks_prefetched() {
+        use futures_util::TryStreamExt;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [10, 3]},
+                "layout": {"class": "H5D_CHUNKED", "dims": [4, 3]}
+            })))
+            .mount(&server)
+            .await;
+        // Earlier regions answer slower, so order is kept despite concurrency
+        for (select, delay) in [("[1:4,0:2]", 150), ("[4:8,0:2]", 50), ("[8:9,0:2]", 0)] {
+            Mock::given(method("GET"))
+                .and(path("/datasets/d-1/value"))
+                .and(query_param("select", select))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_bytes(select.as_bytes().to_vec())
+                        .set_delay(std::time::Duration::from_millis(delay)),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = common::mock_client(&server);
+        let dataset = client.datasets().open("/home/test/file.h5", "d-1").await.unwrap();
+        let regions: Vec<_> = dataset.chunks_prefetched(Some("[1:9,0:2]"), 3).try_collect().await.unwrap();
+        let scanned: Vec<_> = regions.iter().map(|r| (r.rows.clone(), r.data.to_vec())).collect();
+        assert_eq!(scanned, vec![
+            (1..4, b"[1:4,0:2]".to_vec()),
+            (4..8, b"[4:8,0:2]".to_vec()),
+            (8..9, b"[8:9,0:2]".to_vec()),
+        ]);
+
+        let result: Result<Vec<_>, _> = dataset.chunks_prefetched(Some("[::2,:]"), 2).try_collect().await;
+        assert!(matches!(result, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that create_dataset_with_data creates, chunks and fills a dataset in one call
+    #[tokio::test]
+    async fn test_create_dataset_with_data() {
+        use hsds_client::CreateOptions;
+
+        let server = common::mock_server().await;
+        Mock::given(method("POST"))
+            .and(path("/datasets"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": "d-new", "attributeCount": 0, "root": "g-root"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-new/value"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let data: Vec<i32> = (0..10).collect();
+        let options = CreateOptions::new().with_chunk_threshold_bytes(16).with_target_request_bytes(24);
+        let dataset = client.datasets()
+            .create_dataset_with_data("/home/test/file.h5", "g-root", "counts", &data, &[5, 2], &options)
+            .await
+            .unwrap();
+        assert_eq!(dataset.id, "d-new");
+
+        let requests = server.received_requests().await.unwrap();
+        let create: serde_json::Value = requests[0].body_json().unwrap();
+        assert_eq!(create["type"], "H5T_STD_I32LE");
+        assert_eq!(create["shape"], json!([5, 2]));
+        assert_eq!(create["link"], json!({"id": "g-root", "name": "counts"}));
+        assert_eq!(create["creationProperties"]["layout"]["class"], "H5D_CHUNKED");
+
+        let writes: Vec<_> = requests.iter().filter(|r| r.method.as_str() == "PUT").collect();
+        let selections: Vec<String> = writes
+            .iter()
+            .map(|r| r.url.query_pairs().find(|(k, _)| k == "select").unwrap().1.into_owned())
+            .collect();
+        assert_eq!(selections, vec!["[0:3,:]", "[3:5,:]"]);
+        assert_eq!(writes[1].body, [6i32, 7, 8, 9].iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>());
+
+        let result = client.datasets()
+            .create_dataset_with_data("/home/test/file.h5", "g-root", "bad", &data, &[4, 2], &CreateOptions::new())
+            .await;
+        assert!(matches!(result, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that clients configured for special floats read NaN strings and nulls
+    #[tokio::test]
+    async fn test_special_float_reads() {
+        use hsds_client::SpecialFloats;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [3]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [0.5, "NaN", null]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": []})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let strict = client.datasets().read_dataset_values_streamed::<f64>("/home/test/file.h5", "d-1", None).await;
+        assert!(matches!(strict, Err(HsdsError::Conversion { .. })));
+
+        let tolerant = client.with_options(|o| o.with_special_floats(SpecialFloats::tolerant()));
+        let values: Vec<f64> = tolerant.datasets().read_dataset_values_streamed("/home/test/file.h5", "d-1", None).await.unwrap();
+        assert_eq!(values[0], 0.5);
+        assert!(values[1].is_nan() && values[2].is_nan());
+
+        let scaled: Vec<f32> = tolerant.datasets().get_scaled("/home/test/file.h5", "d-1", None).await.unwrap();
+        assert!(scaled[1].is_nan() && scaled[2].is_nan());
+    }
+
+    /// Test that ParallelIngest splits a dataset by chunk rows and tracks completion in attributes
+    #[tokio::test]
+    async fn test_parallel_ingest() {
+        use hsds_client::{ParallelIngest, INGEST_PLAN_ATTRIBUTE};
+        use wiremock::matchers::path_regex;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-1",
+                "shape": {"class": "H5S_SIMPLE", "dims": [100, 4]},
+                "layout": {"class": "H5D_CHUNKED", "dims": [16, 4]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "units", "value": "V"},
+                {"name": "__ingest_done_0__", "value": [0, 48]},
+                {"name": "__ingest_done_7__", "value": [0, 1]}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path_regex(r"^/datasets/d-1/attributes/__ingest_done_\d+__$"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path_regex(r"^/datasets/d-1/attributes/.+$"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/datasets/d-1/attributes/{}", INGEST_PLAN_ATTRIBUTE)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [0, 48, 80, 100]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let plan = ParallelIngest::plan(&client, "/home/test/file.h5", "d-1", 3).await.unwrap();
+        let selections: Vec<String> = plan.partitions().iter().map(|p| p.selection()).collect();
+        assert_eq!(selections, vec!["[0:48,:]", "[48:80,:]", "[80:100,:]"]);
+
+        let requests = server.received_requests().await.unwrap();
+        let stored = requests.iter().find(|r| r.method.as_str() == "PUT").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&stored.body).unwrap();
+        assert_eq!(body["value"], json!([0, 48, 80, 100]));
+
+        let worker = ParallelIngest::open(&client, "/home/test/file.h5", "d-1").await.unwrap();
+        assert_eq!(worker.partitions(), plan.partitions());
+        assert_eq!(worker.partition(2).unwrap().rows(), 20);
+        worker.mark_complete(1).await.unwrap();
+        assert!(worker.mark_complete(3).await.is_err());
+
+        let status = worker.status().await.unwrap();
+        assert_eq!((status.completed, status.partitions), (vec![0], 3));
+    }
+
+    /// Test that element codecs pick binary or JSON transfers by type
+    #[tokio::test]
+    async fn test_read_write_elements() {
+        use wiremock::matchers::body_json;
+
+        #[derive(Debug, PartialEq, hsds_client::ElementCodec)]
+        struct Point {
+            x: f32,
+            y: f32,
+        }
+
+        let server = common::mock_server().await;
+        Mock::given(method("POST"))
+            .and(path("/datasets"))
+            .and(body_json(json!({
+                "type": {"class": "H5T_COMPOUND", "fields": [
+                    {"name": "x", "type": "H5T_IEEE_F32LE"},
+                    {"name": "y", "type": "H5T_IEEE_F32LE"}
+                ]},
+                "shape": [2],
+                "link": {"id": "g-1", "name": "points"}
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "d-points"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-points"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-points",
+                "shape": {"class": "H5S_SIMPLE", "dims": [2]}
+            })))
+            .mount(&server)
+            .await;
+        let mut payload = Vec::new();
+        for v in [1.0f32, 2.0, 3.0, 4.0] {
+            payload.extend_from_slice(&v.to_le_bytes());
+        }
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-points/value"))
+            .and(header("Content-Type", "application/octet-stream"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-points/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(payload.clone()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-names"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-names",
+                "shape": {"class": "H5S_SIMPLE", "dims": [2, 3]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-names/value"))
+            .and(query_param("select", "[1,0:2]"))
+            .and(body_json(json!({"value": [["a", "b"]]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-names/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [["a", "b", "c"], ["d", "e", "f"]]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+        let id = datasets.create_dataset_of::<Point>(domain, "g-1", "points", vec![2]).await.unwrap();
+        let points = vec![Point { x: 1.0, y: 2.0 }, Point { x: 3.0, y: 4.0 }];
+        datasets.write_elements(domain, &id, None, &points).await.unwrap();
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.iter().find(|r| r.method.as_str() == "PUT").unwrap().body, payload);
+        assert_eq!(datasets.read_elements::<Point>(domain, &id, None).await.unwrap(), points);
+
+        let names = ["a".to_string(), "b".to_string()];
+        datasets.write_elements(domain, "d-names", Some("[1,0:2]"), &names).await.unwrap();
+        let names: Vec<String> = datasets.read_elements(domain, "d-names", None).await.unwrap();
+        assert_eq!(names, ["a", "b", "c", "d", "e", "f"]);
+    }
+
+    /// Test that shareable value URLs carry domain, selection and optional credentials
+    #[tokio::test]
+    async fn test_value_url() {
+        use hsds_client::BasicAuth;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-1234/value"))
+            .and(query_param("domain", "/shared/run 1.h5"))
+            .and(query_param("select", "[0:2]"))
+            .and(header("Authorization", "Basic cmVhZGVyOnBAc3M="))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1, 2]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let public = client.datasets().value_url("/shared/run 1.h5", "d-1234", None, None)
+            .expect("Failed to build URL");
+        assert_eq!(public.as_str(), format!("{}/datasets/d-1234/value?domain=%2Fshared%2Frun%201.h5", server.uri()));
+
+        let url = client.datasets()
+            .value_url("/shared/run 1.h5", "d-1234", Some("[0:2]"), Some(&BasicAuth::new("reader", "p@ss")))
+            .expect("Failed to build URL");
+        assert_eq!(url.username(), "reader");
+        assert!(!url.as_str().contains("p@ss"));
+
+        // A plain HTTP client turns the embedded credentials into basic auth
+        let body: serde_json::Value = reqwest::get(url).await.expect("GET failed").json().await.expect("Bad body");
+        assert_eq!(body["value"], json!([1, 2]));
+    }
+
+    /// Test that read_shaped converts with the selection's shape, indexed dimensions keeping length 1
+    #[tokio::test]
+    async fn test_read_shaped() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-grid"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-grid",
+                "shape": {"class": "H5S_SIMPLE", "dims": [3, 4]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-grid/value"))
+            .and(query_param("select", "[1,:]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[4, 5, 6, 7]]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-grid/value"))
+            .and(query_param("select", "[0:3:2,1:3]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[1, 2], [9, 10]]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+
+        let row = datasets.read_shaped(domain, "d-grid", Some("[1,:]")).await.unwrap();
+        assert_eq!(row.shape(), &[1, 4]);
+        assert_eq!(row.to_vec::<i64>().unwrap(), vec![4, 5, 6, 7]);
+        assert_eq!(row.to_vec::<f64>().unwrap(), vec![4.0, 5.0, 6.0, 7.0]);
+
+        let block = datasets.read_shaped(domain, "d-grid", Some("[0:3:2,1:3]")).await.unwrap();
+        assert_eq!(block.shape(), &[2, 2]);
+        assert_eq!(block.to_vec::<u16>().unwrap(), vec![1, 2, 9, 10]);
+    }
+
+    /// Test that datasets read into arrays shaped by their metadata, binary when types match
+    #[cfg(feature = "ndarray")]
+    #[tokio::test]
+    async fn test_read_dataset_ndarray() {
+        use ndarray::{arr0, arr2, ArrayD};
+
+        let server = common::mock_server().await;
+        let dataset = |id: &str, dtype: serde_json::Value, shape: serde_json::Value| {
+            Mock::given(method("GET"))
+                .and(path(format!("/datasets/{}", id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": id, "type": dtype, "shape": shape})))
+        };
+        dataset("d-f64", json!({"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"}), json!({"class": "H5S_SIMPLE", "dims": [2, 3]}))
+            .mount(&server)
+            .await;
+        dataset("d-i16", json!("H5T_STD_I16BE"), json!({"class": "H5S_SIMPLE", "dims": [3, 4]}))
+            .mount(&server)
+            .await;
+        dataset("d-scalar", json!("H5T_STD_U8LE"), json!({"class": "H5S_SCALAR"}))
+            .mount(&server)
+            .await;
+        dataset("d-null", json!("H5T_STD_U8LE"), json!({"class": "H5S_NULL"}))
+            .mount(&server)
+            .await;
+        let doubles: Vec<u8> = (0..6).flat_map(|i| (i as f64 * 0.5).to_le_bytes()).collect();
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-f64/value"))
+            .and(header("Accept", "application/octet-stream"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(doubles))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-i16/value"))
+            .and(query_param("select", "[0:3:2,1:3]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[1, 2], [-9, 10]]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-i16/value"))
+            .and(query_param("select", "[1,:]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [[4, 5, 6, 7]]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-scalar/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![42u8]))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+
+        let grid: ArrayD<f64> = datasets.read_dataset_ndarray(domain, "d-f64", None).await.unwrap();
+        assert_eq!(grid, arr2(&[[0.0, 0.5, 1.0], [1.5, 2.0, 2.5]]).into_dyn());
+
+        // A big-endian dataset is converted from JSON
+        let block: ArrayD<f32> = datasets.read_dataset_ndarray(domain, "d-i16", Some("[0:3:2,1:3]")).await.unwrap();
+        assert_eq!(block, arr2(&[[1.0, 2.0], [-9.0, 10.0]]).into_dyn());
+        let row: ArrayD<i64> = datasets.read_dataset_ndarray(domain, "d-i16", Some("[1,:]")).await.unwrap();
+        assert_eq!(row, arr2(&[[4, 5, 6, 7]]).into_dyn());
+        let err = datasets.read_dataset_ndarray::<u8>(domain, "d-i16", Some("[0:3:2,1:3]")).await.unwrap_err();
+        assert!(matches!(err, HsdsError::Conversion { .. }), "got {:?}", err);
+
+        let scalar: ArrayD<u8> = datasets.read_dataset_ndarray(domain, "d-scalar", None).await.unwrap();
+        assert_eq!(scalar, arr0(42).into_dyn());
+        let empty: ArrayD<u8> = datasets.read_dataset_ndarray(domain, "d-null", None).await.unwrap();
+        assert_eq!(empty.shape(), &[0]);
+    }
+
+    /// Test writing arrays into a dataset as binary or nested JSON blocks
+    #[cfg(feature = "ndarray")]
+    #[tokio::test]
+    async fn test_write_dataset_ndarray() {
+        use ndarray::{arr1, arr2};
+        use wiremock::matchers::{body_bytes, body_json};
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-f64"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-f64",
+                "type": {"class": "H5T_FLOAT", "base": "H5T_IEEE_F64LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [4, 3]}
+            })))
+            .mount(&server)
+            .await;
+        let transposed: Vec<u8> = [1.0f64, 3.0, 5.0, 2.0, 4.0, 6.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-f64/value"))
+            .and(query_param("select", "[1:3,0:3]"))
+            .and(header("Content-Type", "application/octet-stream"))
+            .and(body_bytes(transposed))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-i16"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-i16",
+                "type": "H5T_STD_I16BE",
+                "shape": {"class": "H5S_SIMPLE", "dims": [5]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-i16/value"))
+            .and(query_param("select", "[3:5]"))
+            .and(body_json(json!({"value": [7, 8]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+
+        // A transposed view is sent in row-major order
+        let columns = arr2(&[[1.0f64, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        datasets.write_dataset_ndarray(domain, "d-f64", columns.t(), Some(&[1, 0])).await.unwrap();
+
+        // A big-endian dataset is written as JSON
+        let tail = arr1(&[7i16, 8]);
+        datasets.write_dataset_ndarray(domain, "d-i16", tail.view(), Some(&[3])).await.unwrap();
+
+        let err = datasets.write_dataset_ndarray(domain, "d-i16", tail.view(), Some(&[4])).await.unwrap_err();
+        assert!(matches!(err, HsdsError::InvalidParameter(_)), "got {:?}", err);
+        let err = datasets.write_dataset_ndarray(domain, "d-f64", tail.view(), None).await.unwrap_err();
+        assert!(matches!(err, HsdsError::InvalidParameter(_)), "got {:?}", err);
+    }
+
+    /// Test that deleting a dataset removes its hard links and reports soft links to it
+    #[tokio::test]
+    async fn test_delete_dataset_with_unlink() {
+        use hsds_client::{DeleteOptions, DeleteReport};
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "a", "class": "H5L_TYPE_HARD", "collection": "groups", "id": "g-a"},
+                {"title": "b", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-1"},
+                {"title": "keep", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-2"},
+                {"title": "alias", "class": "H5L_TYPE_SOFT", "h5path": "/a/x"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-a/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "x", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-1"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/datasets/d-1"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/groups/g-root/links/b"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/groups/g-a/links/x"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"message": "not found"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+
+        // A link already gone counts as removed
+        let report = datasets.delete_dataset_with(domain, "d-1", &DeleteOptions::default()).await.expect("Delete failed");
+        assert_eq!(report.unlinked, vec!["/a/x", "/b"]);
+        assert_eq!(report.broken, vec!["/alias"]);
+        assert!(!report.is_clean());
+
+        let kept = datasets.delete_dataset_with(domain, "d-1", &DeleteOptions::new().with_unlink(false)).await.expect("Delete failed");
+        assert_eq!(kept, DeleteReport { unlinked: vec![], broken: vec!["/a/x".to_string(), "/alias".to_string(), "/b".to_string()] });
+    }
+
+    /// Test that get_or_create_dataset returns a concurrent creator's dataset and checks maxdims
+    #[tokio::test]
+    async fn test_get_or_create_dataset_race() {
+        use hsds_client::models::DatasetCreateRequest;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/raw"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datasets"))
+            .respond_with(ResponseTemplate::new(409))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/raw"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "link": {"title": "raw", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-raw"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-raw"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-raw",
+                "type": {"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [10], "maxdims": [0]}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+        let request = DatasetCreateRequest::from_hsds_type("H5T_STD_I32LE", vec![10]);
+
+        let mut extensible = request.clone();
+        extensible.maxdims = Some(vec![0]);
+        let dataset = datasets.get_or_create_dataset(domain, "g-root", "raw", extensible).await.unwrap();
+        assert_eq!(dataset.id, "d-raw");
+
+        let mut bounded = request.clone();
+        bounded.maxdims = Some(vec![100]);
+        let err = datasets.get_or_create_dataset(domain, "g-root", "raw", bounded).await.unwrap_err();
+        assert!(matches!(err, HsdsError::Conflict(_)), "got {:?}", err);
+
+        // Appended to since it was created with fewer rows
+        let mut initial = request.clone();
+        initial.shape = Some(hsds_client::models::ShapeSpec::Dimensions(vec![4]));
+        initial.maxdims = Some(vec![0]);
+        let dataset = datasets.get_or_create_dataset(domain, "g-root", "raw", initial).await.unwrap();
+        assert_eq!(dataset.id, "d-raw");
+
+        let mut fixed = request.clone();
+        fixed.maxdims = None;
+        let err = datasets.get_or_create_dataset(domain, "g-root", "raw", fixed).await.unwrap_err();
+        assert!(matches!(err, HsdsError::Conflict(_)), "got {:?}", err);
+
+        let mut scalar = request;
+        scalar.shape = None;
+        let err = datasets.get_or_create_dataset(domain, "g-root", "raw", scalar).await.unwrap_err();
+        assert!(matches!(err, HsdsError::Conflict(_)), "got {:?}", err);
+    }
+
+    /// Test that get_or_create_dataset compares the dims of a fixed-size dataset
+    #[tokio::test]
+    async fn test_get_or_create_fixed_dataset() {
+        use hsds_client::models::DatasetCreateRequest;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/grid"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "link": {"title": "grid", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-grid"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-grid"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-grid",
+                "type": {"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [4, 4], "maxdims": [4, 4]}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let datasets = client.datasets();
+        let domain = "/home/test/file.h5";
+
+        let same = DatasetCreateRequest::from_hsds_type("H5T_STD_I32LE", vec![4, 4]);
+        let dataset = datasets.get_or_create_dataset(domain, "g-root", "grid", same.clone()).await.unwrap();
+        assert_eq!(dataset.id, "d-grid");
+
+        let mut explicit = same;
+        explicit.maxdims = Some(vec![4, 4]);
+        let dataset = datasets.get_or_create_dataset(domain, "g-root", "grid", explicit).await.unwrap();
+        assert_eq!(dataset.id, "d-grid");
+
+        let larger = DatasetCreateRequest::from_hsds_type("H5T_STD_I32LE", vec![4, 8]);
+        let err = datasets.get_or_create_dataset(domain, "g-root", "grid", larger).await.unwrap_err();
+        assert!(matches!(err, HsdsError::Conflict(_)), "got {:?}", err);
+
+        let mut extensible = DatasetCreateRequest::from_hsds_type("H5T_STD_I32LE", vec![4, 4]);
+        extensible.maxdims = Some(vec![0, 4]);
+        let err = datasets.get_or_create_dataset(domain, "g-root", "grid", extensible).await.unwrap_err();
+        assert!(matches!(err, HsdsError::Conflict(_)), "got {:?}", err);
+    }
+}
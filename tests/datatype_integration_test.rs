@@ -2,6 +2,8 @@ use hsds_client::{HsdsClient, BasicAuth, HsdsResult};
 use std::time::{SystemTime, UNIX_EPOCH};
 use serde_json::{json, Value};
 
+mod common;
+
 /// Helper to create a test client
 fn create_test_client() -> HsdsResult<HsdsClient> {
     HsdsClient::new(
@@ -70,8 +72,8 @@ async fn test_commit_integer_datatype() {
         .expect("Failed to commit datatype");
     
     // Verify the response contains an id
-    assert!(result.get("id").is_some(), "Response should contain datatype id");
-    let datatype_id = result.get("id").unwrap().as_str().unwrap();
+    assert!(!result.id.is_empty(), "Response should contain datatype id");
+    let datatype_id = result.id.as_str();
     assert!(!datatype_id.is_empty(), "Datatype ID should not be empty");
     
     // Clean up
@@ -97,11 +99,11 @@ async fn test_commit_compound_datatype() {
         .expect("Failed to commit compound datatype");
     
     // Verify the response
-    assert!(result.get("id").is_some(), "Response should contain datatype id");
-    let datatype_id = result.get("id").unwrap().as_str().unwrap();
+    assert!(!result.id.is_empty(), "Response should contain datatype id");
+    let datatype_id = result.id.as_str();
     
     // Verify the datatype class is compound
-    if let Some(datatype_info) = result.get("type") {
+    if let Some(datatype_info) = &result.data_type {
         assert_eq!(
             datatype_info.get("class").unwrap().as_str().unwrap(),
             "H5T_COMPOUND",
@@ -132,8 +134,8 @@ async fn test_commit_float_datatype() {
         .expect("Failed to commit float datatype");
     
     // Verify the response
-    assert!(result.get("id").is_some(), "Response should contain datatype id");
-    let datatype_id = result.get("id").unwrap().as_str().unwrap();
+    assert!(!result.id.is_empty(), "Response should contain datatype id");
+    let datatype_id = result.id.as_str();
     
     // Clean up
     client.datatypes().delete_datatype(&domain_path, datatype_id).await.ok();
@@ -157,7 +159,7 @@ async fn test_get_datatype() {
     let commit_result = client.datatypes().commit_datatype(&domain_path, datatype_def).await
         .expect("Failed to commit datatype");
     
-    let datatype_id = commit_result.get("id").unwrap().as_str().unwrap();
+    let datatype_id = commit_result.id.as_str();
     
     // Now get the datatype information
     let result = client.datatypes().get_datatype(&domain_path, datatype_id).await
@@ -227,14 +229,14 @@ async fn test_delete_datatype() {
     let commit_result = client.datatypes().commit_datatype(&domain_path, datatype_def).await
         .expect("Failed to commit datatype");
     
-    let datatype_id = commit_result.get("id").unwrap().as_str().unwrap();
+    let datatype_id = commit_result.id.as_str();
     
     // Verify the datatype exists
     let _get_result = client.datatypes().get_datatype(&domain_path, datatype_id).await
         .expect("Datatype should exist before deletion");
     
     // Delete the datatype
-    let _delete_result = client.datatypes().delete_datatype(&domain_path, datatype_id).await
+    client.datatypes().delete_datatype(&domain_path, datatype_id).await
         .expect("Failed to delete datatype");
     
     // Verify the datatype no longer exists
@@ -278,17 +280,17 @@ async fn test_datatype_operations_without_domain() {
     
     // Try to commit a datatype to a non-existent domain
     let datatype_def = create_integer_datatype();
-    let commit_result = client.datatypes().commit_datatype(&nonexistent_domain, datatype_def).await;
+    let commit_result = client.datatypes().commit_datatype(nonexistent_domain, datatype_def).await;
     
     // This should fail
     assert!(commit_result.is_err(), "Committing datatype to non-existent domain should fail");
     
     // Try to get a datatype from a non-existent domain
-    let get_result = client.datatypes().get_datatype(&nonexistent_domain, "some-id").await;
+    let get_result = client.datatypes().get_datatype(nonexistent_domain, "some-id").await;
     assert!(get_result.is_err(), "Getting datatype from non-existent domain should fail");
     
     // Try to delete a datatype from a non-existent domain
-    let delete_result = client.datatypes().delete_datatype(&nonexistent_domain, "some-id").await;
+    let delete_result = client.datatypes().delete_datatype(nonexistent_domain, "some-id").await;
     assert!(delete_result.is_err(), "Deleting datatype from non-existent domain should fail");
 }
 
@@ -337,15 +339,15 @@ async fn test_multiple_datatype_operations() {
     
     let int_result = client.datatypes().commit_datatype(&domain_path, integer_def).await
         .expect("Failed to commit integer datatype");
-    let int_id = int_result.get("id").unwrap().as_str().unwrap();
+    let int_id = int_result.id.as_str();
     
     let compound_result = client.datatypes().commit_datatype(&domain_path, compound_def).await
         .expect("Failed to commit compound datatype");
-    let compound_id = compound_result.get("id").unwrap().as_str().unwrap();
+    let compound_id = compound_result.id.as_str();
     
     let float_result = client.datatypes().commit_datatype(&domain_path, float_def).await
         .expect("Failed to commit float datatype");
-    let float_id = float_result.get("id").unwrap().as_str().unwrap();
+    let float_id = float_result.id.as_str();
     
     // Verify all datatypes exist and have correct types
     let int_get = client.datatypes().get_datatype(&domain_path, int_id).await
@@ -372,3 +374,234 @@ async fn test_multiple_datatype_operations() {
     client.datatypes().delete_datatype(&domain_path, float_id).await.ok();
     client.domains().delete_domain(&domain_path).await.ok();
 }
+
+#[derive(hsds_client::ElementCodec, hsds_client::schema::Schema)]
+struct Reading {
+    sensor: u32,
+    value: f64,
+}
+
+/// Test that the schema registry commits a type once and reuses it across loads
+#[tokio::test]
+async fn test_schema_registry_reuses_committed_type() {
+    use hsds_client::schema::SchemaRegistry;
+    
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let mut registry = SchemaRegistry::load(&client, &domain_path).await
+        .expect("Failed to load registry");
+    let first = registry.create_dataset::<Reading>(&root_group_id, "a", vec![4], None).await
+        .expect("Failed to create dataset");
+    let type_id = registry.type_id("Reading").expect("Schema should be registered").to_string();
+    
+    let mut reloaded = SchemaRegistry::load(&client, &domain_path).await
+        .expect("Failed to reload registry");
+    assert_eq!(reloaded.type_id("Reading"), Some(type_id.as_str()));
+    let second = reloaded.create_dataset::<Reading>(&root_group_id, "b", vec![8], None).await
+        .expect("Failed to create dataset");
+    assert_ne!(first.id, second.id);
+    assert_eq!(reloaded.commit::<Reading>().await.expect("Failed to commit"), type_id);
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test that the server returns the types of tests/fixtures/datatypes.json as recorded there
+#[tokio::test]
+async fn test_type_fixtures_match_server() {
+    use hsds_client::{DatasetCreateRequest, H5Type, ShapeSpec};
+
+    let _ = env_logger::try_init();
+
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    let fixtures: Value = serde_json::from_str(include_str!("fixtures/datatypes.json")).unwrap();
+
+    client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+
+    let mut type_ids = Vec::new();
+    for fixture in fixtures["datatypes"].as_array().unwrap() {
+        let committed = client.datatypes().commit_datatype(&domain_path, json!({"type": fixture["type"]})).await
+            .expect("Failed to commit datatype");
+        let fetched = client.datatypes().get_datatype(&domain_path, &committed.id).await
+            .expect("Failed to get datatype");
+        assert_eq!(fetched["type"], fixture["type"]);
+        let mut keys: Vec<_> = fetched.as_object().unwrap().keys().collect();
+        let mut expected: Vec<_> = fixture.as_object().unwrap().keys().collect();
+        keys.sort();
+        expected.sort();
+        assert_eq!(keys, expected);
+        type_ids.push(committed.id);
+    }
+
+    // A dataset of a committed type reports the type's definition and id
+    let request = DatasetCreateRequest {
+        data_type: H5Type::named(type_ids[0].as_str()),
+        shape: Some(ShapeSpec::Dimensions(vec![10])),
+        maxdims: None,
+        creation_properties: None,
+        link: None,
+    };
+    let dataset = client.datasets().create_dataset(&domain_path, request).await
+        .expect("Failed to create dataset");
+    let dataset = client.datasets().get_dataset(&domain_path, &dataset.id).await
+        .expect("Failed to get dataset");
+    let mut expected = fixtures["committed_dataset"]["type"].clone();
+    expected["id"] = json!(type_ids[0]);
+    assert_eq!(serde_json::to_value(dataset.data_type.unwrap()).unwrap(), expected);
+
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Tests against a mock server, runnable without HSDS
+mod mock {
+    use super::common;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    /// Test that the schema registry commits an unregistered type and records its id
+    #[tokio::test]
+    async fn test_schema_registry_commit() {
+        use hsds_client::schema::{Schema, SchemaRegistry, SCHEMA_ATTRIBUTE};
+        use hsds_client::ElementCodec;
+        use wiremock::matchers::body_json;
+
+        #[derive(ElementCodec, Schema)]
+        struct Event {
+            ts: i64,
+        }
+
+        let server = common::mock_server().await;
+        let attr_path = format!("/groups/g-1/attributes/{}", SCHEMA_ATTRIBUTE);
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-1"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(attr_path.as_str()))
+            .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datatypes"))
+            .and(body_json(json!({"type": {"class": "H5T_COMPOUND", "fields": [{"name": "ts", "type": "H5T_STD_I64LE"}]}})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "t-1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(attr_path.as_str()))
+            .and(body_json(json!({"type": String::hsds_type(), "value": "{\"Event\":\"t-1\"}"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let mut registry = SchemaRegistry::load(&client, "/home/test/file.h5").await
+            .expect("Failed to load registry");
+        assert_eq!(registry.names().count(), 0);
+
+        assert_eq!(registry.commit::<Event>().await.expect("Failed to commit"), "t-1");
+        assert_eq!(registry.commit::<Event>().await.expect("Failed to reuse"), "t-1");
+    }
+
+    /// Test that recording a schema merges with a map another writer changed, using If-Match
+    #[tokio::test]
+    async fn test_schema_registry_merges_concurrent_map() {
+        use hsds_client::schema::{Schema, SchemaRegistry, SCHEMA_ATTRIBUTE};
+        use hsds_client::ElementCodec;
+        use wiremock::matchers::{body_json, header};
+
+        #[derive(ElementCodec, Schema)]
+        #[schema(name = "Event")]
+        struct Tick {
+            ts: i64,
+        }
+
+        #[derive(ElementCodec, Schema)]
+        struct Level {
+            value: f32,
+        }
+
+        let server = common::mock_server().await;
+        let attr_path = format!("/groups/g-1/attributes/{}", SCHEMA_ATTRIBUTE);
+        let map = |value: &str| json!({"type": String::hsds_type(), "value": value});
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-1"})))
+            .mount(&server)
+            .await;
+        // Empty when loaded; another writer records "Other" before our first write
+        Mock::given(method("GET"))
+            .and(path(attr_path.as_str()))
+            .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(attr_path.as_str()))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(map("{\"Other\":\"t-9\"}")))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // Our replace loses to a writer that recorded "Event" first
+        Mock::given(method("PUT"))
+            .and(path(attr_path.as_str()))
+            .and(header("If-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(412))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(attr_path.as_str()))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v2\"")
+                .set_body_json(map("{\"Event\":\"t-2\",\"Other\":\"t-9\"}")))
+            .mount(&server)
+            .await;
+        // Re-read: "Event" is already recorded, so only "Level" is written
+        Mock::given(method("PUT"))
+            .and(path(attr_path.as_str()))
+            .and(query_param("replace", "1"))
+            .and(header("If-Match", "\"v2\""))
+            .and(body_json(map("{\"Event\":\"t-2\",\"Level\":\"t-3\",\"Other\":\"t-9\"}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datatypes"))
+            .and(body_json(json!({"type": Tick::hsds_type()})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "t-1"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datatypes"))
+            .and(body_json(json!({"type": Level::hsds_type()})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "t-3"})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let mut registry = SchemaRegistry::load(&client, "/home/test/file.h5").await
+            .expect("Failed to load registry");
+        assert_eq!(registry.commit::<Tick>().await.expect("Failed to commit"), "t-2", "First recorded id wins");
+        assert_eq!(registry.type_id("Other"), Some("t-9"));
+        assert_eq!(registry.commit::<Level>().await.expect("Failed to commit"), "t-3");
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["Event", "Level", "Other"]);
+    }
+}
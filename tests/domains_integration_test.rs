@@ -1,6 +1,8 @@
 use hsds_client::{HsdsClient, BasicAuth, HsdsResult};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+mod common;
+
 /// Helper to create a test client
 fn create_test_client() -> HsdsResult<HsdsClient> {
     HsdsClient::new(
@@ -178,3 +180,949 @@ async fn test_domain_lifecycle() {
     assert!(client.domains().get_domain(&file_domain).await.is_err());
     assert!(client.domains().get_domain(&folder_domain).await.is_err());
 }
+
+/// Test bulk domain creation and deletion with per-path results
+#[tokio::test]
+async fn test_create_and_delete_domains_bulk() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let base = create_test_domain_name();
+    let paths: Vec<String> = (0..5).map(|i| format!("{}.{}.h5", base, i)).collect();
+    
+    let created = client.domains().create_domains(&paths, None, 3).await;
+    assert_eq!(created.len(), paths.len());
+    for ((path, result), expected) in created.iter().zip(&paths) {
+        assert_eq!(path, expected, "Results should keep input order");
+        assert!(result.is_ok(), "Failed to create {}: {:?}", path, result);
+    }
+    
+    // Deleting includes one path that does not exist
+    let mut to_delete = paths.clone();
+    to_delete.push(format!("{}.missing.h5", base));
+    let deleted = client.domains().delete_domains(&to_delete, 3).await;
+    assert_eq!(deleted.len(), to_delete.len());
+    assert!(deleted[..paths.len()].iter().all(|(_, r)| r.is_ok()));
+    assert!(deleted.last().unwrap().1.is_err(), "Missing domain should fail individually");
+}
+
+/// Test instantiating a domain layout from a JSON template
+#[tokio::test]
+async fn test_create_domain_from_template() {
+    use hsds_client::template::DomainTemplate;
+    
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let template = DomainTemplate::from_json_str(r#"{
+        "attributes": {"facility": {"value": "NT-1"}},
+        "groups": {
+            "diagnostics": {
+                "attributes": {"version": {"type": "H5T_STD_I32LE", "value": 2}},
+                "datasets": {"ip": {"type": "H5T_IEEE_F64LE", "shape": [0], "maxdims": [0]}}
+            }
+        }
+    }"#).expect("Failed to parse template");
+    
+    client.domains().create_domain_from_template(&domain_path, &template).await
+        .expect("Failed to create domain from template");
+    
+    let group_id = client.groups().ensure_group_path(&domain_path, "/diagnostics").await
+        .expect("Template group should exist");
+    let version = client.attributes().get_attribute(&domain_path, "groups", &group_id, "version").await
+        .expect("Template attribute should exist");
+    assert_eq!(version["value"], 2);
+    
+    let datasets = client.datasets().list_datasets(&domain_path).await
+        .expect("Failed to list datasets");
+    assert_eq!(datasets.datasets.len(), 1);
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test that template validation reports missing and mismatched objects
+#[tokio::test]
+async fn test_validate_domain_against_template() {
+    use hsds_client::template::{DomainTemplate, ViolationKind};
+    
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let created = DomainTemplate::from_json_str(r#"{
+        "groups": {"raw": {"datasets": {"ip": {"type": "H5T_IEEE_F64LE", "shape": [10]}}}}
+    }"#).expect("Failed to parse template");
+    client.domains().create_domain_from_template(&domain_path, &created).await
+        .expect("Failed to create domain from template");
+    
+    let report = client.domains().validate_domain(&domain_path, &created).await
+        .expect("Failed to validate domain");
+    assert!(report.is_valid(), "Unexpected violations: {:?}", report.violations);
+    
+    let expected = DomainTemplate::from_json_str(r#"{
+        "attributes": {"facility": {"value": "NT-1"}},
+        "groups": {"raw": {"datasets": {
+            "ip": {"type": "H5T_STD_I32LE", "shape": [10]},
+            "ne": {"type": "H5T_IEEE_F64LE", "shape": [10]}
+        }}}
+    }"#).expect("Failed to parse template");
+    let report = client.domains().validate_domain(&domain_path, &expected).await
+        .expect("Failed to validate domain");
+    let mut kinds: Vec<_> = report.violations.iter().map(|v| (v.path.as_str(), v.kind)).collect();
+    kinds.sort_by_key(|(path, _)| path.to_string());
+    assert_eq!(kinds, vec![
+        ("/@facility", ViolationKind::MissingAttribute),
+        ("/raw/ip", ViolationKind::DatasetMismatch),
+        ("/raw/ne", ViolationKind::MissingObject),
+    ]);
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test recursive discovery of file domains under a folder
+#[tokio::test]
+async fn test_find_domains() {
+    use futures_util::StreamExt;
+    
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let folder = create_test_domain_name().trim_end_matches(".h5").to_string();
+    let subfolder = format!("{}/sub", folder);
+    
+    client.domains().create_folder(&folder).await.expect("Failed to create folder");
+    client.domains().create_folder(&subfolder).await.expect("Failed to create subfolder");
+    let files = [format!("{}/a.h5", folder), format!("{}/b.h5", subfolder), format!("{}/c.h5", subfolder)];
+    for file in &files {
+        client.domains().create_domain(file, None).await.expect("Failed to create domain");
+    }
+    
+    let mut found: Vec<String> = client.domains().find_domains(&folder, Some("*.h5"), true)
+        .map(|entry| entry.expect("Listing failed").name)
+        .collect()
+        .await;
+    found.sort();
+    assert_eq!(found, files.to_vec());
+    
+    let top_level = client.domains().find_domains(&folder, None, false).count().await;
+    assert_eq!(top_level, 1);
+    
+    // Clean up
+    client.domains().delete_domains(&files, 3).await;
+    client.domains().delete_domain(&subfolder).await.ok();
+    client.domains().delete_domain(&folder).await.ok();
+}
+
+/// Test creating, listing and restoring domain snapshots
+#[tokio::test]
+async fn test_snapshots() {
+    use hsds_client::Snapshots;
+    
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create domain");
+    let root_id = domain.root.expect("Domain has no root group");
+    client.attributes().set_attribute(&domain_path, &root_id, "version", 1).await
+        .expect("Failed to write attribute");
+    
+    let snapshots = Snapshots::new(&client, &domain_path).expect("Invalid domain path");
+    assert!(snapshots.list().await.expect("Failed to list snapshots").is_empty());
+    snapshots.create_named("v1").await.expect("Failed to create snapshot");
+    
+    client.attributes().replace_attribute_raw(&domain_path, "groups", &root_id, "version",
+        serde_json::json!({"type": "H5T_STD_I32LE", "value": 2})).await
+        .expect("Failed to overwrite attribute");
+    let backup = snapshots.restore("v1").await.expect("Failed to restore snapshot");
+    
+    let names: Vec<_> = snapshots.list().await.expect("Failed to list snapshots")
+        .into_iter().map(|s| s.name).collect();
+    assert_eq!(names, vec![backup.name.clone(), "v1".to_string()]);
+    
+    let root_id = client.domains().get_domain(&domain_path).await
+        .expect("Failed to get restored domain").root.expect("Domain has no root group");
+    let version = client.attributes().get_attribute(&domain_path, "groups", &root_id, "version").await
+        .expect("Failed to read attribute");
+    assert_eq!(version["value"], serde_json::json!(1));
+    
+    // Clean up
+    snapshots.delete("v1").await.ok();
+    snapshots.delete(&backup.name).await.ok();
+    client.domains().delete_domain(&snapshots.folder()).await.ok();
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Tests against a mock server, runnable without HSDS
+mod mock {
+    use super::common;
+    use hsds_client::{HsdsClient, HsdsError, NoAuth, DomainPath};
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    /// Test that domains with spaces and '+' are percent-encoded on the wire
+    #[tokio::test]
+    async fn test_domain_query_encoding() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(|req: &wiremock::Request| {
+                req.url.query() == Some("domain=%2Fhome%2Ftest%2Fshot%201%2B2.h5")
+            })
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let domain_path = DomainPath::parse("/home/test/shot 1+2.h5").expect("Invalid domain path");
+        client.domains().get_domain(&domain_path).await
+            .expect("Failed to get domain");
+
+        // Invalid paths are rejected before any request is sent
+        let result = client.domains().get_domain("file.h5").await;
+        assert!(matches!(result, Err(HsdsError::InvalidParameter(_))));
+    }
+
+    /// Test that bucket-qualified domains send the bucket as a separate parameter
+    #[tokio::test]
+    async fn test_bucket_qualified_domain() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/test/file.h5"))
+            .and(query_param("bucket", "archive"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        client.domains().get_domain("archive/home/test/file.h5").await
+            .expect("Failed to get domain");
+    }
+
+    /// Test that folders are sent without a trailing slash and kind mismatches fail early
+    #[tokio::test]
+    async fn test_folder_and_file_semantics() {
+        use hsds_client::{DomainCreateRequest, DomainKind};
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/test/runs"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"owner": "test", "class": "folder"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"owner": "test", "class": "folder"})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let folder = client.domains().create_folder("/home/test/runs/").await
+            .expect("Failed to create folder");
+        assert!(folder.is_folder());
+
+        let as_folder = client.domains().create_folder("/home/test/shot.h5").await;
+        assert!(matches!(as_folder, Err(HsdsError::InvalidParameter(_))));
+        let as_file = client.domains().create_domain("/home/test/runs/", None).await;
+        assert!(matches!(as_file, Err(HsdsError::InvalidParameter(_))));
+        let explicit = client.domains().create_domain("/home/test/a.h5", Some(DomainCreateRequest::folder())).await;
+        assert!(matches!(explicit, Err(HsdsError::InvalidParameter(_))));
+
+        let wrong_kind = client.domains().get_domain_of_kind("/home/test/runs", DomainKind::File).await;
+        assert!(matches!(wrong_kind, Err(HsdsError::InvalidParameter(msg)) if msg.contains("is a folder")));
+    }
+
+    /// Test that find_domains descends into subfolders and filters file names
+    #[tokio::test]
+    async fn test_find_domains_recursive() {
+        use futures_util::StreamExt;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/domains"))
+            .and(query_param("domain", "/exp/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"domains": [
+                {"name": "/exp/a.h5", "class": "domain", "root": "g-1"},
+                {"name": "/exp/notes.txt", "class": "domain", "root": "g-2"},
+                {"name": "/exp/2024", "class": "folder"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/domains"))
+            .and(query_param("domain", "/exp/2024/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"domains": [
+                {"name": "/exp/2024/b.h5", "class": "domain", "root": "g-3"}
+            ]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let found: Vec<String> = client.domains().find_domains("/exp", Some("*.h5"), true)
+            .map(|entry| entry.expect("Listing failed").name)
+            .collect()
+            .await;
+        assert_eq!(found, vec!["/exp/a.h5", "/exp/2024/b.h5"]);
+
+        let top_level = client.domains().find_domains("/exp/", None, false).count().await;
+        assert_eq!(top_level, 2);
+    }
+
+    /// Test public readability via the default ACL and the anonymous fallback
+    #[tokio::test]
+    async fn test_is_publicly_readable() {
+        use hsds_client::BasicAuth;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/acls/default"))
+            .and(query_param("domain", "/shared/public.h5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "acl": {"userName": "default", "read": true, "create": false, "update": false,
+                        "delete": false, "readACL": false, "updateACL": false}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/acls/default"))
+            .and(query_param("domain", "/home/bob/private.h5"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({"message": "forbidden"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/bob/private.h5"))
+            .and(|req: &wiremock::Request| !req.headers.contains_key("authorization"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({"message": "no credentials"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = HsdsClient::new(server.uri(), BasicAuth::new("alice", "secret")).expect("Failed to create client");
+        assert!(client.domains().is_publicly_readable("/shared/public.h5").await.expect("Check failed"));
+        assert!(!client.domains().is_publicly_readable("/home/bob/private.h5").await.expect("Check failed"));
+
+        let anonymous = HsdsClient::anonymous(server.uri()).expect("Failed to create client");
+        assert!(anonymous.is_read_only());
+        assert!(matches!(
+            anonymous.domains().delete_domain("/shared/public.h5").await,
+            Err(HsdsError::ReadOnly(_))
+        ));
+    }
+
+    /// Test that search_attributes returns the paths of matching objects
+    #[tokio::test]
+    async fn test_search_attributes() {
+        use hsds_client::AttributePredicate;
+
+        let server = common::mock_server().await;
+        let hard = |name: &str, id: &str| json!({"title": name, "class": "H5L_TYPE_HARD", "id": id});
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "links": [hard("raw", "g-raw"), hard("ip", "d-ip")]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-raw/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "links": [hard("bolo1", "d-b1"), hard("bolo2", "d-b2")]
+            })))
+            .mount(&server)
+            .await;
+        let attributes = [
+            ("/groups/g-root/attributes", json!([])),
+            ("/groups/g-raw/attributes", json!([{"name": "sensor", "value": "bolometer"}])),
+            ("/datasets/d-ip/attributes", json!([{"name": "sensor", "value": "rogowski"}])),
+            ("/datasets/d-b1/attributes", json!([{"name": "sensor", "value": "bolometer"}, {"name": "gain", "value": 4}])),
+            ("/datasets/d-b2/attributes", json!([{"name": "sensor", "value": "bolometer"}, {"name": "gain", "value": 1.5}])),
+        ];
+        for (attributes_path, listing) in attributes {
+            Mock::given(method("GET"))
+                .and(path(attributes_path))
+                .and(query_param("IncludeData", "1"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": listing})))
+                .mount(&server)
+                .await;
+        }
+
+        let client = common::mock_client(&server);
+        let domain = "/home/test/shot.h5";
+        let bolometers = client.domains()
+            .search_attributes(domain, &AttributePredicate::eq("sensor", "bolometer"))
+            .await
+            .expect("Search failed");
+        assert_eq!(bolometers, vec!["/raw", "/raw/bolo1", "/raw/bolo2"]);
+
+        let high_gain = client.domains()
+            .search_attributes(domain, &AttributePredicate::eq("sensor", "bolometer").and(AttributePredicate::gt("gain", 2)))
+            .await
+            .expect("Search failed");
+        assert_eq!(high_gain, vec!["/raw/bolo1"]);
+    }
+
+    /// Test that wait_until_ready polls until the domain and root group respond
+    #[tokio::test]
+    async fn test_wait_until_ready() {
+        use std::time::Duration;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/test/new.h5"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/test/new.h5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-root", "root": "g-root"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/test/never.h5"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let domain = client.domains().wait_until_ready("/home/test/new.h5", Duration::from_secs(5)).await.unwrap();
+        assert_eq!(domain.root.as_deref(), Some("g-root"));
+        assert_eq!(server.received_requests().await.unwrap().len(), 6);
+
+        let result = client.domains().wait_until_ready("/home/test/never.h5", Duration::from_millis(200)).await;
+        assert!(matches!(result, Err(HsdsError::OperationFailed(message)) if message.contains("not ready")));
+    }
+
+    /// Test that copies carry the source domain's ACLs through the user map
+    #[tokio::test]
+    async fn test_copy_acls() {
+        use hsds_client::{CopyJournal, CopyOptions};
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/acls"))
+            .and(query_param("domain", "/home/test/src.h5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"acls": [
+                {"userName": "alice", "create": true, "read": true, "update": true, "delete": true, "readACL": true, "updateACL": true},
+                {"userName": "bob", "read": true},
+                {"userName": "default", "read": true, "create": false}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-src/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-dst/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-src/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/acls/alice.archive"))
+            .and(query_param("domain", "/home/test/dst.h5"))
+            .and(body_json(json!({"create": true, "read": true, "update": true, "delete": true, "readACL": true, "updateACL": true})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(2)
+            .mount(&server)
+            .await;
+        // Unset permissions are not sent
+        Mock::given(method("PUT"))
+            .and(path("/acls/default"))
+            .and(body_json(json!({"read": true, "create": false})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/acls/bob"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let (source, target) = ("/home/test/src.h5", "/home/test/dst.h5");
+        let options = CopyOptions::new()
+            .with_acls(true)
+            .with_acl_user_map([("alice", "alice.archive"), ("bob", "")]);
+        assert_eq!(options.acl_user("bob"), None);
+        assert_eq!(options.acl_user("carol").as_deref(), Some("carol"));
+
+        let users = client.domains().copy_acls(source, target, &options).await.unwrap();
+        assert_eq!(users, vec!["alice.archive".to_string(), "default".to_string()]);
+
+        let mut journal = CopyJournal::new();
+        client.groups()
+            .copy_group_contents(source, "g-src", target, "g-dst", &mut journal, &options)
+            .await
+            .unwrap();
+        assert!(journal.finished);
+        let transfer = journal.transfer.expect("copy records a transfer report");
+        assert_eq!(transfer.requests, 6);
+    }
+
+    /// Test that ensure_domain treats a lost creation race as success and checks ownership
+    #[tokio::test]
+    async fn test_ensure_domain() {
+        use hsds_client::{Acl, EnsureDomainOptions};
+
+        let server = common::mock_server().await;
+        Mock::given(method("PUT"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({"message": "Conflict: resource exists"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/shots/42.h5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "root": "g-1", "owner": "ingest", "class": "domain"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "g-1", "root": "g-1"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/acls"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"acls": {
+                "ingest": {"create": true, "read": true, "update": true, "delete": true, "readACL": true, "updateACL": true},
+                "default": {"create": false, "read": true, "update": false, "delete": false, "readACL": false, "updateACL": false}
+            }})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let options = EnsureDomainOptions::new()
+            .with_owner("ingest")
+            .with_acl("analyst", Acl { read: Some(true), update: Some(false), ..Acl::default() });
+        let ensured = client.domains().ensure_domain("/shots/42.h5", &options).await.expect("Ensure failed");
+        assert!(!ensured.created);
+        assert_eq!(ensured.domain.root.as_deref(), Some("g-1"));
+
+        let wrong_owner = EnsureDomainOptions::new().with_owner("someone");
+        assert!(matches!(
+            client.domains().ensure_domain("/shots/42.h5", &wrong_owner).await,
+            Err(HsdsError::Conflict(_))
+        ));
+        let too_strict = EnsureDomainOptions::new().with_acl("analyst", Acl { update: Some(true), ..Acl::default() });
+        match client.domains().ensure_domain("/shots/42.h5", &too_strict).await {
+            Err(HsdsError::Conflict(message)) => assert!(message.contains("update permission of analyst"), "{}", message),
+            other => panic!("Expected an ACL conflict, got {:?}", other),
+        }
+    }
+
+    /// Test that audit_links reports shared objects, orphans and broken links
+    #[tokio::test]
+    async fn test_audit_links() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "a", "class": "H5L_TYPE_HARD", "collection": "groups", "id": "g-a"},
+                {"title": "b", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-1"},
+                {"title": "ext", "class": "H5L_TYPE_EXTERNAL", "h5path": "/data", "h5domain": "/home/test/gone.h5"},
+                {"title": "soft_bad", "class": "H5L_TYPE_SOFT", "h5path": "/missing"},
+                {"title": "soft_ok", "class": "H5L_TYPE_SOFT", "h5path": "/a/x"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-a/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "up", "class": "H5L_TYPE_SOFT", "h5path": "../b"},
+                {"title": "x", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-1"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"groups": ["g-a", "g-orphan"]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"datasets": ["d-1", "d-orphan"]})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let report = client.domains().audit_links("/home/test/file.h5").await.expect("Audit failed");
+
+        assert_eq!(report.reachable, 3);
+        assert_eq!(report.multiple_parents.len(), 1);
+        assert_eq!(report.multiple_parents[0].id, "d-1");
+        assert_eq!(report.multiple_parents[0].paths, vec!["/b", "/a/x"]);
+        assert_eq!(report.unreachable, vec!["d-orphan", "g-orphan"]);
+
+        let broken: Vec<&str> = report.broken_links.iter().map(|l| l.path.as_str()).collect();
+        assert_eq!(broken, vec!["/ext", "/soft_bad", "/a/up"], "'..' is not a link name");
+        assert!(!report.is_clean());
+    }
+
+    /// Test that external links are resolved with the client configured for their domain
+    #[tokio::test]
+    async fn test_validate_external_links() {
+        use hsds_client::{BearerAuth, ExternalLinkOptions, ExternalTarget};
+
+        let local = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/test/file.h5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root"})))
+            .mount(&local)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/home/test/moved.h5"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&local)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "gone", "class": "H5L_TYPE_EXTERNAL", "h5path": "/x", "h5domain": "/home/test/moved.h5"},
+                {"title": "missing", "class": "H5L_TYPE_EXTERNAL", "h5path": "/calib/nope", "h5domain": "/shared/base.h5"},
+                {"title": "ok", "class": "H5L_TYPE_EXTERNAL", "h5path": "/calib/gain", "h5domain": "hdf5://shared/base.h5"},
+                {"title": "relative", "class": "H5L_TYPE_EXTERNAL", "h5path": "/x", "h5domain": "./moved.h5"}
+            ]})))
+            .mount(&local)
+            .await;
+
+        let shared = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("domain", "/shared/base.h5"))
+            .and(header("Authorization", "Bearer shared-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-base"})))
+            .mount(&shared)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-base/links/calib"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "link": {"title": "calib", "class": "H5L_TYPE_HARD", "collection": "groups", "id": "g-calib"}
+            })))
+            .mount(&shared)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-calib/links/gain"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "link": {"title": "gain", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-gain"}
+            })))
+            .mount(&shared)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-calib/links/nope"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&shared)
+            .await;
+
+        let client = HsdsClient::new(local.uri(), NoAuth).expect("Failed to create client");
+        let shared_client = HsdsClient::new(shared.uri(), BearerAuth::new("shared-token")).expect("Failed to create client");
+        let options = ExternalLinkOptions::new().with_client("/shared/", shared_client);
+
+        let checks = client.domains().validate_external_links("/home/test/file.h5", &options).await
+            .expect("Validation failed");
+
+        assert_eq!(checks.len(), 4);
+        assert_eq!(checks[0].target, ExternalTarget::DomainNotFound);
+        assert!(matches!(&checks[1].target, ExternalTarget::PathNotFound(reason) if reason.contains("/calib/nope")));
+        assert_eq!(checks[2].target, ExternalTarget::Resolved("d-gain".to_string()));
+        assert_eq!((checks[3].target_domain.as_str(), &checks[3].target), ("/home/test/moved.h5", &ExternalTarget::DomainNotFound));
+        assert_eq!(checks.iter().filter(|c| c.is_dead()).count(), 3);
+
+        let relocated = options.with_relative_base(DomainPath::folder("/shared/").unwrap());
+        let checks = client.domains().validate_external_links("/home/test/file.h5", &relocated).await
+            .expect("Validation failed");
+        assert_eq!(checks[3].target_domain, "/shared/moved.h5");
+    }
+
+    /// Test importing HDF5 soft/external links and committed types, reporting what cannot be recreated
+    #[tokio::test]
+    async fn test_import_items() {
+        use hsds_client::{H5Type, ImportItem, ImportOptions};
+        use wiremock::matchers::body_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links/calib"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "link": {"title": "calib", "class": "H5L_TYPE_HARD", "collection": "groups", "id": "g-calib"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datatypes"))
+            .and(body_json(json!({"type": "H5T_STD_U16LE", "link": {"id": "g-calib", "name": "gain_t"}})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "t-gain"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-root/links/latest"))
+            .and(body_json(json!({"h5path": "/calib/gain"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-calib/links/base"))
+            .and(body_json(json!({"h5path": "/data", "h5domain": "base.h5"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-calib/links/shared"))
+            .and(body_json(json!({"h5path": "/", "h5domain": "/shared/lookup.h5"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-root/links/taken"))
+            .respond_with(ResponseTemplate::new(409))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let domain = "/home/test/file.h5";
+        let soft = |group: &str, name: &str, target: &str| ImportItem::SoftLink {
+            group: group.to_string(),
+            name: name.to_string(),
+            target: target.to_string(),
+        };
+        let external = |name: &str, file: &str, target: &str| ImportItem::ExternalLink {
+            group: "/calib".to_string(),
+            name: name.to_string(),
+            file: file.to_string(),
+            target: target.to_string(),
+        };
+        let items = vec![
+            soft("/", "latest", "/calib/gain"),
+            soft("/", "taken", "/calib/gain"),
+            external("base", "base.h5", "/data"),
+            external("shared", "/mnt/archive/lookup.h5", "/"),
+            external("other", "/mnt/archive/other.h5", "/"),
+            ImportItem::NamedType {
+                group: "/calib".to_string(),
+                name: "gain_t".to_string(),
+                datatype: H5Type::named("H5T_STD_U16LE"),
+            },
+            ImportItem::Unsupported {
+                group: "/".to_string(),
+                name: "ud".to_string(),
+                kind: "user-defined link".to_string(),
+            },
+        ];
+        let options = ImportOptions::new().with_external_domain("/mnt/archive/lookup.h5", "/shared/lookup.h5");
+        let report = client.domains().import_items(domain, &items, &options).await.unwrap();
+
+        assert_eq!(report.datatypes.get("/calib/gain_t").map(String::as_str), Some("t-gain"));
+        assert_eq!(report.links, vec!["/latest", "/calib/base", "/calib/shared"]);
+        let untranslated: Vec<&str> = report.untranslated.iter().map(|u| u.path.as_str()).collect();
+        assert_eq!(untranslated, vec!["/taken", "/calib/other", "/ud"]);
+        assert!(report.untranslated[2].reason.contains("user-defined link"));
+        assert!(report.collisions.is_empty());
+        assert!(!report.is_complete());
+        let transfer = report.transfer.expect("Import reports its transfers");
+        assert_eq!(transfer.requests, server.received_requests().await.unwrap().len() as u64);
+    }
+
+    /// Test that imported names already taken are skipped, renamed or overwritten by policy
+    #[tokio::test]
+    async fn test_import_name_collisions() {
+        use hsds_client::{ImportCollision, ImportItem, ImportOptions, NameCollision};
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        for taken in ["a", "a_1"] {
+            Mock::given(method("PUT"))
+                .and(path(format!("/groups/g-root/links/{}", taken)))
+                .respond_with(ResponseTemplate::new(409))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-root/links/a_2"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-root/links/b"))
+            .respond_with(ResponseTemplate::new(409))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/groups/g-root/links/b"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-root/links/b"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let domain = "/home/test/file.h5";
+        let soft = |name: &str| vec![ImportItem::SoftLink {
+            group: "/".to_string(),
+            name: name.to_string(),
+            target: "/data".to_string(),
+        }];
+        let import = |items: Vec<ImportItem>, policy: NameCollision| {
+            let client = client.clone();
+            async move {
+                let options = ImportOptions::new().with_name_collision(policy);
+                client.domains().import_items(domain, &items, &options).await.unwrap()
+            }
+        };
+
+        let skipped = import(soft("a"), NameCollision::Skip).await;
+        assert!(skipped.links.is_empty());
+        assert_eq!(skipped.collisions, vec![ImportCollision { path: "/a".to_string(), linked_as: None }]);
+        assert!(skipped.is_complete());
+
+        let renamed = import(soft("a"), NameCollision::Rename).await;
+        assert_eq!(renamed.links, vec!["/a"]);
+        assert_eq!(renamed.collisions[0].linked_as.as_deref(), Some("/a_2"));
+
+        let overwritten = import(soft("b"), NameCollision::Overwrite).await;
+        assert_eq!(overwritten.links, vec!["/b"]);
+        assert_eq!(overwritten.collisions[0].linked_as.as_deref(), Some("/b"));
+    }
+
+    /// Test that a name sanitizer maps item names and link targets to names HSDS can store
+    #[tokio::test]
+    async fn test_import_name_sanitizer() {
+        use hsds_client::{ImportItem, ImportOptions, NameSanitizer};
+        use wiremock::matchers::body_partial_json;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-root/links/temp_raw"))
+            .and(body_partial_json(json!({"h5path": "/data/ch_1"})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let items = vec![ImportItem::SoftLink {
+            group: "/".to_string(),
+            name: "temp/raw".to_string(),
+            target: "/data/ch\t1".to_string(),
+        }];
+        let domain = "/home/test/file.h5";
+
+        let report = client.domains().import_items(domain, &items, &ImportOptions::new()).await.unwrap();
+        assert_eq!(report.untranslated.len(), 1, "Invalid names are reported without a sanitizer");
+
+        let options = ImportOptions::new().with_name_sanitizer(NameSanitizer::new());
+        let report = client.domains().import_items(domain, &items, &options).await.unwrap();
+        assert_eq!(report.links, vec!["/temp/raw"]);
+        assert!(report.is_complete());
+    }
+
+    /// Test that renaming gives up when the server keeps answering 409
+    #[tokio::test]
+    async fn test_import_rename_gives_up() {
+        use hsds_client::{ImportItem, ImportOptions, NameCollision};
+        use wiremock::matchers::path_regex;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"root": "g-root", "class": "domain"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path_regex(r"^/groups/g-root/links/"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({"message": "locked"})))
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let items = vec![ImportItem::SoftLink { group: "/".to_string(), name: "a".to_string(), target: "/data".to_string() }];
+        let options = ImportOptions::new().with_name_collision(NameCollision::Rename);
+        let report = client.domains().import_items("/home/test/file.h5", &items, &options).await.unwrap();
+
+        assert!(report.links.is_empty());
+        assert_eq!(report.untranslated.len(), 1);
+        let attempts = server.received_requests().await.unwrap().iter().filter(|r| r.method.as_str() == "PUT").count();
+        assert_eq!(attempts, 1001, "The original name and 1000 renames");
+    }
+}
@@ -1,7 +1,9 @@
 use hsds_client::{HsdsClient, BasicAuth, HsdsResult};
-use hsds_client::models::{GroupCreateRequest, LinkRequest};
+use hsds_client::models::{DatasetCreateRequest, GroupCreateRequest, LinkRequest};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+mod common;
+
 /// Helper to create a test client
 fn create_test_client() -> HsdsResult<HsdsClient> {
     HsdsClient::new(
@@ -258,3 +260,472 @@ async fn test_group_lifecycle() {
     client.domains().delete_domain(&domain_path).await
         .expect("Failed to delete test domain");
 }
+
+/// Test ensure_group_path creates missing intermediate groups and is idempotent
+#[tokio::test]
+async fn test_ensure_group_path() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    let leaf_id = client.groups().ensure_group_path(&domain_path, "/a/b/c").await
+        .expect("Failed to ensure group path");
+    
+    // Running again returns the same group
+    let again = client.groups().ensure_group_path(&domain_path, "/a/b/c").await
+        .expect("Failed to ensure existing group path");
+    assert_eq!(leaf_id, again, "ensure_group_path should be idempotent");
+    
+    // Intermediate groups are reachable by link name
+    let a_id = client.groups().get_or_create_group(&domain_path, &root_group_id, "a").await
+        .expect("Failed to resolve group 'a'");
+    let links = client.links().list_links(&domain_path, &a_id, None, None).await
+        .expect("Failed to list links");
+    assert!(links.links.iter().any(|l| l.title == "b"), "Group 'a' should link to 'b'");
+    
+    // Root path returns the root group
+    let root = client.groups().ensure_group_path(&domain_path, "/").await
+        .expect("Failed to ensure root path");
+    assert_eq!(root, root_group_id);
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test children_of splits a group's links into groups and datasets
+#[tokio::test]
+async fn test_children_of() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    
+    client.groups().get_or_create_group(&domain_path, &root_group_id, "sub").await
+        .expect("Failed to create subgroup");
+    let request = DatasetCreateRequest::from_hsds_type_with_link("H5T_STD_I32LE", vec![4], &root_group_id, "values");
+    client.datasets().create_dataset(&domain_path, request).await
+        .expect("Failed to create dataset");
+    
+    let children = client.groups().children_of(&domain_path, &root_group_id).await
+        .expect("Failed to list children");
+    assert_eq!(children.groups.len(), 1);
+    assert_eq!(children.groups[0].title, "sub");
+    assert_eq!(children.datasets.len(), 1);
+    assert_eq!(children.datasets[0].title, "values");
+    assert!(children.datatypes.is_empty());
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Test copying a group tree into another domain
+#[tokio::test]
+async fn test_copy_group_across_domains() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let source_path = create_test_domain_name();
+    let target_path = create_test_domain_name();
+    
+    let source = client.domains().create_domain(&source_path, None).await
+        .expect("Failed to create source domain");
+    let target = client.domains().create_domain(&target_path, None).await
+        .expect("Failed to create target domain");
+    let source_root = source.root.expect("Source domain should have a root group");
+    let target_root = target.root.expect("Target domain should have a root group");
+    
+    let run_id = client.groups().ensure_group_path(&source_path, "/run/raw").await
+        .expect("Failed to create groups");
+    let request = DatasetCreateRequest::from_hsds_type_with_link("H5T_STD_I32LE", vec![3], &run_id, "counts");
+    let dataset = client.datasets().create_dataset(&source_path, request).await
+        .expect("Failed to create dataset");
+    client.datasets().write_dataset_values(&source_path, &dataset.id, hsds_client::models::DatasetValueRequest {
+        start: None, stop: None, step: None, points: None,
+        value: Some(serde_json::json!([1, 2, 3])), value_base64: None,
+    }).await.expect("Failed to write values");
+    
+    let run = client.groups().get_or_create_group(&source_path, &source_root, "run").await
+        .expect("Failed to resolve run group");
+    let mut journal = hsds_client::CopyJournal::new();
+    let copy_id = client.groups()
+        .copy_group(&source_path, &run, &target_path, &target_root, "run_copy", &mut journal, &hsds_client::CopyOptions::new())
+        .await
+        .expect("Failed to copy group");
+    assert!(journal.finished);
+    assert_eq!(journal.created.len(), 3, "Two groups and one dataset are copied");
+    
+    let raw = client.groups().get_or_create_group(&target_path, &copy_id, "raw").await
+        .expect("Copied tree should contain 'raw'");
+    let children = client.groups().children_of(&target_path, &raw).await
+        .expect("Failed to list copied children");
+    let copied_dataset = children.datasets[0].id.clone().expect("Hard link has an id");
+    let values = client.datasets().read_dataset_values_json(&target_path, &copied_dataset, None, None, None).await
+        .expect("Failed to read copied values");
+    assert_eq!(values["value"], serde_json::json!([1, 2, 3]));
+    
+    // Clean up
+    client.domains().delete_domain(&source_path).await.ok();
+    client.domains().delete_domain(&target_path).await.ok();
+}
+
+/// Tests against a mock server, runnable without HSDS
+mod mock {
+    use super::common;
+    use hsds_client::HsdsError;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    /// Test that copying into an existing group resolves name collisions by policy
+    #[tokio::test]
+    async fn test_copy_group_contents_name_collisions() {
+        use hsds_client::{CopyJournal, CopyOptions, LinkCollision, NameCollision};
+        use wiremock::matchers::path_regex;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-src/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "a", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-src"},
+                {"title": "alias", "class": "H5L_TYPE_SOFT", "h5path": "/a"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-dst/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "a", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-other"},
+                {"title": "a_1", "class": "H5L_TYPE_HARD", "collection": "groups", "id": "g-other"},
+                {"title": "alias", "class": "H5L_TYPE_SOFT", "h5path": "/a"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-src",
+                "type": {"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [2]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-src/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1, 2]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex(r"^/(groups|datasets)/[dg]-src/attributes$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datasets"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "d-new"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-new/value"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        // Recreating an identical link is answered like any existing name
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-dst/links/alias"))
+            .respond_with(ResponseTemplate::new(409))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-dst/links/alias"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "link": {"title": "alias", "class": "H5L_TYPE_SOFT", "h5path": "/a"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-dst/links/a_2"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let groups = client.groups();
+        let (source, target) = ("/home/test/src.h5", "/home/test/dst.h5");
+
+        // The default policy refuses to touch the taken name
+        let mut journal = CopyJournal::new();
+        let result = groups.copy_group_contents(source, "g-src", target, "g-dst", &mut journal, &CopyOptions::new()).await;
+        assert!(matches!(result, Err(HsdsError::Conflict(_))));
+
+        let mut journal = CopyJournal::new();
+        let skip = CopyOptions::new().with_name_collision(NameCollision::Skip);
+        groups.copy_group_contents(source, "g-src", target, "g-dst", &mut journal, &skip).await.unwrap();
+        assert!(journal.created.is_empty());
+        assert_eq!(journal.collisions[0].linked_as, None);
+
+        // The identical soft link is not a collision; the dataset link is renamed past a_1
+        let mut journal = CopyJournal::new();
+        let rename = CopyOptions::new().with_name_collision(NameCollision::Rename);
+        groups.copy_group_contents(source, "g-src", target, "g-dst", &mut journal, &rename).await.unwrap();
+        assert_eq!(journal.collisions, vec![LinkCollision {
+            group_id: "g-dst".to_string(),
+            name: "a".to_string(),
+            linked_as: Some("a_2".to_string()),
+        }]);
+        assert_eq!(journal.target_of("d-src"), Some("d-new"));
+    }
+
+    /// Test that a name taken after the target's links were listed goes through the collision policy
+    #[tokio::test]
+    async fn test_copy_link_taken_during_copy() {
+        use hsds_client::{CopyJournal, CopyOptions, LinkCollision, NameCollision};
+        use wiremock::matchers::path_regex;
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-src/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "a", "class": "H5L_TYPE_HARD", "collection": "datatypes", "id": "t-src"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-dst/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datatypes/t-src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "t-src", "type": "H5T_STD_I32LE"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex(r"^/(groups|datatypes)/[gt]-src/attributes$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datatypes"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"id": "t-new"})))
+            .mount(&server)
+            .await;
+        // A concurrent writer took "a" after the listing
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-dst/links/a"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({"message": "exists"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-dst/links/a"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "link": {"title": "a", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-other"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-dst/links/a_1"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let groups = client.groups();
+        let (source, target) = ("/home/test/src.h5", "/home/test/dst.h5");
+
+        // Skipping would leave the copied type unlinked
+        let mut journal = CopyJournal::new();
+        let skip = CopyOptions::new().with_name_collision(NameCollision::Skip).with_rollback_on_error(false);
+        let result = groups.copy_group_contents(source, "g-src", target, "g-dst", &mut journal, &skip).await;
+        assert!(matches!(result, Err(HsdsError::Conflict(_))), "got {:?}", result);
+
+        let mut journal = CopyJournal::new();
+        let rename = CopyOptions::new().with_name_collision(NameCollision::Rename);
+        groups.copy_group_contents(source, "g-src", target, "g-dst", &mut journal, &rename).await.unwrap();
+        assert_eq!(journal.collisions, vec![LinkCollision {
+            group_id: "g-dst".to_string(),
+            name: "a".to_string(),
+            linked_as: Some("a_1".to_string()),
+        }]);
+    }
+
+    /// Test that rollback keeps deleting after an object it cannot classify
+    #[tokio::test]
+    async fn test_rollback_copy_is_best_effort() {
+        use hsds_client::CopyJournal;
+
+        let server = common::mock_server().await;
+        Mock::given(method("DELETE"))
+            .and(path("/datasets/d-new"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut journal: CopyJournal = serde_json::from_value(json!({
+            "target_domain": "/home/test/dst.h5",
+            "created": [
+                {"source_id": "d-src", "target_id": "d-new"},
+                {"source_id": "x-src", "target_id": "x-new"}
+            ],
+            "completed": [],
+            "top_link": null,
+            "finished": false
+        })).unwrap();
+
+        let client = common::mock_client(&server);
+        client.groups().rollback_copy(&mut journal).await.expect("Rollback failed");
+        assert_eq!(journal, CopyJournal::default());
+    }
+
+    /// Test that children_of pages through links and splits them by collection
+    #[tokio::test]
+    async fn test_group_children_of() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-root/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "links": [
+                    {"title": "calib", "class": "H5L_TYPE_HARD", "collection": "groups", "id": "g-1"},
+                    {"title": "data", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-1"},
+                    {"title": "dtype", "class": "H5L_TYPE_HARD", "collection": "datatypes", "id": "t-1"},
+                    {"title": "latest", "class": "H5L_TYPE_SOFT", "h5path": "/data"},
+                    {"title": "remote", "class": "H5L_TYPE_EXTERNAL", "h5path": "/x", "h5domain": "/other.h5"}
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let children = client.groups().children_of("/home/test/file.h5", "g-root").await
+            .expect("Failed to list children");
+
+        assert_eq!(children.len(), 5);
+        assert_eq!(children.groups[0].title, "calib");
+        assert_eq!(children.datasets[0].id.as_deref(), Some("d-1"));
+        assert_eq!(children.datatypes.len(), 1);
+        assert_eq!(children.unresolved.len(), 2);
+    }
+
+    /// Test that a failed group copy deletes what it created
+    #[tokio::test]
+    async fn test_copy_group_rolls_back_on_failure() {
+        use hsds_client::{CopyJournal, CopyOptions};
+
+        let server = common::mock_server().await;
+        let created = |id: &str| ResponseTemplate::new(201).set_body_json(json!({"id": id}));
+        Mock::given(method("POST")).and(path("/groups")).respond_with(created("g-new")).mount(&server).await;
+        Mock::given(method("POST")).and(path("/datasets")).respond_with(created("d-new")).mount(&server).await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-parent/links/copy"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-src/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "d", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-src"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "d-src",
+                "type": {"class": "H5T_INTEGER", "base": "H5T_STD_I32LE"},
+                "shape": {"class": "H5S_SIMPLE", "dims": [4]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/datasets/d-src/value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"value": [1, 2, 3, 4]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datasets/d-new/value"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        for deleted in ["/groups/g-parent/links/copy", "/datasets/d-new", "/groups/g-new"] {
+            Mock::given(method("DELETE"))
+                .and(path(deleted))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = common::mock_client(&server);
+        let mut journal = CopyJournal::new();
+        let result = client.groups()
+            .copy_group("/home/test/src.h5", "g-src", "/home/test/dst.h5", "g-parent", "copy", &mut journal, &CopyOptions::new())
+            .await;
+
+        assert!(result.is_err(), "Value write failure aborts the copy");
+        assert!(journal.transfer.is_some(), "The report covers the failed copy");
+        journal.transfer = None;
+        assert_eq!(journal, CopyJournal::default(), "Rollback resets the journal");
+    }
+
+    /// Test that a kept journal resumes a copy without recreating objects
+    #[tokio::test]
+    async fn test_copy_group_resumes_from_journal() {
+        use hsds_client::{CopyJournal, CopyOptions};
+
+        let server = common::mock_server().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(201)).expect(0).mount(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-src/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"title": "d", "class": "H5L_TYPE_HARD", "collection": "datasets", "id": "d-src"}
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/groups/g-new/links/d"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-src/attributes"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": []})))
+            .mount(&server)
+            .await;
+
+        let mut journal: CopyJournal = serde_json::from_value(json!({
+            "target_domain": "/home/test/dst.h5",
+            "created": [
+                {"source_id": "g-src", "target_id": "g-new"},
+                {"source_id": "d-src", "target_id": "d-new"}
+            ],
+            "completed": ["d-src"],
+            "top_link": ["g-parent", "copy"],
+            "finished": false
+        })).unwrap();
+
+        let client = common::mock_client(&server);
+        let options = CopyOptions::new().with_rollback_on_error(false);
+        let copied = client.groups()
+            .copy_group("/home/test/src.h5", "g-src", "/home/test/dst.h5", "g-parent", "copy", &mut journal, &options)
+            .await
+            .expect("Resumed copy failed");
+
+        assert_eq!(copied, "g-new");
+        assert!(journal.finished);
+        assert!(journal.completed.contains("g-src"));
+    }
+}
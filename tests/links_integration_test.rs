@@ -1,10 +1,12 @@
 use hsds_client::{HsdsClient, BasicAuth, HsdsResult};
 use hsds_client::models::{
-    DatasetCreateRequest, DataTypeSpec, ShapeSpec, LinkRequest, LinkCreateRequest,
+    DatasetCreateRequest, H5Type, ShapeSpec, LinkRequest, LinkCreateRequest,
     GroupCreateRequest
 };
 use uuid::Uuid;
 
+mod common;
+
 /// Helper to create a test client
 fn create_test_client() -> HsdsResult<HsdsClient> {
     HsdsClient::new(
@@ -45,7 +47,7 @@ async fn create_test_dataset(
     dataset_name: &str,
 ) -> Result<String, Box<dyn std::error::Error>> {
     let dataset_request = DatasetCreateRequest {
-        data_type: DataTypeSpec::Predefined("H5T_STD_I32LE".to_string()),
+        data_type: H5Type::named("H5T_STD_I32LE"),
         shape: Some(ShapeSpec::Dimensions(vec![10])),
         maxdims: None,
         creation_properties: None,
@@ -354,7 +356,7 @@ async fn test_delete_link() {
         .expect("Link should exist before deletion");
     
     // Delete the link
-    let _delete_result = client.links().delete_link(&domain_path, &root_group_id, link_name).await
+    client.links().delete_link(&domain_path, &root_group_id, link_name).await
         .expect("Failed to delete link");
     
     // Verify the link no longer exists
@@ -391,7 +393,7 @@ async fn test_list_links_with_pagination() {
     for i in 0..5 {
         let dataset_name = format!("dataset_{}", i);
         let _dataset_id = create_test_dataset(&client, &domain_path, &root_group_id, &dataset_name).await
-            .expect(&format!("Failed to create dataset {}", i));
+            .unwrap_or_else(|_| panic!("Failed to create dataset {}", i));
     }
     
     // List links with a limit
@@ -450,7 +452,7 @@ async fn test_multiple_link_operations() {
     
     // Verify each group has the expected links
     assert!(group1_links.links.len() >= 2, "Group1 should have at least 2 links");
-    assert!(group2_links.links.len() >= 1, "Group2 should have at least 1 link");
+    assert!(!group2_links.links.is_empty(), "Group2 should have at least 1 link");
     
     // Get information about each link
     let link1_info = client.links().get_link(&domain_path, &group1_id, "link_to_dataset").await
@@ -480,3 +482,230 @@ async fn test_multiple_link_operations() {
     // Clean up
     client.domains().delete_domain(&domain_path).await.ok();
 }
+
+/// Test existence checks for domains, objects, links and attributes
+#[tokio::test]
+async fn test_exists_helpers() {
+    let _ = env_logger::try_init();
+    
+    let client = create_test_client().expect("Failed to create client");
+    let domain_path = create_test_domain_name();
+    
+    assert!(!client.domains().domain_exists(&domain_path).await.expect("Failed to check domain"));
+    let domain = client.domains().create_domain(&domain_path, None).await
+        .expect("Failed to create test domain");
+    let root_group_id = domain.root.expect("Domain should have a root group");
+    assert!(client.domains().domain_exists(&domain_path).await.expect("Failed to check domain"));
+    
+    assert!(client.object_exists(&domain_path, &root_group_id).await.expect("Failed to check group"));
+    assert!(!client.links().link_exists(&domain_path, &root_group_id, "nothing").await.expect("Failed to check link"));
+    
+    client.attributes().set_attribute(&domain_path, &root_group_id, "flag", 1).await
+        .expect("Failed to set attribute");
+    assert!(client.attributes().attribute_exists(&domain_path, "groups", &root_group_id, "flag").await
+        .expect("Failed to check attribute"));
+    
+    // Clean up
+    client.domains().delete_domain(&domain_path).await.ok();
+}
+
+/// Tests against a mock server, runnable without HSDS
+mod mock {
+    use super::common;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    /// Test that pattern listings pass the pattern to the server and filter locally
+    #[tokio::test]
+    async fn test_list_matching() {
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .and(query_param("pattern", "chan[0-9]*"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [
+                {"class": "H5L_TYPE_HARD", "title": "chan0", "id": "d-1", "collection": "datasets"},
+                {"class": "H5L_TYPE_HARD", "title": "chan1", "id": "d-2", "collection": "datasets"}
+            ]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // A server that ignores the pattern returns every attribute
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/attributes"))
+            .and(query_param("pattern", "cal_*"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"attributes": [
+                {"name": "cal_gain", "value": 2.0},
+                {"name": "units", "value": "V"},
+                {"name": "cal_offset", "value": 0.1}
+            ]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let links = client.links().list_links_matching("/home/test/file.h5", "g-1", "chan[0-9]*").await
+            .expect("Link listing failed");
+        assert_eq!(links.iter().map(|l| l.title.as_str()).collect::<Vec<_>>(), vec!["chan0", "chan1"]);
+
+        let attributes = client.attributes()
+            .list_attributes_matching("/home/test/file.h5", "groups", "g-1", "cal_*").await
+            .expect("Attribute listing failed");
+        let names: Vec<_> = attributes.iter().map(|a| a["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["cal_gain", "cal_offset"]);
+    }
+
+    /// Test that a listing resumes after the cursor of the last item processed
+    #[tokio::test]
+    async fn test_links_stream_resumable() {
+        use futures_util::{StreamExt, TryStreamExt};
+        use hsds_client::{LinkOrder, PageCursor, PaginationOptions};
+
+        let links = |names: &[&str]| json!({"links": names.iter().map(|name| json!({"title": name, "class": "H5L_TYPE_HARD", "id": "g-x"})).collect::<Vec<_>>()});
+        let server = common::mock_server().await;
+        for (marker, page) in [("b", links(&["c", "d"])), ("c", links(&["d", "e"])), ("e", links(&[]))] {
+            Mock::given(method("GET"))
+                .and(path("/groups/g-1/links"))
+                .and(query_param("Marker", marker))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page))
+                .with_priority(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(links(&["a", "b"])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let api = client.links();
+        let domain = "/home/test/file.h5";
+
+        // Process three links, then "crash" with the cursor saved
+        let first: Vec<_> = api
+            .links_stream_resumable(domain, "g-1", LinkOrder::Name, PaginationOptions::fixed(2))
+            .take(3)
+            .try_collect()
+            .await
+            .expect("Listing failed");
+        assert_eq!(first.iter().map(|(link, _)| link.title.as_str()).collect::<Vec<_>>(), ["a", "b", "c"]);
+        let saved = serde_json::to_string(&first[2].1).unwrap();
+        assert_eq!(saved, "\"c\"");
+
+        let cursor: PageCursor = serde_json::from_str(&saved).unwrap();
+        let rest: Vec<_> = api
+            .links_stream_with(domain, "g-1", LinkOrder::Name, PaginationOptions::fixed(2).with_cursor(cursor))
+            .try_collect()
+            .await
+            .expect("Resumed listing failed");
+        assert_eq!(rest.iter().map(|link| link.title.as_str()).collect::<Vec<_>>(), ["d", "e"]);
+        assert!(serde_json::from_str::<PageCursor>("null").unwrap().is_start());
+    }
+
+    /// Test that links_stream requests creation order and pages with the marker
+    #[tokio::test]
+    async fn test_links_stream_create_order() {
+        use futures_util::StreamExt;
+        use hsds_client::LinkOrder;
+
+        let first_page: Vec<_> = (0..1000)
+            .map(|i| json!({"title": format!("ch{:04}", 999 - i), "class": "H5L_TYPE_HARD", "created": 1000.0 + i as f64}))
+            .collect();
+
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .and(query_param("CreateOrder", "1"))
+            .and(query_param("Marker", "ch0000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "links": [{"title": "late", "class": "H5L_TYPE_HARD", "created": 5000.0}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .and(query_param("CreateOrder", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": first_page})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = common::mock_client(&server);
+        let links: Vec<_> = client.links()
+            .links_stream("/home/test/file.h5", "g-1", LinkOrder::CreateOrder)
+            .collect()
+            .await;
+
+        assert_eq!(links.len(), 1001);
+        let links: Vec<_> = links.into_iter().map(|l| l.expect("Link page failed")).collect();
+        assert_eq!(links[0].title, "ch0999");
+        assert_eq!(links[1000].title, "late");
+        assert!(links.windows(2).all(|w| w[0].created < w[1].created));
+    }
+
+    /// Test that throttled pages are retried by the client only, with a smaller next page
+    #[tokio::test]
+    async fn test_links_stream_throttled() {
+        use futures_util::TryStreamExt;
+        use hsds_client::{LinkOrder, PaginationOptions};
+        use std::time::Duration;
+
+        let link = |name: &str| json!({"title": name, "class": "H5L_TYPE_HARD", "id": "g-x"});
+        let server = common::mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .respond_with(ResponseTemplate::new(503).set_body_json(json!({"message": "busy"})))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .and(query_param("Limit", "4"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [link("a"), link("b"), link("c"), link("d")]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .and(query_param("Limit", "2"))
+            .and(query_param("Marker", "d"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [link("e")]})))
+            .expect(1)
+            .with_priority(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/groups/g-1/links"))
+            .and(query_param("Limit", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"links": [link("a"), link("b")]})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // One client retry; its backoff makes the first page slower than the target
+        let client = common::mock_client(&server)
+            .with_options(|o| o.with_max_retries(1).with_backoff(Duration::from_millis(50)));
+        let options = PaginationOptions::new()
+            .with_page_size_bounds(2, 8)
+            .with_initial_page_size(4)
+            .with_target_latency(Duration::from_millis(40));
+        let titles: Vec<_> = client.links()
+            .links_stream_with("/home/test/file.h5", "g-1", LinkOrder::Name, options)
+            .map_ok(|link| link.title)
+            .try_collect()
+            .await
+            .expect("Listing failed");
+        assert_eq!(titles, vec!["a", "b", "c", "d", "e"]);
+
+        let first_page: Vec<_> = client.links()
+            .links_stream_with("/home/test/file.h5", "g-1", LinkOrder::Name, PaginationOptions::fixed(2).with_max_pages(1))
+            .try_collect()
+            .await
+            .expect("Listing failed");
+        assert_eq!(first_page.len(), 2);
+    }
+}